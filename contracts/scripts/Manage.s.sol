@@ -141,22 +141,26 @@ contract UpgradeBoundlessMarket is RiscZeroManagementScript {
         opts.referenceContract = "build-info-reference:BoundlessMarket";
         opts.referenceBuildInfoDir = "contracts/reference-contract/build-info-reference";
 
-        vm.startBroadcast(deployerAddress());
-        // Upgrade the proxy contract and update assessor image info if needed
+        // Initialize the state added in version 2 when upgrading from version 1, and update the
+        // assessor image info if needed.
         string memory assessorGuestUrl = deploymentConfig.assessorGuestUrl;
-        if (
-            assessorImageId != currentImageID || keccak256(bytes(assessorGuestUrl)) != keccak256(bytes(currentGuestUrl))
-        ) {
-            Upgrades.upgradeProxy(
-                marketAddress,
-                "BoundlessMarket.sol:BoundlessMarket",
-                abi.encodeCall(BoundlessMarket.setImageUrl, (assessorGuestUrl)),
-                opts,
-                marketOwner
-            );
-        } else {
-            Upgrades.upgradeProxy(marketAddress, "BoundlessMarket.sol:BoundlessMarket", "", opts, marketOwner);
+        bool initializeV2 = market.VERSION() < 2;
+        bool updateImage =
+            assessorImageId != currentImageID || keccak256(bytes(assessorGuestUrl)) != keccak256(bytes(currentGuestUrl));
+        bytes memory data = "";
+        if (initializeV2 && updateImage) {
+            bytes[] memory calls = new bytes[](2);
+            calls[0] = abi.encodeCall(BoundlessMarket.initializeV2, ());
+            calls[1] = abi.encodeCall(BoundlessMarket.setImageUrl, (assessorGuestUrl));
+            data = abi.encodeCall(BoundlessMarket.multicall, (calls));
+        } else if (initializeV2) {
+            data = abi.encodeCall(BoundlessMarket.initializeV2, ());
+        } else if (updateImage) {
+            data = abi.encodeCall(BoundlessMarket.setImageUrl, (assessorGuestUrl));
         }
+
+        vm.startBroadcast(deployerAddress());
+        Upgrades.upgradeProxy(marketAddress, "BoundlessMarket.sol:BoundlessMarket", data, opts, marketOwner);
         vm.stopBroadcast();
 
         console2.log("Upgraded BoundlessMarket proxy contract at %s", marketAddress);
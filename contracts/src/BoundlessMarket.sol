@@ -10,6 +10,7 @@ import {SafeCast} from "@openzeppelin/contracts/utils/math/SafeCast.sol";
 import {EIP712Upgradeable} from "@openzeppelin/contracts-upgradeable/utils/cryptography/EIP712Upgradeable.sol";
 import {Ownable2StepUpgradeable} from "@openzeppelin/contracts-upgradeable/access/Ownable2StepUpgradeable.sol";
 import {UUPSUpgradeable} from "@openzeppelin/contracts-upgradeable/proxy/utils/UUPSUpgradeable.sol";
+import {MulticallUpgradeable} from "@openzeppelin/contracts-upgradeable/utils/MulticallUpgradeable.sol";
 import {Initializable} from "@openzeppelin/contracts-upgradeable/proxy/utils/Initializable.sol";
 import {ERC20} from "solmate/tokens/ERC20.sol";
 import {ERC20Burnable} from "@openzeppelin/contracts/token/ERC20/extensions/ERC20Burnable.sol";
@@ -40,7 +41,8 @@ contract BoundlessMarket is
     Initializable,
     EIP712Upgradeable,
     Ownable2StepUpgradeable,
-    UUPSUpgradeable
+    UUPSUpgradeable,
+    MulticallUpgradeable
 {
     using ReceiptClaimLib for ReceiptClaim;
     using SafeCast for int256;
@@ -48,7 +50,7 @@ contract BoundlessMarket is
     using SafeTransferLib for ERC20;
 
     /// @dev The version of the contract, with respect to upgrades.
-    uint64 public constant VERSION = 1;
+    uint64 public constant VERSION = 2;
 
     /// Mapping of request ID to lock-in state. Non-zero for requests that are locked in.
     mapping(RequestId => RequestLock) public requestLocks;
@@ -103,10 +105,17 @@ contract BoundlessMarket is
     function initialize(address initialOwner, string calldata _imageUrl) external initializer {
         __Ownable_init(initialOwner);
         __UUPSUpgradeable_init();
+        __Multicall_init();
         __EIP712_init(BoundlessMarketLib.EIP712_DOMAIN, BoundlessMarketLib.EIP712_DOMAIN_VERSION);
         imageUrl = _imageUrl;
     }
 
+    /// @notice Initializes the state added in version 2, when upgrading a market deployed at version 1.
+    /// @dev Version 2 adds batching of calls with `multicall`.
+    function initializeV2() external onlyOwner reinitializer(2) {
+        __Multicall_init();
+    }
+
     function setImageUrl(string calldata _imageUrl) external onlyOwner {
         imageUrl = _imageUrl;
     }
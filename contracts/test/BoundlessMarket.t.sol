@@ -23,6 +23,7 @@ import {RiscZeroMockVerifier} from "risc0/test/RiscZeroMockVerifier.sol";
 import {TestUtils} from "./TestUtils.sol";
 import {Client} from "./clients/Client.sol";
 import {IERC1967} from "@openzeppelin/contracts/interfaces/IERC1967.sol";
+import {Initializable} from "@openzeppelin/contracts-upgradeable/proxy/utils/Initializable.sol";
 import {UnsafeUpgrades, Upgrades} from "openzeppelin-foundry-upgrades/Upgrades.sol";
 import {Options as UpgradeOptions} from "openzeppelin-foundry-upgrades/Options.sol";
 import {IERC20} from "@openzeppelin/contracts/token/ERC20/IERC20.sol";
@@ -720,6 +721,24 @@ contract BoundlessMarketBasicTest is BoundlessMarketTest {
         vm.snapshotGasLastCall("submitRequest: with maxPrice ether");
     }
 
+    function testSubmitRequestsMulticall() public {
+        Client client = getClient(1);
+        bytes[] memory calls = new bytes[](3);
+        ProofRequest[] memory requests = new ProofRequest[](3);
+        for (uint32 i = 0; i < calls.length; i++) {
+            requests[i] = client.request(i + 1);
+            calls[i] = abi.encodeCall(IBoundlessMarket.submitRequest, (requests[i], client.sign(requests[i])));
+        }
+
+        // Expect an event for each request, emitted by the market itself.
+        for (uint256 i = 0; i < requests.length; i++) {
+            vm.expectEmit(true, true, true, true, address(boundlessMarket));
+            emit IBoundlessMarket.RequestSubmitted(requests[i].id);
+        }
+        boundlessMarket.multicall(calls);
+        vm.snapshotGasLastCall("multicall: submitRequest x3");
+    }
+
     function _testLockRequest(bool withSig) private returns (Client, ProofRequest memory) {
         return _testLockRequest(withSig, "");
     }
@@ -3353,6 +3372,41 @@ contract BoundlessMarketUpgradeTest is BoundlessMarketTest {
         assertEq(imageUrl, "https://assessor.dev.null", "Image URL should be the same after upgrade");
     }
 
+    function testUpgradeInitializeV2() public {
+        vm.startPrank(OWNER_WALLET.addr);
+        proxy = UnsafeUpgrades.deployUUPSProxy(
+            address(new BoundlessMarket(setVerifier, ASSESSOR_IMAGE_ID, address(0))),
+            abi.encodeCall(BoundlessMarket.initialize, (OWNER_WALLET.addr, "https://assessor.dev.null"))
+        );
+        boundlessMarket = BoundlessMarket(proxy);
+
+        // Upgrading initializes version 2, at most once.
+        UnsafeUpgrades.upgradeProxy(
+            proxy,
+            address(new BoundlessMarket(setVerifier, ASSESSOR_IMAGE_ID, address(0))),
+            abi.encodeCall(BoundlessMarket.initializeV2, ()),
+            OWNER_WALLET.addr
+        );
+        vm.expectRevert(Initializable.InvalidInitialization.selector);
+        boundlessMarket.initializeV2();
+        vm.stopPrank();
+        assertEq(boundlessMarket.VERSION(), 2);
+
+        // Calls can be batched with multicall after the upgrade.
+        bytes[] memory calls = new bytes[](2);
+        calls[0] = abi.encodeCall(BoundlessMarket.imageInfo, ());
+        calls[1] = abi.encodeCall(BoundlessMarket.VERSION, ());
+        bytes[] memory results = boundlessMarket.multicall(calls);
+        assertEq(abi.decode(results[1], (uint64)), 2);
+    }
+
+    function testInitializeV2OnlyOwner() public {
+        address notOwner = vm.createWallet("NOT_OWNER").addr;
+        vm.prank(notOwner);
+        vm.expectRevert(abi.encodeWithSelector(Ownable.OwnableUnauthorizedAccount.selector, notOwner));
+        boundlessMarket.initializeV2();
+    }
+
     function testTransferOwnership() public {
         address newOwner = vm.createWallet("NEW_OWNER").addr;
         vm.prank(OWNER_WALLET.addr);
@@ -8,6 +8,29 @@ import "forge-std/Test.sol";
 import {Predicate, PredicateLibrary, PredicateType} from "../../src/types/Predicate.sol";
 
 contract PredicateTest is Test {
+    /// @notice Test vector shared with the Rust implementation. Fields are in alphabetical order,
+    /// as required by vm.parseJson.
+    struct Vector {
+        bytes data;
+        string description;
+        bool expected;
+        bytes journal;
+        uint256 predicateType;
+    }
+
+    function testEvalVectors() public view {
+        string memory path = string.concat(vm.projectRoot(), "/contracts/test/types/predicate-vectors.json");
+        Vector[] memory vectors = abi.decode(vm.parseJson(vm.readFile(path), ".vectors"), (Vector[]));
+        assertGt(vectors.length, 0, "no test vectors");
+        for (uint256 i = 0; i < vectors.length; i++) {
+            Vector memory vector = vectors[i];
+            Predicate memory predicate =
+                Predicate({predicateType: PredicateType(vector.predicateType), data: vector.data});
+            bool result = predicate.eval(vector.journal, sha256(vector.journal));
+            assertEq(result, vector.expected, vector.description);
+        }
+    }
+
     function testEvalDigestMatch() public pure {
         bytes32 hash = keccak256("test");
         Predicate memory predicate = PredicateLibrary.createDigestMatchPredicate(hash);
@@ -53,4 +76,32 @@ contract PredicateTest is Test {
         bool result = predicate.eval(journal, keccak256(journal));
         assertFalse(result, "Predicate evaluation should be false for non-matching prefix");
     }
+
+    function testEvalMaskedMatch() public pure {
+        bytes memory journal = abi.encode(uint256(7), uint256(42));
+        Predicate memory predicate = PredicateLibrary.createMaskedMatchPredicate(
+            32, abi.encodePacked(type(uint256).max), abi.encode(uint256(42))
+        );
+        assertTrue(predicate.eval(journal, sha256(journal)), "ABI word should match");
+
+        predicate = PredicateLibrary.createMaskedMatchPredicate(
+            32, abi.encodePacked(type(uint256).max), abi.encode(uint256(43))
+        );
+        assertFalse(predicate.eval(journal, sha256(journal)), "ABI word should not match");
+    }
+
+    function testEvalAnyDigestMatch() public pure {
+        bytes32[] memory digests = new bytes32[](2);
+        digests[0] = sha256("a");
+        digests[1] = sha256("b");
+        Predicate memory predicate = PredicateLibrary.createAnyDigestMatchPredicate(digests);
+        assertTrue(predicate.eval("b", sha256("b")), "Digest should be in the set");
+        assertFalse(predicate.eval("c", sha256("c")), "Digest should not be in the set");
+    }
+
+    function testEvalLengthRange() public pure {
+        Predicate memory predicate = PredicateLibrary.createLengthRangePredicate(2, 4);
+        assertTrue(predicate.eval("abc", sha256("abc")), "Length should be in range");
+        assertFalse(predicate.eval("abcde", sha256("abcde")), "Length should be out of range");
+    }
 }
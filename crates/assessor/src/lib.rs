@@ -223,6 +223,19 @@ mod tests {
         claim.evaluate_requirements().unwrap();
     }
 
+    #[test]
+    #[test_log::test]
+    fn test_claim_masked_match() {
+        let mut request = proving_request(1, Address::ZERO, B256::ZERO, vec![]);
+        request.requirements.predicate = Predicate::masked_match(1, [0xff], [2]);
+
+        let mut claim = Fulfillment { request, signature: vec![], journal: vec![1, 2, 3] };
+        claim.evaluate_requirements().unwrap();
+
+        claim.journal = vec![1, 3, 3];
+        assert!(matches!(claim.evaluate_requirements(), Err(Error::PredicateEvaluationError)));
+    }
+
     #[test]
     #[test_log::test]
     fn test_domain_serde() {
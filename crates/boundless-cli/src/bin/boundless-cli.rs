@@ -40,7 +40,7 @@ use std::{
     io::BufReader,
     num::ParseIntError,
     path::{Path, PathBuf},
-    time::{Duration, SystemTime},
+    time::Duration,
 };
 
 use alloy::{
@@ -52,11 +52,13 @@ use alloy::{
     },
     providers::{network::EthereumWallet, Provider, ProviderBuilder},
     rpc::types::{TransactionInput, TransactionRequest},
-    signers::{local::PrivateKeySigner, Signer},
+    signers::Signer,
     sol_types::SolValue,
 };
 use anyhow::{anyhow, bail, ensure, Context, Result};
-use boundless_cli::{convert_timestamp, fetch_url, DefaultProver, OrderFulfilled};
+use boundless_cli::{
+    convert_timestamp, fetch_assumptions, fetch_guest_env, fetch_url, DefaultProver, OrderFulfilled,
+};
 use clap::{Args, Parser, Subcommand};
 use hex::FromHex;
 use risc0_aggregation::SetInclusionReceiptVerifierParameters;
@@ -64,7 +66,7 @@ use risc0_ethereum_contracts::{set_verifier::SetVerifierService, IRiscZeroVerifi
 use risc0_zkvm::{
     default_executor,
     sha::{Digest, Digestible},
-    ExecutorEnv, Journal, SessionInfo,
+    Journal, SessionInfo,
 };
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
@@ -72,12 +74,16 @@ use url::Url;
 
 use boundless_market::{
     client::{Client, ClientBuilder},
+    clock::Clock,
     contracts::{
-        boundless_market::BoundlessMarketService, Callback, Input, InputType, Offer, Predicate,
-        PredicateType, ProofRequest, RequestId, Requirements, UNSPECIFIED_SELECTOR,
+        boundless_market::BoundlessMarketService, Callback, Input, Offer, Predicate, PredicateType,
+        ProofRequest, RequestId, Requirements, UNSPECIFIED_SELECTOR,
     },
-    input::{GuestEnv, InputBuilder},
+    deployments::{Deployment, Deployments, DEPLOYMENTS_FILE_ENV},
+    input::InputBuilder,
+    request_template::RequestTemplate,
     selector::ProofType,
+    signer::{AnySigner, SignerArgs},
     storage::{StorageProvider, StorageProviderConfig},
 };
 
@@ -210,6 +216,40 @@ enum RequestCommands {
         callback_gas_limit: Option<u64>,
     },
 
+    /// Submit a proof request built from a YAML, JSON or TOML request template
+    SubmitTemplate {
+        /// Storage provider to use
+        #[clap(flatten)]
+        storage_config: Option<StorageProviderConfig>,
+
+        /// Path to the request template
+        template: PathBuf,
+
+        /// Template variable, given as NAME=VALUE. Takes precedence over the template variables.
+        #[clap(long = "var", value_parser = parse_template_var)]
+        vars: Vec<(String, String)>,
+
+        /// Optional identifier for the request
+        #[clap(long)]
+        id: Option<u32>,
+
+        /// Wait until the request is fulfilled
+        #[clap(short, long, default_value = "false")]
+        wait: bool,
+
+        /// Submit the request offchain via the provided order stream service url
+        #[clap(short, long, requires = "order_stream_url")]
+        offchain: bool,
+
+        /// Offchain order stream service URL to submit offchain requests to
+        #[clap(
+            long,
+            env = "ORDER_STREAM_URL",
+            default_value = "https://order-stream.beboundless.xyz"
+        )]
+        order_stream_url: Option<Url>,
+    },
+
     /// Get the status of a given request
     Status {
         /// The proof request identifier
@@ -398,21 +438,25 @@ struct GlobalConfig {
     #[clap(short, long, env = "RPC_URL", default_value = "http://localhost:8545")]
     rpc_url: Url,
 
-    /// Private key of the wallet (without 0x prefix)
-    #[clap(long, env = "PRIVATE_KEY", hide_env_values = true)]
-    private_key: PrivateKeySigner,
+    /// Wallet signer, as a private key or a signer URI
+    #[clap(flatten)]
+    signer: SignerArgs,
 
-    /// Address of the market contract
+    /// Address of the market contract, defaults to the deployment on the chain
     #[clap(short, long, env = "BOUNDLESS_MARKET_ADDRESS")]
-    boundless_market_address: Address,
+    boundless_market_address: Option<Address>,
 
-    /// Address of the VerifierRouter contract
+    /// Address of the VerifierRouter contract, defaults to the deployment on the chain
     #[clap(short, long, env = "VERIFIER_ADDRESS")]
-    verifier_address: Address,
+    verifier_address: Option<Address>,
 
-    /// Address of the SetVerifier contract
+    /// Address of the SetVerifier contract, defaults to the deployment on the chain
     #[clap(short, long, env = "SET_VERIFIER_ADDRESS")]
-    set_verifier_address: Address,
+    set_verifier_address: Option<Address>,
+
+    /// Path of a TOML file overriding or extending the known deployments
+    #[clap(long, env = DEPLOYMENTS_FILE_ENV)]
+    deployments_file: Option<PathBuf>,
 
     /// Tx timeout in seconds
     #[clap(long, env = "TX_TIMEOUT", value_parser = |arg: &str| -> Result<Duration, ParseIntError> {Ok(Duration::from_secs(arg.parse()?))})]
@@ -423,6 +467,42 @@ struct GlobalConfig {
     log_level: LevelFilter,
 }
 
+impl GlobalConfig {
+    /// Resolves the contract addresses, from the arguments or from the known deployment on the
+    /// chain of the provider.
+    async fn deployment(&self, provider: &impl Provider<Ethereum>) -> Result<Deployment> {
+        let chain_id = provider.get_chain_id().await.context("failed to get chain ID")?;
+        if let (Some(market), Some(set_verifier), Some(verifier)) =
+            (self.boundless_market_address, self.set_verifier_address, self.verifier_address)
+        {
+            return Ok(Deployment::new(chain_id, market, set_verifier)
+                .with_verifier_router_address(verifier));
+        }
+
+        let deployments = match &self.deployments_file {
+            Some(path) => Deployments::default()
+                .with_overrides_from_file(path)
+                .with_context(|| format!("failed to load deployments file {}", path.display()))?,
+            None => Deployments::default(),
+        };
+        let mut deployment = deployments.resolve(chain_id)?;
+        if let Some(market) = self.boundless_market_address {
+            if market != deployment.boundless_market_address {
+                // The order stream of the deployment only serves its own market.
+                deployment.order_stream_url = None;
+            }
+            deployment.boundless_market_address = market;
+        }
+        if let Some(set_verifier) = self.set_verifier_address {
+            deployment.set_verifier_address = set_verifier;
+        }
+        if let Some(verifier) = self.verifier_address {
+            deployment.verifier_router_address = Some(verifier);
+        }
+        Ok(deployment)
+    }
+}
+
 #[derive(Parser, Debug)]
 #[clap(author, version, about = "CLI for the Boundless market", long_about = None)]
 struct MainArgs {
@@ -453,8 +533,11 @@ async fn main() -> Result<()> {
             }
             if err.kind() == clap::error::ErrorKind::MissingRequiredArgument {
                 eprintln!("\nThe Boundless CLI requires certain configuration values, which can be provided either:");
-                eprintln!("1. As environment variables (PRIVATE_KEY, BOUNDLESS_MARKET_ADDRESS, VERIFIER_ADDRESS, SET_VERIFIER_ADDRESS)");
-                eprintln!("2. As command-line arguments (--private-key <KEY> --boundless-market-address <ADDR>  --verifier-address <ADDR> --set-verifier-address <ADDR>)");
+                eprintln!("1. As environment variables (PRIVATE_KEY or SIGNER_URI)");
+                eprintln!(
+                    "2. As command-line arguments (--private-key <KEY> or --signer-uri <URI>)"
+                );
+                eprintln!("Contract addresses default to the known deployment on the chain of the RPC URL, and can be overridden with BOUNDLESS_MARKET_ADDRESS, VERIFIER_ADDRESS and SET_VERIFIER_ADDRESS.");
                 eprintln!();
             }
 
@@ -482,12 +565,14 @@ async fn main() -> Result<()> {
 }
 
 pub(crate) async fn run(args: &MainArgs) -> Result<()> {
-    let caller = args.config.private_key.address();
-    let wallet = EthereumWallet::from(args.config.private_key.clone());
+    let signer = args.config.signer.signer().await.context("Failed to create signer")?;
+    let caller = signer.address();
+    let wallet = EthereumWallet::from(signer.clone());
     let provider = ProviderBuilder::new().wallet(wallet).on_http(args.config.rpc_url.clone());
+    let deployment = args.config.deployment(&provider).await?;
 
     let mut boundless_market =
-        BoundlessMarketService::new(args.config.boundless_market_address, provider.clone(), caller);
+        BoundlessMarketService::new(deployment.boundless_market_address, provider.clone(), caller);
 
     if let Some(tx_timeout) = args.config.tx_timeout {
         boundless_market = boundless_market.with_timeout(tx_timeout);
@@ -495,18 +580,35 @@ pub(crate) async fn run(args: &MainArgs) -> Result<()> {
 
     match &args.command {
         Command::Account(account_cmd) => {
-            handle_account_command(account_cmd, boundless_market, args.config.private_key.clone())
-                .await
+            handle_account_command(account_cmd, boundless_market, &signer).await
         }
         Command::Request(request_cmd) => {
-            handle_request_command(request_cmd, args, boundless_market, provider.clone()).await
+            handle_request_command(
+                request_cmd,
+                args,
+                &signer,
+                &deployment,
+                boundless_market,
+                provider.clone(),
+            )
+            .await
         }
         Command::Proving(proving_cmd) => {
-            handle_proving_command(proving_cmd, args, boundless_market, caller, provider.clone())
-                .await
+            handle_proving_command(
+                proving_cmd,
+                args,
+                &signer,
+                &deployment,
+                boundless_market,
+                caller,
+                provider.clone(),
+            )
+            .await
         }
         Command::Ops(operation_cmd) => handle_ops_command(operation_cmd, boundless_market).await,
-        Command::Config { show_sensitive } => handle_config_command(args, *show_sensitive).await,
+        Command::Config { show_sensitive } => {
+            handle_config_command(args, &signer, &deployment, *show_sensitive).await
+        }
     }
 }
 
@@ -532,7 +634,7 @@ where
 async fn handle_account_command<P>(
     cmd: &AccountCommands,
     boundless_market: BoundlessMarketService<P>,
-    private_key: PrivateKeySigner,
+    signer: &AnySigner,
 ) -> Result<()>
 where
     P: Provider<Ethereum> + 'static + Clone,
@@ -559,7 +661,7 @@ where
         }
         AccountCommands::DepositStake { amount } => {
             tracing::info!("Depositing {} HP as stake", format_ether(*amount));
-            match boundless_market.deposit_stake_with_permit(*amount, &private_key).await {
+            match boundless_market.deposit_stake_with_permit(*amount, signer).await {
                 Ok(_) => {
                     tracing::info!("Successfully deposited {} HP as stake", format_ether(*amount));
                     Ok(())
@@ -596,6 +698,8 @@ where
 async fn handle_request_command<P>(
     cmd: &RequestCommands,
     args: &MainArgs,
+    signer: &AnySigner,
+    deployment: &Deployment,
     boundless_market: BoundlessMarketService<P>,
     provider: impl Provider<Ethereum> + 'static + Clone,
 ) -> Result<()>
@@ -615,10 +719,9 @@ where
                 )
                 .transpose()?;
             let client = ClientBuilder::new()
-                .with_private_key(args.config.private_key.clone())
+                .with_signer(signer.clone())
                 .with_rpc_url(args.config.rpc_url.clone())
-                .with_boundless_market_address(args.config.boundless_market_address)
-                .with_set_verifier_address(args.config.set_verifier_address)
+                .with_deployment(deployment.clone())
                 .with_storage_provider_config(offer_args.storage_config.clone())
                 .await?
                 .with_order_stream_url(order_stream_url)
@@ -626,7 +729,7 @@ where
                 .build()
                 .await?;
 
-            submit_offer(client, &args.config.private_key, offer_args).await
+            submit_offer(client, signer, offer_args).await
         }
         RequestCommands::Submit {
             storage_config,
@@ -654,10 +757,9 @@ where
                 )
                 .transpose()?;
             let client = ClientBuilder::new()
-                .with_private_key(args.config.private_key.clone())
+                .with_signer(signer.clone())
                 .with_rpc_url(args.config.rpc_url.clone())
-                .with_boundless_market_address(args.config.boundless_market_address)
-                .with_set_verifier_address(args.config.set_verifier_address)
+                .with_deployment(deployment.clone())
                 .with_order_stream_url(order_stream_url.clone())
                 .with_storage_provider_config(storage_config.clone())
                 .await?
@@ -669,7 +771,7 @@ where
                 id,
                 yaml_request,
                 client,
-                &args.config.private_key,
+                signer,
                 SubmitOptions {
                     wait: *wait,
                     offchain: *offchain,
@@ -681,6 +783,44 @@ where
             )
             .await
         }
+        RequestCommands::SubmitTemplate {
+            storage_config,
+            template,
+            vars,
+            id,
+            wait,
+            offchain,
+            order_stream_url,
+        } => {
+            tracing::info!("Submitting proof request from template");
+            let id = match id {
+                Some(id) => *id,
+                None => boundless_market.index_from_rand().await?,
+            };
+
+            let order_stream_url = offchain
+                .then_some(
+                    order_stream_url
+                        .clone()
+                        .ok_or(anyhow!("offchain flag set, but order stream URL not provided")),
+                )
+                .transpose()?;
+            let client = ClientBuilder::new()
+                .with_signer(signer.clone())
+                .with_rpc_url(args.config.rpc_url.clone())
+                .with_deployment(deployment.clone())
+                .with_order_stream_url(order_stream_url.clone())
+                .with_storage_provider_config(storage_config.clone())
+                .await?
+                .with_timeout(args.config.tx_timeout)
+                .build()
+                .await?;
+
+            let vars = vars.iter().cloned().collect();
+            let template = RequestTemplate::load_with_variables(template, &vars)
+                .with_context(|| format!("Failed to load request template at {template:?}"))?;
+            submit_template(id, &template, client, signer, *wait, *offchain).await
+        }
         RequestCommands::Status { request_id, expires_at } => {
             tracing::info!("Checking status for request 0x{:x}", request_id);
             let status = boundless_market.get_status(*request_id, *expires_at).await?;
@@ -702,7 +842,10 @@ where
             tracing::info!("Verifying proof for request 0x{:x}", request_id);
             let (journal, seal) = boundless_market.get_request_fulfillment(*request_id).await?;
             let journal_digest = <[u8; 32]>::from(Journal::new(journal.to_vec()).digest()).into();
-            let verifier = IRiscZeroVerifier::new(args.config.verifier_address, provider.clone());
+            let verifier_address = deployment
+                .verifier_router_address
+                .context("verifier router address not set; use --verifier-address")?;
+            let verifier = IRiscZeroVerifier::new(verifier_address, provider.clone());
 
             verifier
                 .verify(seal, *image_id, journal_digest)
@@ -720,6 +863,8 @@ where
 async fn handle_proving_command<P>(
     cmd: &ProvingCommands,
     args: &MainArgs,
+    signer: &AnySigner,
+    deployment: &Deployment,
     boundless_market: BoundlessMarketService<P>,
     caller: Address,
     provider: impl Provider<Ethereum> + 'static + Clone,
@@ -744,10 +889,9 @@ where
             } else if let Some(request_id) = request_id {
                 tracing::debug!("Loading request from blockchain: 0x{:x}", request_id);
                 let client = ClientBuilder::new()
-                    .with_private_key(args.config.private_key.clone())
+                    .with_signer(signer.clone())
                     .with_rpc_url(args.config.rpc_url.clone())
-                    .with_boundless_market_address(args.config.boundless_market_address)
-                    .with_set_verifier_address(args.config.set_verifier_address)
+                    .with_deployment(deployment.clone())
                     .with_order_stream_url(order_stream_url.clone())
                     .with_timeout(args.config.tx_timeout)
                     .build()
@@ -778,7 +922,7 @@ where
             let domain = boundless_market.eip712_domain().await?;
 
             let mut set_verifier =
-                SetVerifierService::new(args.config.set_verifier_address, provider.clone(), caller);
+                SetVerifierService::new(deployment.set_verifier_address, provider.clone(), caller);
 
             if let Some(tx_timeout) = args.config.tx_timeout {
                 set_verifier = set_verifier.with_timeout(tx_timeout);
@@ -791,10 +935,9 @@ where
             let prover = DefaultProver::new(set_builder_elf, assessor_elf, caller, domain)?;
 
             let client = ClientBuilder::new()
-                .with_private_key(args.config.private_key.clone())
+                .with_signer(signer.clone())
                 .with_rpc_url(args.config.rpc_url.clone())
-                .with_boundless_market_address(args.config.boundless_market_address)
-                .with_set_verifier_address(args.config.set_verifier_address)
+                .with_deployment(deployment.clone())
                 .with_order_stream_url(order_stream_url.clone())
                 .with_timeout(args.config.tx_timeout)
                 .build()
@@ -806,7 +949,7 @@ where
             let sig: Bytes = order.signature.as_bytes().into();
             order.request.verify_signature(
                 &sig,
-                args.config.boundless_market_address,
+                deployment.boundless_market_address,
                 boundless_market.get_chain_id().await?,
             )?;
 
@@ -848,10 +991,9 @@ where
         ProvingCommands::Lock { request_id, request_digest, tx_hash, order_stream_url } => {
             tracing::info!("Locking proof request 0x{:x}", request_id);
             let client = ClientBuilder::new()
-                .with_private_key(args.config.private_key.clone())
+                .with_signer(signer.clone())
                 .with_rpc_url(args.config.rpc_url.clone())
-                .with_boundless_market_address(args.config.boundless_market_address)
-                .with_set_verifier_address(args.config.set_verifier_address)
+                .with_deployment(deployment.clone())
                 .with_order_stream_url(order_stream_url.clone())
                 .with_timeout(args.config.tx_timeout)
                 .build()
@@ -863,7 +1005,7 @@ where
             let sig: Bytes = order.signature.as_bytes().into();
             order.request.verify_signature(
                 &sig,
-                args.config.boundless_market_address,
+                deployment.boundless_market_address,
                 boundless_market.get_chain_id().await?,
             )?;
 
@@ -895,7 +1037,7 @@ where
     if offer.biddingStart == 0 {
         // Adding a delay to bidding start lets provers see and evaluate the request
         // before the price starts to ramp up
-        offer = Offer { biddingStart: now_timestamp() + 30, ..offer };
+        offer = Offer { biddingStart: client.clock.now() + 30, ..offer };
     }
 
     // Resolve the ELF and input from command line arguments.
@@ -1053,7 +1195,7 @@ where
     if request_yaml.offer.biddingStart == 0 {
         // Adding a delay to bidding start lets provers see and evaluate the request
         // before the price starts to ramp up
-        request_yaml.offer = Offer { biddingStart: now_timestamp() + 30, ..request_yaml.offer };
+        request_yaml.offer = Offer { biddingStart: client.clock.now() + 30, ..request_yaml.offer };
     }
 
     // Create a new request with the provided ID
@@ -1144,26 +1286,77 @@ where
     Ok(())
 }
 
+/// Submit a proof request built from a request template
+///
+/// Building the request executes the guest if the template predicate or pricing needs it, which
+/// serves as the preflight check.
+async fn submit_template<P, S>(
+    id: u32,
+    template: &RequestTemplate,
+    client: Client<P, S>,
+    signer: &impl Signer,
+    wait: bool,
+    offchain: bool,
+) -> Result<()>
+where
+    P: Provider<Ethereum> + 'static + Clone,
+    S: StorageProvider + Clone,
+{
+    let mut request =
+        template.build_request(&client).await.context("Failed to build request from template")?;
+    request.id = RequestId::new(client.caller(), id).into();
+
+    // Submit the request
+    let (request_id, expires_at) = if offchain {
+        tracing::info!("Submitting request offchain");
+        client.submit_request_offchain_with_signer(&request, signer).await?
+    } else {
+        tracing::info!("Submitting request onchain");
+        client.submit_request_with_signer(&request, signer).await?
+    };
+
+    tracing::info!(
+        "Submitted request 0x{request_id:x}, expires at {}",
+        convert_timestamp(expires_at)
+    );
+
+    // Wait for fulfillment if requested
+    if wait {
+        tracing::info!("Waiting for request fulfillment...");
+        let (journal, seal) = client
+            .wait_for_request_fulfillment(request_id, Duration::from_secs(5), expires_at)
+            .await?;
+
+        tracing::info!("Request fulfilled!");
+        tracing::info!(
+            "Journal: {} - Seal: {}",
+            serde_json::to_string_pretty(&journal)?,
+            serde_json::to_string_pretty(&seal)?
+        );
+    }
+
+    Ok(())
+}
+
+/// Parse a template variable given as NAME=VALUE
+fn parse_template_var(arg: &str) -> Result<(String, String)> {
+    let (name, value) =
+        arg.split_once('=').ok_or_else(|| anyhow!("expected NAME=VALUE, got {arg:?}"))?;
+    Ok((name.to_string(), value.to_string()))
+}
+
 /// Execute a proof request using the RISC Zero zkVM executor
 async fn execute(request: &ProofRequest) -> Result<SessionInfo> {
     tracing::info!("Fetching ELF from {}", request.imageUrl);
     let elf = fetch_url(&request.imageUrl).await?;
 
     tracing::info!("Processing input");
-    let input = match request.input.inputType {
-        InputType::Inline => GuestEnv::decode(&request.input.data)?.stdin,
-        InputType::Url => {
-            let input_url =
-                std::str::from_utf8(&request.input.data).context("Input URL is not valid UTF-8")?;
-            tracing::info!("Fetching input from {}", input_url);
-            GuestEnv::decode(&fetch_url(input_url).await?)?.stdin
-        }
-        _ => bail!("Unsupported input type"),
-    };
+    let env = fetch_guest_env(request).await?;
+    let assumptions = fetch_assumptions(&env).await?;
 
     tracing::info!("Executing program in zkVM");
     r0vm_is_installed()?;
-    let env = ExecutorEnv::builder().write_slice(&input).build()?;
+    let env = env.into_executor_env(assumptions)?;
     default_executor().execute(env, &elf)
 }
 
@@ -1177,27 +1370,33 @@ fn r0vm_is_installed() -> Result<()> {
     }
 }
 
-// Get current timestamp with appropriate error handling
-fn now_timestamp() -> u64 {
-    SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).expect("Time went backwards").as_secs()
-}
-
 /// Handle config command
-async fn handle_config_command(args: &MainArgs, show_sensitive: bool) -> Result<()> {
+async fn handle_config_command(
+    args: &MainArgs,
+    signer: &AnySigner,
+    deployment: &Deployment,
+    show_sensitive: bool,
+) -> Result<()> {
     tracing::info!("Displaying CLI configuration");
     println!("\n=== Boundless CLI Configuration ===\n");
 
     // Show configuration
     println!("RPC URL: {}", args.config.rpc_url);
-    if show_sensitive {
-        println!("Private Key: <available but cannot be displayed>");
-    } else {
-        println!("Private Key: <hidden> (use --show-sensitive to reveal)");
+    match (&args.config.signer.signer_uri, show_sensitive) {
+        (Some(_), true) => println!("Signer: <signer URI available but cannot be displayed>"),
+        (Some(_), false) => {
+            println!("Signer: <signer URI hidden> (use --show-sensitive to reveal)")
+        }
+        (None, true) => println!("Private Key: <available but cannot be displayed>"),
+        (None, false) => println!("Private Key: <hidden> (use --show-sensitive to reveal)"),
     }
-    println!("Wallet Address: {}", args.config.private_key.address());
-    println!("Boundless Market Address: {}", args.config.boundless_market_address);
-    println!("Verifier Address: {}", args.config.verifier_address);
-    println!("Set Verifier Address: {}", args.config.set_verifier_address);
+    println!("Wallet Address: {}", signer.address());
+    println!("Boundless Market Address: {}", deployment.boundless_market_address);
+    match deployment.verifier_router_address {
+        Some(address) => println!("Verifier Address: {address}"),
+        None => println!("Verifier Address: <not set>"),
+    }
+    println!("Set Verifier Address: {}", deployment.set_verifier_address);
     if let Some(timeout) = args.config.tx_timeout {
         println!("Transaction Timeout: {} seconds", timeout.as_secs());
     } else {
@@ -1208,7 +1407,7 @@ async fn handle_config_command(args: &MainArgs, show_sensitive: bool) -> Result<
     // Validate RPC connection
     println!("\n=== Environment Validation ===\n");
     print!("Testing RPC connection... ");
-    let wallet = EthereumWallet::from(args.config.private_key.clone());
+    let wallet = EthereumWallet::from(signer.clone());
     let provider = ProviderBuilder::new().wallet(wallet).on_http(args.config.rpc_url.clone());
 
     let rpc_ok = match provider.get_chain_id().await {
@@ -1225,9 +1424,9 @@ async fn handle_config_command(args: &MainArgs, show_sensitive: bool) -> Result<
     // Check market contract
     print!("Testing Boundless Market contract... ");
     let boundless_market = BoundlessMarketService::new(
-        args.config.boundless_market_address,
+        deployment.boundless_market_address,
         provider.clone(),
-        args.config.private_key.address(),
+        signer.address(),
     );
 
     let market_ok = match boundless_market.get_chain_id().await {
@@ -1244,9 +1443,9 @@ async fn handle_config_command(args: &MainArgs, show_sensitive: bool) -> Result<
     // Check set verifier contract
     print!("Testing Set Verifier contract... ");
     let set_verifier = SetVerifierService::new(
-        args.config.set_verifier_address,
+        deployment.set_verifier_address,
         provider.clone(),
-        args.config.private_key.address(),
+        signer.address(),
     );
 
     let (image_id, _) = match set_verifier.image_info().await {
@@ -1271,22 +1470,29 @@ async fn handle_config_command(args: &MainArgs, show_sensitive: bool) -> Result<
     call_data.extend_from_slice(&hex::decode("3cadf449")?);
     call_data.extend_from_slice(&FixedBytes::from(selector).abi_encode());
 
-    // Create a transaction request with the call data
-    let tx = TransactionRequest {
-        to: Some(TxKind::Call(args.config.verifier_address)),
-        input: TransactionInput::new(call_data.into()),
-        ..Default::default()
-    };
-
     // Check verifier contract
     print!("Testing VerifierRouter contract... ");
-    let verifier_ok = match provider.call(tx).await {
-        Ok(_) => {
-            println!("✅ Contract responds");
-            true
+    let verifier_ok = match deployment.verifier_router_address {
+        Some(verifier_address) => {
+            // Create a transaction request with the call data
+            let tx = TransactionRequest {
+                to: Some(TxKind::Call(verifier_address)),
+                input: TransactionInput::new(call_data.into()),
+                ..Default::default()
+            };
+            match provider.call(tx).await {
+                Ok(_) => {
+                    println!("✅ Contract responds");
+                    true
+                }
+                Err(e) => {
+                    println!("❌ Contract error: {}", e);
+                    false
+                }
+            }
         }
-        Err(e) => {
-            println!("❌ Contract error: {}", e);
+        None => {
+            println!("❌ Address not set");
             false
         }
     };
@@ -1311,6 +1517,7 @@ mod tests {
         providers::WalletProvider,
     };
     use boundless_market::{
+        clock::SystemClock,
         contracts::{hit_points::default_allowance, RequestStatus},
         selector::is_groth16_selector,
     };
@@ -1339,7 +1546,7 @@ mod tests {
             Offer {
                 minPrice: U256::from(20000000000000u64),
                 maxPrice: U256::from(40000000000000u64),
-                biddingStart: now_timestamp(),
+                biddingStart: SystemClock.now(),
                 timeout: 420,
                 lockTimeout: 420,
                 rampUpPeriod: 1,
@@ -1383,10 +1590,11 @@ mod tests {
 
         let config = GlobalConfig {
             rpc_url: anvil.endpoint_url(),
-            private_key,
-            boundless_market_address: ctx.boundless_market_address,
-            verifier_address: ctx.verifier_address,
-            set_verifier_address: ctx.set_verifier_address,
+            signer: private_key.into(),
+            boundless_market_address: Some(ctx.boundless_market_address),
+            verifier_address: Some(ctx.verifier_address),
+            set_verifier_address: Some(ctx.set_verifier_address),
+            deployments_file: None,
             tx_timeout: None,
             log_level: LevelFilter::INFO,
         };
@@ -1839,10 +2047,11 @@ mod tests {
 
         let prover_config = GlobalConfig {
             rpc_url: anvil.endpoint_url(),
-            private_key: ctx.prover_signer.clone(),
-            boundless_market_address: ctx.boundless_market_address,
-            verifier_address: ctx.verifier_address,
-            set_verifier_address: ctx.set_verifier_address,
+            signer: ctx.prover_signer.clone().into(),
+            boundless_market_address: Some(ctx.boundless_market_address),
+            verifier_address: Some(ctx.verifier_address),
+            set_verifier_address: Some(ctx.set_verifier_address),
+            deployments_file: None,
             tx_timeout: None,
             log_level: LevelFilter::INFO,
         };
@@ -2107,10 +2316,11 @@ mod tests {
 
         let prover_config = GlobalConfig {
             rpc_url: anvil.endpoint_url(),
-            private_key: ctx.prover_signer.clone(),
-            boundless_market_address: ctx.boundless_market_address,
-            verifier_address: ctx.verifier_address,
-            set_verifier_address: ctx.set_verifier_address,
+            signer: ctx.prover_signer.clone().into(),
+            boundless_market_address: Some(ctx.boundless_market_address),
+            verifier_address: Some(ctx.verifier_address),
+            set_verifier_address: Some(ctx.set_verifier_address),
+            deployments_file: None,
             tx_timeout: None,
             log_level: LevelFilter::INFO,
         };
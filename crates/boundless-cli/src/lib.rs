@@ -38,7 +38,7 @@ use url::Url;
 use boundless_market::{
     contracts::{
         AssessorJournal, AssessorReceipt, EIP712DomainSaltless,
-        Fulfillment as BoundlessFulfillment, InputType,
+        Fulfillment as BoundlessFulfillment, InputType, ProofRequest,
     },
     input::{GuestEnv, InputBuilder},
     order_stream_client::Order,
@@ -100,6 +100,32 @@ pub async fn fetch_url(url_str: &str) -> Result<Vec<u8>> {
     }
 }
 
+/// Fetches and decodes the [GuestEnv] of the given request.
+pub async fn fetch_guest_env(request: &ProofRequest) -> Result<GuestEnv> {
+    Ok(match request.input.inputType {
+        InputType::Inline => GuestEnv::decode(&request.input.data)?,
+        InputType::Url => {
+            let input_url =
+                std::str::from_utf8(&request.input.data).context("input url is not utf8")?;
+            tracing::info!("Fetching input from {}", input_url);
+            GuestEnv::decode(&fetch_url(input_url).await?)?
+        }
+        _ => bail!("Unsupported input type"),
+    })
+}
+
+/// Fetches the assumption receipts referenced by the given [GuestEnv].
+pub async fn fetch_assumptions(env: &GuestEnv) -> Result<Vec<Receipt>> {
+    let mut receipts = Vec::with_capacity(env.assumptions.len());
+    for url in &env.assumptions {
+        tracing::info!("Fetching assumption receipt from {}", url);
+        let receipt = bincode::deserialize(&fetch_url(url).await?)
+            .with_context(|| format!("failed to decode assumption receipt from {url}"))?;
+        receipts.push(receipt);
+    }
+    Ok(receipts)
+}
+
 async fn fetch_http(url: &Url) -> Result<Vec<u8>> {
     let response = reqwest::get(url.as_str()).await?;
     let status = response.status();
@@ -188,6 +214,24 @@ impl DefaultProver {
         Ok(receipt)
     }
 
+    // Proves the given [elf] with the given [env], fetching any assumption receipts it references.
+    pub(crate) async fn prove_env(
+        &self,
+        elf: Vec<u8>,
+        env: GuestEnv,
+        opts: ProverOpts,
+    ) -> Result<Receipt> {
+        let assumptions = fetch_assumptions(&env).await?;
+        let receipt = tokio::task::spawn_blocking(move || {
+            let env = env.into_executor_env(assumptions)?;
+
+            default_prover().prove_with_opts(env, &elf, &opts)
+        })
+        .await??
+        .receipt;
+        Ok(receipt)
+    }
+
     pub(crate) async fn compress(&self, succinct_receipt: &Receipt) -> Result<Receipt> {
         let prover = default_prover();
         if prover.get_name() == "bonsai" {
@@ -244,29 +288,15 @@ impl DefaultProver {
     ) -> Result<(BoundlessFulfillment, Receipt, AssessorReceipt)> {
         let request = order.request.clone();
         let order_elf = fetch_url(&request.imageUrl).await?;
-        let order_input: Vec<u8> = match request.input.inputType {
-            InputType::Inline => GuestEnv::decode(&request.input.data)?.stdin,
-            InputType::Url => {
-                GuestEnv::decode(
-                    &fetch_url(
-                        std::str::from_utf8(&request.input.data)
-                            .context("input url is not utf8")?,
-                    )
-                    .await?,
-                )?
-                .stdin
-            }
-            _ => bail!("Unsupported input type"),
-        };
+        let order_env = fetch_guest_env(&request).await?;
 
         let selector = request.requirements.selector;
         if !self.supported_selectors.is_supported(selector) {
             bail!("Unsupported selector {}", request.requirements.selector);
         };
 
-        let order_receipt = self
-            .prove(order_elf.clone(), order_input.clone(), vec![], ProverOpts::succinct())
-            .await?;
+        let order_receipt =
+            self.prove_env(order_elf.clone(), order_env, ProverOpts::succinct()).await?;
 
         let order_journal = order_receipt.journal.bytes.clone();
         let order_image_id = compute_image_id(&order_elf)?;
@@ -12,10 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{env, fs, path::Path};
+use std::{collections::BTreeSet, env, fs, path::Path};
+
+use alloy_json_abi::JsonAbi;
 
 // Contracts to copy to the artificats folder for. If the contract is a directory, all .sol files in the directory.
-const CONTRACTS_TO_COPY: [&str; 3] = ["IBoundlessMarket.sol", "IHitPoints.sol", "types"];
+// IBoundlessMarket is not copied, as its bindings are generated from the ABI snapshot.
+const CONTRACTS_TO_COPY: [&str; 2] = ["IHitPoints.sol", "types"];
 
 // Contracts to exclude from generating types for automatically.
 const EXCLUDE_CONTRACTS: [&str; 2] = [
@@ -39,6 +42,14 @@ const ARTIFACT_TARGET_CONTRACTS: [&str; 8] = [
 // Output filename for the generated types. The file is placed in the build directory.
 const BOUNDLESS_MARKET_RS: &str = "boundless_market_generated.rs";
 
+// Versioned snapshot of the IBoundlessMarket ABI, relative to the crate root. Consumers can diff it
+// across releases to detect breaking changes, and the build fails if it drifts from the contracts.
+const ABI_SNAPSHOT: &str = "abi/IBoundlessMarket.json";
+
+// Output filename for the ABI checks generated from the snapshot. The file is placed in the build
+// directory.
+const BOUNDLESS_MARKET_ABI_RS: &str = "boundless_market_abi.rs";
+
 fn insert_derives(contents: &mut String, find_str: &str, insert_str: &str) {
     let mut cur_pos = 0;
     while let Some(struct_pos) =
@@ -50,9 +61,51 @@ fn insert_derives(contents: &mut String, find_str: &str, insert_str: &str) {
     }
 }
 
-// TODO: This is a bit fragile (e.g. it breaks if there is an unmatched brace in a comment).
-// Using alloy's `syn-solidity` would be the robust way of doing this.
-// (It may also be over-engineering, as we'd like to deprecate this whole script)
+// Returns a copy of the Solidity source with comments and string literals replaced by spaces, such
+// that keywords and braces can be searched for without matching text inside of them. Byte offsets
+// are preserved, so positions in the masked copy are valid positions in the source.
+fn mask_comments_and_strings(source: &str) -> String {
+    let bytes = source.as_bytes();
+    let find_from = |start: usize, pattern: &[u8]| {
+        bytes[start..].windows(pattern.len()).position(|w| w == pattern).map(|i| start + i)
+    };
+
+    let mut masked = bytes.to_vec();
+    let mut i = 0;
+    while i < bytes.len() {
+        let end = match (bytes[i], bytes.get(i + 1)) {
+            (b'/', Some(b'/')) => find_from(i + 2, b"\n").unwrap_or(bytes.len()),
+            (b'/', Some(b'*')) => find_from(i + 2, b"*/").map_or(bytes.len(), |j| j + 2),
+            (quote @ (b'"' | b'\''), _) => {
+                let mut j = i + 1;
+                while j < bytes.len() && bytes[j] != quote {
+                    j += if bytes[j] == b'\\' { 2 } else { 1 };
+                }
+                (j + 1).min(bytes.len())
+            }
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        masked[i..end].fill(b' ');
+        i = end;
+    }
+    // Only whole comments and literals are masked, and they start and end on ASCII characters.
+    String::from_utf8(masked).unwrap()
+}
+
+// Finds the first occurrence of the given keyword in masked Solidity source.
+fn find_keyword(code: &str, keyword: &str) -> Option<usize> {
+    let is_ident_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '$';
+    code.match_indices(keyword).map(|(i, _)| i).find(|&i| {
+        let before = code[..i].chars().next_back();
+        let after = code[i + keyword.len()..].chars().next();
+        !before.is_some_and(is_ident_char) && after.is_some_and(char::is_whitespace)
+    })
+}
+
+// Finds the brace closing the first opened brace in masked Solidity source.
 fn find_matching_brace(contents: &str) -> Option<usize> {
     let mut stack = Vec::new();
     for (i, c) in contents.char_indices() {
@@ -70,11 +123,85 @@ fn find_matching_brace(contents: &str) -> Option<usize> {
     None
 }
 
+// Returns the Solidity type of an ABI parameter, using the name of its internal type such that
+// structs, enums and user defined value types refer to the ones declared in the types/*.sol files.
+fn solidity_param_type(param: &serde_json::Value) -> String {
+    let ty = param["type"].as_str().unwrap();
+    let Some(internal_type) = param["internalType"].as_str() else {
+        assert!(!ty.starts_with("tuple"), "missing internal type for tuple parameter {param}");
+        return ty.to_string();
+    };
+    let name = ["struct ", "enum ", "contract "]
+        .iter()
+        .find_map(|prefix| internal_type.strip_prefix(prefix))
+        .unwrap_or(internal_type);
+    // Drop the library or contract qualifier, e.g. `IBoundlessMarket.Foo` becomes `Foo`.
+    name.rsplit_once('.').map_or(name, |(_, name)| name).to_string()
+}
+
+// Returns the comma separated parameters of an ABI item, as Solidity source.
+fn solidity_params(params: &serde_json::Value) -> String {
+    params
+        .as_array()
+        .map(|params| {
+            params
+                .iter()
+                .map(|param| {
+                    let mut decl = solidity_param_type(param);
+                    if param["indexed"].as_bool() == Some(true) {
+                        decl.push_str(" indexed");
+                    }
+                    let name = param["name"].as_str().unwrap_or_default();
+                    if !name.is_empty() {
+                        decl.push(' ');
+                        decl.push_str(name);
+                    }
+                    decl
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .unwrap_or_default()
+}
+
+// Generates the Solidity source of the IBoundlessMarket interface from the functions, events and
+// errors of the ABI snapshot, such that the `sol!` bindings match the snapshot by construction.
+fn generate_interface_from_abi(abi: &serde_json::Value) -> String {
+    let mut items = Vec::new();
+    for item in abi.as_array().expect("ABI snapshot must be an array") {
+        let name = item["name"].as_str().unwrap_or_default();
+        let inputs = solidity_params(&item["inputs"]);
+        match item["type"].as_str().unwrap() {
+            "function" => {
+                let mutability = match item["stateMutability"].as_str() {
+                    Some("nonpayable") | None => String::new(),
+                    Some(mutability) => format!(" {mutability}"),
+                };
+                let outputs = solidity_params(&item["outputs"]);
+                let returns = if outputs.is_empty() {
+                    String::new()
+                } else {
+                    format!(" returns ({outputs})")
+                };
+                items.push(format!("function {name}({inputs}) external{mutability}{returns};"));
+            }
+            "event" => {
+                let anonymous =
+                    if item["anonymous"].as_bool() == Some(true) { " anonymous" } else { "" };
+                items.push(format!("event {name}({inputs}){anonymous};"));
+            }
+            "error" => items.push(format!("error {name}({inputs});")),
+            ty => panic!("unsupported item type {ty} in {ABI_SNAPSHOT}"),
+        }
+    }
+    format!("interface IBoundlessMarket {{\n    {}\n}}\n", items.join("\n    "))
+}
+
 // NOTE: if alloy ever fixes https://github.com/alloy-rs/core/issues/688 this function
 // can be deleted and we should be able to just use the alloy::sol! macro
 // Note, we also remove libraries from each file, as some of the libraries reference
 // the `Account` struct, which we do not support (see EXCLUDE_CONTRACTS at top).
-fn rewrite_solidity_interface_files() {
+fn rewrite_solidity_interface_files(abi_snapshot: &serde_json::Value) {
     println!("cargo::rerun-if-env-changed=CARGO_MANIFEST_DIR");
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
     let sol_iface_dir = Path::new(&manifest_dir).join("src").join("contracts").join("artifacts");
@@ -96,8 +223,12 @@ fn rewrite_solidity_interface_files() {
             let mut sol_contents = fs::read_to_string(&path).unwrap();
 
             // Remove libraries from each file.
-            while let Some(start) = sol_contents.find("library ") {
-                if let Some(end) = find_matching_brace(&sol_contents[start..]) {
+            loop {
+                let masked = mask_comments_and_strings(&sol_contents);
+                let Some(start) = find_keyword(&masked, "library") else {
+                    break;
+                };
+                if let Some(end) = find_matching_brace(&masked[start..]) {
                     sol_contents.replace_range(start..start + end + 1, "");
                 } else {
                     // print the file name and panic if we can't find the matching brace
@@ -107,7 +238,8 @@ fn rewrite_solidity_interface_files() {
 
             // skip the sol(rpc) insert if building for the zkvm
             if target_os != "zkvm" {
-                if let Some(iface_pos) = sol_contents.find("interface ") {
+                let masked = mask_comments_and_strings(&sol_contents);
+                if let Some(iface_pos) = find_keyword(&masked, "interface") {
                     sol_contents.insert_str(iface_pos, "#[sol(rpc)]\n");
                 }
             }
@@ -119,6 +251,12 @@ fn rewrite_solidity_interface_files() {
         }
     }
 
+    // The IBoundlessMarket bindings are generated from the ABI snapshot rather than the source.
+    if target_os != "zkvm" {
+        combined_sol_contents.push_str("#[sol(rpc)]\n");
+    }
+    combined_sol_contents.push_str(&generate_interface_from_abi(abi_snapshot));
+
     let mut alloy_import = "alloy_sol_types";
     if target_os != "zkvm" {
         alloy_import = "alloy";
@@ -134,7 +272,7 @@ fn rewrite_solidity_interface_files() {
         pub mod boundless_market_contract {{
             use serde::{{Deserialize, Serialize}};
             {alloy_import}::sol! {{
-            #![sol(all_derives)]
+            #![sol(all_derives, abi)]
             {combined_sol_contents}
 }}
 }}
@@ -144,6 +282,135 @@ fn rewrite_solidity_interface_files() {
     .unwrap();
 }
 
+// Loads the ABI snapshot, returning its version, its ABI and the raw ABI JSON.
+fn load_abi_snapshot(manifest_dir: &Path) -> (u64, JsonAbi, serde_json::Value) {
+    let snapshot_path = manifest_dir.join(ABI_SNAPSHOT);
+    println!("cargo:rerun-if-changed={}", snapshot_path.display());
+
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&snapshot_path).unwrap()).unwrap();
+    let version = json["version"]
+        .as_u64()
+        .unwrap_or_else(|| panic!("missing ABI version in {}", snapshot_path.display()));
+    let abi = serde_json::from_value(json["abi"].clone()).unwrap();
+    (version, abi, json["abi"].clone())
+}
+
+// Returns the full signatures of the functions, events and errors of the ABI.
+fn abi_signatures(abi: &JsonAbi) -> BTreeSet<String> {
+    let functions = abi.functions().map(|f| f.full_signature());
+    let events = abi.events().map(|e| e.full_signature());
+    // Errors have no outputs or modifiers, so their signature is already complete.
+    let errors = abi.errors().map(|e| e.signature());
+    functions.chain(events).chain(errors).collect()
+}
+
+// Fails the build if the full signatures of the compiled IBoundlessMarket ABI, including outputs,
+// state mutability and indexed flags, differ from the snapshot. The contract bindings are generated
+// from the snapshot, and the selectors of the struct types they use are checked against it at
+// compile time by the code from `generate_abi_checks`.
+fn check_compiled_abi(manifest_dir: &Path, snapshot: &JsonAbi) {
+    let contracts_dir = manifest_dir.parent().unwrap().parent().unwrap().join("contracts");
+    let artifact_path = contracts_dir.join("out/IBoundlessMarket.sol/IBoundlessMarket.json");
+    println!("cargo:rerun-if-changed={}", artifact_path.display());
+
+    // If running under cargo publish, the contracts directory doesn't exist, so we exit early.
+    if !contracts_dir.join("src").is_dir() {
+        return;
+    }
+    if !artifact_path.exists() {
+        panic!(
+            "{} is missing, run `forge build` to compare the IBoundlessMarket ABI with {ABI_SNAPSHOT}",
+            artifact_path.display()
+        );
+    }
+
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&artifact_path).unwrap()).unwrap();
+    let compiled: JsonAbi = serde_json::from_value(json["abi"].clone()).unwrap();
+
+    let expected = abi_signatures(snapshot);
+    let actual = abi_signatures(&compiled);
+    if expected != actual {
+        let removed = expected.difference(&actual).map(|sig| format!("  - {sig}"));
+        let added = actual.difference(&expected).map(|sig| format!("  + {sig}"));
+        panic!(
+            "the compiled IBoundlessMarket ABI differs from {ABI_SNAPSHOT}:\n{}\n\
+             Update the snapshot, and bump its version if the change is breaking.",
+            removed.chain(added).collect::<Vec<_>>().join("\n")
+        );
+    }
+}
+
+// Writes compile-time checks that the bindings generated from the ABI snapshot declare exactly its
+// functions, events and errors, by selector, along with the snapshot itself. As selectors cover the
+// fields of struct parameters, this checks the structs of the types/*.sol files against the
+// snapshot. The full signatures are compared in `check_compiled_abi`.
+fn generate_abi_checks(manifest_dir: &Path, version: u64, abi: &JsonAbi) {
+    let mut checks = String::new();
+    let mut check_items = |kind: &str, enum_name: &str, items: Vec<(String, String)>| {
+        checks.push_str(&format!(
+            "    assert!(\n        IBoundlessMarket::{enum_name}::SELECTORS.len() == {},\n        \
+             \"the {kind}s of IBoundlessMarket differ from {ABI_SNAPSHOT}\"\n    );\n",
+            items.len()
+        ));
+        for (signature, selector) in items {
+            checks.push_str(&format!(
+                "    assert!(\n        contains(IBoundlessMarket::{enum_name}::SELECTORS, {selector}),\n        \
+                 \"{kind} `{signature}` of {ABI_SNAPSHOT} differs from the IBoundlessMarket bindings\"\n    );\n"
+            ));
+        }
+    };
+    check_items(
+        "function",
+        "IBoundlessMarketCalls",
+        abi.functions().map(|f| (f.signature(), format!("{:?}", f.selector().0))).collect(),
+    );
+    check_items(
+        "event",
+        "IBoundlessMarketEvents",
+        abi.events().map(|e| (e.signature(), format!("{:?}", e.selector().0))).collect(),
+    );
+    check_items(
+        "error",
+        "IBoundlessMarketErrors",
+        abi.errors().map(|e| (e.signature(), format!("{:?}", e.selector().0))).collect(),
+    );
+
+    let snapshot_path = manifest_dir.join(ABI_SNAPSHOT);
+    let contents = format!(
+        r#"// Auto-generated from {ABI_SNAPSHOT}, do not edit manually
+
+/// Version of the `IBoundlessMarket` ABI snapshot the contract types are checked against.
+pub const IBOUNDLESS_MARKET_ABI_VERSION: u64 = {version};
+
+/// The `IBoundlessMarket` ABI snapshot, as JSON with `version` and `abi` fields.
+pub const IBOUNDLESS_MARKET_ABI: &str = include_str!({snapshot_path:?});
+
+const _: () = {{
+    const fn contains<const N: usize>(selectors: &[[u8; N]], selector: [u8; N]) -> bool {{
+        let mut i = 0;
+        while i < selectors.len() {{
+            let mut j = 0;
+            while j < N && selectors[i][j] == selector[j] {{
+                j += 1;
+            }}
+            if j == N {{
+                return true;
+            }}
+            i += 1;
+        }}
+        false
+    }}
+
+{checks}}};
+"#
+    );
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join(BOUNDLESS_MARKET_ABI_RS), contents).unwrap();
+}
+
 fn copy_interfaces_and_types() {
     println!("cargo::rerun-if-env-changed=CARGO_CFG_TARGET_OS");
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
@@ -292,8 +559,14 @@ fn get_interfaces(contract: &str) -> &str {
 fn main() {
     println!("cargo::rerun-if-changed=build.rs");
 
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let (abi_version, abi, abi_json) = load_abi_snapshot(Path::new(&manifest_dir));
+
     copy_interfaces_and_types();
-    rewrite_solidity_interface_files();
+    rewrite_solidity_interface_files(&abi_json);
+
+    check_compiled_abi(Path::new(&manifest_dir), &abi);
+    generate_abi_checks(Path::new(&manifest_dir), abi_version, &abi);
 
     println!("cargo::rerun-if-env-changed=CARGO_CFG_TARGET_OS");
     let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap();
@@ -0,0 +1,23 @@
+CREATE TABLE events (
+    block_number BIGINT NOT NULL,
+    log_index BIGINT NOT NULL,
+    block_timestamp BIGINT NOT NULL,
+    tx_hash TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    request_id TEXT,
+    client TEXT,
+    prover TEXT,
+    account TEXT,
+    value TEXT,
+    PRIMARY KEY (block_number, log_index)
+);
+
+CREATE INDEX events_request_id ON events (request_id);
+CREATE INDEX events_client ON events (client);
+CREATE INDEX events_prover ON events (prover);
+CREATE INDEX events_block_timestamp ON events (block_timestamp);
+
+CREATE TABLE last_block (
+    id INTEGER PRIMARY KEY,
+    block TEXT
+)
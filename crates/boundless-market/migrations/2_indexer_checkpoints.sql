@@ -0,0 +1,4 @@
+CREATE TABLE checkpoints (
+    block BIGINT PRIMARY KEY,
+    block_hash TEXT NOT NULL
+);
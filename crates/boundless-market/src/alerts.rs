@@ -0,0 +1,590 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, fmt, path::PathBuf, sync::Arc, sync::Mutex, time::Duration};
+
+use alloy::{
+    primitives::{utils::format_ether, Address, U256},
+    providers::Provider,
+    transports::TransportError,
+};
+use serde::Serialize;
+use url::Url;
+
+use crate::contracts::{
+    boundless_market::{BoundlessMarketService, MarketError},
+    hit_points::HitPointsService,
+};
+
+/// Errors that can occur when checking balances.
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum AlertError {
+    /// A watch needs a contract address that was not configured.
+    #[error("no {0} address configured to check the {1} balance")]
+    MissingAddress(&'static str, BalanceSource),
+
+    /// Failed to query the native balance.
+    #[error("RPC error: {0}")]
+    Rpc(#[from] TransportError),
+
+    /// Failed to query the market contract.
+    #[error("market error: {0}")]
+    Market(#[from] MarketError),
+
+    /// Failed to query the hit points contract.
+    #[error("hit points error: {0}")]
+    HitPoints(#[source] anyhow::Error),
+}
+
+/// The balance watched by a [BalanceWatch].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum BalanceSource {
+    /// Native token balance of the address.
+    Native,
+    /// Deposited balance of the address in the market contract.
+    Market,
+    /// Deposited stake balance of the address in the market contract.
+    Stake,
+    /// Hit points token balance of the address.
+    HitPoints,
+}
+
+impl fmt::Display for BalanceSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BalanceSource::Native => "native",
+            BalanceSource::Market => "market",
+            BalanceSource::Stake => "stake",
+            BalanceSource::HitPoints => "hit points",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Severity of an [Alert], ordered from [AlertLevel::Ok] to [AlertLevel::Error].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertLevel {
+    /// The balance is above all thresholds.
+    Ok,
+    /// The balance is below the warning threshold.
+    Warning,
+    /// The balance is below the error threshold.
+    Error,
+}
+
+/// A balance to watch, with the thresholds below which to alert.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct BalanceWatch {
+    /// Name of the watch, included in its alerts.
+    pub name: String,
+    /// Balance to watch.
+    pub source: BalanceSource,
+    /// Address whose balance is watched.
+    pub address: Address,
+    /// Threshold below which to raise a warning.
+    pub warn_below: Option<U256>,
+    /// Threshold below which to raise an error.
+    pub error_below: Option<U256>,
+}
+
+impl BalanceWatch {
+    /// Creates a watch of the given balance of the given address, with no thresholds.
+    pub fn new(source: BalanceSource, address: Address) -> Self {
+        Self {
+            name: format!("{source} balance"),
+            source,
+            address,
+            warn_below: None,
+            error_below: None,
+        }
+    }
+
+    /// Sets the name of the watch.
+    pub fn with_name(self, name: impl Into<String>) -> Self {
+        Self { name: name.into(), ..self }
+    }
+
+    /// Sets the threshold below which to raise a warning.
+    pub fn with_warn_below(self, warn_below: impl Into<Option<U256>>) -> Self {
+        Self { warn_below: warn_below.into(), ..self }
+    }
+
+    /// Sets the threshold below which to raise an error.
+    pub fn with_error_below(self, error_below: impl Into<Option<U256>>) -> Self {
+        Self { error_below: error_below.into(), ..self }
+    }
+
+    /// Returns the alert level of the given balance, and the threshold it is below, if any.
+    pub fn level(&self, balance: U256) -> (AlertLevel, Option<U256>) {
+        match (self.error_below, self.warn_below) {
+            (Some(threshold), _) if balance < threshold => (AlertLevel::Error, Some(threshold)),
+            (_, Some(threshold)) if balance < threshold => (AlertLevel::Warning, Some(threshold)),
+            _ => (AlertLevel::Ok, None),
+        }
+    }
+}
+
+/// An alert raised when the level of a watched balance changes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct Alert {
+    /// Name of the watch.
+    pub name: String,
+    /// Watched balance.
+    pub source: BalanceSource,
+    /// Address whose balance is watched.
+    pub address: Address,
+    /// Current balance.
+    pub balance: U256,
+    /// Current level.
+    pub level: AlertLevel,
+    /// Level at the previous check, if any.
+    pub previous: Option<AlertLevel>,
+    /// Threshold the balance is below, if any.
+    pub threshold: Option<U256>,
+}
+
+impl Alert {
+    /// Whether the alert reports the balance recovering to a lower severity.
+    pub fn is_recovery(&self) -> bool {
+        self.previous.is_some_and(|previous| self.level < previous)
+    }
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let balance = format_ether(self.balance);
+        match (self.level, self.threshold) {
+            (AlertLevel::Ok, _) => {
+                write!(f, "{} of {} recovered: {balance}", self.name, self.address)
+            }
+            (level, Some(threshold)) => {
+                let kind = if level == AlertLevel::Error { "error" } else { "warning" };
+                write!(
+                    f,
+                    "{} of {} < {kind} threshold: {balance} < {}",
+                    self.name,
+                    self.address,
+                    format_ether(threshold)
+                )
+            }
+            (level, None) => write!(f, "{} of {} at {level:?}: {balance}", self.name, self.address),
+        }
+    }
+}
+
+/// A destination for [Alert]s.
+#[async_trait::async_trait]
+pub trait AlertSink: Send + Sync {
+    /// Delivers the alert.
+    async fn send(&self, alert: &Alert) -> anyhow::Result<()>;
+}
+
+/// Sink logging alerts with [tracing], at the level matching their severity.
+#[derive(Clone, Debug, Default)]
+pub struct TracingSink;
+
+#[async_trait::async_trait]
+impl AlertSink for TracingSink {
+    async fn send(&self, alert: &Alert) -> anyhow::Result<()> {
+        match alert.level {
+            AlertLevel::Error => tracing::error!("{alert}"),
+            AlertLevel::Warning => tracing::warn!("{alert}"),
+            AlertLevel::Ok => tracing::info!("{alert}"),
+        }
+        Ok(())
+    }
+}
+
+/// Sink posting alerts as JSON to a webhook URL.
+#[derive(Clone, Debug)]
+pub struct WebhookSink {
+    client: reqwest::Client,
+    url: Url,
+}
+
+impl WebhookSink {
+    /// Creates a sink posting to the given URL.
+    pub fn new(url: Url) -> Self {
+        Self { client: reqwest::Client::new(), url }
+    }
+}
+
+#[async_trait::async_trait]
+impl AlertSink for WebhookSink {
+    async fn send(&self, alert: &Alert) -> anyhow::Result<()> {
+        self.client.post(self.url.clone()).json(alert).send().await?.error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Sink running a command for each alert.
+///
+/// The alert is passed to the command as JSON in the `BOUNDLESS_ALERT` environment variable,
+/// and as a human readable message in `BOUNDLESS_ALERT_MESSAGE`.
+#[derive(Clone, Debug)]
+pub struct CommandSink {
+    program: PathBuf,
+    args: Vec<String>,
+}
+
+impl CommandSink {
+    /// Creates a sink running the given program with the given arguments.
+    pub fn new(
+        program: impl Into<PathBuf>,
+        args: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self { program: program.into(), args: args.into_iter().map(Into::into).collect() }
+    }
+}
+
+#[async_trait::async_trait]
+impl AlertSink for CommandSink {
+    async fn send(&self, alert: &Alert) -> anyhow::Result<()> {
+        let mut command = std::process::Command::new(&self.program);
+        command
+            .args(&self.args)
+            .env("BOUNDLESS_ALERT", serde_json::to_string(alert)?)
+            .env("BOUNDLESS_ALERT_MESSAGE", alert.to_string());
+        let status = tokio::task::spawn_blocking(move || command.status()).await??;
+        anyhow::ensure!(status.success(), "alert command {:?} failed: {status}", self.program);
+        Ok(())
+    }
+}
+
+/// Checks a set of balances against their thresholds on a schedule, and delivers an [Alert] to
+/// each sink whenever the level of a balance changes.
+///
+/// Alerts are de-duplicated: a balance staying below a threshold only raises one alert, and
+/// recovering above it raises a recovery alert. A balance that is fine on the first check raises
+/// no alert. Balances are identified by their source and address, so each should be watched at
+/// most once.
+///
+/// # Examples
+/// ```no_run
+/// # use std::time::Duration;
+/// # use alloy::primitives::{utils::parse_ether, Address};
+/// # use alloy::providers::ProviderBuilder;
+/// # use boundless_market::alerts::{BalanceMonitor, BalanceSource, BalanceWatch, TracingSink};
+/// # async fn example(market: Address, wallet: Address) -> anyhow::Result<()> {
+/// let provider = ProviderBuilder::new().on_http("http://localhost:8545".parse()?);
+/// let monitor = BalanceMonitor::new(provider)
+///     .with_market_address(market)
+///     .with_watch(
+///         BalanceWatch::new(BalanceSource::Native, wallet).with_warn_below(parse_ether("0.1")?),
+///     )
+///     .with_watch(BalanceWatch::new(BalanceSource::Stake, wallet).with_error_below(parse_ether("1")?))
+///     .with_sink(TracingSink)
+///     .with_interval(Duration::from_secs(60));
+/// monitor.run().await;
+/// # Ok(())
+/// # }
+/// ```
+pub struct BalanceMonitor<P> {
+    provider: P,
+    market_address: Option<Address>,
+    hit_points_address: Option<Address>,
+    watches: Vec<BalanceWatch>,
+    sinks: Vec<Arc<dyn AlertSink>>,
+    interval: Duration,
+    levels: Mutex<HashMap<(BalanceSource, Address), AlertLevel>>,
+}
+
+impl<P> BalanceMonitor<P>
+where
+    P: Provider + Clone,
+{
+    /// Creates a monitor with no watches and no sinks, checking every minute.
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            market_address: None,
+            hit_points_address: None,
+            watches: Vec::new(),
+            sinks: Vec::new(),
+            interval: Duration::from_secs(60),
+            levels: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the market contract address, needed for [BalanceSource::Market] and
+    /// [BalanceSource::Stake] watches.
+    pub fn with_market_address(self, market_address: Address) -> Self {
+        Self { market_address: Some(market_address), ..self }
+    }
+
+    /// Sets the hit points contract address, needed for [BalanceSource::HitPoints] watches.
+    pub fn with_hit_points_address(self, hit_points_address: Address) -> Self {
+        Self { hit_points_address: Some(hit_points_address), ..self }
+    }
+
+    /// Adds a balance to watch.
+    pub fn with_watch(mut self, watch: BalanceWatch) -> Self {
+        self.watches.push(watch);
+        self
+    }
+
+    /// Adds a sink to deliver alerts to.
+    pub fn with_sink(mut self, sink: impl AlertSink + 'static) -> Self {
+        self.sinks.push(Arc::new(sink));
+        self
+    }
+
+    /// Sets the interval between checks.
+    pub fn with_interval(self, interval: Duration) -> Self {
+        Self { interval, ..self }
+    }
+
+    /// Returns the watched balances.
+    pub fn watches(&self) -> &[BalanceWatch] {
+        &self.watches
+    }
+
+    /// Replaces the watched balances, e.g. after a configuration change.
+    ///
+    /// The level of a balance that stays watched is kept, so that it only raises an alert if its
+    /// level changes under the new thresholds.
+    pub fn set_watches(&mut self, watches: Vec<BalanceWatch>) {
+        self.levels.get_mut().unwrap().retain(|(source, address), _| {
+            watches.iter().any(|watch| watch.source == *source && watch.address == *address)
+        });
+        self.watches = watches;
+    }
+
+    /// Replaces the sinks alerts are delivered to.
+    pub fn set_sinks(&mut self, sinks: Vec<Arc<dyn AlertSink>>) {
+        self.sinks = sinks;
+    }
+
+    async fn balance(&self, watch: &BalanceWatch) -> Result<U256, AlertError> {
+        let market = || {
+            self.market_address.ok_or(AlertError::MissingAddress("market", watch.source)).map(
+                |address| {
+                    BoundlessMarketService::new(address, self.provider.clone(), watch.address)
+                },
+            )
+        };
+        match watch.source {
+            BalanceSource::Native => Ok(self.provider.get_balance(watch.address).await?),
+            BalanceSource::Market => Ok(market()?.balance_of(watch.address).await?),
+            BalanceSource::Stake => Ok(market()?.balance_of_stake(watch.address).await?),
+            BalanceSource::HitPoints => {
+                let address = self
+                    .hit_points_address
+                    .ok_or(AlertError::MissingAddress("hit points", watch.source))?;
+                HitPointsService::new(address, self.provider.clone(), watch.address)
+                    .balance_of(watch.address)
+                    .await
+                    .map_err(AlertError::HitPoints)
+            }
+        }
+    }
+
+    /// Checks all watched balances once, and delivers the resulting alerts to the sinks.
+    ///
+    /// Returns the alerts raised. A failure to query one balance does not prevent checking the
+    /// others, but is returned after all balances are checked.
+    pub async fn check(&self) -> Result<Vec<Alert>, AlertError> {
+        let mut alerts = Vec::new();
+        let mut first_err = None;
+        for watch in &self.watches {
+            let balance = match self.balance(watch).await {
+                Ok(balance) => balance,
+                Err(err) => {
+                    tracing::warn!("Failed to check {} of {}: {err}", watch.name, watch.address);
+                    first_err.get_or_insert(err);
+                    continue;
+                }
+            };
+            tracing::trace!("{} of {} is: {balance}", watch.name, watch.address);
+
+            let (level, threshold) = watch.level(balance);
+            let previous = self.levels.lock().unwrap().insert((watch.source, watch.address), level);
+            if previous == Some(level) || (previous.is_none() && level == AlertLevel::Ok) {
+                continue;
+            }
+            alerts.push(Alert {
+                name: watch.name.clone(),
+                source: watch.source,
+                address: watch.address,
+                balance,
+                level,
+                previous,
+                threshold,
+            });
+        }
+
+        for alert in &alerts {
+            for sink in &self.sinks {
+                if let Err(err) = sink.send(alert).await {
+                    tracing::warn!("Failed to deliver alert for {}: {err:?}", alert.name);
+                }
+            }
+        }
+
+        match first_err {
+            Some(err) => Err(err),
+            None => Ok(alerts),
+        }
+    }
+
+    /// Checks the watched balances at the configured interval, forever.
+    pub async fn run(&self) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            // Failures are logged in check, and retried on the next tick.
+            let _ = self.check().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::{
+        network::{EthereumWallet, TransactionBuilder},
+        node_bindings::Anvil,
+        primitives::utils::parse_ether,
+        providers::ProviderBuilder,
+        rpc::types::TransactionRequest,
+        signers::local::LocalSigner,
+    };
+
+    #[derive(Clone, Default)]
+    struct RecordingSink(Arc<Mutex<Vec<Alert>>>);
+
+    #[async_trait::async_trait]
+    impl AlertSink for RecordingSink {
+        async fn send(&self, alert: &Alert) -> anyhow::Result<()> {
+            self.0.lock().unwrap().push(alert.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn watch_level() {
+        let watch = BalanceWatch::new(BalanceSource::Native, Address::ZERO)
+            .with_warn_below(U256::from(10))
+            .with_error_below(U256::from(5));
+        assert_eq!(watch.level(U256::from(10)), (AlertLevel::Ok, None));
+        assert_eq!(watch.level(U256::from(9)), (AlertLevel::Warning, Some(U256::from(10))));
+        assert_eq!(watch.level(U256::from(4)), (AlertLevel::Error, Some(U256::from(5))));
+
+        let unset = BalanceWatch::new(BalanceSource::Native, Address::ZERO);
+        assert_eq!(unset.level(U256::ZERO), (AlertLevel::Ok, None));
+    }
+
+    #[tokio::test]
+    async fn balance_monitor_dedup_and_recovery() -> anyhow::Result<()> {
+        let anvil = Anvil::default().args(["--balance", "10"]).spawn();
+        let signer = LocalSigner::from(anvil.keys()[0].clone());
+        let address = signer.address();
+        let provider = ProviderBuilder::new()
+            .wallet(EthereumWallet::from(signer))
+            .on_http(anvil.endpoint_url());
+        let transfer = |to: Address, amount: &str| {
+            let provider = provider.clone();
+            let tx =
+                TransactionRequest::default().with_to(to).with_value(parse_ether(amount).unwrap());
+            async move { provider.send_transaction(tx).await?.watch().await }
+        };
+
+        let sink = RecordingSink::default();
+        let monitor = BalanceMonitor::new(provider.clone())
+            .with_watch(
+                BalanceWatch::new(BalanceSource::Native, address)
+                    .with_warn_below(parse_ether("9")?)
+                    .with_error_below(parse_ether("5")?),
+            )
+            .with_sink(sink.clone());
+
+        // No alert while the balance is above the thresholds.
+        assert!(monitor.check().await?.is_empty());
+
+        transfer(Address::ZERO, "2").await?;
+        let alerts = monitor.check().await?;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(
+            (alerts[0].level, alerts[0].previous),
+            (AlertLevel::Warning, Some(AlertLevel::Ok))
+        );
+
+        // Staying below the threshold does not raise the alert again.
+        assert!(monitor.check().await?.is_empty());
+
+        transfer(Address::ZERO, "4").await?;
+        assert_eq!(monitor.check().await?[0].level, AlertLevel::Error);
+
+        // Top up the balance from another account.
+        let funder = LocalSigner::from(anvil.keys()[1].clone());
+        ProviderBuilder::new()
+            .wallet(EthereumWallet::from(funder))
+            .on_http(anvil.endpoint_url())
+            .send_transaction(
+                TransactionRequest::default().with_to(address).with_value(parse_ether("6")?),
+            )
+            .await?
+            .watch()
+            .await?;
+        let alerts = monitor.check().await?;
+        assert_eq!(alerts[0].level, AlertLevel::Ok);
+        assert!(alerts[0].is_recovery());
+
+        assert_eq!(sink.0.lock().unwrap().len(), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn balance_monitor_set_watches() -> anyhow::Result<()> {
+        let anvil = Anvil::default().args(["--balance", "10"]).spawn();
+        let address = anvil.addresses()[0];
+        let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+        let watch = |warn_below: &str| {
+            BalanceWatch::new(BalanceSource::Native, address)
+                .with_warn_below(parse_ether(warn_below).unwrap())
+        };
+
+        let mut monitor = BalanceMonitor::new(provider).with_watch(watch("11"));
+        assert_eq!(monitor.check().await?[0].level, AlertLevel::Warning);
+
+        // Raising the threshold keeps the balance at the same level, so it does not alert again.
+        monitor.set_watches(vec![watch("12")]);
+        assert!(monitor.check().await?.is_empty());
+
+        // Lowering the threshold below the balance recovers it.
+        monitor.set_watches(vec![watch("5")]);
+        let alerts = monitor.check().await?;
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].is_recovery());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn balance_monitor_missing_address() {
+        let anvil = Anvil::default().spawn();
+        let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+        let monitor = BalanceMonitor::new(provider)
+            .with_watch(BalanceWatch::new(BalanceSource::Stake, Address::ZERO));
+        assert!(matches!(
+            monitor.check().await,
+            Err(AlertError::MissingAddress("market", BalanceSource::Stake))
+        ));
+    }
+}
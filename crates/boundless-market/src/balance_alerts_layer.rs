@@ -12,12 +12,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! Deprecated in favor of [BalanceMonitor], which checks balances on a schedule and delivers
+//! alerts to pluggable sinks. The layer is kept as a shim, checking the balance with a
+//! [BalanceMonitor] after every transaction and logging alerts with [TracingSink].
+#![allow(deprecated)]
+
+use std::sync::Arc;
+
 use alloy::network::Ethereum;
 use alloy::primitives::{Address, U256};
 use alloy::providers::{PendingTransactionBuilder, Provider, ProviderLayer, RootProvider};
 use alloy::transports::TransportResult;
 
+use crate::alerts::{BalanceMonitor, BalanceSource, BalanceWatch, TracingSink};
+
 /// Configuration for the BalanceAlertLayer
+#[deprecated(note = "use boundless_market::alerts::BalanceWatch instead")]
 #[derive(Debug, Clone, Default)]
 pub struct BalanceAlertConfig {
     /// Address to periodically check the balance of
@@ -28,9 +38,25 @@ pub struct BalanceAlertConfig {
     pub error_threshold: Option<U256>,
 }
 
+impl BalanceAlertConfig {
+    /// Returns the equivalent watch of the native balance, if any threshold is set.
+    pub(crate) fn watch(&self) -> Option<BalanceWatch> {
+        if self.warn_threshold.is_none() && self.error_threshold.is_none() {
+            return None;
+        }
+        Some(
+            BalanceWatch::new(BalanceSource::Native, self.watch_address)
+                .with_name("balance")
+                .with_warn_below(self.warn_threshold)
+                .with_error_below(self.error_threshold),
+        )
+    }
+}
+
 /// A layer that can be added to an alloy Provider
 /// to log warnings and errors when the balance of a given address
 /// falls below certain thresholds.
+#[deprecated(note = "use boundless_market::alerts::BalanceMonitor instead")]
 #[derive(Debug, Clone, Default)]
 pub struct BalanceAlertLayer {
     config: BalanceAlertConfig,
@@ -41,10 +67,11 @@ pub struct BalanceAlertLayer {
 /// falls below certain thresholds.
 ///
 /// This checks the balance after every transaction sent via send_transaction
-/// and errors, warns or trace logs accordingly
+/// and errors or warns when the balance crosses a threshold.
 ///
 /// # Examples
 /// ```no_run
+/// # #![allow(deprecated)]
 /// # use alloy::primitives::{U256, utils::parse_ether};
 /// # use alloy::providers::ProviderBuilder;
 /// # use alloy::network::EthereumWallet;
@@ -79,19 +106,30 @@ where
 
 /// A provider that checks the balance of a given address
 /// and logs warnings and errors when the balance falls below certain thresholds.
-#[derive(Clone, Debug)]
+#[deprecated(note = "use boundless_market::alerts::BalanceMonitor instead")]
+#[derive(Clone)]
 pub struct BalanceAlertProvider<P> {
     inner: P,
     config: BalanceAlertConfig,
+    monitor: Arc<BalanceMonitor<RootProvider>>,
+}
+
+impl<P> std::fmt::Debug for BalanceAlertProvider<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BalanceAlertProvider").field("config", &self.config).finish_non_exhaustive()
+    }
 }
 
 impl<P> BalanceAlertProvider<P>
 where
     P: Provider,
 {
-    #[allow(clippy::missing_const_for_fn)]
     fn new(inner: P, config: BalanceAlertConfig) -> Self {
-        Self { inner, config }
+        let mut monitor = BalanceMonitor::new(inner.root().clone()).with_sink(TracingSink);
+        if let Some(watch) = config.watch() {
+            monitor = monitor.with_watch(watch);
+        }
+        Self { inner, config, monitor: Arc::new(monitor) }
     }
 }
 
@@ -116,23 +154,8 @@ where
         encoded_tx: &[u8],
     ) -> TransportResult<PendingTransactionBuilder<Ethereum>> {
         let res = self.inner.send_raw_transaction(encoded_tx).await;
-        let balance = self.inner.get_balance(self.config.watch_address).await?;
-
-        if balance < self.config.error_threshold.unwrap_or(U256::ZERO) {
-            tracing::error!(
-                "balance of {} < error threshold: {}",
-                self.config.watch_address,
-                balance
-            );
-        } else if balance < self.config.warn_threshold.unwrap_or(U256::ZERO) {
-            tracing::warn!(
-                "balance of {} < warning threshold: {}",
-                self.config.watch_address,
-                balance
-            );
-        } else {
-            tracing::trace!("balance of {} is: {}", self.config.watch_address, balance);
-        }
+        // Failures to check the balance are logged by the monitor, and do not fail the transaction.
+        let _ = self.monitor.check().await;
         res
     }
 }
@@ -12,10 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{env, str::FromStr, time::Duration};
+use std::{collections::VecDeque, env, str::FromStr, sync::Arc, time::Duration};
 
 use alloy::{
-    network::{Ethereum, EthereumWallet},
+    consensus::{BlockHeader, Transaction},
+    eips::BlockNumberOrTag,
+    network::{Ethereum, EthereumWallet, TxSigner},
     primitives::{Address, Bytes, U256},
     providers::{
         fillers::{
@@ -24,28 +26,36 @@ use alloy::{
         },
         Identity, Provider, ProviderBuilder, RootProvider,
     },
-    signers::{
-        k256::ecdsa::SigningKey,
-        local::{LocalSigner, PrivateKeySigner},
-        Signer,
-    },
+    rpc::types::{Filter, Log},
+    signers::{local::PrivateKeySigner, Signer},
 };
 use alloy_primitives::{PrimitiveSignature, B256};
-use alloy_sol_types::SolStruct;
+use alloy_sol_types::{SolCall, SolEvent, SolEventInterface, SolStruct};
 use anyhow::{anyhow, Context, Result};
+use async_stream::stream;
+use futures_util::Stream;
 use risc0_aggregation::SetInclusionReceipt;
 use risc0_ethereum_contracts::set_verifier::SetVerifierService;
 use risc0_zkvm::{sha::Digest, ReceiptClaim};
 use url::Url;
 
+#[allow(deprecated)]
+use crate::balance_alerts_layer::{BalanceAlertConfig, BalanceAlertLayer, BalanceAlertProvider};
 use crate::{
-    balance_alerts_layer::{BalanceAlertConfig, BalanceAlertLayer, BalanceAlertProvider},
+    alerts::BalanceMonitor,
+    clock::{ChainClock, ClockObj, SystemClock},
     contracts::{
-        boundless_market::{BoundlessMarketService, MarketError},
-        ProofRequest, RequestError,
+        boundless_market::{find_submitted_request, BoundlessMarketService, MarketError},
+        IBoundlessMarket::{self, IBoundlessMarketEvents},
+        Offer, ProofRequest, RequestError, RequestStatus,
+    },
+    deployments::{Deployment, DeploymentError, Deployments},
+    fulfillments::{
+        verify_fulfillment, FulfillmentData, FulfillmentSource, FulfillmentSourceError,
+        HttpArchiveSource, OrderStreamSource,
     },
-    now_timestamp,
     order_stream_client::{Client as OrderStreamClient, Order},
+    signer::{SignerError, SignerUri},
     storage::{
         storage_provider_from_env, BuiltinStorageProvider, BuiltinStorageProviderError,
         StorageProvider, StorageProviderConfig,
@@ -55,6 +65,7 @@ use crate::{
 // Default bidding start delay (from the current time) in seconds
 const BIDDING_START_DELAY: u64 = 30;
 
+#[allow(deprecated)]
 type ProviderWallet = FillProvider<
     JoinFill<
         JoinFill<
@@ -79,23 +90,124 @@ pub enum ClientError {
     /// Request error
     #[error("RequestError {0}")]
     RequestError(#[from] RequestError),
+    /// Fulfillment source error
+    #[error("Fulfillment source error {0}")]
+    FulfillmentSourceError(#[from] FulfillmentSourceError),
+    /// Deployment error
+    #[error("Deployment error {0}")]
+    DeploymentError(#[from] DeploymentError),
+    /// Signer error
+    #[error("Signer error {0}")]
+    SignerError(#[from] SignerError),
+    /// Fetching a fulfillment failed both on-chain and from the fulfillment sources
+    #[error(
+        "Failed to fetch fulfillment on-chain: {chain}; and from fulfillment sources: {sources}"
+    )]
+    FulfillmentFetchFailed {
+        /// Error of the on-chain lookup
+        chain: MarketError,
+        /// Error of the fulfillment sources fallback
+        sources: Box<ClientError>,
+    },
     /// General error
     #[error("Error {0}")]
     Error(#[from] anyhow::Error),
 }
 
+/// Lifecycle event of a request, as emitted by [Client::watch_request].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RequestEvent {
+    /// The request was submitted on-chain.
+    Submitted,
+    /// The request was locked by a prover.
+    Locked {
+        /// Address of the prover locking the request.
+        prover: Address,
+        /// Price the prover will be paid, if the locked offer could be determined.
+        price: Option<U256>,
+    },
+    /// The request was fulfilled.
+    Fulfilled {
+        /// Journal of the fulfillment.
+        journal: Bytes,
+        /// Seal of the fulfillment.
+        seal: Bytes,
+    },
+    /// The lock expired before the request was fulfilled. Any prover can still fulfill the request
+    /// until it expires.
+    LockExpired,
+    /// The prover that locked the request was slashed.
+    Slashed,
+    /// The request expired without being fulfilled.
+    Expired,
+}
+
+/// Source of the logs followed by [Client::watch_request].
+enum LogCursor {
+    /// Catch up on past logs before installing a filter.
+    Start,
+    /// Poll the changes of a log filter installed from the given block.
+    Filter(U256, u64),
+    /// Query the logs from the given block, when log filters are not supported.
+    Poll(u64),
+}
+
+/// State of a request followed by [Client::watch_request].
+struct RequestWatch<P: Provider<Ethereum> + Clone + 'static> {
+    request_id: U256,
+    provider: P,
+    cursor: LogCursor,
+    // Logs received but not yet turned into events, so that they are retried on errors.
+    pending: VecDeque<Log>,
+    state: RequestState,
+}
+
+/// Progress of a watched request, updated only once a batch of logs is fully processed.
+#[derive(Clone, Default)]
+struct RequestState {
+    // Position of the last processed log, to skip logs delivered twice on fallback to polling.
+    last_log: Option<(u64, u64)>,
+    offer: Option<Offer>,
+    deadlines: Option<(u64, u64)>,
+    locked: bool,
+    lock_expired: bool,
+    expired: bool,
+}
+
+impl<P: Provider<Ethereum> + Clone + 'static> Drop for RequestWatch<P> {
+    fn drop(&mut self) {
+        // Uninstall the log filter rather than leaving it on the node until it times out.
+        if let LogCursor::Filter(filter_id, _) = self.cursor {
+            let Ok(handle) = tokio::runtime::Handle::try_current() else {
+                return;
+            };
+            let provider = self.provider.clone();
+            handle.spawn(async move {
+                if let Err(err) = provider.uninstall_filter(filter_id).await {
+                    tracing::debug!("Failed to uninstall log filter: {err}");
+                }
+            });
+        }
+    }
+}
+
 /// Builder for the client
 pub struct ClientBuilder<P> {
     boundless_market_addr: Option<Address>,
     set_verifier_addr: Option<Address>,
     rpc_url: Option<Url>,
     wallet: Option<EthereumWallet>,
-    local_signer: Option<PrivateKeySigner>,
+    signer: Option<Arc<dyn Signer>>,
     order_stream_url: Option<Url>,
     storage_provider: Option<P>,
     tx_timeout: Option<std::time::Duration>,
     bidding_start_delay: u64,
+    #[allow(deprecated)]
     balance_alerts: Option<BalanceAlertConfig>,
+    fulfillment_archive_url: Option<Url>,
+    deployment: Option<Deployment>,
+    clock_sync_interval: Option<std::time::Duration>,
 }
 
 impl<P> Default for ClientBuilder<P> {
@@ -108,12 +220,15 @@ impl<P> Default for ClientBuilder<P> {
             set_verifier_addr: None,
             rpc_url: None,
             wallet: None,
-            local_signer: None,
+            signer: None,
             order_stream_url: None,
             storage_provider: None,
             tx_timeout: None,
             bidding_start_delay: BIDDING_START_DELAY,
             balance_alerts: None,
+            fulfillment_archive_url: None,
+            deployment: None,
+            clock_sync_interval: None,
         }
     }
 }
@@ -146,13 +261,42 @@ impl ClientBuilder<BuiltinStorageProvider> {
 
 impl<P: StorageProvider> ClientBuilder<P> {
     /// Build the client
+    ///
+    /// If the market or set verifier address is not set, the deployment is looked up in the
+    /// [Deployments] registry by the chain ID of the RPC provider.
     pub async fn build(self) -> Result<Client<ProviderWallet, P>> {
+        let rpc_url = self.rpc_url.context("RPC URL not set")?;
+        let (boundless_market_addr, set_verifier_addr, deployment) =
+            match (self.boundless_market_addr, self.set_verifier_addr) {
+                (Some(market), Some(set_verifier)) => (market, set_verifier, self.deployment),
+                (market, set_verifier) => {
+                    let provider = ProviderBuilder::new().on_http(rpc_url.clone());
+                    let chain_id =
+                        provider.get_chain_id().await.context("Failed to get chain ID")?;
+                    let deployment = Deployments::load()?.resolve(chain_id)?;
+                    (
+                        market.unwrap_or(deployment.boundless_market_address),
+                        set_verifier.unwrap_or(deployment.set_verifier_address),
+                        Some(deployment),
+                    )
+                }
+            };
+        // Only default to the order stream of the deployment when using its market.
+        let order_stream_url = match (self.order_stream_url, &deployment) {
+            (Some(url), _) => Some(url),
+            (None, Some(deployment))
+                if deployment.boundless_market_address == boundless_market_addr =>
+            {
+                deployment.order_stream_url()?
+            }
+            (None, _) => None,
+        };
         let mut client = Client::from_parts(
             self.wallet.context("Wallet not set")?,
-            self.rpc_url.context("RPC URL not set")?,
-            self.boundless_market_addr.context("Boundless market address not set")?,
-            self.set_verifier_addr.context("Set verifier address not set")?,
-            self.order_stream_url,
+            rpc_url.clone(),
+            boundless_market_addr,
+            set_verifier_addr,
+            order_stream_url,
             self.storage_provider,
             self.balance_alerts,
         )
@@ -160,18 +304,47 @@ impl<P: StorageProvider> ClientBuilder<P> {
         if let Some(timeout) = self.tx_timeout {
             client = client.with_timeout(timeout);
         }
-        if let Some(local_signer) = self.local_signer {
-            client = client.with_local_signer(local_signer);
+        if let Some(signer) = self.signer {
+            client = Client { signer: Some(signer), ..client };
+        }
+        if let Some(url) = self.fulfillment_archive_url {
+            client = client.with_fulfillment_source(Arc::new(HttpArchiveSource::new(url)));
         }
         client = client.with_bidding_start_delay(self.bidding_start_delay);
+        if let Some(interval) = self.clock_sync_interval {
+            let clock = ChainClock::new(ProviderBuilder::new().on_http(rpc_url));
+            clock.sync().await.context("Failed to sync chain clock")?;
+            clock.spawn_sync(interval);
+            client = client.with_clock(Arc::new(clock));
+        }
         Ok(client)
     }
 
+    /// Keep the chain clock of the client synced at the given interval, about the block time of
+    /// the chain
+    ///
+    /// Otherwise, the clock is only synced once when building the client, and drifts from the
+    /// chain over time. The sync task stops when the client and its clones are dropped.
+    pub fn with_clock_sync_interval(self, interval: std::time::Duration) -> Self {
+        Self { clock_sync_interval: Some(interval), ..self }
+    }
+
     /// Set the Boundless market address
     pub fn with_boundless_market_address(self, boundless_market_addr: Address) -> Self {
         Self { boundless_market_addr: Some(boundless_market_addr), ..self }
     }
 
+    /// Set the market and set verifier addresses, and the order stream URL if not set, from a
+    /// known deployment
+    pub fn with_deployment(self, deployment: Deployment) -> Self {
+        Self {
+            boundless_market_addr: Some(deployment.boundless_market_address),
+            set_verifier_addr: Some(deployment.set_verifier_address),
+            deployment: Some(deployment),
+            ..self
+        }
+    }
+
     /// Set the set verifier address
     pub fn with_set_verifier_address(self, set_verifier_addr: Address) -> Self {
         Self { set_verifier_addr: Some(set_verifier_addr), ..self }
@@ -184,9 +357,20 @@ impl<P: StorageProvider> ClientBuilder<P> {
 
     /// Set the private key
     pub fn with_private_key(self, private_key: PrivateKeySigner) -> Self {
+        self.with_signer(private_key)
+    }
+
+    /// Set the signer, used both to sign requests and as the wallet sending transactions
+    ///
+    /// Any alloy signer can be used, e.g. one created from a
+    /// [SignerUri](crate::signer::SignerUri) to sign with a keystore, a remote signer or a KMS key.
+    pub fn with_signer<T>(self, signer: T) -> Self
+    where
+        T: Signer + TxSigner<PrimitiveSignature> + Clone + Send + Sync + 'static,
+    {
         Self {
-            wallet: Some(EthereumWallet::from(private_key.clone())),
-            local_signer: Some(private_key),
+            wallet: Some(EthereumWallet::from(signer.clone())),
+            signer: Some(Arc::new(signer)),
             ..self
         }
     }
@@ -219,9 +403,16 @@ impl<P: StorageProvider> ClientBuilder<P> {
     }
 
     /// Set the balance alerts configuration
+    #[deprecated(note = "use Client::balance_monitor to watch balances instead")]
+    #[allow(deprecated)]
     pub fn with_balance_alerts(self, config: BalanceAlertConfig) -> Self {
         Self { balance_alerts: Some(config), ..self }
     }
+
+    /// Set the URL of a fulfillment archive, used to fetch proofs that cannot be found on-chain
+    pub fn with_fulfillment_archive_url(self, fulfillment_archive_url: Option<Url>) -> Self {
+        Self { fulfillment_archive_url, ..self }
+    }
 }
 
 #[derive(Clone)]
@@ -235,10 +426,14 @@ pub struct Client<P, S> {
     pub storage_provider: Option<S>,
     /// Order stream client to submit requests off-chain.
     pub offchain_client: Option<OrderStreamClient>,
-    /// Local signer for signing requests.
-    pub local_signer: Option<LocalSigner<SigningKey>>,
+    /// Signer for signing requests.
+    pub signer: Option<Arc<dyn Signer>>,
     /// Bidding start delay with regard to the current time, in seconds.
     pub bidding_start_delay: u64,
+    /// Sources to fetch fulfillments from when they cannot be found on-chain, in order.
+    pub fulfillment_sources: Vec<Arc<dyn FulfillmentSource>>,
+    /// Clock used to compute bidding start times.
+    pub clock: ClockObj,
 }
 
 impl<P, S> Client<P, S>
@@ -247,6 +442,8 @@ where
     S: StorageProvider,
 {
     /// Create a new client
+    ///
+    /// The client uses the system clock, see [Client::with_clock] to follow the chain instead.
     pub fn new(
         boundless_market: BoundlessMarketService<P>,
         set_verifier: SetVerifierService<P>,
@@ -258,8 +455,10 @@ where
             set_verifier,
             storage_provider: None,
             offchain_client: None,
-            local_signer: None,
+            signer: None,
             bidding_start_delay: BIDDING_START_DELAY,
+            fulfillment_sources: Vec::new(),
+            clock: Arc::new(SystemClock),
         }
     }
 
@@ -273,6 +472,14 @@ where
         self.boundless_market.caller()
     }
 
+    /// Create a [BalanceMonitor] on the provider and market of the client
+    ///
+    /// Add watches and sinks to the returned monitor, and run it to alert on low balances.
+    pub fn balance_monitor(&self) -> BalanceMonitor<P> {
+        BalanceMonitor::new(self.provider())
+            .with_market_address(*self.boundless_market.instance().address())
+    }
+
     /// Set the Boundless market service
     pub fn with_boundless_market(self, boundless_market: BoundlessMarketService<P>) -> Self {
         Self { boundless_market, ..self }
@@ -302,9 +509,12 @@ where
         }
     }
 
-    /// Set the local signer
-    pub fn with_local_signer(self, local_signer: LocalSigner<SigningKey>) -> Self {
-        Self { local_signer: Some(local_signer), ..self }
+    /// Set the signer used to sign requests
+    ///
+    /// Any alloy signer can be used, e.g. a [PrivateKeySigner] or a signer created from a
+    /// [SignerUri].
+    pub fn with_signer(self, signer: impl Signer + 'static) -> Self {
+        Self { signer: Some(Arc::new(signer)), ..self }
     }
 
     /// Set the bidding start delay, in seconds.
@@ -312,6 +522,20 @@ where
         Self { bidding_start_delay, ..self }
     }
 
+    /// Set the clock used to compute bidding start times
+    pub fn with_clock(self, clock: ClockObj) -> Self {
+        Self { clock, ..self }
+    }
+
+    /// Add a source to fetch fulfillments from when they cannot be found on-chain.
+    ///
+    /// Sources are tried in the order they are added, and fulfillments they return are verified
+    /// against the requirements of the request and the market verifier before use.
+    pub fn with_fulfillment_source(mut self, source: Arc<dyn FulfillmentSource>) -> Self {
+        self.fulfillment_sources.push(source);
+        self
+    }
+
     /// Upload an image to the storage provider
     pub async fn upload_image(&self, elf: &[u8]) -> Result<Url, ClientError> {
         Ok(self
@@ -336,14 +560,14 @@ where
 
     /// Submit a proof request.
     ///
-    /// Requires a local signer to be set to sign the request.
+    /// Requires a signer to be set to sign the request.
     /// If the request ID is not set, a random ID will be generated.
     /// If the bidding start is not set, the current time will be used, plus a delay.
     pub async fn submit_request(&self, request: &ProofRequest) -> Result<(U256, u64), ClientError>
     where
         <S as StorageProvider>::Error: std::fmt::Debug,
     {
-        let signer = self.local_signer.as_ref().context("Local signer not set")?;
+        let signer = self.signer.as_deref().context("Signer not set")?;
         self.submit_request_with_signer(request, signer).await
     }
 
@@ -355,7 +579,7 @@ where
     pub async fn submit_request_with_signer(
         &self,
         request: &ProofRequest,
-        signer: &impl Signer,
+        signer: &(impl Signer + ?Sized),
     ) -> Result<(U256, u64), ClientError>
     where
         <S as StorageProvider>::Error: std::fmt::Debug,
@@ -370,7 +594,7 @@ where
             return Err(MarketError::AddressMismatch(client_address, signer.address()))?;
         };
         if request.offer.biddingStart == 0 {
-            request.offer.biddingStart = now_timestamp() + self.bidding_start_delay
+            request.offer.biddingStart = self.clock.now() + self.bidding_start_delay
         };
 
         request.validate()?;
@@ -379,6 +603,45 @@ where
         Ok((request_id, request.expires_at()))
     }
 
+    /// Submit a batch of proof requests in a single transaction.
+    ///
+    /// Accepts a signer to sign the requests. Requests without an ID are assigned a random one,
+    /// and requests without a bidding start use the current time plus a delay. The total max
+    /// price of the batch is deposited at once if the client balance does not cover it.
+    /// Returns the request ID and expiration of each request, in order.
+    pub async fn submit_requests_with_signer(
+        &self,
+        requests: &[ProofRequest],
+        signer: &(impl Signer + ?Sized),
+    ) -> Result<Vec<(U256, u64)>, ClientError>
+    where
+        <S as StorageProvider>::Error: std::fmt::Debug,
+    {
+        let requests = self.prepare_requests(requests, signer).await?;
+        for request in &requests {
+            request.validate()?;
+        }
+
+        let request_ids = self.boundless_market.submit_requests(&requests, signer).await?;
+        Ok(request_ids.into_iter().zip(requests.iter().map(|r| r.expires_at())).collect())
+    }
+
+    /// Submit a batch of proof requests in a single transaction.
+    ///
+    /// Requires a signer to be set to sign the requests.
+    /// Requests without an ID are assigned a random one, and requests without a bidding start use
+    /// the current timestamp plus a delay.
+    pub async fn submit_requests(
+        &self,
+        requests: &[ProofRequest],
+    ) -> Result<Vec<(U256, u64)>, ClientError>
+    where
+        <S as StorageProvider>::Error: std::fmt::Debug,
+    {
+        let signer = self.signer.as_deref().context("Signer not set")?;
+        self.submit_requests_with_signer(requests, signer).await
+    }
+
     /// Submit a proof request with a signature bytes.
     ///
     /// Accepts a signature bytes to be used as the request signature.
@@ -403,7 +666,7 @@ where
     pub async fn submit_request_offchain_with_signer(
         &self,
         request: &ProofRequest,
-        signer: &impl Signer,
+        signer: &(impl Signer + ?Sized),
     ) -> Result<(U256, u64), ClientError>
     where
         <S as StorageProvider>::Error: std::fmt::Debug,
@@ -422,7 +685,7 @@ where
             return Err(MarketError::AddressMismatch(client_address, signer.address()))?;
         };
         if request.offer.biddingStart == 0 {
-            request.offer.biddingStart = now_timestamp() + self.bidding_start_delay
+            request.offer.biddingStart = self.clock.now() + self.bidding_start_delay
         };
         // Ensure address' balance is sufficient to cover the request
         let balance = self.boundless_market.balance_of(client_address).await?;
@@ -441,7 +704,7 @@ where
 
     /// Submit a proof request offchain via the order stream service.
     ///
-    /// Requires a local signer to be set to sign the request.
+    /// Requires a signer to be set to sign the request.
     /// If the request ID is not set, a random ID will be generated.
     /// If the bidding start is not set, the current timestamp plus a delay will be used.
     pub async fn submit_request_offchain(
@@ -451,10 +714,221 @@ where
     where
         <S as StorageProvider>::Error: std::fmt::Debug,
     {
-        let signer = self.local_signer.as_ref().context("Local signer not set")?;
+        let signer = self.signer.as_deref().context("Signer not set")?;
         self.submit_request_offchain_with_signer(request, signer).await
     }
 
+    /// Submit a batch of proof requests offchain via the order stream service, in a single call.
+    ///
+    /// Accepts a signer to sign the requests. Requests without an ID are assigned a random one,
+    /// and requests without a bidding start use the current time plus a delay. The client balance
+    /// must cover the total max price of the batch. Returns the result for each request, in order.
+    pub async fn submit_requests_offchain_with_signer(
+        &self,
+        requests: &[ProofRequest],
+        signer: &(impl Signer + ?Sized),
+    ) -> Result<Vec<Result<(U256, u64), ClientError>>, ClientError>
+    where
+        <S as StorageProvider>::Error: std::fmt::Debug,
+    {
+        let offchain_client = self
+            .offchain_client
+            .as_ref()
+            .context("Order stream client not available. Please provide an order stream URL")?;
+        let requests = self.prepare_requests(requests, signer).await?;
+
+        // Ensure address' balance is sufficient to cover the whole batch
+        let total_max_price =
+            requests.iter().map(|request| U256::from(request.offer.maxPrice)).sum::<U256>();
+        let balance = self.boundless_market.balance_of(signer.address()).await?;
+        if balance < total_max_price {
+            return Err(ClientError::Error(anyhow!(
+                "Insufficient balance to cover requests: {} < {}.\nMake sure to top up your balance by depositing on the Boundless Market.",
+                balance,
+                total_max_price
+            )));
+        }
+
+        let orders = offchain_client.submit_requests(&requests, signer).await?;
+        Ok(orders
+            .into_iter()
+            .map(|order| {
+                let order = order?;
+                Ok((order.request.id, order.request.expires_at()))
+            })
+            .collect())
+    }
+
+    /// Submit a batch of proof requests offchain via the order stream service, in a single call.
+    ///
+    /// Requires a signer to be set to sign the requests.
+    /// Requests without an ID are assigned a random one, and requests without a bidding start use
+    /// the current timestamp plus a delay.
+    pub async fn submit_requests_offchain(
+        &self,
+        requests: &[ProofRequest],
+    ) -> Result<Vec<Result<(U256, u64), ClientError>>, ClientError>
+    where
+        <S as StorageProvider>::Error: std::fmt::Debug,
+    {
+        let signer = self.signer.as_deref().context("Signer not set")?;
+        self.submit_requests_offchain_with_signer(requests, signer).await
+    }
+
+    /// Assigns IDs and bidding start times to the requests of a batch, and checks that they are
+    /// all issued by the given signer.
+    async fn prepare_requests(
+        &self,
+        requests: &[ProofRequest],
+        signer: &(impl Signer + ?Sized),
+    ) -> Result<Vec<ProofRequest>, ClientError> {
+        let mut requests = requests.to_vec();
+        let missing_ids = requests.iter().filter(|request| request.id == U256::ZERO).count();
+        let mut ids = self.boundless_market.request_ids_from_rand(missing_ids).await?.into_iter();
+        let bidding_start = self.clock.now() + self.bidding_start_delay;
+        for request in requests.iter_mut() {
+            if request.id == U256::ZERO {
+                request.id = ids.next().expect("one ID per request without an ID");
+            }
+            let client_address = request.client_address();
+            if client_address != signer.address() {
+                return Err(MarketError::AddressMismatch(client_address, signer.address()))?;
+            };
+            if request.offer.biddingStart == 0 {
+                request.offer.biddingStart = bidding_start;
+            };
+        }
+        Ok(requests)
+    }
+
+    /// Resubmit a proof request with a new offer, under the same request ID.
+    ///
+    /// Only one request per ID can ever be fulfilled, so the original request is superseded:
+    /// whichever version is locked first wins. This is typically used to raise the price of a
+    /// request no prover locked, e.g. with [Offer::with_price_bump]. Fails if the request was
+    /// already locked or fulfilled. If the new offer keeps the bidding start of the original
+    /// request, the auction restarts at the current time plus a delay.
+    pub async fn resubmit_request_with_signer(
+        &self,
+        request: &ProofRequest,
+        offer: Offer,
+        signer: &(impl Signer + ?Sized),
+    ) -> Result<(U256, u64), ClientError>
+    where
+        <S as StorageProvider>::Error: std::fmt::Debug,
+    {
+        let request = self.prepare_resubmission(request, offer).await?;
+        self.submit_request_with_signer(&request, signer).await
+    }
+
+    /// Resubmit a proof request with a new offer, under the same request ID.
+    ///
+    /// Requires a signer to be set to sign the request.
+    /// See [Client::resubmit_request_with_signer].
+    pub async fn resubmit_request(
+        &self,
+        request: &ProofRequest,
+        offer: Offer,
+    ) -> Result<(U256, u64), ClientError>
+    where
+        <S as StorageProvider>::Error: std::fmt::Debug,
+    {
+        let signer = self.signer.as_deref().context("Signer not set")?;
+        self.resubmit_request_with_signer(request, offer, signer).await
+    }
+
+    /// Resubmit a proof request offchain with a new offer, under the same request ID.
+    ///
+    /// The order of the original request is cancelled on the order stream before the new one is
+    /// submitted, so that brokers stop pricing it. Fails if the request was already locked or
+    /// fulfilled. If the new offer keeps the bidding start of the original request, the auction
+    /// restarts at the current time plus a delay.
+    pub async fn resubmit_request_offchain_with_signer(
+        &self,
+        request: &ProofRequest,
+        offer: Offer,
+        signer: &(impl Signer + ?Sized),
+    ) -> Result<(U256, u64), ClientError>
+    where
+        <S as StorageProvider>::Error: std::fmt::Debug,
+    {
+        let offchain_client = self
+            .offchain_client
+            .as_ref()
+            .context("Order stream client not available. Please provide an order stream URL")?;
+        let resubmission = self.prepare_resubmission(request, offer).await?;
+
+        // The original order may not be open anymore, e.g. if it was already cancelled.
+        if let Err(err) = offchain_client.cancel_request(request, signer).await {
+            tracing::warn!("Failed to cancel order 0x{:x}: {err:?}", request.id);
+        }
+
+        self.submit_request_offchain_with_signer(&resubmission, signer).await
+    }
+
+    /// Resubmit a proof request offchain with a new offer, under the same request ID.
+    ///
+    /// Requires a signer to be set to sign the request.
+    /// See [Client::resubmit_request_offchain_with_signer].
+    pub async fn resubmit_request_offchain(
+        &self,
+        request: &ProofRequest,
+        offer: Offer,
+    ) -> Result<(U256, u64), ClientError>
+    where
+        <S as StorageProvider>::Error: std::fmt::Debug,
+    {
+        let signer = self.signer.as_deref().context("Signer not set")?;
+        self.resubmit_request_offchain_with_signer(request, offer, signer).await
+    }
+
+    /// Cancel the offchain order of a proof request that was not yet locked.
+    ///
+    /// Brokers connected to the order stream are notified of the cancellation and skip the
+    /// request, unless they already started pricing it. Note that a signed request remains valid
+    /// on-chain until it expires, so a broker may still lock it.
+    pub async fn cancel_request_offchain_with_signer(
+        &self,
+        request: &ProofRequest,
+        signer: &(impl Signer + ?Sized),
+    ) -> Result<(), ClientError> {
+        let offchain_client = self
+            .offchain_client
+            .as_ref()
+            .context("Order stream client not available. Please provide an order stream URL")?;
+        offchain_client.cancel_request(request, signer).await?;
+        Ok(())
+    }
+
+    /// Cancel the offchain order of a proof request that was not yet locked.
+    ///
+    /// Requires a signer to be set to sign the cancellation.
+    pub async fn cancel_request_offchain(&self, request: &ProofRequest) -> Result<(), ClientError> {
+        let signer = self.signer.as_deref().context("Signer not set")?;
+        self.cancel_request_offchain_with_signer(request, signer).await
+    }
+
+    /// Checks that a request can still be superseded, and returns a copy with the new offer.
+    async fn prepare_resubmission(
+        &self,
+        request: &ProofRequest,
+        mut offer: Offer,
+    ) -> Result<ProofRequest, ClientError> {
+        match self.boundless_market.get_status(request.id, Some(request.expires_at())).await? {
+            RequestStatus::Unknown | RequestStatus::Expired => {}
+            status => {
+                return Err(ClientError::Error(anyhow!(
+                    "request 0x{:x} cannot be resubmitted, status is {status:?}",
+                    request.id
+                )));
+            }
+        }
+        if offer.biddingStart == request.offer.biddingStart {
+            offer.biddingStart = 0;
+        }
+        Ok(ProofRequest { offer, ..request.clone() })
+    }
+
     /// Wait for a request to be fulfilled.
     ///
     /// The check interval is the time between each check for fulfillment.
@@ -471,6 +945,260 @@ where
             .await?)
     }
 
+    /// Watch the lifecycle of a request.
+    ///
+    /// Returns a stream of [RequestEvent] built from the market events for the request, followed
+    /// through a log filter installed on the RPC node, or by polling the logs at the check interval
+    /// when log filters are not supported. Past events are replayed first, within the event query
+    /// window of the market service. The stream ends once the request is fulfilled, slashed, or
+    /// expires without having been locked.
+    ///
+    /// Errors are logged and retried at the next check interval.
+    pub fn watch_request(
+        &self,
+        request_id: U256,
+        check_interval: Duration,
+    ) -> impl Stream<Item = RequestEvent> + '_ {
+        stream! {
+            let mut watch = RequestWatch {
+                request_id,
+                provider: self.boundless_market.instance().provider().clone(),
+                cursor: LogCursor::Start,
+                pending: VecDeque::new(),
+                state: RequestState::default(),
+            };
+            // Requests submitted off-chain only appear on-chain once locked, so their offer is
+            // fetched from the order stream to know when they expire.
+            if let Some(offchain_client) = &self.offchain_client {
+                if let Ok(order) = offchain_client.fetch_order(request_id, None).await {
+                    watch.state.offer = Some(order.request.offer);
+                }
+            }
+
+            let mut interval = tokio::time::interval(check_interval);
+            loop {
+                interval.tick().await;
+                let events = match self.poll_request(&mut watch).await {
+                    Ok(events) => events,
+                    Err(err) => {
+                        tracing::warn!("Failed to poll request 0x{:x}: {}", request_id, err);
+                        continue;
+                    }
+                };
+                for event in events {
+                    let last = match event {
+                        RequestEvent::Fulfilled { .. } | RequestEvent::Slashed => true,
+                        // A locked request can still be slashed after it expires.
+                        RequestEvent::Expired => !watch.state.locked,
+                        _ => false,
+                    };
+                    yield event;
+                    if last {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the new events of the watched request.
+    ///
+    /// The pending logs and the request state are only updated once all the events are built, so
+    /// that a failure part way through retries the whole batch rather than dropping events.
+    async fn poll_request(&self, watch: &mut RequestWatch<P>) -> Result<Vec<RequestEvent>> {
+        self.fetch_request_logs(watch).await?;
+
+        let mut state = watch.state.clone();
+        let mut events = Vec::new();
+        let mut processed = 0;
+        let mut finished = false;
+        for log in watch.pending.iter() {
+            processed += 1;
+            let position =
+                (log.block_number.unwrap_or_default(), log.log_index.unwrap_or_default());
+            if state.last_log.is_some_and(|last| position <= last) {
+                continue;
+            }
+            let event = IBoundlessMarketEvents::decode_log(&log.inner, true)
+                .context("failed to decode market event")?;
+            match event.data {
+                IBoundlessMarketEvents::RequestSubmitted(_) => {
+                    if state.offer.is_none() {
+                        let tx = self.transaction(log).await?;
+                        let call = find_submitted_request(tx.input(), watch.request_id)
+                            .context("Failed to decode input")?;
+                        state.offer = Some(call.request.offer);
+                    }
+                    events.push(RequestEvent::Submitted);
+                }
+                IBoundlessMarketEvents::RequestLocked(data) => {
+                    let price = self.lock_price(log, state.offer.as_ref()).await?;
+                    state.locked = true;
+                    events.push(RequestEvent::Locked { prover: data.prover, price });
+                }
+                IBoundlessMarketEvents::RequestFulfilled(_) => {
+                    let (journal, seal) =
+                        self.boundless_market.get_request_fulfillment(watch.request_id).await?;
+                    events.push(RequestEvent::Fulfilled { journal, seal });
+                    finished = true;
+                }
+                IBoundlessMarketEvents::ProverSlashed(_) => {
+                    events.push(RequestEvent::Slashed);
+                    finished = true;
+                }
+                _ => {}
+            }
+            state.last_log = Some(position);
+            if finished {
+                break;
+            }
+        }
+
+        if !finished {
+            // Once locked, the deadlines recorded by the market take precedence over the offer.
+            if state.locked && state.deadlines.is_none() {
+                let instance = self.boundless_market.instance();
+                let lock_deadline = instance.requestLockDeadline(watch.request_id).call().await?._0;
+                let deadline = instance.requestDeadline(watch.request_id).call().await?._0;
+                state.deadlines = Some((lock_deadline, deadline));
+            }
+            let deadlines = state.deadlines.or_else(|| {
+                state.offer.as_ref().map(|offer| (offer.lock_deadline(), offer.deadline()))
+            });
+            if let Some((lock_deadline, deadline)) = deadlines {
+                let timestamp = self.boundless_market.get_latest_block_timestamp().await?;
+                if state.locked && !state.lock_expired && timestamp > lock_deadline {
+                    state.lock_expired = true;
+                    events.push(RequestEvent::LockExpired);
+                }
+                if !state.expired && timestamp > deadline {
+                    state.expired = true;
+                    events.push(RequestEvent::Expired);
+                }
+            }
+        }
+
+        watch.pending.drain(..processed);
+        watch.state = state;
+        Ok(events)
+    }
+
+    /// Appends the new logs of the watched request to its pending logs.
+    async fn fetch_request_logs(&self, watch: &mut RequestWatch<P>) -> Result<()> {
+        let provider = self.boundless_market.instance().provider();
+        let filter = Filter::new()
+            .address(*self.boundless_market.instance().address())
+            .event_signature(vec![
+                IBoundlessMarket::RequestSubmitted::SIGNATURE_HASH,
+                IBoundlessMarket::RequestLocked::SIGNATURE_HASH,
+                IBoundlessMarket::RequestFulfilled::SIGNATURE_HASH,
+                IBoundlessMarket::ProverSlashed::SIGNATURE_HASH,
+            ])
+            .topic1(watch.request_id);
+
+        match watch.cursor {
+            LogCursor::Start => {
+                let config = self.boundless_market.event_query_config();
+                let latest_block = provider.get_block_number().await?;
+                let from_block =
+                    latest_block.saturating_sub(config.block_range * config.max_iterations);
+                self.query_logs(watch, &filter, from_block, latest_block).await?;
+                watch.cursor = match provider.new_filter(&filter.from_block(latest_block + 1)).await
+                {
+                    Ok(filter_id) => LogCursor::Filter(filter_id, latest_block + 1),
+                    Err(err) => {
+                        tracing::debug!("Log filters not supported, polling logs instead: {err}");
+                        LogCursor::Poll(latest_block + 1)
+                    }
+                };
+            }
+            LogCursor::Filter(filter_id, filter_block) => {
+                match provider.get_filter_changes::<Log>(filter_id).await {
+                    Ok(logs) => watch.pending.extend(logs),
+                    Err(err) => {
+                        // The node may drop filters that are not polled often enough.
+                        tracing::warn!("Log filter failed, polling logs instead: {err}");
+                        let from_block = watch
+                            .state
+                            .last_log
+                            .map_or(filter_block, |(block, _)| block.max(filter_block));
+                        watch.cursor = LogCursor::Poll(from_block);
+                    }
+                }
+            }
+            LogCursor::Poll(from_block) => {
+                let latest_block = provider.get_block_number().await?;
+                if latest_block >= from_block {
+                    self.query_logs(watch, &filter, from_block, latest_block).await?;
+                    watch.cursor = LogCursor::Poll(latest_block + 1);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends the logs in the given block range to the pending logs, querying the range in
+    /// chunks of the event query block range.
+    async fn query_logs(
+        &self,
+        watch: &mut RequestWatch<P>,
+        filter: &Filter,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<()> {
+        let block_range = self.boundless_market.event_query_config().block_range.max(1);
+        let mut logs = Vec::new();
+        let mut lower_block = from_block;
+        while lower_block <= to_block {
+            let upper_block = (lower_block + block_range - 1).min(to_block);
+            let filter = filter.clone().from_block(lower_block).to_block(upper_block);
+            logs.extend(self.boundless_market.instance().provider().get_logs(&filter).await?);
+            lower_block = upper_block + 1;
+        }
+        watch.pending.extend(logs);
+        Ok(())
+    }
+
+    /// Returns the price at which a request was locked, from the request in the lock transaction
+    /// or the given offer.
+    async fn lock_price(&self, log: &Log, offer: Option<&Offer>) -> Result<Option<U256>> {
+        let tx = self.transaction(log).await?;
+        let offer =
+            if let Ok(call) = IBoundlessMarket::lockRequestCall::abi_decode(tx.input(), true) {
+                call.request.offer
+            } else if let Ok(call) =
+                IBoundlessMarket::lockRequestWithSignatureCall::abi_decode(tx.input(), true)
+            {
+                call.request.offer
+            } else if let Some(offer) = offer {
+                offer.clone()
+            } else {
+                return Ok(None);
+            };
+
+        let block_timestamp = match log.block_timestamp {
+            Some(timestamp) => timestamp,
+            None => self
+                .provider()
+                .get_block_by_number(BlockNumberOrTag::Number(
+                    log.block_number.context("block number is none")?,
+                ))
+                .await?
+                .context("failed to get block")?
+                .header
+                .timestamp(),
+        };
+        Ok(Some(offer.price_at(block_timestamp)?))
+    }
+
+    async fn transaction(&self, log: &Log) -> Result<alloy::rpc::types::Transaction> {
+        self.provider()
+            .get_transaction_by_hash(log.transaction_hash.context("tx hash is none")?)
+            .await
+            .context("Failed to get transaction")?
+            .context("Transaction not found")
+    }
+
     /// Get the [SetInclusionReceipt] for a request.
     ///
     /// Example:
@@ -493,13 +1221,85 @@ where
         request_id: U256,
         image_id: B256,
     ) -> Result<(Bytes, SetInclusionReceipt<ReceiptClaim>), ClientError> {
-        let (journal, seal) = self.boundless_market.get_request_fulfillment(request_id).await?;
+        let (journal, seal) = self.fetch_fulfillment(request_id, image_id).await?;
         let claim = ReceiptClaim::ok(Digest::from(image_id.0), journal.to_vec());
         let receipt =
             self.set_verifier.fetch_receipt_with_claim(seal, claim, journal.to_vec()).await?;
         Ok((journal, receipt))
     }
 
+    /// Fetch the journal and seal of a fulfilled request.
+    ///
+    /// The fulfillment is recovered from the fulfilling transaction on-chain. If this fails, e.g.
+    /// because the event or transaction is older than the RPC provider keeps, the fulfillment
+    /// sources of the client are tried in order. Fulfillments from these sources are checked
+    /// against the requirements of the request and the verifier of the market, and rejected
+    /// fulfillments are skipped. If the fallback fails as well, both errors are returned as
+    /// [ClientError::FulfillmentFetchFailed].
+    pub async fn fetch_fulfillment(
+        &self,
+        request_id: U256,
+        image_id: B256,
+    ) -> Result<(Bytes, Bytes), ClientError> {
+        let err = match self.boundless_market.get_request_fulfillment(request_id).await {
+            Ok(fulfillment) => return Ok(fulfillment),
+            Err(err) => err,
+        };
+        if self.fulfillment_sources.is_empty() {
+            return Err(err.into());
+        }
+        tracing::debug!("Failed to fetch fulfillment of 0x{request_id:x} from chain: {err}");
+
+        match self.fetch_fulfillment_from_sources(request_id, image_id).await {
+            Ok(Some(fulfillment)) => Ok(fulfillment),
+            Ok(None) => Err(err.into()),
+            Err(sources_err) => Err(ClientError::FulfillmentFetchFailed {
+                chain: err,
+                sources: Box::new(sources_err),
+            }),
+        }
+    }
+
+    /// Fetch the journal and seal of a fulfilled request from the fulfillment sources, returning
+    /// `None` if no source has a fulfillment accepted by the verifier.
+    async fn fetch_fulfillment_from_sources(
+        &self,
+        request_id: U256,
+        image_id: B256,
+    ) -> Result<Option<(Bytes, Bytes)>, ClientError> {
+        let order = self.fetch_order(request_id, None, None).await?;
+        let requirements = order.request.requirements;
+        if requirements.imageId != image_id {
+            return Err(ClientError::Error(anyhow!(
+                "Image ID {image_id} does not match the request image ID {}",
+                requirements.imageId
+            )));
+        }
+        let verifier = self.boundless_market.verifier_address().await?;
+        for source in &self.fulfillment_sources {
+            let fulfillment: FulfillmentData = match source.fetch(request_id).await {
+                Ok(Some(fulfillment)) => fulfillment,
+                Ok(None) => continue,
+                Err(err) => {
+                    tracing::warn!(
+                        "Failed to fetch fulfillment of 0x{request_id:x} from {}: {err}",
+                        source.name()
+                    );
+                    continue;
+                }
+            };
+            if verify_fulfillment(self.provider(), verifier, &requirements, &fulfillment).await? {
+                tracing::debug!("Fetched fulfillment of 0x{request_id:x} from {}", source.name());
+                return Ok(Some((fulfillment.journal, fulfillment.seal)));
+            }
+            tracing::warn!(
+                "Fulfillment of 0x{request_id:x} from {} rejected by the verifier",
+                source.name()
+            );
+        }
+        Ok(None)
+    }
+
     /// Fetch an order as a proof request and signature pair.
     ///
     /// If the request is not found in the boundless market, it will be fetched from the order stream service.
@@ -539,32 +1339,60 @@ impl Client<ProviderWallet, BuiltinStorageProvider> {
     /// Create a new client from environment variables
     ///
     /// The following environment variables are required:
-    /// - PRIVATE_KEY: The private key of the wallet
+    /// - PRIVATE_KEY: The private key of the wallet, or SIGNER_URI: The [SignerUri] of the key
     /// - RPC_URL: The URL of the RPC server
-    /// - ORDER_STREAM_URL: The URL of the order stream server
+    ///
+    /// The following environment variables are optional:
     /// - BOUNDLESS_MARKET_ADDRESS: The address of the market contract
     /// - SET_VERIFIER_ADDRESS: The address of the set verifier contract
+    /// - ORDER_STREAM_URL: The URL of the order stream server
+    /// - FULFILLMENT_ARCHIVE_URL: The URL of a fulfillment archive
+    ///
+    /// Addresses and the order stream URL that are not set are taken from the [Deployments]
+    /// registry, by the chain ID of the RPC server.
+    ///
+    /// The chain clock of the client is synced once. To keep it synced, pass a [ChainClock]
+    /// started with [ChainClock::spawn_sync] to [Client::with_clock].
     pub async fn from_env() -> Result<Self, ClientError> {
-        let private_key_str = env::var("private_key").context("private_key not set")?;
-        let private_key =
-            PrivateKeySigner::from_str(&private_key_str).context("Invalid private_key")?;
+        let signer_uri = match env::var("SIGNER_URI") {
+            Ok(uri) => uri,
+            Err(_) => env::var("private_key").context("SIGNER_URI or private_key not set")?,
+        };
+        let signer = SignerUri::from_str(&signer_uri)?.signer().await?;
         let rpc_url_str = env::var("RPC_URL").context("RPC_URL not set")?;
         let rpc_url = Url::parse(&rpc_url_str).context("Invalid RPC_URL")?;
-        let boundless_market_address_str =
-            env::var("BOUNDLESS_MARKET_ADDRESS").context("BOUNDLESS_MARKET_ADDRESS not set")?;
-        let boundless_market_address = Address::from_str(&boundless_market_address_str)
+        let boundless_market_address = env::var("BOUNDLESS_MARKET_ADDRESS")
+            .ok()
+            .map(|addr| Address::from_str(&addr))
+            .transpose()
             .context("Invalid BOUNDLESS_MARKET_ADDRESS")?;
-        let set_verifier_address_str =
-            env::var("SET_VERIFIER_ADDRESS").context("SET_VERIFIER_ADDRESS not set")?;
-        let set_verifier_address =
-            Address::from_str(&set_verifier_address_str).context("Invalid SET_VERIFIER_ADDRESS")?;
+        let set_verifier_address = env::var("SET_VERIFIER_ADDRESS")
+            .ok()
+            .map(|addr| Address::from_str(&addr))
+            .transpose()
+            .context("Invalid SET_VERIFIER_ADDRESS")?;
 
-        let caller = private_key.address();
-        let wallet = EthereumWallet::from(private_key.clone());
+        let caller = Signer::address(&signer);
+        let wallet = EthereumWallet::from(signer.clone());
+        #[allow(deprecated)]
         let provider = ProviderBuilder::new()
             .wallet(wallet.clone())
             .layer(BalanceAlertLayer::default())
             .on_http(rpc_url);
+        let chain_id = provider.get_chain_id().await.context("Failed to get chain ID")?;
+
+        let (boundless_market_address, set_verifier_address, deployment) =
+            match (boundless_market_address, set_verifier_address) {
+                (Some(market), Some(set_verifier)) => (market, set_verifier, None),
+                (market, set_verifier) => {
+                    let deployment = Deployments::load()?.resolve(chain_id)?;
+                    (
+                        market.unwrap_or(deployment.boundless_market_address),
+                        set_verifier.unwrap_or(deployment.set_verifier_address),
+                        Some(deployment),
+                    )
+                }
+            };
 
         let boundless_market =
             BoundlessMarketService::new(boundless_market_address, provider.clone(), caller);
@@ -575,31 +1403,56 @@ impl Client<ProviderWallet, BuiltinStorageProvider> {
             Err(_) => None,
         };
 
-        let chain_id = provider.get_chain_id().await.context("Failed to get chain ID")?;
+        let clock = ChainClock::new(provider.clone());
+        clock.sync().await.context("Failed to sync chain clock")?;
 
-        let order_stream_url = env::var("ORDER_STREAM_URL");
-        let offchain_client = match order_stream_url {
-            Ok(url) => Some(OrderStreamClient::new(
-                Url::parse(&url).context("Invalid ORDER_STREAM_URL")?,
-                boundless_market_address,
-                chain_id,
-            )),
-            Err(_) => None,
+        let order_stream_url = match env::var("ORDER_STREAM_URL") {
+            Ok(url) => Some(Url::parse(&url).context("Invalid ORDER_STREAM_URL")?),
+            // Only default to the order stream of the deployment when using its market.
+            Err(_) => match deployment {
+                Some(deployment)
+                    if deployment.boundless_market_address == boundless_market_address =>
+                {
+                    deployment.order_stream_url()?
+                }
+                _ => None,
+            },
         };
+        let offchain_client = order_stream_url
+            .map(|url| OrderStreamClient::new(url, boundless_market_address, chain_id));
+
+        let mut fulfillment_sources: Vec<Arc<dyn FulfillmentSource>> = Vec::new();
+        if let Ok(url) = env::var("FULFILLMENT_ARCHIVE_URL") {
+            let url = Url::parse(&url).context("Invalid FULFILLMENT_ARCHIVE_URL")?;
+            fulfillment_sources.push(Arc::new(HttpArchiveSource::new(url)));
+        }
+        if let Some(client) = &offchain_client {
+            fulfillment_sources.push(Arc::new(OrderStreamSource::new(client.clone())));
+        }
 
         Ok(Self {
             boundless_market,
             set_verifier,
             storage_provider,
             offchain_client,
-            local_signer: Some(private_key),
+            signer: Some(Arc::new(signer)),
             bidding_start_delay: BIDDING_START_DELAY,
+            fulfillment_sources,
+            clock: Arc::new(clock),
         })
     }
 }
 
 impl<P: StorageProvider> Client<ProviderWallet, P> {
     /// Create a new client from parts
+    ///
+    /// The chain clock of the client is synced once. To keep it synced, pass a
+    /// [ChainClock] started with [ChainClock::spawn_sync] to [Client::with_clock], or use
+    /// [ClientBuilder::with_clock_sync_interval].
+    ///
+    /// The `balance_alerts` are deprecated, pass `None` and use [Client::balance_monitor] to watch
+    /// balances instead.
+    #[allow(deprecated)]
     pub async fn from_parts(
         wallet: EthereumWallet,
         rpc_url: Url,
@@ -621,16 +1474,26 @@ impl<P: StorageProvider> Client<ProviderWallet, P> {
         let set_verifier = SetVerifierService::new(set_verifier_address, provider.clone(), caller);
 
         let chain_id = provider.get_chain_id().await.context("Failed to get chain ID")?;
+        let clock = ChainClock::new(provider.clone());
+        clock.sync().await.context("Failed to sync chain clock")?;
         let offchain_client = order_stream_url
             .map(|url| OrderStreamClient::new(url, boundless_market_address, chain_id));
+        let fulfillment_sources = offchain_client
+            .iter()
+            .map(|client| {
+                Arc::new(OrderStreamSource::new(client.clone())) as Arc<dyn FulfillmentSource>
+            })
+            .collect();
 
         Ok(Self {
             boundless_market,
             set_verifier,
             storage_provider,
             offchain_client,
-            local_signer: None,
+            signer: None,
             bidding_start_delay: BIDDING_START_DELAY,
+            fulfillment_sources,
+            clock: Arc::new(clock),
         })
     }
 }
@@ -0,0 +1,250 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Clocks used for deadline computations.
+//!
+//! The market compares the bidding start, lock timeout and timeout of a request against the
+//! timestamp of the block that includes a transaction, not against the time of the host sending
+//! it. On hosts with a skewed system clock, or on chains with irregular block times, the
+//! [SystemClock] can therefore be off by enough to lock an order that is already expired, or to
+//! set a bidding start that is still in the future for the chain. The [ChainClock] estimates the
+//! time of the chain from the latest block headers instead.
+
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use alloy::{
+    eips::BlockNumberOrTag, network::Ethereum, providers::Provider, transports::TransportError,
+};
+use thiserror::Error;
+
+/// Errors that can occur when syncing a [ChainClock].
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum ClockError {
+    /// RPC error.
+    #[error("RPC error {0}")]
+    RpcError(#[from] TransportError),
+
+    /// The latest block was not returned by the provider.
+    #[error("latest block not found")]
+    MissingBlock,
+}
+
+/// A source of the current time, as a UNIX timestamp in seconds.
+pub trait Clock: Send + Sync {
+    /// Returns the current UNIX timestamp, in seconds.
+    fn now(&self) -> u64;
+}
+
+/// Shared, dynamically typed [Clock].
+pub type ClockObj = Arc<dyn Clock>;
+
+/// The system clock of the host.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time before UNIX epoch")
+            .as_secs()
+    }
+}
+
+/// A clock that only moves when told to, for tests.
+#[derive(Clone, Debug, Default)]
+pub struct FakeClock(Arc<AtomicU64>);
+
+impl FakeClock {
+    /// Creates a clock stopped at the given timestamp.
+    pub fn new(timestamp: u64) -> Self {
+        Self(Arc::new(AtomicU64::new(timestamp)))
+    }
+
+    /// Sets the timestamp of the clock.
+    pub fn set(&self, timestamp: u64) {
+        self.0.store(timestamp, Ordering::SeqCst);
+    }
+
+    /// Moves the clock forward by the given number of seconds.
+    pub fn advance(&self, secs: u64) {
+        self.0.fetch_add(secs, Ordering::SeqCst);
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Observation {
+    number: u64,
+    timestamp: u64,
+    observed_at: Instant,
+}
+
+/// A clock following the timestamps of the chain.
+///
+/// The clock records the timestamp of the latest block header it has observed, and projects it
+/// forward with the monotonic clock of the host, so it does not depend on the system time being
+/// correct. The projection is only as fresh as the latest observation, so the clock should be
+/// synced about once per block, either with [ChainClock::spawn_sync] or by feeding it the headers
+/// fetched by another service with [ChainClock::observe].
+///
+/// Until the first block is observed, the clock falls back to the system clock.
+#[derive(Clone)]
+pub struct ChainClock<P> {
+    provider: P,
+    latest: Arc<Mutex<Option<Observation>>>,
+    // Latest time returned, so that the clock never goes backwards when a block is observed with
+    // a timestamp behind the projection.
+    last: Arc<AtomicU64>,
+}
+
+impl<P> ChainClock<P> {
+    /// Creates a clock following the chain of the given provider.
+    pub fn new(provider: P) -> Self {
+        Self { provider, latest: Arc::new(Mutex::new(None)), last: Arc::new(AtomicU64::new(0)) }
+    }
+
+    /// Records the number and timestamp of a block header.
+    ///
+    /// Only the first observation of each new block is recorded, as it is the closest to the
+    /// time the block was produced.
+    pub fn observe(&self, number: u64, timestamp: u64) {
+        self.observe_at(number, timestamp, Instant::now());
+    }
+
+    fn observe_at(&self, number: u64, timestamp: u64, observed_at: Instant) {
+        let mut latest = self.latest.lock().unwrap();
+        if latest.is_some_and(|prev| number <= prev.number) {
+            return;
+        }
+        *latest = Some(Observation { number, timestamp, observed_at });
+    }
+
+    /// Returns the number of the latest observed block, if any.
+    pub fn latest_block(&self) -> Option<u64> {
+        self.latest.lock().unwrap().map(|obs| obs.number)
+    }
+}
+
+impl<P> ChainClock<P>
+where
+    P: Provider<Ethereum>,
+{
+    /// Fetches the latest block header and records it, returning the updated time of the clock.
+    pub async fn sync(&self) -> Result<u64, ClockError> {
+        let block = self
+            .provider
+            .get_block_by_number(BlockNumberOrTag::Latest)
+            .await?
+            .ok_or(ClockError::MissingBlock)?;
+        self.observe(block.header.number, block.header.timestamp);
+        Ok(self.now())
+    }
+}
+
+impl<P> ChainClock<P>
+where
+    P: Provider<Ethereum> + Clone + 'static,
+{
+    /// Spawns a task syncing the clock at the given interval, until the returned handle is
+    /// aborted or all other clones of the clock are dropped.
+    pub fn spawn_sync(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let clock = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                if Arc::strong_count(&clock.latest) == 1 {
+                    break;
+                }
+                if let Err(err) = clock.sync().await {
+                    tracing::warn!("Failed to sync chain clock: {err}");
+                }
+            }
+        })
+    }
+}
+
+impl<P> Clock for ChainClock<P>
+where
+    P: Send + Sync,
+{
+    fn now(&self) -> u64 {
+        let latest = *self.latest.lock().unwrap();
+        let projected = match latest {
+            Some(obs) => obs.timestamp + obs.observed_at.elapsed().as_secs(),
+            None => SystemClock.now(),
+        };
+        self.last.fetch_max(projected, Ordering::SeqCst).max(projected)
+    }
+}
+
+impl<P> Debug for ChainClock<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChainClock").field("latest", &self.latest).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fake_clock() {
+        let clock = FakeClock::new(100);
+        assert_eq!(clock.now(), 100);
+        clock.advance(5);
+        assert_eq!(clock.now(), 105);
+        clock.clone().set(42);
+        assert_eq!(clock.now(), 42);
+    }
+
+    #[test]
+    fn chain_clock_projection() {
+        let clock = ChainClock::new(());
+        let start = Instant::now() - Duration::from_secs(10);
+
+        // Far from the system time, as on a host with a skewed clock.
+        clock.observe_at(100, 1_000, start);
+        assert_eq!(clock.latest_block(), Some(100));
+        assert!((1_010..1_012).contains(&clock.now()));
+
+        // Later observations of the same block do not move the projection.
+        clock.observe(100, 1_000);
+        assert!((1_010..1_012).contains(&clock.now()));
+
+        clock.observe_at(102, 1_004, start + Duration::from_secs(4));
+        assert_eq!(clock.latest_block(), Some(102));
+        assert!((1_010..1_012).contains(&clock.now()));
+
+        // The clock does not go backwards when a block is behind the projection.
+        let before = clock.now();
+        clock.observe(103, 1_005);
+        assert!(clock.now() >= before);
+    }
+}
@@ -15,7 +15,10 @@ struct Predicate {
 
 enum PredicateType {
     DigestMatch,
-    PrefixMatch
+    PrefixMatch,
+    MaskedMatch,
+    AnyDigestMatch,
+    LengthRange
 }
 
 library PredicateLibrary {
@@ -36,6 +39,37 @@ library PredicateLibrary {
         return Predicate({predicateType: PredicateType.PrefixMatch, data: prefix});
     }
 
+    /// @notice Creates a masked match predicate.
+    /// @dev The journal matches if, for each byte of the mask, the journal byte at the given offset
+    /// is equal to the value byte, in the bits set in the mask.
+    /// @param offset The offset in the journal at which to start comparing.
+    /// @param mask The mask of bits to compare.
+    /// @param value The expected value, of the same length as the mask.
+    /// @return A Predicate struct with type MaskedMatch and the packed offset, mask and value.
+    function createMaskedMatchPredicate(uint32 offset, bytes memory mask, bytes memory value)
+        internal
+        pure
+        returns (Predicate memory)
+    {
+        require(mask.length == value.length, "mask and value length mismatch");
+        return Predicate({predicateType: PredicateType.MaskedMatch, data: abi.encodePacked(offset, mask, value)});
+    }
+
+    /// @notice Creates a predicate matching any one of the given journal digests.
+    /// @param digests The digests to match.
+    /// @return A Predicate struct with type AnyDigestMatch and the packed digests.
+    function createAnyDigestMatchPredicate(bytes32[] memory digests) internal pure returns (Predicate memory) {
+        return Predicate({predicateType: PredicateType.AnyDigestMatch, data: abi.encodePacked(digests)});
+    }
+
+    /// @notice Creates a predicate bounding the length of the journal.
+    /// @param minLength The minimum journal length, inclusive.
+    /// @param maxLength The maximum journal length, inclusive.
+    /// @return A Predicate struct with type LengthRange and the packed bounds.
+    function createLengthRangePredicate(uint32 minLength, uint32 maxLength) internal pure returns (Predicate memory) {
+        return Predicate({predicateType: PredicateType.LengthRange, data: abi.encodePacked(minLength, maxLength)});
+    }
+
     /// @notice Evaluates the predicate against the given journal and journal digest.
     /// @param predicate The predicate to evaluate.
     /// @param journal The journal to evaluate against.
@@ -50,6 +84,12 @@ library PredicateLibrary {
             return bytes32(predicate.data) == journalDigest;
         } else if (predicate.predicateType == PredicateType.PrefixMatch) {
             return startsWith(journal, predicate.data);
+        } else if (predicate.predicateType == PredicateType.MaskedMatch) {
+            return maskedMatch(journal, predicate.data);
+        } else if (predicate.predicateType == PredicateType.AnyDigestMatch) {
+            return anyDigestMatch(predicate.data, journalDigest);
+        } else if (predicate.predicateType == PredicateType.LengthRange) {
+            return lengthInRange(journal, predicate.data);
         } else {
             revert("Unreachable code");
         }
@@ -75,6 +115,60 @@ library PredicateLibrary {
         return keccak256(slice) == keccak256(prefix);
     }
 
+    /// @notice Checks if the journal matches the packed offset, mask and value in the given data.
+    /// @param journal The journal to check.
+    /// @param data The packed uint32 offset, mask and value, with the mask and value of equal length.
+    /// @return True if the journal matches, false otherwise or if the data is malformed.
+    function maskedMatch(bytes memory journal, bytes memory data) internal pure returns (bool) {
+        if (data.length < 4 || (data.length - 4) % 2 != 0) {
+            return false;
+        }
+        uint256 offset = uint32(bytes4(data));
+        uint256 length = (data.length - 4) / 2;
+        if (journal.length < offset + length) {
+            return false;
+        }
+        for (uint256 i = 0; i < length; i++) {
+            bytes1 mask = data[4 + i];
+            if (journal[offset + i] & mask != data[4 + length + i] & mask) {
+                return false;
+            }
+        }
+        return true;
+    }
+
+    /// @notice Checks if the journal digest is one of the packed digests in the given data.
+    /// @param data The packed list of digests.
+    /// @param journalDigest The digest of the journal.
+    /// @return True if the digest is in the list, false otherwise or if the data is malformed.
+    function anyDigestMatch(bytes memory data, bytes32 journalDigest) internal pure returns (bool) {
+        if (data.length % 32 != 0) {
+            return false;
+        }
+        for (uint256 i = 0; i < data.length; i += 32) {
+            bytes32 digest;
+            assembly {
+                digest := mload(add(add(data, 0x20), i))
+            }
+            if (digest == journalDigest) {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    /// @notice Checks if the journal length is within the packed bounds in the given data.
+    /// @param journal The journal to check.
+    /// @param data The packed uint32 minimum and maximum length, both inclusive.
+    /// @return True if the length is within the bounds, false otherwise or if the data is malformed.
+    function lengthInRange(bytes memory journal, bytes memory data) internal pure returns (bool) {
+        if (data.length != 8) {
+            return false;
+        }
+        uint64 bounds = uint64(bytes8(data));
+        return journal.length >= uint32(bounds >> 32) && journal.length <= uint32(bounds);
+    }
+
     /// @notice Computes the EIP-712 digest for the given predicate.
     /// @param predicate The predicate to compute the digest for.
     /// @return The EIP-712 digest of the predicate.
@@ -14,38 +14,72 @@
 
 use std::{
     fmt::Debug,
-    sync::atomic::{AtomicU64, Ordering},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     time::Duration,
 };
 
 use alloy::{
     consensus::{BlockHeader, Transaction},
-    eips::BlockNumberOrTag,
+    eips::{BlockId, BlockNumberOrTag},
     network::Ethereum,
-    primitives::{Address, Bytes, B256, U256},
-    providers::Provider,
-    rpc::types::{Log, TransactionReceipt},
+    primitives::{Address, Bytes, FixedBytes, B256, U256},
+    providers::{Provider, RootProvider},
+    rpc::types::{Log, TransactionReceipt, TransactionRequest},
     signers::Signer,
+    transports::TransportError,
 };
 use alloy_sol_types::{SolCall, SolEvent};
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, ensure, Context, Result};
+use risc0_aggregation::SetInclusionReceiptVerifierParameters;
 use risc0_ethereum_contracts::event_query::EventQueryConfig;
+use risc0_zkvm::{sha::Digestible, Digest};
 use thiserror::Error;
 
+use crate::alerts::{BalanceMonitor, BalanceSource, BalanceWatch, TracingSink};
 use crate::contracts::token::{IERC20Permit, IHitPoints::IHitPointsErrors, Permit, IERC20};
 
 use super::{
     eip712_domain, AssessorReceipt, EIP712DomainSaltless, Fulfillment,
-    IBoundlessMarket::{self, IBoundlessMarketInstance},
+    IBoundlessMarket::{self, IBoundlessMarketErrors, IBoundlessMarketInstance},
     Offer, ProofRequest, RequestError, RequestId, RequestStatus, TxnErr, TXN_CONFIRM_TIMEOUT,
 };
 
+#[allow(missing_docs)]
+mod multicall {
+    alloy::sol! {
+        /// Batching interface implemented by the market contract, since version 2.
+        interface IMulticall {
+            function multicall(bytes[] calldata data) external returns (bytes[] memory results);
+        }
+    }
+}
+use multicall::IMulticall;
+
+#[allow(missing_docs)]
+mod verifier {
+    alloy::sol! {
+        /// Getter of the verifier used by the market contract.
+        interface IMarketVerifier {
+            function VERIFIER() external view returns (address);
+        }
+
+        /// Lookup of the verifier registered for a selector in a verifier router.
+        interface IVerifierRouter {
+            function getVerifier(bytes4 selector) external view returns (address);
+        }
+    }
+}
+use verifier::{IMarketVerifier, IVerifierRouter};
+
 /// Boundless market errors.
 #[derive(Error, Debug)]
 pub enum MarketError {
     /// Transaction error.
     #[error("Transaction error: {0}")]
-    TxnError(#[from] TxnErr),
+    TxnError(TxnErr),
 
     /// Request not fulfilled.
     #[error("Request is not fulfilled 0x{0:x}")]
@@ -82,12 +116,191 @@ pub enum MarketError {
     /// Timeout reached.
     #[error("Timeout: 0x{0:x}")]
     TimeoutReached(U256),
+
+    /// Simulated call to the contract reverted.
+    #[error("Simulation reverted: {0}")]
+    SimulationReverted(TxnErr),
+
+    /// RPC error other than a revert, such as a rate limit or timeout, which may succeed on
+    /// retry.
+    #[error("RPC error: {0}")]
+    RpcError(TransportError),
+
+    /// Transaction reverted, and replaying it did not reproduce the revert.
+    #[error("Transaction reverted: {0}")]
+    TxnReverted(B256),
+
+    /// The request is locked, and cannot be locked again.
+    #[error("Market reverted with RequestIsLocked(0x{0:x})")]
+    RequestIsLocked(U256),
+
+    /// The request has expired, or was neither locked nor priced in the same transaction.
+    #[error("Market reverted with RequestIsExpiredOrNotPriced(0x{0:x})")]
+    RequestIsExpiredOrNotPriced(U256),
+
+    /// The request is not locked.
+    #[error("Market reverted with RequestIsNotLocked(0x{0:x})")]
+    RequestIsNotLocked(U256),
+
+    /// The request is fulfilled.
+    #[error("Market reverted with RequestIsFulfilled(0x{0:x})")]
+    RequestIsFulfilled(U256),
+
+    /// The request is slashed.
+    #[error("Market reverted with RequestIsSlashed(0x{0:x})")]
+    RequestIsSlashed(U256),
+
+    /// The lock deadline of the request has passed.
+    #[error("Market reverted with RequestLockIsExpired(0x{request_id:x}, lock deadline {lock_deadline})")]
+    RequestLockIsExpired {
+        /// ID of the request.
+        request_id: U256,
+        /// Lock deadline of the request.
+        lock_deadline: u64,
+    },
+
+    /// The deadline of the request has passed.
+    #[error("Market reverted with RequestIsExpired(0x{request_id:x}, deadline {deadline})")]
+    RequestIsExpired {
+        /// ID of the request.
+        request_id: U256,
+        /// Deadline of the request.
+        deadline: u64,
+    },
+
+    /// The deadline of the request has not passed.
+    #[error("Market reverted with RequestIsNotExpired(0x{request_id:x}, deadline {deadline})")]
+    RequestIsNotExpired {
+        /// ID of the request.
+        request_id: U256,
+        /// Deadline of the request.
+        deadline: u64,
+    },
+
+    /// The fulfilled request does not match the locked request.
+    #[error("Market reverted with InvalidRequestFulfillment(0x{request_id:x}, provided {provided}, locked {locked})")]
+    InvalidRequestFulfillment {
+        /// ID of the request.
+        request_id: U256,
+        /// Digest of the fulfilled request.
+        provided: B256,
+        /// Digest of the locked request.
+        locked: B256,
+    },
+
+    /// The account balance is too low.
+    #[error("Market reverted with InsufficientBalance({0})")]
+    InsufficientBalance(Address),
+
+    /// The signature is invalid.
+    #[error("Market reverted with InvalidSignature")]
+    InvalidSignature,
+
+    /// The request is malformed.
+    #[error("Market reverted with InvalidRequest")]
+    InvalidRequest,
+
+    /// A transfer of funds failed.
+    #[error("Market reverted with TransferFailed")]
+    TransferFailed,
+
+    /// The selector of the seal does not match the selector required by the request.
+    #[error("Market reverted with SelectorMismatch(required {required}, provided {provided})")]
+    SelectorMismatch {
+        /// Selector required by the request.
+        required: FixedBytes<4>,
+        /// Selector of the seal.
+        provided: FixedBytes<4>,
+    },
+
+    /// The batch is larger than the market allows.
+    #[error("Market reverted with BatchSizeExceedsLimit({batch_size}, limit {limit})")]
+    BatchSizeExceedsLimit {
+        /// Size of the batch.
+        batch_size: U256,
+        /// Maximum size of a batch.
+        limit: U256,
+    },
+}
+
+impl From<IBoundlessMarketErrors> for MarketError {
+    fn from(err: IBoundlessMarketErrors) -> Self {
+        use IBoundlessMarketErrors as E;
+        match err {
+            E::RequestIsLocked(e) => Self::RequestIsLocked(U256::from(e.requestId)),
+            E::RequestIsExpiredOrNotPriced(e) => {
+                Self::RequestIsExpiredOrNotPriced(U256::from(e.requestId))
+            }
+            E::RequestIsNotLocked(e) => Self::RequestIsNotLocked(U256::from(e.requestId)),
+            E::RequestIsFulfilled(e) => Self::RequestIsFulfilled(U256::from(e.requestId)),
+            E::RequestIsSlashed(e) => Self::RequestIsSlashed(U256::from(e.requestId)),
+            E::RequestLockIsExpired(e) => Self::RequestLockIsExpired {
+                request_id: U256::from(e.requestId),
+                lock_deadline: e.lockDeadline,
+            },
+            E::RequestIsExpired(e) => {
+                Self::RequestIsExpired { request_id: U256::from(e.requestId), deadline: e.deadline }
+            }
+            E::RequestIsNotExpired(e) => Self::RequestIsNotExpired {
+                request_id: U256::from(e.requestId),
+                deadline: e.deadline,
+            },
+            E::InvalidRequestFulfillment(e) => Self::InvalidRequestFulfillment {
+                request_id: U256::from(e.requestId),
+                provided: e.provided,
+                locked: e.locked,
+            },
+            E::InsufficientBalance(e) => Self::InsufficientBalance(e.account),
+            E::InvalidSignature(_) => Self::InvalidSignature,
+            E::InvalidRequest(_) => Self::InvalidRequest,
+            E::TransferFailed(_) => Self::TransferFailed,
+            E::SelectorMismatch(e) => {
+                Self::SelectorMismatch { required: e.required, provided: e.provided }
+            }
+            E::BatchSizeExceedsLimit(e) => {
+                Self::BatchSizeExceedsLimit { batch_size: e.batchSize, limit: e.limit }
+            }
+            // Errors inherited from the contract dependencies, such as OpenZeppelin upgradeable
+            // contracts, are not expected from the market methods used here.
+            #[allow(unreachable_patterns)]
+            other => Self::TxnError(TxnErr::BoundlessMarketErr(other)),
+        }
+    }
+}
+
+impl From<TxnErr> for MarketError {
+    fn from(err: TxnErr) -> Self {
+        match err {
+            TxnErr::BoundlessMarketErr(err) => err.into(),
+            err => MarketError::TxnError(err),
+        }
+    }
 }
 
 impl From<alloy::contract::Error> for MarketError {
     fn from(err: alloy::contract::Error) -> Self {
         tracing::debug!("raw alloy contract error: {:?}", err);
-        MarketError::Error(TxnErr::from(err).into())
+        TxnErr::from(err).into()
+    }
+}
+
+/// Maps the error from a simulated call, distinguishing a contract revert from other failures.
+///
+/// Only error responses carrying revert data, the execution reverted code, or an execution
+/// reverted message are reverts. The latter covers nodes reporting reverts without data under a
+/// generic code, such as -32000. Other error responses, such as rate limits or unsupported
+/// methods, are returned as [MarketError::RpcError].
+fn simulation_err(err: alloy::contract::Error) -> MarketError {
+    match err {
+        alloy::contract::Error::TransportError(TransportError::ErrorResp(ref payload))
+            if payload.code == 3
+                || payload.as_revert_data().is_some()
+                || payload.message.to_lowercase().contains("execution reverted") =>
+        {
+            MarketError::SimulationReverted(err.into())
+        }
+        alloy::contract::Error::TransportError(err) => MarketError::RpcError(err),
+        err => err.into(),
     }
 }
 
@@ -99,15 +312,8 @@ pub struct BoundlessMarketService<P> {
     caller: Address,
     timeout: Duration,
     event_query_config: EventQueryConfig,
-    balance_alert_config: StakeBalanceAlertConfig,
-}
-
-#[derive(Clone, Debug, Default)]
-struct StakeBalanceAlertConfig {
-    /// Threshold at which to log a warning
-    warn_threshold: Option<U256>,
-    /// Threshold at which to log an error
-    error_threshold: Option<U256>,
+    /// Deprecated stake balance alerts, checked after locking a request or withdrawing stake.
+    stake_balance_monitor: Option<Arc<BalanceMonitor<RootProvider>>>,
 }
 
 impl<P> Clone for BoundlessMarketService<P>
@@ -121,7 +327,7 @@ where
             caller: self.caller,
             timeout: self.timeout,
             event_query_config: self.event_query_config.clone(),
-            balance_alert_config: self.balance_alert_config.clone(),
+            stake_balance_monitor: self.stake_balance_monitor.clone(),
         }
     }
 }
@@ -171,7 +377,7 @@ impl<P: Provider> BoundlessMarketService<P> {
             caller,
             timeout: TXN_CONFIRM_TIMEOUT,
             event_query_config: EventQueryConfig::default(),
-            balance_alert_config: StakeBalanceAlertConfig::default(),
+            stake_balance_monitor: None,
         }
     }
 
@@ -186,18 +392,20 @@ impl<P: Provider> BoundlessMarketService<P> {
     }
 
     /// Set stake balance thresholds to warn or error alert on
+    #[deprecated(note = "use a BalanceMonitor with a BalanceSource::Stake watch instead")]
     pub fn with_stake_balance_alert(
         self,
         warn_threshold: &Option<U256>,
         error_threshold: &Option<U256>,
     ) -> Self {
-        Self {
-            balance_alert_config: StakeBalanceAlertConfig {
-                warn_threshold: *warn_threshold,
-                error_threshold: *error_threshold,
-            },
-            ..self
-        }
+        let watch = BalanceWatch::new(BalanceSource::Stake, self.caller)
+            .with_warn_below(*warn_threshold)
+            .with_error_below(*error_threshold);
+        let monitor = BalanceMonitor::new(self.instance.provider().root().clone())
+            .with_market_address(*self.instance.address())
+            .with_watch(watch)
+            .with_sink(TracingSink);
+        Self { stake_balance_monitor: Some(Arc::new(monitor)), ..self }
     }
 
     /// Returns the market contract instance.
@@ -205,11 +413,53 @@ impl<P: Provider> BoundlessMarketService<P> {
         &self.instance
     }
 
+    /// Returns the event query configuration.
+    pub fn event_query_config(&self) -> &EventQueryConfig {
+        &self.event_query_config
+    }
+
     /// Returns the caller address.
     pub fn caller(&self) -> Address {
         self.caller
     }
 
+    /// Returns the timeout for transaction confirmation.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Replays a mined transaction that reverted as an `eth_call` against the block it was
+    /// included in, and decodes the revert reason.
+    ///
+    /// Returns `None` if the replay succeeds, e.g. when the transaction ran out of gas.
+    async fn revert_reason(
+        &self,
+        tx: TransactionRequest,
+        receipt: &TransactionReceipt,
+    ) -> Option<MarketError> {
+        let block = receipt.block_number.map(BlockId::number).unwrap_or(BlockId::latest());
+        tracing::debug!("Replaying reverted tx {} at block {block:?}", receipt.transaction_hash);
+        match self.instance.provider().call(tx).block(block).await {
+            Ok(_) => None,
+            Err(err) => Some(alloy::contract::Error::TransportError(err).into()),
+        }
+    }
+
+    /// Returns an error with the decoded revert reason if the transaction reverted.
+    async fn check_receipt(
+        &self,
+        tx: TransactionRequest,
+        receipt: &TransactionReceipt,
+    ) -> Result<(), MarketError> {
+        if receipt.status() {
+            return Ok(());
+        }
+        Err(self
+            .revert_reason(tx, receipt)
+            .await
+            .unwrap_or(MarketError::TxnReverted(receipt.transaction_hash)))
+    }
+
     /// Get the EIP-712 domain associated with the market contract.
     ///
     /// If not cached, this function will fetch the chain ID with an RPC call.
@@ -262,7 +512,7 @@ impl<P: Provider> BoundlessMarketService<P> {
     pub async fn submit_request_with_value(
         &self,
         request: &ProofRequest,
-        signer: &impl Signer,
+        signer: &(impl Signer + ?Sized),
         value: impl Into<U256>,
     ) -> Result<U256, MarketError> {
         tracing::debug!("calling submitRequest({:x?})", request);
@@ -288,6 +538,7 @@ impl<P: Provider> BoundlessMarketService<P> {
             .get_receipt()
             .await
             .context("failed to confirm tx")?;
+        self.check_receipt(call.into_transaction_request(), &receipt).await?;
 
         // Look for the logs for submitting the transaction.
         let log = extract_tx_log::<IBoundlessMarket::RequestSubmitted>(&receipt)?;
@@ -312,6 +563,7 @@ impl<P: Provider> BoundlessMarketService<P> {
             .get_receipt()
             .await
             .context("failed to confirm tx")?;
+        self.check_receipt(call.into_transaction_request(), &receipt).await?;
 
         // Look for the logs for submitting the transaction.
         let log = extract_tx_log::<IBoundlessMarket::RequestSubmitted>(&receipt)?;
@@ -324,7 +576,7 @@ impl<P: Provider> BoundlessMarketService<P> {
     pub async fn submit_request(
         &self,
         request: &ProofRequest,
-        signer: &impl Signer,
+        signer: &(impl Signer + ?Sized),
     ) -> Result<U256, MarketError> {
         let balance = self
             .balance_of(signer.address())
@@ -335,6 +587,103 @@ impl<P: Provider> BoundlessMarketService<P> {
         self.submit_request_with_value(request, signer, value).await
     }
 
+    /// Returns whether the market supports batching calls with `multicall`.
+    ///
+    /// Multicall was added in version 2 of the market contract, so markets that have not been
+    /// upgraded revert on it.
+    pub async fn supports_multicall(&self) -> Result<bool, MarketError> {
+        let call = self.instance.call_builder(&IMulticall::multicallCall { data: vec![] });
+        match call.call().await.map_err(simulation_err) {
+            Ok(_) => Ok(true),
+            Err(MarketError::SimulationReverted(_)) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Submit a batch of requests in a single transaction, using the market's multicall.
+    ///
+    /// All requests must be signed by the given signer. Deposits funds to the client account
+    /// if there are not enough to cover the total max price of the offers. Returns the request
+    /// IDs in the order they were submitted.
+    ///
+    /// If the market does not support multicall, the requests are submitted one transaction at
+    /// a time instead.
+    pub async fn submit_requests(
+        &self,
+        requests: &[ProofRequest],
+        signer: &(impl Signer + ?Sized),
+    ) -> Result<Vec<U256>, MarketError> {
+        if !self.supports_multicall().await? {
+            tracing::debug!("market does not support multicall, submitting requests one by one");
+            let mut request_ids = Vec::with_capacity(requests.len());
+            for request in requests {
+                request_ids.push(self.submit_request(request, signer).await?);
+            }
+            return Ok(request_ids);
+        }
+
+        let chain_id = self.get_chain_id().await.context("failed to get chain ID")?;
+        let mut data = Vec::with_capacity(requests.len());
+        let mut total_max_price = U256::ZERO;
+        for request in requests {
+            let client_address = request.client_address();
+            if client_address != signer.address() {
+                return Err(MarketError::AddressMismatch(client_address, signer.address()));
+            };
+            let client_sig = request
+                .sign_request(signer, *self.instance.address(), chain_id)
+                .await
+                .context("failed to sign request")?;
+            let call = IBoundlessMarket::submitRequestCall {
+                request: request.clone(),
+                clientSignature: client_sig.as_bytes().into(),
+            };
+            data.push(Bytes::from(call.abi_encode()));
+            total_max_price += U256::from(request.offer.maxPrice);
+        }
+
+        let balance = self
+            .balance_of(signer.address())
+            .await
+            .context("failed to get whether the client balance can cover the offer max prices")?;
+        if balance < total_max_price {
+            self.deposit(total_max_price - balance).await?;
+        }
+
+        tracing::debug!("calling multicall with {} submitRequest calls", data.len());
+        let call =
+            self.instance.call_builder(&IMulticall::multicallCall { data }).from(self.caller);
+        let pending_tx = call.send().await?;
+        tracing::debug!("broadcasting tx {}", pending_tx.tx_hash());
+
+        let receipt = pending_tx
+            .with_timeout(Some(self.timeout))
+            .get_receipt()
+            .await
+            .context("failed to confirm tx")?;
+        self.check_receipt(call.into_transaction_request(), &receipt).await?;
+
+        let request_ids = receipt
+            .inner
+            .logs()
+            .iter()
+            .filter(|log| log.topic0() == Some(&IBoundlessMarket::RequestSubmitted::SIGNATURE_HASH))
+            .map(|log| {
+                log.log_decode::<IBoundlessMarket::RequestSubmitted>()
+                    .map(|log| U256::from(log.inner.data.requestId))
+                    .context("failed to decode RequestSubmitted event")
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if request_ids.len() != requests.len() {
+            return Err(MarketError::Error(anyhow!(
+                "transaction emitted {} RequestSubmitted events for {} requests",
+                request_ids.len(),
+                requests.len()
+            )));
+        }
+        Ok(request_ids)
+    }
+
     /// Lock the request to the prover, giving them exclusive rights to be paid to
     /// fulfill this request, and also making them subject to slashing penalties if they fail to
     /// deliver. At this point, the price for fulfillment is also set, based on the reverse Dutch
@@ -351,7 +700,7 @@ impl<P: Provider> BoundlessMarketService<P> {
         let is_locked_in: bool =
             self.instance.requestIsLocked(request.id).call().await.context("call failed")?._0;
         if is_locked_in {
-            return Err(MarketError::Error(anyhow!("request is already locked")));
+            return Err(MarketError::RequestIsLocked(request.id));
         }
 
         tracing::debug!("Calling lockRequest({:x?}, {:x?})", request, client_sig);
@@ -385,13 +734,15 @@ impl<P: Provider> BoundlessMarketService<P> {
             .context("failed to confirm tx")?;
 
         if !receipt.status() {
-            // TODO: Get + print revertReason
-            return Err(MarketError::LockRevert(receipt.transaction_hash));
+            return Err(self
+                .revert_reason(call.into_transaction_request(), &receipt)
+                .await
+                .unwrap_or(MarketError::LockRevert(receipt.transaction_hash)));
         }
 
         tracing::info!("Registered request {:x}: {}", request.id, receipt.transaction_hash);
 
-        self.check_stake_balance().await?;
+        self.check_stake_balance().await;
 
         Ok(receipt.block_number.context("TXN Receipt missing block number")?)
     }
@@ -415,7 +766,7 @@ impl<P: Provider> BoundlessMarketService<P> {
         let is_locked_in: bool =
             self.instance.requestIsLocked(request.id).call().await.context("call failed")?._0;
         if is_locked_in {
-            return Err(MarketError::Error(anyhow!("request is already locked-in")));
+            return Err(MarketError::RequestIsLocked(request.id));
         }
 
         tracing::debug!(
@@ -430,7 +781,7 @@ impl<P: Provider> BoundlessMarketService<P> {
             .instance
             .lockRequestWithSignature(request.clone(), client_sig.clone(), prover_sig.clone())
             .from(self.caller);
-        let pending_tx = call.send().await.context("Failed to lock")?;
+        let pending_tx = call.send().await?;
 
         tracing::debug!("Broadcasting tx {}", pending_tx.tx_hash());
 
@@ -441,11 +792,10 @@ impl<P: Provider> BoundlessMarketService<P> {
             .context("failed to confirm tx")?;
 
         if !receipt.status() {
-            // TODO: Get + print revertReason
-            return Err(MarketError::Error(anyhow!(
-                "lockRequestWithSignature failed [{}], possibly outbid",
-                receipt.transaction_hash
-            )));
+            return Err(self
+                .revert_reason(call.into_transaction_request(), &receipt)
+                .await
+                .unwrap_or(MarketError::LockRevert(receipt.transaction_hash)));
         }
 
         tracing::info!("Registered request {:x}: {}", request.id, receipt.transaction_hash);
@@ -469,6 +819,7 @@ impl<P: Provider> BoundlessMarketService<P> {
             .get_receipt()
             .await
             .context("failed to confirm tx")?;
+        self.check_receipt(call.into_transaction_request(), &receipt).await?;
 
         let log = extract_tx_log::<IBoundlessMarket::ProverSlashed>(&receipt)?;
         Ok(log.inner.data)
@@ -498,6 +849,7 @@ impl<P: Provider> BoundlessMarketService<P> {
             .get_receipt()
             .await
             .context("failed to confirm tx")?;
+        self.check_receipt(call.into_transaction_request(), &receipt).await?;
 
         tracing::info!(
             "Submitted proof for request {:x}: {:x}",
@@ -533,6 +885,7 @@ impl<P: Provider> BoundlessMarketService<P> {
             .get_receipt()
             .await
             .context("failed to confirm tx")?;
+        self.check_receipt(call.into_transaction_request(), &receipt).await?;
 
         tracing::info!(
             "Submitted proof for request {:x}: {:x}",
@@ -563,12 +916,34 @@ impl<P: Provider> BoundlessMarketService<P> {
             .get_receipt()
             .await
             .context("failed to confirm tx")?;
+        self.check_receipt(call.into_transaction_request(), &receipt).await?;
 
         tracing::info!("Submitted proof for batch {:?}: {}", fill_ids, receipt.transaction_hash);
 
         Ok(())
     }
 
+    /// Checks that a batch of fulfillments would be accepted by the market, without sending a
+    /// transaction.
+    ///
+    /// Calls `verifyBatchDelivery` with `eth_call`, checking the seals, the assessor receipt and
+    /// the request digests. A revert is returned as [MarketError::SimulationReverted].
+    pub async fn verify_batch_delivery(
+        &self,
+        fulfillments: &[Fulfillment],
+        assessor_fill: &AssessorReceipt,
+    ) -> Result<(), MarketError> {
+        tracing::debug!("Calling verifyBatchDelivery({fulfillments:?}, {assessor_fill:?})");
+        self.instance
+            .verifyBatchDelivery(fulfillments.to_vec(), assessor_fill.clone())
+            .from(self.caller)
+            .call()
+            .await
+            .map_err(simulation_err)?;
+
+        Ok(())
+    }
+
     /// Fulfill a batch of requests by delivering the proof for each application and withdraw from the prover balance.
     ///
     /// See [BoundlessMarketService::fulfill] for more details.
@@ -590,6 +965,7 @@ impl<P: Provider> BoundlessMarketService<P> {
             .get_receipt()
             .await
             .context("failed to confirm tx")?;
+        self.check_receipt(call.into_transaction_request(), &receipt).await?;
 
         tracing::info!("Submitted proof for batch {:?}: {}", fill_ids, receipt.transaction_hash);
 
@@ -619,12 +995,42 @@ impl<P: Provider> BoundlessMarketService<P> {
             .get_receipt()
             .await
             .context("failed to confirm tx")?;
+        self.check_receipt(call.into_transaction_request(), &tx_receipt).await?;
 
         tracing::info!("Submitted merkle root and proof for batch {}", tx_receipt.transaction_hash);
 
         Ok(())
     }
 
+    /// Simulates [BoundlessMarketService::submit_merkle_and_fulfill] with `eth_call`, without
+    /// sending a transaction.
+    ///
+    /// A revert is returned as [MarketError::SimulationReverted].
+    pub async fn simulate_submit_merkle_and_fulfill(
+        &self,
+        verifier_address: Address,
+        root: B256,
+        seal: Bytes,
+        fulfillments: &[Fulfillment],
+        assessor_fill: &AssessorReceipt,
+    ) -> Result<(), MarketError> {
+        tracing::debug!("Simulating submitRootAndFulfillBatch({root:?}, {seal:x}, {fulfillments:?}, {assessor_fill:?})");
+        self.instance
+            .submitRootAndFulfillBatch(
+                verifier_address,
+                root,
+                seal,
+                fulfillments.to_vec(),
+                assessor_fill.clone(),
+            )
+            .from(self.caller)
+            .call()
+            .await
+            .map_err(simulation_err)?;
+
+        Ok(())
+    }
+
     /// Combined function to submit a new merkle root to the set-verifier and call `fulfillBatchAndWithdraw`.
     /// Useful to reduce the transaction count for fulfillments
     pub async fn submit_merkle_and_fulfill_and_withdraw(
@@ -654,6 +1060,7 @@ impl<P: Provider> BoundlessMarketService<P> {
             .get_receipt()
             .await
             .context("failed to confirm tx")?;
+        self.check_receipt(call.into_transaction_request(), &tx_receipt).await?;
 
         tracing::info!("Submitted merkle root and proof for batch {}", tx_receipt.transaction_hash);
 
@@ -712,6 +1119,7 @@ impl<P: Provider> BoundlessMarketService<P> {
             .get_receipt()
             .await
             .context("failed to confirm tx")?;
+        self.check_receipt(call.into_transaction_request(), &tx_receipt).await?;
 
         tracing::info!("Fulfilled proof for batch {}", tx_receipt.transaction_hash);
 
@@ -772,6 +1180,7 @@ impl<P: Provider> BoundlessMarketService<P> {
             .get_receipt()
             .await
             .context("failed to confirm tx")?;
+        self.check_receipt(call.into_transaction_request(), &tx_receipt).await?;
 
         tracing::info!("Fulfilled proof for batch {}", tx_receipt.transaction_hash);
 
@@ -842,7 +1251,7 @@ impl<P: Provider> BoundlessMarketService<P> {
             .context("Failed to get latest block number")?)
     }
 
-    async fn get_latest_block_timestamp(&self) -> Result<u64, MarketError> {
+    pub(crate) async fn get_latest_block_timestamp(&self) -> Result<u64, MarketError> {
         let block = self
             .instance
             .provider()
@@ -894,20 +1303,21 @@ impl<P: Provider> BoundlessMarketService<P> {
 
             if let Some((_, data)) = logs.first() {
                 // get the calldata inputs
+                let tx_hash = data.transaction_hash.context("tx hash is none")?;
                 let tx_data = self
                     .instance
                     .provider()
-                    .get_transaction_by_hash(data.transaction_hash.context("tx hash is none")?)
+                    .get_transaction_by_hash(tx_hash)
                     .await
                     .context("Failed to get transaction")?
                     .context("Transaction not found")?;
                 let inputs = tx_data.input();
-                let fills = decode_calldata(inputs).context("Failed to decode calldata")?;
-                for fill in fills {
-                    if fill.id == request_id {
-                        return Ok((fill.journal.clone(), fill.seal.clone()));
-                    }
+                if let Some(fill) = find_fulfillment(inputs, request_id) {
+                    return Ok((fill.journal, fill.seal));
                 }
+                tracing::debug!(
+                    "Fulfillment of request 0x{request_id:x} not found in calldata of tx {tx_hash}"
+                );
             }
 
             // Move the upper_block down for the next iteration
@@ -967,8 +1377,7 @@ impl<P: Provider> BoundlessMarketService<P> {
                     .await
                     .context("Failed to get transaction")?
                     .context("Transaction not found")?;
-                let inputs = tx_data.input();
-                let calldata = IBoundlessMarket::submitRequestCall::abi_decode(inputs, true)
+                let calldata = find_submitted_request(tx_data.input(), request_id)
                     .context("Failed to decode input")?;
                 return Ok((calldata.request, calldata.clientSignature));
             }
@@ -1007,8 +1416,7 @@ impl<P: Provider> BoundlessMarketService<P> {
                 .await
                 .context("Failed to get transaction")?
                 .context("Transaction not found")?;
-            let inputs = tx_data.input();
-            let calldata = IBoundlessMarket::submitRequestCall::abi_decode(inputs, true)
+            let calldata = find_submitted_request(tx_data.input(), request_id)
                 .context("Failed to decode input")?;
             return Ok((calldata.request, calldata.clientSignature));
         }
@@ -1100,6 +1508,20 @@ impl<P: Provider> BoundlessMarketService<P> {
         Ok(RequestId::u256(self.caller, index))
     }
 
+    /// Randomly generates `count` distinct request IDs.
+    ///
+    /// It does not guarantee that the IDs are not in use by the time the caller uses them.
+    pub async fn request_ids_from_rand(&self, count: usize) -> Result<Vec<U256>, MarketError> {
+        let mut ids = Vec::with_capacity(count);
+        while ids.len() < count {
+            let id = self.request_id_from_rand().await?;
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        Ok(ids)
+    }
+
     /// Returns the image ID and URL of the assessor guest.
     pub async fn image_info(&self) -> Result<(B256, String)> {
         tracing::debug!("Calling imageInfo()");
@@ -1109,6 +1531,36 @@ impl<P: Provider> BoundlessMarketService<P> {
         Ok((image_id, image_url))
     }
 
+    /// Returns the address of the RISC Zero verifier used by the market, usually a verifier
+    /// router accepting both Groth16 and set inclusion seals.
+    pub async fn verifier_address(&self) -> Result<Address> {
+        tracing::debug!("Calling VERIFIER()");
+        let contract = IMarketVerifier::new(*self.instance.address(), self.instance.provider());
+        Ok(contract.VERIFIER().call().await.context("VERIFIER call failed")?._0)
+    }
+
+    /// Returns the address of the set verifier for the given set builder image ID, as registered
+    /// in the verifier router used by the market.
+    pub async fn set_verifier_address(
+        &self,
+        set_builder_image_id: impl Into<Digest>,
+    ) -> Result<Address> {
+        let router = self.verifier_address().await?;
+        let verifier_params =
+            SetInclusionReceiptVerifierParameters { image_id: set_builder_image_id.into() }
+                .digest();
+        let selector: FixedBytes<4> = verifier_params.as_bytes()[0..4].try_into()?;
+        tracing::debug!("Calling getVerifier({selector}) on router {router}");
+        let contract = IVerifierRouter::new(router, self.instance.provider());
+        let set_verifier = contract
+            .getVerifier(selector)
+            .call()
+            .await
+            .with_context(|| format!("No set verifier registered for selector {selector}"))?
+            ._0;
+        Ok(set_verifier)
+    }
+
     /// Get the chain ID.
     ///
     /// This function implements caching to save the chain ID after the first successful fetch.
@@ -1122,17 +1574,22 @@ impl<P: Provider> BoundlessMarketService<P> {
         Ok(id)
     }
 
-    /// Approve a spender to spend `value` amount of HitPoints on behalf of the caller.
-    pub async fn approve_deposit_stake(&self, value: U256) -> Result<()> {
-        let spender = *self.instance.address();
-        tracing::debug!("Calling approve({:?}, {})", spender, value);
-        let token_address = self
+    /// Returns the address of the stake token (HitPoints) contract of the market.
+    pub async fn stake_token_address(&self) -> Result<Address, MarketError> {
+        Ok(self
             .instance
             .STAKE_TOKEN_CONTRACT()
             .call()
             .await
             .context("STAKE_TOKEN_CONTRACT call failed")?
-            ._0;
+            ._0)
+    }
+
+    /// Approve a spender to spend `value` amount of HitPoints on behalf of the caller.
+    pub async fn approve_deposit_stake(&self, value: U256) -> Result<()> {
+        let spender = *self.instance.address();
+        tracing::debug!("Calling approve({:?}, {})", spender, value);
+        let token_address = self.stake_token_address().await?;
         let contract = IERC20::new(token_address, self.instance.provider());
         let call = contract.approve(spender, value).from(self.caller);
         let pending_tx = call.send().await.map_err(IHitPointsErrors::decode_error)?;
@@ -1172,15 +1629,9 @@ impl<P: Provider> BoundlessMarketService<P> {
     pub async fn deposit_stake_with_permit(
         &self,
         value: U256,
-        signer: &impl Signer,
+        signer: &(impl Signer + ?Sized),
     ) -> Result<(), MarketError> {
-        let token_address = self
-            .instance
-            .STAKE_TOKEN_CONTRACT()
-            .call()
-            .await
-            .context("STAKE_TOKEN_CONTRACT call failed")?
-            ._0;
+        let token_address = self.stake_token_address().await?;
         let contract = IERC20Permit::new(token_address, self.instance.provider());
         let call = contract.nonces(self.caller());
         let nonce = call.call().await.map_err(IHitPointsErrors::decode_error)?._0;
@@ -1230,7 +1681,7 @@ impl<P: Provider> BoundlessMarketService<P> {
             .await
             .context("failed to confirm tx")?;
         tracing::debug!("Submitted stake withdraw {}", tx_hash);
-        self.check_stake_balance().await?;
+        self.check_stake_balance().await;
         Ok(())
     }
 
@@ -1241,26 +1692,13 @@ impl<P: Provider> BoundlessMarketService<P> {
         Ok(balance)
     }
 
-    /// Check the current stake balance against the alert config
-    /// and log a warning or error or below the thresholds.
-    async fn check_stake_balance(&self) -> Result<(), MarketError> {
-        let stake_balance = self.balance_of_stake(self.caller()).await?;
-        if stake_balance < self.balance_alert_config.error_threshold.unwrap_or(U256::ZERO) {
-            tracing::error!(
-                "stake balance {} for {} < error threshold",
-                stake_balance,
-                self.caller(),
-            );
-        } else if stake_balance < self.balance_alert_config.warn_threshold.unwrap_or(U256::ZERO) {
-            tracing::warn!(
-                "stake balance {} for {} < warning threshold",
-                stake_balance,
-                self.caller(),
-            );
-        } else {
-            tracing::trace!("stake balance for {} is: {}", self.caller(), stake_balance);
+    /// Check the stake balance against the deprecated stake balance alerts, if set.
+    ///
+    /// Failures to check the balance are logged by the monitor.
+    async fn check_stake_balance(&self) {
+        if let Some(monitor) = &self.stake_balance_monitor {
+            let _ = monitor.check().await;
         }
-        Ok(())
     }
 }
 
@@ -1328,36 +1766,100 @@ impl Offer {
 }
 
 fn decode_calldata(data: &Bytes) -> Result<Vec<Fulfillment>> {
-    if let Ok(call) = IBoundlessMarket::submitRootAndFulfillBatchCall::abi_decode(data, true) {
+    decode_calldata_with(data, true)
+}
+
+/// Selectors of the market calls carrying fulfillments.
+const FULFILLMENT_SELECTORS: [[u8; 4]; 10] = [
+    IBoundlessMarket::submitRootAndFulfillBatchCall::SELECTOR,
+    IBoundlessMarket::submitRootAndFulfillBatchAndWithdrawCall::SELECTOR,
+    IBoundlessMarket::fulfillCall::SELECTOR,
+    IBoundlessMarket::fulfillBatchCall::SELECTOR,
+    IBoundlessMarket::fulfillAndWithdrawCall::SELECTOR,
+    IBoundlessMarket::fulfillBatchAndWithdrawCall::SELECTOR,
+    IBoundlessMarket::priceAndFulfillCall::SELECTOR,
+    IBoundlessMarket::priceAndFulfillBatchCall::SELECTOR,
+    IBoundlessMarket::priceAndFulfillAndWithdrawCall::SELECTOR,
+    IBoundlessMarket::priceAndFulfillBatchAndWithdrawCall::SELECTOR,
+];
+
+/// Finds the fulfillment of the request in the calldata of a transaction.
+///
+/// The market may have been called through another contract, such as a multicall, a smart
+/// contract wallet or a relayer, in which case the fulfillment call is ABI encoded within the
+/// calldata of the outer call. The calldata is then scanned for embedded fulfillment calls.
+pub(crate) fn find_fulfillment(data: &Bytes, request_id: U256) -> Option<Fulfillment> {
+    let find = |fills: Vec<Fulfillment>| fills.into_iter().find(|fill| fill.id == request_id);
+    if let Some(fill) = decode_calldata(data).ok().and_then(find) {
+        return Some(fill);
+    }
+    // Embedded calls are followed by the padding and the remaining arguments of the outer
+    // call, so they are decoded without validation.
+    (1..data.len().saturating_sub(4))
+        .filter(|&offset| FULFILLMENT_SELECTORS.iter().any(|sel| data[offset..].starts_with(sel)))
+        .find_map(|offset| decode_calldata_with(&data[offset..], false).ok().and_then(find))
+}
+
+/// Decodes the submission of the request from the calldata of the transaction that emitted its
+/// `RequestSubmitted` event.
+///
+/// The request is either submitted directly with `submitRequest`, or within a batch of
+/// `submitRequest` calls sent through the market's `multicall`, as done by
+/// [BoundlessMarketService::submit_requests].
+pub fn find_submitted_request(
+    data: &[u8],
+    request_id: U256,
+) -> Result<IBoundlessMarket::submitRequestCall> {
+    if let Ok(call) = IBoundlessMarket::submitRequestCall::abi_decode(data, true) {
+        ensure!(
+            call.request.id == request_id,
+            "submitted request 0x{:x} does not match request 0x{request_id:x}",
+            call.request.id
+        );
+        return Ok(call);
+    }
+    let multicall = IMulticall::multicallCall::abi_decode(data, true)
+        .context("calldata is neither a submitRequest nor a multicall call")?;
+    multicall
+        .data
+        .iter()
+        .filter_map(|call| IBoundlessMarket::submitRequestCall::abi_decode(call, true).ok())
+        .find(|call| call.request.id == request_id)
+        .with_context(|| format!("multicall does not submit request 0x{request_id:x}"))
+}
+
+fn decode_calldata_with(data: &[u8], validate: bool) -> Result<Vec<Fulfillment>> {
+    if let Ok(call) = IBoundlessMarket::submitRootAndFulfillBatchCall::abi_decode(data, validate) {
         return Ok(call.fills);
     }
     if let Ok(call) =
-        IBoundlessMarket::submitRootAndFulfillBatchAndWithdrawCall::abi_decode(data, true)
+        IBoundlessMarket::submitRootAndFulfillBatchAndWithdrawCall::abi_decode(data, validate)
     {
         return Ok(call.fills);
     }
-    if let Ok(call) = IBoundlessMarket::fulfillCall::abi_decode(data, true) {
+    if let Ok(call) = IBoundlessMarket::fulfillCall::abi_decode(data, validate) {
         return Ok(vec![call.fill]);
     }
-    if let Ok(call) = IBoundlessMarket::fulfillBatchCall::abi_decode(data, true) {
+    if let Ok(call) = IBoundlessMarket::fulfillBatchCall::abi_decode(data, validate) {
         return Ok(call.fills);
     }
-    if let Ok(call) = IBoundlessMarket::fulfillAndWithdrawCall::abi_decode(data, true) {
+    if let Ok(call) = IBoundlessMarket::fulfillAndWithdrawCall::abi_decode(data, validate) {
         return Ok(vec![call.fill]);
     }
-    if let Ok(call) = IBoundlessMarket::fulfillBatchAndWithdrawCall::abi_decode(data, true) {
+    if let Ok(call) = IBoundlessMarket::fulfillBatchAndWithdrawCall::abi_decode(data, validate) {
         return Ok(call.fills);
     }
-    if let Ok(call) = IBoundlessMarket::priceAndFulfillCall::abi_decode(data, true) {
+    if let Ok(call) = IBoundlessMarket::priceAndFulfillCall::abi_decode(data, validate) {
         return Ok(vec![call.fill]);
     }
-    if let Ok(call) = IBoundlessMarket::priceAndFulfillBatchCall::abi_decode(data, true) {
+    if let Ok(call) = IBoundlessMarket::priceAndFulfillBatchCall::abi_decode(data, validate) {
         return Ok(call.fills);
     }
-    if let Ok(call) = IBoundlessMarket::priceAndFulfillAndWithdrawCall::abi_decode(data, true) {
+    if let Ok(call) = IBoundlessMarket::priceAndFulfillAndWithdrawCall::abi_decode(data, validate) {
         return Ok(vec![call.fill]);
     }
-    if let Ok(call) = IBoundlessMarket::priceAndFulfillBatchAndWithdrawCall::abi_decode(data, true)
+    if let Ok(call) =
+        IBoundlessMarket::priceAndFulfillBatchAndWithdrawCall::abi_decode(data, validate)
     {
         return Ok(call.fills);
     }
@@ -1370,16 +1872,21 @@ fn decode_calldata(data: &Bytes) -> Result<Vec<Fulfillment>> {
 
 #[cfg(test)]
 mod tests {
-    use super::decode_calldata;
+    use super::{
+        decode_calldata, find_fulfillment, find_submitted_request, simulation_err, IMulticall,
+        MarketError, TxnErr,
+    };
     use crate::{
+        clock::{Clock, SystemClock},
         contracts::{
             AssessorReceipt, Fulfillment, IBoundlessMarket, Input, InputType, Offer, Predicate,
             PredicateType, ProofRequest, RequestId, Requirements,
         },
-        now_timestamp,
     };
-    use alloy::primitives::{aliases::U160, utils::parse_ether, Address, Bytes, B256, U256};
-    use alloy_sol_types::SolCall;
+    use alloy::primitives::{
+        aliases::U160, utils::parse_ether, Address, Bytes, FixedBytes, B256, U256,
+    };
+    use alloy_sol_types::{SolCall, SolError, SolInterface};
     use risc0_zkvm::sha::Digest;
 
     fn ether(value: &str) -> U256 {
@@ -1449,7 +1956,7 @@ mod tests {
             Offer {
                 minPrice: U256::from(1),
                 maxPrice: U256::from(4),
-                biddingStart: now_timestamp(),
+                biddingStart: SystemClock.now(),
                 timeout: 100,
                 rampUpPeriod: 1,
                 lockTimeout: 100,
@@ -1545,4 +2052,130 @@ mod tests {
         };
         decode_calldata(&call.abi_encode().into()).unwrap();
     }
+
+    #[test]
+    fn test_find_submitted_request() {
+        let submit = |idx: u32| {
+            let request = ProofRequest::new(
+                RequestId::new(Address::ZERO, idx),
+                Requirements::new(Digest::ZERO, Predicate::prefix_match(vec![])),
+                "https://image.dev.null",
+                Input::inline(vec![]),
+                test_offer(0),
+            );
+            IBoundlessMarket::submitRequestCall {
+                request,
+                clientSignature: Bytes::from(vec![idx as u8]),
+            }
+        };
+        let id = |idx: u32| U256::from(RequestId::new(Address::ZERO, idx));
+
+        let data = submit(1).abi_encode();
+        let call = find_submitted_request(&data, id(1)).unwrap();
+        assert_eq!(call.clientSignature, Bytes::from(vec![1]));
+        assert!(find_submitted_request(&data, id(2)).is_err());
+
+        // Requests submitted in a batch through the market's multicall.
+        let data = IMulticall::multicallCall {
+            data: vec![submit(1).abi_encode().into(), submit(2).abi_encode().into()],
+        }
+        .abi_encode();
+        let call = find_submitted_request(&data, id(2)).unwrap();
+        assert_eq!(call.request.id, id(2));
+        assert_eq!(call.clientSignature, Bytes::from(vec![2]));
+        assert!(find_submitted_request(&data, id(3)).is_err());
+    }
+
+    #[test]
+    fn test_decode_market_errors() {
+        let decode = |data: Vec<u8>| -> MarketError {
+            IBoundlessMarket::IBoundlessMarketErrors::abi_decode(&data, true).unwrap().into()
+        };
+
+        let account = Address::repeat_byte(1);
+        let err = decode(IBoundlessMarket::InsufficientBalance { account }.abi_encode());
+        assert!(matches!(err, MarketError::InsufficientBalance(addr) if addr == account));
+        assert_eq!(err.to_string(), format!("Market reverted with InsufficientBalance({account})"));
+
+        let err = decode(
+            IBoundlessMarket::SelectorMismatch {
+                required: FixedBytes([1; 4]),
+                provided: FixedBytes([2; 4]),
+            }
+            .abi_encode(),
+        );
+        assert!(matches!(
+            err,
+            MarketError::SelectorMismatch { required, provided }
+                if required == FixedBytes([1; 4]) && provided == FixedBytes([2; 4])
+        ));
+
+        let err = decode(IBoundlessMarket::InvalidSignature {}.abi_encode());
+        assert!(matches!(err, MarketError::InvalidSignature));
+
+        // Errors from other contracts are kept as transaction errors.
+        let err = MarketError::from(TxnErr::BytesDecode);
+        assert!(matches!(err, MarketError::TxnError(TxnErr::BytesDecode)));
+    }
+
+    #[test]
+    fn test_simulation_err() {
+        let err = |payload: &str| {
+            let payload = serde_json::from_str(payload).unwrap();
+            simulation_err(alloy::contract::Error::TransportError(
+                alloy::transports::TransportError::ErrorResp(payload),
+            ))
+        };
+
+        let revert = err(r#"{"code":3,"message":"execution reverted","data":"0xf3abf6b1"}"#);
+        assert!(matches!(revert, MarketError::SimulationReverted(_)));
+
+        let revert_without_data = err(r#"{"code":-32000,"message":"execution reverted"}"#);
+        assert!(matches!(revert_without_data, MarketError::SimulationReverted(_)));
+
+        let rate_limited = err(r#"{"code":-32005,"message":"rate limit exceeded"}"#);
+        assert!(matches!(rate_limited, MarketError::RpcError(_)));
+
+        let unsupported = err(r#"{"code":-32601,"message":"method not supported"}"#);
+        assert!(matches!(unsupported, MarketError::RpcError(_)));
+    }
+
+    #[test]
+    fn test_find_nested_fulfillment() {
+        let fill = |id: u64| Fulfillment {
+            id: U256::from(id),
+            requestDigest: B256::default(),
+            imageId: B256::default(),
+            journal: Bytes::from(vec![id as u8]),
+            seal: Bytes::from(vec![1, 2, 3]),
+        };
+        let assessor_receipt = AssessorReceipt {
+            seal: Bytes::from(vec![1, 2, 3]),
+            selectors: vec![],
+            prover: Address::from(U160::from(1)),
+            callbacks: vec![],
+        };
+        let inner = IBoundlessMarket::fulfillBatchCall {
+            fills: vec![fill(1), fill(2)],
+            assessorReceipt: assessor_receipt,
+        };
+        let data: Bytes = inner.abi_encode().into();
+        assert_eq!(find_fulfillment(&data, U256::from(2)).unwrap().journal, Bytes::from(vec![2]));
+        assert!(find_fulfillment(&data, U256::from(3)).is_none());
+
+        // The same call relayed through a smart contract wallet.
+        alloy::sol! {
+            function execute(address target, bytes data, uint256 value) external payable;
+        }
+        let outer: Bytes = executeCall {
+            target: Address::from(U160::from(2)),
+            data: data.clone(),
+            value: U256::ZERO,
+        }
+        .abi_encode()
+        .into();
+        assert!(decode_calldata(&outer).is_err());
+        assert_eq!(find_fulfillment(&outer, U256::from(1)).unwrap().journal, Bytes::from(vec![1]));
+        assert!(find_fulfillment(&outer, U256::from(3)).is_none());
+    }
 }
@@ -58,6 +58,11 @@ const TXN_CONFIRM_TIMEOUT: Duration = Duration::from_secs(45);
 include!(concat!(env!("OUT_DIR"), "/boundless_market_generated.rs"));
 pub use boundless_market_contract::*;
 
+// boundless_market_abi.rs contains the versioned IBoundlessMarket ABI snapshot, and compile-time
+// checks that the types above declare the same functions, events and errors.
+// See the build.rs script in this crate for more details.
+include!(concat!(env!("OUT_DIR"), "/boundless_market_abi.rs"));
+
 #[allow(missing_docs)]
 #[cfg(not(target_os = "zkvm"))]
 pub mod token {
@@ -114,7 +119,7 @@ pub mod token {
         /// contract address and chain ID.
         pub async fn sign(
             &self,
-            signer: &impl Signer,
+            signer: &(impl Signer + ?Sized),
             contract_addr: Address,
             chain_id: u64,
         ) -> Result<PrimitiveSignature> {
@@ -326,6 +331,10 @@ pub enum RequestError {
     /// Request digest mismatch.
     #[error("request digest mismatch")]
     DigestMismatch,
+
+    /// The word index of a predicate overflows the journal offset.
+    #[error("predicate word index {0} is out of range")]
+    PredicateIndexOutOfRange(u32),
 }
 
 #[cfg(not(target_os = "zkvm"))]
@@ -481,7 +490,7 @@ impl ProofRequest {
     /// contract address and chain ID.
     pub async fn sign_request(
         &self,
-        signer: &impl Signer,
+        signer: &(impl Signer + ?Sized),
         contract_addr: Address,
         chain_id: u64,
     ) -> Result<PrimitiveSignature, RequestError> {
@@ -581,6 +590,42 @@ impl Predicate {
     pub fn prefix_match(prefix: impl Into<Bytes>) -> Self {
         Self { predicateType: PredicateType::PrefixMatch, data: prefix.into() }
     }
+
+    /// Returns a predicate to match the bytes of the journal at the given offset against the
+    /// given value, in the bits set in the mask.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mask and value are not of the same length.
+    pub fn masked_match(offset: u32, mask: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Self {
+        let (mask, value) = (mask.as_ref(), value.as_ref());
+        assert_eq!(mask.len(), value.len(), "mask and value length mismatch");
+        let data = [&offset.to_be_bytes(), mask, value].concat();
+        Self { predicateType: PredicateType::MaskedMatch, data: data.into() }
+    }
+
+    /// Returns a predicate to match the word at the given index of an ABI encoded journal. This
+    /// can be used to constrain a single static field of the journal, such as an address or
+    /// `uint256`.
+    ///
+    /// Returns an error if the byte offset of the word does not fit in a `u32`.
+    pub fn abi_word_match(index: u32, word: impl Into<B256>) -> Result<Self, RequestError> {
+        let offset = index.checked_mul(32).ok_or(RequestError::PredicateIndexOutOfRange(index))?;
+        Ok(Self::masked_match(offset, [0xff; 32], word.into()))
+    }
+
+    /// Returns a predicate to match any one of the given journal digests.
+    pub fn any_digest_match<D: Into<Digest>>(digests: impl IntoIterator<Item = D>) -> Self {
+        let data: Vec<u8> =
+            digests.into_iter().flat_map(|digest| digest.into().as_bytes().to_vec()).collect();
+        Self { predicateType: PredicateType::AnyDigestMatch, data: data.into() }
+    }
+
+    /// Returns a predicate to bound the length of the journal, inclusive of both bounds.
+    pub fn length_range(min_length: u32, max_length: u32) -> Self {
+        let data = [min_length.to_be_bytes(), max_length.to_be_bytes()].concat();
+        Self { predicateType: PredicateType::LengthRange, data: data.into() }
+    }
 }
 
 impl Callback {
@@ -711,6 +756,14 @@ impl Offer {
         let lock_stake = mcycle_price * U256::from(mcycle);
         Self { lockStake: lock_stake, ..self }
     }
+
+    /// Raises the offer minimum and maximum prices by the given percentage.
+    ///
+    /// Useful to resubmit a request that no prover locked at the original price.
+    pub fn with_price_bump(self, percent: u32) -> Self {
+        let bump = |price: U256| price + price * U256::from(percent) / U256::from(100);
+        Self { minPrice: bump(self.minPrice), maxPrice: bump(self.maxPrice), ..self }
+    }
 }
 
 use sha2::{Digest as _, Sha256};
@@ -726,17 +779,52 @@ impl Predicate {
         match self.predicateType {
             PredicateType::DigestMatch => self.data.as_ref() == Sha256::digest(journal).as_slice(),
             PredicateType::PrefixMatch => journal.as_ref().starts_with(&self.data),
+            PredicateType::MaskedMatch => masked_match(journal.as_ref(), &self.data),
+            PredicateType::AnyDigestMatch => {
+                let digest = Sha256::digest(journal);
+                self.data.len() % 32 == 0
+                    && self.data.chunks(32).any(|chunk| chunk == digest.as_slice())
+            }
+            PredicateType::LengthRange => {
+                let Ok(bounds) = <[u8; 8]>::try_from(self.data.as_ref()) else {
+                    return false;
+                };
+                let min_length = u32::from_be_bytes(bounds[..4].try_into().unwrap()) as usize;
+                let max_length = u32::from_be_bytes(bounds[4..].try_into().unwrap()) as usize;
+                (min_length..=max_length).contains(&journal.as_ref().len())
+            }
             PredicateType::__Invalid => panic!("invalid PredicateType"),
         }
     }
 }
 
+/// Evaluates a [PredicateType::MaskedMatch] predicate, with data consisting of the big-endian
+/// `u32` offset followed by the mask and value of equal length.
+fn masked_match(journal: &[u8], data: &[u8]) -> bool {
+    if data.len() < 4 || (data.len() - 4) % 2 != 0 {
+        return false;
+    }
+    let offset = u32::from_be_bytes(data[..4].try_into().unwrap()) as usize;
+    let (mask, value) = data[4..].split_at((data.len() - 4) / 2);
+    let Some(window) = offset.checked_add(mask.len()).and_then(|end| journal.get(offset..end))
+    else {
+        return false;
+    };
+    window.iter().zip(mask).zip(value).all(|((j, m), v)| j & m == v & m)
+}
+
 #[cfg(not(target_os = "zkvm"))]
 /// The Boundless market module.
 pub mod boundless_market;
 #[cfg(not(target_os = "zkvm"))]
 /// The Hit Points module.
 pub mod hit_points;
+#[cfg(not(target_os = "zkvm"))]
+/// The offer pricing module.
+pub mod offer_builder;
+#[cfg(not(target_os = "zkvm"))]
+/// The smart contract requestor module, for requests validated with ERC-1271.
+pub mod smart_contract_requestor;
 
 #[cfg(not(target_os = "zkvm"))]
 #[derive(Error, Debug)]
@@ -869,7 +957,59 @@ pub mod bytecode;
 #[cfg(test)]
 mod tests {
     use super::*;
-    use alloy::signers::local::PrivateKeySigner;
+    use alloy::{signers::local::PrivateKeySigner, sol_types::SolValue};
+
+    /// Predicate test vector, shared with the Solidity tests.
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct PredicateVector {
+        description: String,
+        predicate_type: u8,
+        data: Bytes,
+        journal: Bytes,
+        expected: bool,
+    }
+
+    #[test]
+    fn predicate_vectors() {
+        #[derive(serde::Deserialize)]
+        struct Vectors {
+            vectors: Vec<PredicateVector>,
+        }
+        let vectors: Vectors = serde_json::from_str(include_str!(
+            "../../../../contracts/test/types/predicate-vectors.json"
+        ))
+        .unwrap();
+        assert!(!vectors.vectors.is_empty());
+
+        for vector in vectors.vectors {
+            let predicate = Predicate {
+                predicateType: PredicateType::try_from(vector.predicate_type).unwrap(),
+                data: vector.data,
+            };
+            assert_eq!(predicate.eval(&vector.journal), vector.expected, "{}", vector.description);
+        }
+    }
+
+    #[test]
+    fn predicate_constructors() {
+        let journal = (U256::from(7), U256::from(42)).abi_encode();
+        assert!(Predicate::abi_word_match(1, B256::from(U256::from(42))).unwrap().eval(&journal));
+        assert!(!Predicate::abi_word_match(0, B256::from(U256::from(42))).unwrap().eval(&journal));
+        assert!(!Predicate::abi_word_match(2, B256::ZERO).unwrap().eval(&journal));
+        assert!(matches!(
+            Predicate::abi_word_match(u32::MAX / 32 + 1, B256::ZERO),
+            Err(RequestError::PredicateIndexOutOfRange(_))
+        ));
+
+        let digests = [b"a".as_slice(), b"b"]
+            .map(|j| Digest::try_from(Sha256::digest(j).as_slice()).unwrap());
+        assert!(Predicate::any_digest_match(digests).eval(b"b"));
+        assert!(!Predicate::any_digest_match(digests).eval(b"c"));
+
+        assert!(Predicate::length_range(2, 4).eval(b"abcd"));
+        assert!(!Predicate::length_range(2, 4).eval(b"abcde"));
+    }
 
     async fn create_order(
         signer: &impl Signer,
@@ -936,6 +1076,15 @@ mod tests {
         req.verify_signature(&Bytes::from(client_sig), contract_addr, chain_id).unwrap();
     }
 
+    #[test]
+    fn offer_price_bump() {
+        let offer =
+            Offer::default().with_min_price(U256::from(100)).with_max_price(U256::from(200));
+        let bumped = offer.with_price_bump(25);
+        assert_eq!(bumped.minPrice, U256::from(125));
+        assert_eq!(bumped.maxPrice, U256::from(250));
+    }
+
     #[tokio::test]
     async fn test_request_id() {
         // Test case 1: Regular signature
@@ -0,0 +1,418 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helper for proposing an [Offer] from a local execution of the guest, the current gas price,
+//! and optionally the recent lock history of the market.
+
+use alloy::{
+    consensus::{BlockHeader, Transaction},
+    eips::BlockNumberOrTag,
+    primitives::{B256, U256},
+    providers::Provider,
+};
+use alloy_sol_types::SolCall;
+use anyhow::{Context, Result};
+use risc0_zkvm::{default_executor, sha::Digest, ExecutorEnv};
+
+use super::{
+    boundless_market::{BoundlessMarketService, MarketError},
+    IBoundlessMarket, Offer,
+};
+use crate::input::GuestEnv;
+
+/// Gas used to lock and fulfill a request, covered by the max price of the proposed offer.
+///
+/// Matches the default lock and fulfill estimates used by the broker.
+pub const DEFAULT_GAS_ESTIMATE: u64 = 200_000 + 750_000;
+
+/// Default maximum number of recent locks inspected when sampling the market lock history.
+pub const DEFAULT_MAX_HISTORY_SAMPLES: usize = 100;
+
+/// Price tiers trading off the time until a request is locked against its cost.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PricingTier {
+    /// Start at a price most provers have locked at recently, with a short ramp-up.
+    Fast,
+    /// Start at the median price provers have locked at recently.
+    #[default]
+    Standard,
+    /// Start at the minimum price, with a long ramp-up and timeout.
+    Economy,
+}
+
+impl PricingTier {
+    /// Default (min, max) price per million cycles, in wei.
+    fn price_per_mcycle(&self) -> (U256, U256) {
+        const MILLI_ETH: u64 = 1_000_000_000_000_000;
+        match self {
+            PricingTier::Fast => (U256::from(MILLI_ETH), U256::from(4 * MILLI_ETH)),
+            PricingTier::Standard => (U256::from(MILLI_ETH), U256::from(2 * MILLI_ETH)),
+            PricingTier::Economy => (U256::from(MILLI_ETH / 2), U256::from(MILLI_ETH)),
+        }
+    }
+
+    /// Ramp-up period, lock timeout and timeout, in seconds.
+    fn timing(&self) -> (u32, u32, u32) {
+        match self {
+            PricingTier::Fast => (60, 600, 1200),
+            PricingTier::Standard => (300, 1200, 2400),
+            PricingTier::Economy => (900, 3600, 7200),
+        }
+    }
+
+    /// Quantile of the sampled lock positions used to raise the starting price, if any.
+    fn lock_quantile(&self) -> Option<f64> {
+        match self {
+            PricingTier::Fast => Some(0.9),
+            PricingTier::Standard => Some(0.5),
+            PricingTier::Economy => None,
+        }
+    }
+}
+
+/// Builder proposing an [Offer] for a [PricingTier].
+///
+/// The cycle count is either given directly, or measured by executing the guest locally with
+/// [OfferBuilder::with_executed_cycles]. The proposed max price covers the gas cost of locking
+/// and fulfilling the request at the current gas price. When a history window is set, the
+/// starting price is raised towards the point in the ramp-up at which recent requests were locked.
+///
+/// ```no_run
+/// # async fn example(
+/// #     market: boundless_market::contracts::boundless_market::BoundlessMarketService<
+/// #         impl alloy::providers::Provider,
+/// #     >,
+/// #     elf: &[u8],
+/// #     env: boundless_market::input::GuestEnv,
+/// # ) -> anyhow::Result<()> {
+/// use boundless_market::contracts::offer_builder::{OfferBuilder, PricingTier};
+///
+/// let offer = OfferBuilder::new(PricingTier::Fast)
+///     .with_executed_cycles(elf, env)?
+///     .with_history_blocks(1000)
+///     .build(&market)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct OfferBuilder {
+    tier: PricingTier,
+    cycles: Option<u64>,
+    min_price_per_mcycle: Option<U256>,
+    max_price_per_mcycle: Option<U256>,
+    lock_stake_per_mcycle: Option<U256>,
+    gas_estimate: Option<u64>,
+    history_blocks: Option<u64>,
+    history_image_id: Option<Digest>,
+    max_history_samples: Option<usize>,
+}
+
+impl OfferBuilder {
+    /// Creates a new offer builder for the given tier.
+    pub fn new(tier: PricingTier) -> Self {
+        Self { tier, ..Default::default() }
+    }
+
+    /// Sets the number of cycles needed to prove the request.
+    pub fn with_cycles(self, cycles: u64) -> Self {
+        Self { cycles: Some(cycles), ..self }
+    }
+
+    /// Executes the guest with the given input to measure the number of cycles.
+    ///
+    /// The count is the total size of the segments, which is what provers are charged for.
+    pub fn with_executed_cycles(self, elf: &[u8], env: GuestEnv) -> Result<Self> {
+        let env: ExecutorEnv = env.try_into()?;
+        let session_info = default_executor().execute(env, elf).context("failed to execute ELF")?;
+        let cycles = session_info.segments.iter().map(|segment| 1u64 << segment.po2).sum();
+        tracing::debug!("Executed guest in {cycles} cycles");
+        Ok(self.with_cycles(cycles))
+    }
+
+    /// Overrides the tier minimum price per million cycles.
+    pub fn with_min_price_per_mcycle(self, mcycle_price: U256) -> Self {
+        Self { min_price_per_mcycle: Some(mcycle_price), ..self }
+    }
+
+    /// Overrides the tier maximum price per million cycles.
+    pub fn with_max_price_per_mcycle(self, mcycle_price: U256) -> Self {
+        Self { max_price_per_mcycle: Some(mcycle_price), ..self }
+    }
+
+    /// Sets the lock-in stake per million cycles, in stake token (HP) units. Defaults to zero.
+    ///
+    /// The stake is denominated in the stake token of the market rather than in ETH, so it is not
+    /// derived from the price of the offer.
+    pub fn with_lock_stake_per_mcycle(self, mcycle_price: U256) -> Self {
+        Self { lock_stake_per_mcycle: Some(mcycle_price), ..self }
+    }
+
+    /// Sets the gas used to lock and fulfill the request. Defaults to [DEFAULT_GAS_ESTIMATE].
+    pub fn with_gas_estimate(self, gas_estimate: u64) -> Self {
+        Self { gas_estimate: Some(gas_estimate), ..self }
+    }
+
+    /// Samples the `RequestLocked` events from the given number of most recent blocks.
+    ///
+    /// Events are queried in chunks of the block range of the [EventQueryConfig] of the market,
+    /// from the most recent block.
+    ///
+    /// [EventQueryConfig]: risc0_ethereum_contracts::event_query::EventQueryConfig
+    pub fn with_history_blocks(self, blocks: u64) -> Self {
+        Self { history_blocks: Some(blocks), ..self }
+    }
+
+    /// Only samples the locks of requests for the given image.
+    pub fn with_history_image_id(self, image_id: impl Into<Digest>) -> Self {
+        Self { history_image_id: Some(image_id.into()), ..self }
+    }
+
+    /// Sets the maximum number of recent locks inspected when sampling the lock history, each
+    /// costing a transaction lookup. Defaults to [DEFAULT_MAX_HISTORY_SAMPLES].
+    pub fn with_max_history_samples(self, samples: usize) -> Self {
+        Self { max_history_samples: Some(samples), ..self }
+    }
+
+    /// Proposes an offer using the current gas price and, if enabled, the market lock history.
+    ///
+    /// The bidding start is left unset so that it is filled in when the request is submitted.
+    pub async fn build<P: Provider>(
+        &self,
+        market: &BoundlessMarketService<P>,
+    ) -> Result<Offer, MarketError> {
+        let gas_price = market
+            .instance()
+            .provider()
+            .get_gas_price()
+            .await
+            .context("failed to get gas price")?;
+        let lock_positions = match self.history_blocks {
+            Some(blocks) => {
+                let image_id = self.history_image_id.map(|id| B256::from(<[u8; 32]>::from(id)));
+                let max_samples = self.max_history_samples.unwrap_or(DEFAULT_MAX_HISTORY_SAMPLES);
+                sample_lock_positions(market, blocks, image_id, max_samples).await?
+            }
+            None => Vec::new(),
+        };
+        Ok(self.propose(U256::from(gas_price), &lock_positions)?)
+    }
+
+    /// Proposes an offer for the given gas price, in wei, and sampled lock positions.
+    ///
+    /// Each lock position is the fraction of the ramp-up period elapsed when a request was locked.
+    pub fn propose(&self, gas_price: U256, lock_positions: &[f64]) -> Result<Offer> {
+        let cycles = self.cycles.context("cycle count must be set")?;
+        let mcycles = cycles.div_ceil(1_000_000).max(1);
+        let (tier_min, tier_max) = self.tier.price_per_mcycle();
+        let min_price_per_mcycle = self.min_price_per_mcycle.unwrap_or(tier_min);
+        let max_price_per_mcycle = self.max_price_per_mcycle.unwrap_or(tier_max);
+        anyhow::ensure!(
+            min_price_per_mcycle <= max_price_per_mcycle,
+            "min price per mcycle must not exceed max price per mcycle"
+        );
+
+        let gas_cost = gas_price * U256::from(self.gas_estimate.unwrap_or(DEFAULT_GAS_ESTIMATE));
+        let max_price = max_price_per_mcycle * U256::from(mcycles) + gas_cost;
+        let mut min_price = min_price_per_mcycle * U256::from(mcycles);
+        if let Some(position) = self.tier.lock_quantile().and_then(|q| quantile(lock_positions, q))
+        {
+            // Start where the ramp reached when a comparable share of recent requests were locked.
+            let position_bps = U256::from((position * 10_000.0).round() as u64);
+            min_price += (max_price - min_price) * position_bps / U256::from(10_000);
+        }
+        let lock_stake = self.lock_stake_per_mcycle.unwrap_or(U256::ZERO) * U256::from(mcycles);
+
+        let (ramp_up_period, lock_timeout, timeout) = self.tier.timing();
+        Ok(Offer::default()
+            .with_min_price(min_price)
+            .with_max_price(max_price)
+            .with_lock_stake(lock_stake)
+            .with_ramp_up_period(ramp_up_period)
+            .with_lock_timeout(lock_timeout)
+            .with_timeout(timeout))
+    }
+}
+
+/// Returns the position in the ramp-up period, from 0 to 1, at which the offer was locked.
+fn lock_position(offer: &Offer, locked_at: u64) -> Option<f64> {
+    if offer.rampUpPeriod == 0 {
+        return None;
+    }
+    let elapsed = locked_at.saturating_sub(offer.biddingStart);
+    Some((elapsed as f64 / offer.rampUpPeriod as f64).min(1.0))
+}
+
+/// Returns the `q` quantile of the given samples, using the nearest rank.
+fn quantile(samples: &[f64], q: f64) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let rank = ((sorted.len() - 1) as f64 * q).round() as usize;
+    Some(sorted[rank])
+}
+
+/// Queries the `RequestLocked` events from the given number of most recent blocks, and returns
+/// the ramp-up position at which each request was locked.
+///
+/// Events are queried in chunks of blocks, from the most recent one, until `max_samples` locks
+/// were inspected. Locks of requests for another image than `image_id`, if set, are skipped, as
+/// are locks submitted through a contract other than the market, as the request cannot be decoded
+/// from the transaction calldata.
+async fn sample_lock_positions<P: Provider>(
+    market: &BoundlessMarketService<P>,
+    blocks: u64,
+    image_id: Option<B256>,
+    max_samples: usize,
+) -> Result<Vec<f64>, MarketError> {
+    let provider = market.instance().provider();
+    let latest_block =
+        provider.get_block_number().await.context("Failed to get latest block number")?;
+    let start_block = latest_block.saturating_sub(blocks);
+    let block_range = market.event_query_config().block_range.max(1);
+
+    let mut positions = Vec::new();
+    let mut samples = 0;
+    let mut upper_block = latest_block;
+    'chunks: while upper_block > start_block && samples < max_samples {
+        let lower_block = upper_block.saturating_sub(block_range).max(start_block);
+        let mut event_filter = market.instance().RequestLocked_filter();
+        event_filter.filter = event_filter.filter.from_block(lower_block + 1).to_block(upper_block);
+        let logs = event_filter.query().await?;
+
+        // Sample the most recent locks first.
+        for (_, log) in logs.into_iter().rev() {
+            if samples >= max_samples {
+                break 'chunks;
+            }
+            samples += 1;
+
+            let tx = provider
+                .get_transaction_by_hash(log.transaction_hash.context("tx hash is none")?)
+                .await
+                .context("Failed to get transaction")?
+                .context("Transaction not found")?;
+            let request =
+                if let Ok(call) = IBoundlessMarket::lockRequestCall::abi_decode(tx.input(), true) {
+                    call.request
+                } else if let Ok(call) =
+                    IBoundlessMarket::lockRequestWithSignatureCall::abi_decode(tx.input(), true)
+                {
+                    call.request
+                } else {
+                    continue;
+                };
+            if image_id.is_some_and(|image_id| request.requirements.imageId != image_id) {
+                continue;
+            }
+
+            let locked_at = match log.block_timestamp {
+                Some(timestamp) => timestamp,
+                None => {
+                    let block_number = log.block_number.context("block number is none")?;
+                    provider
+                        .get_block_by_number(BlockNumberOrTag::Number(block_number))
+                        .await
+                        .context("failed to get block")?
+                        .context("failed to get block")?
+                        .header
+                        .timestamp()
+                }
+            };
+            positions.extend(lock_position(&request.offer, locked_at));
+        }
+        upper_block = lower_block;
+    }
+    tracing::debug!(
+        "Sampled {} lock positions from {samples} locks in the last {blocks} blocks",
+        positions.len()
+    );
+    Ok(positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn propose_without_history() {
+        let offer = OfferBuilder::new(PricingTier::Standard)
+            .with_cycles(2_500_000)
+            .with_min_price_per_mcycle(U256::from(100))
+            .with_max_price_per_mcycle(U256::from(200))
+            .with_gas_estimate(1000)
+            .propose(U256::from(2), &[])
+            .unwrap();
+
+        // 2.5M cycles rounds up to 3 mcycles, plus 2000 wei of gas on the max price.
+        assert_eq!(offer.minPrice, U256::from(300));
+        assert_eq!(offer.maxPrice, U256::from(600 + 2000));
+        assert_eq!(offer.lockStake, U256::ZERO);
+        assert_eq!(offer.biddingStart, 0);
+        assert_eq!(offer.rampUpPeriod, 300);
+    }
+
+    #[test]
+    fn propose_with_lock_stake() {
+        let offer = OfferBuilder::new(PricingTier::Standard)
+            .with_cycles(2_500_000)
+            .with_lock_stake_per_mcycle(U256::from(50))
+            .propose(U256::from(2), &[])
+            .unwrap();
+
+        // The stake is in HP, independent of the ETH price per mcycle.
+        assert_eq!(offer.lockStake, U256::from(150));
+    }
+
+    #[test]
+    fn propose_with_history() {
+        let builder = OfferBuilder::new(PricingTier::Fast)
+            .with_cycles(1_000_000)
+            .with_min_price_per_mcycle(U256::from(1000))
+            .with_max_price_per_mcycle(U256::from(2000))
+            .with_gas_estimate(0);
+        let positions = [0.1, 0.2, 0.5, 0.8, 0.9];
+
+        // The fast tier starts where 90% of the sampled requests had been locked.
+        let offer = builder.clone().propose(U256::ZERO, &positions).unwrap();
+        assert_eq!(offer.minPrice, U256::from(1900));
+        assert_eq!(offer.maxPrice, U256::from(2000));
+
+        // The economy tier ignores the history and starts at the minimum price.
+        let offer = OfferBuilder { tier: PricingTier::Economy, ..builder }
+            .propose(U256::ZERO, &positions)
+            .unwrap();
+        assert_eq!(offer.minPrice, U256::from(1000));
+    }
+
+    #[test]
+    fn propose_requires_cycles() {
+        OfferBuilder::default().propose(U256::ZERO, &[]).unwrap_err();
+    }
+
+    #[test]
+    fn lock_positions() {
+        let offer = Offer::default().with_bidding_start(100).with_ramp_up_period(50);
+        assert_eq!(lock_position(&offer, 90), Some(0.0));
+        assert_eq!(lock_position(&offer, 125), Some(0.5));
+        assert_eq!(lock_position(&offer, 400), Some(1.0));
+        assert_eq!(lock_position(&offer.with_ramp_up_period(0), 125), None);
+
+        assert_eq!(quantile(&[], 0.5), None);
+        assert_eq!(quantile(&[0.3, 0.1, 0.2], 0.5), Some(0.2));
+        assert_eq!(quantile(&[0.3, 0.1, 0.2], 1.0), Some(0.3));
+    }
+}
@@ -0,0 +1,322 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers to make requests on behalf of a smart contract wallet.
+//!
+//! Requests from a smart contract wallet have the smart contract signed flag set in their
+//! [RequestId], and their signature is validated by the market with an ERC-1271
+//! `isValidSignature` call to the wallet, instead of ECDSA recovery. The signature bytes are
+//! opaque to the market, and their format is defined by the wallet.
+
+use std::sync::Arc;
+
+use alloy::{
+    network::{Ethereum, TransactionBuilder},
+    primitives::{Address, Bytes, FixedBytes, B256, U256},
+    providers::Provider,
+    rpc::types::TransactionRequest,
+};
+use alloy_sol_types::SolCall;
+use anyhow::Context;
+use thiserror::Error;
+
+use super::{
+    boundless_market::{BoundlessMarketService, MarketError},
+    IBoundlessMarket, ProofRequest, RequestError, RequestId,
+};
+
+#[allow(missing_docs)]
+mod wallet {
+    alloy::sol! {
+        #[sol(rpc)]
+        interface IERC1271 {
+            function isValidSignature(bytes32 hash, bytes memory signature) external view returns (bytes4 magicValue);
+        }
+
+        #[sol(rpc)]
+        interface IWalletExecute {
+            function execute(address target, bytes memory data, uint256 value) external payable;
+        }
+    }
+}
+
+pub use wallet::{IWalletExecute, IERC1271};
+
+/// Value returned by `isValidSignature` when the signature is valid, as defined by ERC-1271.
+pub const ERC1271_MAGIC_VALUE: FixedBytes<4> = FixedBytes([0x16, 0x26, 0xba, 0x7e]);
+
+/// Encodes the call to a smart contract wallet making it call a target contract.
+///
+/// The market does not define how calls are made through a wallet, so the encoding depends on the
+/// wallet. See [ExecuteCallEncoder] for wallets exposing an `execute(target, data, value)` function.
+pub trait WalletCallEncoder: Send + Sync {
+    /// Returns the calldata for the wallet to call `target` with `data`, forwarding `value` wei.
+    fn encode_call(&self, target: Address, data: Bytes, value: U256) -> Bytes;
+}
+
+/// Encodes calls as [IWalletExecute::execute], as implemented by the smart contract requestor
+/// example.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExecuteCallEncoder;
+
+impl WalletCallEncoder for ExecuteCallEncoder {
+    fn encode_call(&self, target: Address, data: Bytes, value: U256) -> Bytes {
+        IWalletExecute::executeCall { target, data, value }.abi_encode().into()
+    }
+}
+
+/// Errors that can occur when making requests on behalf of a smart contract wallet.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum RequestorError {
+    /// The request ID does not have the smart contract signed flag set.
+    #[error("request 0x{0:x} is not flagged as smart contract signed")]
+    NotSmartContractSigned(U256),
+
+    /// The request client is not the smart contract wallet.
+    #[error("request client {0} does not match the smart contract wallet {1}")]
+    AddressMismatch(Address, Address),
+
+    /// The wallet did not return the ERC-1271 magic value for the signature.
+    #[error("wallet {wallet} rejected the signature of request 0x{request_id:x}: isValidSignature returned 0x{magic_value:x}")]
+    SignatureRejected {
+        /// Address of the wallet.
+        wallet: Address,
+        /// ID of the request.
+        request_id: U256,
+        /// Value returned by the wallet.
+        magic_value: FixedBytes<4>,
+    },
+
+    /// The `isValidSignature` call to the wallet reverted or failed.
+    #[error("isValidSignature call to wallet {0} failed: {1}")]
+    SignatureCheckFailed(Address, alloy::contract::Error),
+
+    /// The deposit through the wallet failed.
+    #[error("deposit through wallet {0} failed: {1:#}")]
+    DepositFailed(Address, anyhow::Error),
+
+    /// Request malformed.
+    #[error("Request error {0}")]
+    RequestError(#[from] RequestError),
+
+    /// Market error.
+    #[error("Market error {0}")]
+    MarketError(#[from] MarketError),
+}
+
+/// Makes requests to the market on behalf of a smart contract wallet implementing ERC-1271.
+///
+/// Deposits are sent to the wallet as a call forwarding a `deposit()` call to the market, encoded
+/// by a [WalletCallEncoder], so the caller must be allowed to make calls through the wallet. By
+/// default, the call is encoded with [ExecuteCallEncoder].
+#[derive(Clone)]
+pub struct SmartContractRequestor<P> {
+    wallet: Address,
+    market: BoundlessMarketService<P>,
+    call_encoder: Arc<dyn WalletCallEncoder>,
+}
+
+impl<P> SmartContractRequestor<P>
+where
+    P: Provider<Ethereum> + 'static + Clone,
+{
+    /// Creates a requestor for the given wallet, sending transactions through the given market
+    /// service.
+    pub fn new(wallet: Address, market: BoundlessMarketService<P>) -> Self {
+        Self { wallet, market, call_encoder: Arc::new(ExecuteCallEncoder) }
+    }
+
+    /// Sets the encoding of the calls made through the wallet, such as deposits.
+    pub fn with_call_encoder(self, call_encoder: impl WalletCallEncoder + 'static) -> Self {
+        Self { call_encoder: Arc::new(call_encoder), ..self }
+    }
+
+    /// Returns the address of the smart contract wallet.
+    pub fn address(&self) -> Address {
+        self.wallet
+    }
+
+    /// Returns the request ID with the given index, with the smart contract signed flag set.
+    ///
+    /// The market fulfills each request ID at most once, so wallets typically use the index as a
+    /// nonce to avoid paying twice for the same work.
+    pub fn request_id(&self, index: u32) -> RequestId {
+        RequestId::new(self.wallet, index).set_smart_contract_signed_flag()
+    }
+
+    /// Checks that the request is made on behalf of the wallet.
+    pub fn check_request(&self, request: &ProofRequest) -> Result<(), RequestorError> {
+        let request_id = RequestId::try_from(request.id)?;
+        if !request_id.smart_contract_signed {
+            return Err(RequestorError::NotSmartContractSigned(request.id));
+        }
+        if request_id.addr != self.wallet {
+            return Err(RequestorError::AddressMismatch(request_id.addr, self.wallet));
+        }
+        Ok(())
+    }
+
+    /// Returns the EIP-712 hash of the request that the market passes to `isValidSignature`.
+    pub async fn signing_hash(&self, request: &ProofRequest) -> Result<B256, RequestorError> {
+        let chain_id = self.market.get_chain_id().await?;
+        Ok(request.signing_hash(*self.market.instance().address(), chain_id)?)
+    }
+
+    /// Checks the signature with an `eth_call` to `isValidSignature` on the wallet, as the market
+    /// does when the request is locked.
+    pub async fn check_signature(
+        &self,
+        request: &ProofRequest,
+        signature: &Bytes,
+    ) -> Result<(), RequestorError> {
+        self.check_request(request)?;
+        let hash = self.signing_hash(request).await?;
+        let wallet = IERC1271::new(self.wallet, self.market.instance().provider().clone());
+        tracing::debug!(
+            "Calling isValidSignature({hash:x}) on wallet {} for request 0x{:x}",
+            self.wallet,
+            request.id
+        );
+        let magic_value = wallet
+            .isValidSignature(hash, signature.clone())
+            .call()
+            .await
+            .map_err(|err| RequestorError::SignatureCheckFailed(self.wallet, err))?
+            .magicValue;
+        if magic_value != ERC1271_MAGIC_VALUE {
+            return Err(RequestorError::SignatureRejected {
+                wallet: self.wallet,
+                request_id: request.id,
+                magic_value,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the market balance of the wallet.
+    pub async fn balance(&self) -> Result<U256, RequestorError> {
+        Ok(self.market.balance_of(self.wallet).await?)
+    }
+
+    /// Deposits Ether into the market account of the wallet.
+    ///
+    /// The value is sent by the caller to the wallet, which forwards it to the market.
+    pub async fn deposit(&self, value: U256) -> Result<(), RequestorError> {
+        let deposit_call = IBoundlessMarket::depositCall {}.abi_encode();
+        let input = self.call_encoder.encode_call(
+            *self.market.instance().address(),
+            deposit_call.into(),
+            value,
+        );
+        let tx = TransactionRequest::default()
+            .with_from(self.market.caller())
+            .with_to(self.wallet)
+            .with_input(input)
+            .with_value(value);
+        tracing::debug!("Forwarding deposit() through wallet {} with value {value}", self.wallet);
+        let res = async {
+            let pending_tx = self
+                .market
+                .instance()
+                .provider()
+                .send_transaction(tx)
+                .await
+                .context("failed to send deposit tx")?;
+            tracing::debug!("Broadcasting deposit tx {}", pending_tx.tx_hash());
+            let receipt = pending_tx
+                .with_timeout(Some(self.market.timeout()))
+                .get_receipt()
+                .await
+                .context("failed to confirm tx")?;
+            anyhow::ensure!(receipt.status(), "tx {} reverted", receipt.transaction_hash);
+            Ok(())
+        };
+        res.await.map_err(|err| RequestorError::DepositFailed(self.wallet, err))
+    }
+
+    /// Deposits the amount needed for the wallet balance to cover the max price of the request.
+    pub async fn fund(&self, request: &ProofRequest) -> Result<(), RequestorError> {
+        let balance = self.balance().await?;
+        let max_price = request.offer.maxPrice;
+        if balance < max_price {
+            self.deposit(max_price - balance).await?;
+        }
+        Ok(())
+    }
+
+    /// Submits the request on-chain, after checking the signature with the wallet.
+    ///
+    /// Checking the signature first gives a clear error when the wallet rejects it, rather than
+    /// a request that no prover can lock.
+    pub async fn submit_request(
+        &self,
+        request: &ProofRequest,
+        signature: &Bytes,
+    ) -> Result<U256, RequestorError> {
+        request.validate()?;
+        self.check_signature(request, signature).await?;
+        Ok(self.market.submit_request_with_signature_bytes(request, signature).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contracts::{Input, Offer, Predicate, Requirements};
+    use alloy::providers::ProviderBuilder;
+    use risc0_zkvm::sha::Digest;
+
+    fn request(id: RequestId) -> ProofRequest {
+        ProofRequest::new(
+            id,
+            Requirements::new(Digest::ZERO, Predicate::prefix_match(vec![])),
+            "https://example.com/guest",
+            Input::inline(vec![]),
+            Offer::default(),
+        )
+    }
+
+    #[test]
+    fn check_request() {
+        let wallet = Address::repeat_byte(1);
+        let provider = ProviderBuilder::new().on_http("http://localhost:8545".parse().unwrap());
+        let market = BoundlessMarketService::new(Address::repeat_byte(2), provider, wallet);
+        let requestor = SmartContractRequestor::new(wallet, market);
+
+        let request_id = requestor.request_id(7);
+        assert!(request_id.smart_contract_signed);
+        assert_eq!(request_id.addr, wallet);
+
+        requestor.check_request(&request(request_id)).unwrap();
+
+        let target = Address::repeat_byte(2);
+        let data = Bytes::from(IBoundlessMarket::depositCall {}.abi_encode());
+        let call = ExecuteCallEncoder.encode_call(target, data.clone(), U256::from(5));
+        let decoded = IWalletExecute::executeCall::abi_decode(&call, true).unwrap();
+        assert_eq!((decoded.target, decoded.data, decoded.value), (target, data, U256::from(5)));
+
+        assert!(matches!(
+            requestor.check_request(&request(RequestId::new(wallet, 7))),
+            Err(RequestorError::NotSmartContractSigned(_))
+        ));
+
+        let other = Address::repeat_byte(3);
+        let request_id = RequestId::new(other, 7).set_smart_contract_signed_flag();
+        assert!(matches!(
+            requestor.check_request(&request(request_id)),
+            Err(RequestorError::AddressMismatch(addr, _)) if addr == other
+        ));
+    }
+}
@@ -0,0 +1,295 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Registry of the known Boundless deployments, keyed by chain ID.
+//!
+//! The built-in entries mirror `contracts/deployment.toml`. They can be extended or overridden
+//! with a TOML file in the following format, e.g. to point at a local deployment:
+//!
+//! ```toml
+//! [chains.anvil]
+//! chain-id = 31337
+//! boundless-market = "0x5FbDB2315678afecb367f032d93F642f64180aa3"
+//! set-verifier = "0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512"
+//! order-stream-url = "http://localhost:8585"
+//! ```
+//!
+//! Fields left out of an entry for a known chain keep their built-in value.
+
+use std::{borrow::Cow, collections::BTreeMap, path::Path};
+
+use alloy::primitives::{address, Address};
+use serde::Deserialize;
+use thiserror::Error;
+use url::Url;
+
+/// Environment variable holding the path of a TOML file overriding the built-in deployments.
+pub const DEPLOYMENTS_FILE_ENV: &str = "BOUNDLESS_DEPLOYMENTS";
+
+/// Errors that can occur when resolving a deployment.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum DeploymentError {
+    /// No deployment is known for the chain.
+    #[error(
+        "no Boundless deployment known for chain ID {0}; set the contract addresses explicitly"
+    )]
+    UnknownChain(u64),
+
+    /// An entry for a chain without a built-in deployment is missing a required field.
+    #[error("deployment for chain ID {chain_id} is missing {field}")]
+    MissingField {
+        /// Chain ID of the entry.
+        chain_id: u64,
+        /// Name of the missing field.
+        field: &'static str,
+    },
+
+    /// Invalid order stream URL.
+    #[error("invalid order stream URL for chain ID {0}: {1}")]
+    InvalidUrl(u64, url::ParseError),
+
+    /// IO error.
+    #[error("IO error {0}")]
+    IoError(#[from] std::io::Error),
+
+    /// Malformed deployments file.
+    #[error("malformed deployments file: {0}")]
+    Malformed(#[from] toml::de::Error),
+}
+
+/// Addresses and endpoints of a Boundless deployment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Deployment {
+    /// Chain ID of the deployment.
+    pub chain_id: u64,
+    /// Address of the BoundlessMarket contract.
+    pub boundless_market_address: Address,
+    /// Address of the RiscZeroSetVerifier contract.
+    pub set_verifier_address: Address,
+    /// Address of the RiscZeroVerifierRouter contract, if known.
+    pub verifier_router_address: Option<Address>,
+    /// Address of the stake token (HitPoints) contract, if known.
+    pub stake_token_address: Option<Address>,
+    /// URL of the order stream service, if any.
+    pub order_stream_url: Option<Cow<'static, str>>,
+}
+
+impl Deployment {
+    /// Creates a deployment from the addresses of the market and set verifier.
+    pub fn new(
+        chain_id: u64,
+        boundless_market_address: Address,
+        set_verifier_address: Address,
+    ) -> Self {
+        Self {
+            chain_id,
+            boundless_market_address,
+            set_verifier_address,
+            verifier_router_address: None,
+            stake_token_address: None,
+            order_stream_url: None,
+        }
+    }
+
+    /// Sets the address of the verifier router.
+    pub fn with_verifier_router_address(self, verifier_router_address: Address) -> Self {
+        Self { verifier_router_address: Some(verifier_router_address), ..self }
+    }
+
+    /// Sets the address of the stake token.
+    pub fn with_stake_token_address(self, stake_token_address: Address) -> Self {
+        Self { stake_token_address: Some(stake_token_address), ..self }
+    }
+
+    /// Sets the URL of the order stream service.
+    pub fn with_order_stream_url(self, order_stream_url: impl Into<Cow<'static, str>>) -> Self {
+        Self { order_stream_url: Some(order_stream_url.into()), ..self }
+    }
+
+    /// Returns the parsed URL of the order stream service, if any.
+    pub fn order_stream_url(&self) -> Result<Option<Url>, DeploymentError> {
+        self.order_stream_url
+            .as_deref()
+            .map(|url| {
+                Url::parse(url).map_err(|err| DeploymentError::InvalidUrl(self.chain_id, err))
+            })
+            .transpose()
+    }
+}
+
+/// Production deployment on Ethereum Sepolia.
+pub const SEPOLIA: Deployment = Deployment {
+    chain_id: 11155111,
+    boundless_market_address: address!("0x006b92674E2A8d397884e293969f8eCD9f615f4C"),
+    set_verifier_address: address!("0xad2c6335191EA71Ffe2045A8d54b93A851ceca77"),
+    verifier_router_address: Some(address!("0x925d8331ddc0a1F0d96E68CF073DFE1d92b69187")),
+    stake_token_address: Some(address!("0xe5321cF13B07Bf6f6dD621E85E45C8e28adedCc9")),
+    order_stream_url: Some(Cow::Borrowed("https://eth-sepolia.beboundless.xyz/")),
+};
+
+/// Entry of a deployments file. All fields are optional so that an entry can override only some
+/// of the fields of a built-in deployment.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct DeploymentEntry {
+    chain_id: u64,
+    boundless_market: Option<Address>,
+    set_verifier: Option<Address>,
+    verifier_router: Option<Address>,
+    stake_token: Option<Address>,
+    order_stream_url: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct DeploymentsFile {
+    #[serde(default)]
+    chains: BTreeMap<String, DeploymentEntry>,
+}
+
+/// Registry of deployments, keyed by chain ID.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Deployments {
+    entries: BTreeMap<u64, Deployment>,
+}
+
+impl Default for Deployments {
+    /// Returns the built-in deployments.
+    fn default() -> Self {
+        Self { entries: BTreeMap::from([(SEPOLIA.chain_id, SEPOLIA)]) }
+    }
+}
+
+impl Deployments {
+    /// Returns the built-in deployments, overridden by the file at the path given in the
+    /// `BOUNDLESS_DEPLOYMENTS` environment variable, if set.
+    pub fn load() -> Result<Self, DeploymentError> {
+        match std::env::var_os(DEPLOYMENTS_FILE_ENV) {
+            Some(path) => Self::default().with_overrides_from_file(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Returns the deployment on the given chain, if known.
+    pub fn get(&self, chain_id: u64) -> Option<&Deployment> {
+        self.entries.get(&chain_id)
+    }
+
+    /// Returns the deployment on the given chain, or an error if none is known.
+    pub fn resolve(&self, chain_id: u64) -> Result<Deployment, DeploymentError> {
+        self.get(chain_id).cloned().ok_or(DeploymentError::UnknownChain(chain_id))
+    }
+
+    /// Adds a deployment, replacing any deployment on the same chain.
+    pub fn insert(&mut self, deployment: Deployment) {
+        self.entries.insert(deployment.chain_id, deployment);
+    }
+
+    /// Applies the overrides of the given deployments file.
+    pub fn with_overrides_from_file(self, path: impl AsRef<Path>) -> Result<Self, DeploymentError> {
+        self.with_overrides(&std::fs::read_to_string(path)?)
+    }
+
+    /// Applies the overrides of the given deployments file contents.
+    pub fn with_overrides(mut self, contents: &str) -> Result<Self, DeploymentError> {
+        let file: DeploymentsFile = toml::from_str(contents)?;
+        for entry in file.chains.into_values() {
+            let chain_id = entry.chain_id;
+            let missing = |field| DeploymentError::MissingField { chain_id, field };
+            let mut deployment = match self.entries.remove(&chain_id) {
+                Some(deployment) => deployment,
+                None => Deployment::new(
+                    chain_id,
+                    entry.boundless_market.ok_or(missing("boundless-market"))?,
+                    entry.set_verifier.ok_or(missing("set-verifier"))?,
+                ),
+            };
+            if let Some(address) = entry.boundless_market {
+                deployment.boundless_market_address = address;
+            }
+            if let Some(address) = entry.set_verifier {
+                deployment.set_verifier_address = address;
+            }
+            if let Some(address) = entry.verifier_router {
+                deployment.verifier_router_address = Some(address);
+            }
+            if let Some(address) = entry.stake_token {
+                deployment.stake_token_address = Some(address);
+            }
+            if let Some(url) = entry.order_stream_url {
+                deployment.order_stream_url = Some(url.into());
+            }
+            // Fail early on invalid URLs, rather than when the deployment is used.
+            deployment.order_stream_url()?;
+            self.insert(deployment);
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin() {
+        let deployments = Deployments::default();
+        assert_eq!(deployments.resolve(11155111).unwrap(), SEPOLIA);
+        assert!(SEPOLIA.order_stream_url().unwrap().is_some());
+        assert!(matches!(deployments.resolve(1), Err(DeploymentError::UnknownChain(1))));
+    }
+
+    #[test]
+    fn overrides() {
+        let market = Address::repeat_byte(1);
+        let set_verifier = Address::repeat_byte(2);
+        let deployments = Deployments::default()
+            .with_overrides(&format!(
+                r#"
+                [chains.anvil]
+                chain-id = 31337
+                boundless-market = "{market}"
+                set-verifier = "{set_verifier}"
+                order-stream-url = "http://localhost:8585"
+
+                [chains.sepolia]
+                chain-id = 11155111
+                boundless-market = "{market}"
+                "#
+            ))
+            .unwrap();
+
+        let anvil = deployments.resolve(31337).unwrap();
+        assert_eq!(anvil.boundless_market_address, market);
+        assert_eq!(anvil.set_verifier_address, set_verifier);
+        assert_eq!(anvil.verifier_router_address, None);
+        assert_eq!(anvil.order_stream_url().unwrap().unwrap().as_str(), "http://localhost:8585/");
+
+        // Fields left out keep their built-in value.
+        let sepolia = deployments.resolve(11155111).unwrap();
+        assert_eq!(sepolia.boundless_market_address, market);
+        assert_eq!(sepolia.set_verifier_address, SEPOLIA.set_verifier_address);
+        assert_eq!(sepolia.order_stream_url, SEPOLIA.order_stream_url);
+
+        let err = Deployments::default()
+            .with_overrides(&format!("[chains.new]\nchain-id = 1\nboundless-market = \"{market}\""))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            DeploymentError::MissingField { chain_id: 1, field: "set-verifier" }
+        ));
+    }
+}
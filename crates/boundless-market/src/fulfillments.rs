@@ -0,0 +1,322 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Retrieval of the journal and seal of fulfilled requests from multiple sources.
+//!
+//! The market only stores whether a request is fulfilled, so the journal and seal are recovered
+//! from the calldata of the fulfilling transaction by the [ChainSource]. This requires the
+//! `ProofDelivered` event to be within the range of the event queries, and the transaction to
+//! be available from the RPC provider. An [HttpArchiveSource] serves fulfillments archived by a
+//! broker, and an [OrderStreamSource] serves fulfillments of offchain orders submitted to the
+//! order stream, so that they can be retrieved without an archive node.
+//!
+//! Fulfillments from sources other than the chain are not trusted, and should be checked with
+//! [verify_fulfillment] against the requirements of the request before use.
+
+use std::path::Path;
+
+use alloy::{
+    network::Ethereum,
+    primitives::{Address, Bytes, B256, U256},
+    providers::Provider,
+};
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    contracts::{
+        boundless_market::{BoundlessMarketService, MarketError},
+        Requirements,
+    },
+    order_stream_client,
+};
+
+#[allow(missing_docs)]
+mod verifier {
+    alloy::sol! {
+        #[sol(rpc)]
+        interface IRiscZeroVerifier {
+            function verify(bytes calldata seal, bytes32 imageId, bytes32 journalDigest) external view;
+        }
+    }
+}
+
+use verifier::IRiscZeroVerifier;
+
+/// Errors that can occur when retrieving a fulfillment.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum FulfillmentSourceError {
+    /// Market error.
+    #[error("Market error {0}")]
+    MarketError(#[from] MarketError),
+
+    /// HTTP error.
+    #[error("HTTP error {0}")]
+    HttpError(#[from] reqwest::Error),
+
+    /// IO error.
+    #[error("IO error {0}")]
+    IoError(#[from] std::io::Error),
+
+    /// Malformed archive entry.
+    #[error("malformed fulfillment {0}")]
+    Malformed(#[from] serde_json::Error),
+
+    /// Order stream error.
+    #[error("Order stream error {0}")]
+    OrderStreamError(#[from] anyhow::Error),
+}
+
+/// Journal and seal of a fulfilled request.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct FulfillmentData {
+    /// Journal of the proof.
+    #[schema(value_type = Object)]
+    pub journal: Bytes,
+    /// Seal of the proof, e.g. a set inclusion seal for proofs delivered in a batch.
+    #[schema(value_type = Object)]
+    pub seal: Bytes,
+}
+
+/// A source of fulfillments.
+#[async_trait]
+pub trait FulfillmentSource: Send + Sync {
+    /// Returns a short description of the source, for logging.
+    fn name(&self) -> String;
+
+    /// Returns the fulfillment of the request, or `None` if the source does not have it.
+    async fn fetch(
+        &self,
+        request_id: U256,
+    ) -> Result<Option<FulfillmentData>, FulfillmentSourceError>;
+}
+
+/// Fulfillments recovered from the calldata of the fulfilling transaction on chain.
+///
+/// Fulfillments made through another contract, such as a multicall, a smart contract wallet or
+/// a relayer, are found by scanning the calldata for embedded fulfillment calls.
+#[derive(Clone)]
+pub struct ChainSource<P> {
+    market: BoundlessMarketService<P>,
+}
+
+impl<P> ChainSource<P> {
+    /// Creates a source reading from the given market.
+    pub fn new(market: BoundlessMarketService<P>) -> Self {
+        Self { market }
+    }
+}
+
+#[async_trait]
+impl<P> FulfillmentSource for ChainSource<P>
+where
+    P: Provider<Ethereum> + 'static + Clone,
+{
+    fn name(&self) -> String {
+        format!("chain ({})", self.market.instance().address())
+    }
+
+    async fn fetch(
+        &self,
+        request_id: U256,
+    ) -> Result<Option<FulfillmentData>, FulfillmentSourceError> {
+        match self.market.get_request_fulfillment(request_id).await {
+            Ok((journal, seal)) => Ok(Some(FulfillmentData { journal, seal })),
+            Err(MarketError::ProofNotFound(_)) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// Fulfillments served over HTTP from an archive, such as the one kept by a broker.
+///
+/// The fulfillment of a request is fetched from `<base_url>/fulfillments/0x<request_id>.json`,
+/// as a JSON encoded [FulfillmentData]. A directory written with [write_archive_entry] can be
+/// served as such an archive by any static HTTP server.
+#[derive(Clone, Debug)]
+pub struct HttpArchiveSource {
+    client: reqwest::Client,
+    base_url: Url,
+}
+
+impl HttpArchiveSource {
+    /// Creates a source reading from the archive at the given URL.
+    pub fn new(base_url: Url) -> Self {
+        Self { client: reqwest::Client::new(), base_url }
+    }
+
+    fn url(&self, request_id: U256) -> Url {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .expect("archive URL cannot be a base")
+            .pop_if_empty()
+            .push("fulfillments")
+            .push(&archive_file_name(request_id));
+        url
+    }
+}
+
+#[async_trait]
+impl FulfillmentSource for HttpArchiveSource {
+    fn name(&self) -> String {
+        format!("archive ({})", self.base_url)
+    }
+
+    async fn fetch(
+        &self,
+        request_id: U256,
+    ) -> Result<Option<FulfillmentData>, FulfillmentSourceError> {
+        let response = self.client.get(self.url(request_id)).send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(response.error_for_status()?.json().await?))
+    }
+}
+
+/// Fulfillments of offchain orders, served by the order stream.
+///
+/// Brokers submit the fulfillments of orders received from the order stream after delivering
+/// them, see [order_stream_client::Client::submit_fulfillment].
+#[derive(Clone, Debug)]
+pub struct OrderStreamSource {
+    client: order_stream_client::Client,
+}
+
+impl OrderStreamSource {
+    /// Creates a source reading from the given order stream.
+    pub fn new(client: order_stream_client::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl FulfillmentSource for OrderStreamSource {
+    fn name(&self) -> String {
+        format!("order stream ({})", self.client.base_url)
+    }
+
+    async fn fetch(
+        &self,
+        request_id: U256,
+    ) -> Result<Option<FulfillmentData>, FulfillmentSourceError> {
+        Ok(self.client.fetch_fulfillment(request_id).await?)
+    }
+}
+
+fn archive_file_name(request_id: U256) -> String {
+    format!("0x{request_id:x}.json")
+}
+
+/// Writes the fulfillment of a request to an archive directory, in the layout read by
+/// [HttpArchiveSource] when the directory is served over HTTP.
+pub async fn write_archive_entry(
+    dir: impl AsRef<Path>,
+    request_id: U256,
+    fulfillment: &FulfillmentData,
+) -> Result<(), FulfillmentSourceError> {
+    let dir = dir.as_ref().join("fulfillments");
+    tokio::fs::create_dir_all(&dir).await?;
+    let path = dir.join(archive_file_name(request_id));
+    // Write to a temporary file first, so that partial entries are never served.
+    let tmp_path = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp_path, serde_json::to_vec(fulfillment)?).await?;
+    tokio::fs::rename(&tmp_path, &path).await?;
+    Ok(())
+}
+
+/// Checks a fulfillment against the requirements of the request.
+///
+/// The journal must satisfy the predicate of the requirements, and the seal must verify against
+/// the journal and the required image ID, with a call to the `verify` method of the given RISC
+/// Zero verifier, e.g. the verifier router of the market. Returns `false` if either check fails.
+pub async fn verify_fulfillment<P>(
+    provider: P,
+    verifier: Address,
+    requirements: &Requirements,
+    fulfillment: &FulfillmentData,
+) -> Result<bool, FulfillmentSourceError>
+where
+    P: Provider<Ethereum>,
+{
+    if !requirements.predicate.eval(&fulfillment.journal) {
+        tracing::debug!("Fulfillment journal does not match the predicate of the request");
+        return Ok(false);
+    }
+    let journal_digest = B256::from_slice(&Sha256::digest(&fulfillment.journal));
+    let verifier = IRiscZeroVerifier::new(verifier, provider);
+    match verifier
+        .verify(fulfillment.seal.clone(), requirements.imageId, journal_digest)
+        .call()
+        .await
+    {
+        Ok(_) => Ok(true),
+        Err(alloy::contract::Error::TransportError(err))
+            if err
+                .as_error_resp()
+                .is_some_and(|resp| resp.code == 3 || resp.as_revert_data().is_some()) =>
+        {
+            tracing::debug!("Verifier rejected fulfillment: {err}");
+            Ok(false)
+        }
+        Err(err) => Err(MarketError::from(err).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    #[tokio::test]
+    async fn http_archive_source() {
+        let server = MockServer::start();
+        let fulfillment =
+            FulfillmentData { journal: Bytes::from(vec![1, 2]), seal: Bytes::from(vec![3, 4]) };
+        let found = server.mock(|when, then| {
+            when.method(GET).path("/archive/fulfillments/0x2a.json");
+            then.status(200).json_body(serde_json::to_value(&fulfillment).unwrap());
+        });
+        let missing = server.mock(|when, then| {
+            when.method(GET).path("/archive/fulfillments/0x2b.json");
+            then.status(404);
+        });
+
+        let source = HttpArchiveSource::new(server.url("/archive/").parse().unwrap());
+        assert_eq!(source.fetch(U256::from(0x2a)).await.unwrap(), Some(fulfillment));
+        assert_eq!(source.fetch(U256::from(0x2b)).await.unwrap(), None);
+        found.assert();
+        missing.assert();
+    }
+
+    #[tokio::test]
+    async fn archive_entry_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let fulfillment =
+            FulfillmentData { journal: Bytes::from(vec![1, 2]), seal: Bytes::from(vec![3, 4]) };
+        write_archive_entry(dir.path(), U256::from(0x2a), &fulfillment).await.unwrap();
+
+        let source = HttpArchiveSource::new("http://localhost/archive".parse().unwrap());
+        let url = source.url(U256::from(0x2a));
+        assert_eq!(url.path(), "/archive/fulfillments/0x2a.json");
+        let contents =
+            std::fs::read(dir.path().join(url.path().strip_prefix("/archive/").unwrap())).unwrap();
+        assert_eq!(serde_json::from_slice::<FulfillmentData>(&contents).unwrap(), fulfillment);
+    }
+}
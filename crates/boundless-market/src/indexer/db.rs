@@ -0,0 +1,348 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::str::FromStr;
+
+use alloy::primitives::{Address, B256, U256};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions},
+    QueryBuilder, Row, Sqlite,
+};
+use thiserror::Error;
+
+use super::{EventFilter, IndexedEvent};
+
+/// Errors from the indexer database.
+#[derive(Error, Debug)]
+pub enum DbError {
+    /// SQL error.
+    #[error("SQL error: {0}")]
+    SqlErr(#[from] sqlx::Error),
+
+    /// Migration error.
+    #[error("SQL Migration error: {0}")]
+    MigrateErr(#[from] sqlx::migrate::MigrateError),
+
+    /// A stored value could not be decoded.
+    #[error("Invalid {0} value: {1}")]
+    BadValue(&'static str, String),
+}
+
+const SQL_BLOCK_KEY: i64 = 0;
+
+/// Number of checkpoint block hashes kept for detecting reorgs.
+const CHECKPOINT_HISTORY: i64 = 64;
+
+/// Sqlite store of indexed market events.
+#[derive(Clone)]
+pub struct IndexerDb {
+    pool: SqlitePool,
+}
+
+impl IndexerDb {
+    /// Opens the database at the given connection string, creating it if missing.
+    pub async fn new(conn_str: &str) -> Result<Self, DbError> {
+        let opts = SqliteConnectOptions::from_str(conn_str)?
+            .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
+            .create_if_missing(true)
+            .busy_timeout(std::time::Duration::from_secs(5));
+
+        let pool = SqlitePoolOptions::new()
+            // set timeouts to None for sqlite in-memory:
+            // https://github.com/launchbadge/sqlx/issues/1647
+            .max_lifetime(None)
+            .idle_timeout(None)
+            .min_connections(1)
+            .max_connections(1);
+
+        let pool = pool.connect_with(opts).await?;
+
+        sqlx::migrate!("./migrations").run(&pool).await?;
+
+        Ok(Self { pool })
+    }
+
+    #[cfg(test)]
+    pub(crate) fn from(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores the given events and advances the checkpoint to `last_block` atomically, so that
+    /// an interrupted sync resumes from the last fully indexed block range.
+    ///
+    /// The hash of `last_block` is recorded with the checkpoint, for detecting reorgs of the
+    /// indexed blocks. Events already stored are ignored.
+    pub async fn add_events(
+        &self,
+        events: &[IndexedEvent],
+        last_block: u64,
+        last_block_hash: B256,
+    ) -> Result<(), DbError> {
+        let mut txn = self.pool.begin().await?;
+        for event in events {
+            sqlx::query(
+                "INSERT OR IGNORE INTO events \
+                 (block_number, log_index, block_timestamp, tx_hash, kind, request_id, client, \
+                 prover, account, value) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+            )
+            .bind(event.block_number as i64)
+            .bind(event.log_index as i64)
+            .bind(event.block_timestamp as i64)
+            .bind(format!("{:x}", event.tx_hash))
+            .bind(event.kind.as_str())
+            .bind(event.request_id.map(|id| format!("{id:x}")))
+            .bind(event.client.map(|addr| format!("{addr:x}")))
+            .bind(event.prover.map(|addr| format!("{addr:x}")))
+            .bind(event.account.map(|addr| format!("{addr:x}")))
+            .bind(event.value.map(|value| format!("{value:x}")))
+            .execute(&mut *txn)
+            .await?;
+        }
+        sqlx::query("REPLACE INTO last_block (id, block) VALUES ($1, $2)")
+            .bind(SQL_BLOCK_KEY)
+            .bind(last_block.to_string())
+            .execute(&mut *txn)
+            .await?;
+        sqlx::query("REPLACE INTO checkpoints (block, block_hash) VALUES ($1, $2)")
+            .bind(last_block as i64)
+            .bind(format!("{last_block_hash:x}"))
+            .execute(&mut *txn)
+            .await?;
+        sqlx::query(
+            "DELETE FROM checkpoints WHERE block NOT IN \
+             (SELECT block FROM checkpoints ORDER BY block DESC LIMIT $1)",
+        )
+        .bind(CHECKPOINT_HISTORY)
+        .execute(&mut *txn)
+        .await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Returns the recorded checkpoints as `(block, block_hash)` pairs, newest first.
+    pub async fn get_checkpoints(&self) -> Result<Vec<(u64, B256)>, DbError> {
+        let rows = sqlx::query("SELECT block, block_hash FROM checkpoints ORDER BY block DESC")
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter()
+            .map(|row| {
+                let block: i64 = row.try_get("block")?;
+                let hash: String = row.try_get("block_hash")?;
+                let hash = hash.parse().map_err(|_| DbError::BadValue("block_hash", hash))?;
+                Ok((block as u64, hash))
+            })
+            .collect()
+    }
+
+    /// Removes all events and checkpoints after the given block, and moves the last indexed
+    /// block back to it, so that the removed range is indexed again.
+    pub async fn rewind(&self, block: u64) -> Result<(), DbError> {
+        let mut txn = self.pool.begin().await?;
+        sqlx::query("DELETE FROM events WHERE block_number > $1")
+            .bind(block as i64)
+            .execute(&mut *txn)
+            .await?;
+        sqlx::query("DELETE FROM checkpoints WHERE block > $1")
+            .bind(block as i64)
+            .execute(&mut *txn)
+            .await?;
+        sqlx::query("REPLACE INTO last_block (id, block) VALUES ($1, $2)")
+            .bind(SQL_BLOCK_KEY)
+            .bind(block.to_string())
+            .execute(&mut *txn)
+            .await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Returns the last fully indexed block, if any.
+    pub async fn get_last_block(&self) -> Result<Option<u64>, DbError> {
+        let res = sqlx::query("SELECT block FROM last_block WHERE id = $1")
+            .bind(SQL_BLOCK_KEY)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        let Some(row) = res else {
+            return Ok(None);
+        };
+
+        let block_str: String = row.try_get("block")?;
+
+        Ok(Some(block_str.parse().map_err(|_err| DbError::BadValue("block", block_str))?))
+    }
+
+    /// Returns the events matching the filter, in chain order.
+    pub async fn get_events(&self, filter: &EventFilter) -> Result<Vec<IndexedEvent>, DbError> {
+        let mut query: QueryBuilder<Sqlite> = QueryBuilder::new("SELECT * FROM events WHERE 1 = 1");
+        if let Some(id) = filter.request_id {
+            query.push(" AND request_id = ").push_bind(format!("{id:x}"));
+        }
+        if let Some(client) = filter.client {
+            query.push(" AND client = ").push_bind(format!("{client:x}"));
+        }
+        if let Some(prover) = filter.prover {
+            query.push(" AND prover = ").push_bind(format!("{prover:x}"));
+        }
+        if let Some(account) = filter.account {
+            query.push(" AND account = ").push_bind(format!("{account:x}"));
+        }
+        if let Some(from) = filter.from_timestamp {
+            query.push(" AND block_timestamp >= ").push_bind(from as i64);
+        }
+        if let Some(to) = filter.to_timestamp {
+            query.push(" AND block_timestamp <= ").push_bind(to as i64);
+        }
+        if !filter.kinds.is_empty() {
+            query.push(" AND kind IN (");
+            let mut kinds = query.separated(", ");
+            for kind in &filter.kinds {
+                kinds.push_bind(kind.as_str());
+            }
+            query.push(")");
+        }
+        query.push(" ORDER BY block_number, log_index");
+
+        let rows = query.build().fetch_all(&self.pool).await?;
+        rows.iter().map(decode_event).collect()
+    }
+}
+
+fn decode_event(row: &sqlx::sqlite::SqliteRow) -> Result<IndexedEvent, DbError> {
+    fn parse<T: FromStr>(name: &'static str, value: String) -> Result<T, DbError> {
+        value.parse().map_err(|_| DbError::BadValue(name, value))
+    }
+    fn parse_u256(name: &'static str, value: String) -> Result<U256, DbError> {
+        U256::from_str_radix(&value, 16).map_err(|_| DbError::BadValue(name, value))
+    }
+
+    let kind: String = row.try_get("kind")?;
+    let block_number: i64 = row.try_get("block_number")?;
+    let log_index: i64 = row.try_get("log_index")?;
+    let block_timestamp: i64 = row.try_get("block_timestamp")?;
+    Ok(IndexedEvent {
+        kind: parse("kind", kind)?,
+        block_number: block_number as u64,
+        log_index: log_index as u64,
+        block_timestamp: block_timestamp as u64,
+        tx_hash: parse::<B256>("tx_hash", row.try_get("tx_hash")?)?,
+        request_id: row
+            .try_get::<Option<String>, _>("request_id")?
+            .map(|id| parse_u256("request_id", id))
+            .transpose()?,
+        client: row
+            .try_get::<Option<String>, _>("client")?
+            .map(|addr| parse::<Address>("client", addr))
+            .transpose()?,
+        prover: row
+            .try_get::<Option<String>, _>("prover")?
+            .map(|addr| parse::<Address>("prover", addr))
+            .transpose()?,
+        account: row
+            .try_get::<Option<String>, _>("account")?
+            .map(|addr| parse::<Address>("account", addr))
+            .transpose()?,
+        value: row
+            .try_get::<Option<String>, _>("value")?
+            .map(|value| parse_u256("value", value))
+            .transpose()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::EventKind;
+
+    fn event(kind: EventKind, block_number: u64, request_id: u64) -> IndexedEvent {
+        IndexedEvent {
+            kind,
+            block_number,
+            log_index: 0,
+            block_timestamp: block_number * 10,
+            tx_hash: B256::repeat_byte(block_number as u8),
+            request_id: Some(U256::from(request_id)),
+            client: Some(Address::repeat_byte(1)),
+            prover: (kind == EventKind::Locked).then(|| Address::repeat_byte(2)),
+            account: None,
+            value: None,
+        }
+    }
+
+    #[sqlx::test]
+    async fn add_events(pool: SqlitePool) {
+        let db = IndexerDb::from(pool);
+        assert_eq!(db.get_last_block().await.unwrap(), None);
+
+        let events = [event(EventKind::Submitted, 1, 1), event(EventKind::Locked, 2, 1)];
+        db.add_events(&events, 5, B256::repeat_byte(5)).await.unwrap();
+        assert_eq!(db.get_last_block().await.unwrap(), Some(5));
+
+        // Re-indexing the same events should not duplicate them.
+        db.add_events(&events, 6, B256::repeat_byte(6)).await.unwrap();
+        assert_eq!(db.get_last_block().await.unwrap(), Some(6));
+        assert_eq!(db.get_events(&EventFilter::default()).await.unwrap(), events);
+    }
+
+    #[sqlx::test]
+    async fn get_events(pool: SqlitePool) {
+        let db = IndexerDb::from(pool);
+        let events = [
+            event(EventKind::Submitted, 1, 1),
+            event(EventKind::Locked, 2, 1),
+            event(EventKind::Submitted, 3, 2),
+            event(EventKind::Fulfilled, 4, 1),
+        ];
+        db.add_events(&events, 4, B256::repeat_byte(4)).await.unwrap();
+
+        let by_request =
+            db.get_events(&EventFilter::default().with_request_id(U256::from(1))).await.unwrap();
+        assert_eq!(by_request, [events[0].clone(), events[1].clone(), events[3].clone()]);
+
+        let by_prover =
+            db.get_events(&EventFilter::default().with_prover(Address::repeat_byte(2))).await;
+        assert_eq!(by_prover.unwrap(), [events[1].clone()]);
+
+        let by_client =
+            db.get_events(&EventFilter::default().with_client(Address::repeat_byte(3))).await;
+        assert!(by_client.unwrap().is_empty());
+
+        let by_time = db
+            .get_events(
+                &EventFilter::default()
+                    .with_time_range(20, 30)
+                    .with_kinds([EventKind::Submitted, EventKind::Fulfilled]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(by_time, [events[2].clone()]);
+    }
+
+    #[sqlx::test]
+    async fn rewind(pool: SqlitePool) {
+        let db = IndexerDb::from(pool);
+        let events = [event(EventKind::Submitted, 1, 1), event(EventKind::Locked, 3, 1)];
+        db.add_events(&events[..1], 2, B256::repeat_byte(2)).await.unwrap();
+        db.add_events(&events[1..], 4, B256::repeat_byte(4)).await.unwrap();
+        assert_eq!(
+            db.get_checkpoints().await.unwrap(),
+            [(4, B256::repeat_byte(4)), (2, B256::repeat_byte(2))]
+        );
+
+        db.rewind(2).await.unwrap();
+        assert_eq!(db.get_last_block().await.unwrap(), Some(2));
+        assert_eq!(db.get_checkpoints().await.unwrap(), [(2, B256::repeat_byte(2))]);
+        assert_eq!(db.get_events(&EventFilter::default()).await.unwrap(), events[..1]);
+    }
+}
@@ -0,0 +1,482 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Indexer following the BoundlessMarket events into a local sqlite store.
+//!
+//! The [MarketIndexer] scans the chain forward in fixed block ranges, recording each range
+//! together with a checkpoint, so that a restarted indexer resumes where it stopped. Only blocks
+//! with enough confirmations are indexed, and the checkpoints record block hashes so that events
+//! of reorged blocks are removed and indexed again. Indexed events can be queried by request ID,
+//! client, prover and time range.
+
+use std::{collections::HashMap, fmt, str::FromStr, time::Duration};
+
+use alloy::{
+    consensus::BlockHeader,
+    eips::BlockNumberOrTag,
+    network::{Ethereum, TransactionResponse},
+    primitives::{Address, B256, U256},
+    providers::Provider,
+    rpc::types::{Filter, Log},
+    sol_types::{SolEvent, SolEventInterface},
+    transports::{RpcError, TransportErrorKind},
+};
+use anyhow::Context;
+use thiserror::Error;
+
+use crate::contracts::{
+    boundless_market::{BoundlessMarketService, MarketError},
+    IBoundlessMarket, RequestId,
+};
+
+mod db;
+
+pub use db::{DbError, IndexerDb};
+
+/// Default number of blocks queried at once.
+pub const DEFAULT_BLOCK_RANGE: u64 = 1000;
+
+/// Default number of confirmations before a block is indexed.
+pub const DEFAULT_CONFIRMATIONS: u64 = 3;
+
+/// Errors from the indexer.
+#[derive(Error, Debug)]
+pub enum IndexerError {
+    /// Database error.
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DbError),
+
+    /// RPC error.
+    #[error("RPC error: {0}")]
+    RpcError(#[from] RpcError<TransportErrorKind>),
+
+    /// Boundless market error.
+    #[error("Boundless market error: {0}")]
+    BoundlessMarketError(#[from] MarketError),
+
+    /// General indexer error.
+    #[error("Indexer error: {0}")]
+    Error(#[from] anyhow::Error),
+}
+
+/// Kind of an indexed market event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    /// `RequestSubmitted` event.
+    Submitted,
+    /// `RequestLocked` event.
+    Locked,
+    /// `RequestFulfilled` event.
+    Fulfilled,
+    /// `ProofDelivered` event.
+    ProofDelivered,
+    /// `ProverSlashed` event.
+    Slashed,
+    /// `Deposit` event.
+    Deposit,
+}
+
+impl EventKind {
+    /// Returns the name of the event kind, as stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::Submitted => "submitted",
+            EventKind::Locked => "locked",
+            EventKind::Fulfilled => "fulfilled",
+            EventKind::ProofDelivered => "proof_delivered",
+            EventKind::Slashed => "slashed",
+            EventKind::Deposit => "deposit",
+        }
+    }
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EventKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "submitted" => Ok(EventKind::Submitted),
+            "locked" => Ok(EventKind::Locked),
+            "fulfilled" => Ok(EventKind::Fulfilled),
+            "proof_delivered" => Ok(EventKind::ProofDelivered),
+            "slashed" => Ok(EventKind::Slashed),
+            "deposit" => Ok(EventKind::Deposit),
+            _ => Err(anyhow::anyhow!("unknown event kind: {s}")),
+        }
+    }
+}
+
+/// A market event recorded by the indexer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexedEvent {
+    /// Kind of the event.
+    pub kind: EventKind,
+    /// Block in which the event was emitted.
+    pub block_number: u64,
+    /// Index of the log within the block.
+    pub log_index: u64,
+    /// Timestamp of the block, in seconds since the UNIX epoch.
+    pub block_timestamp: u64,
+    /// Hash of the transaction emitting the event.
+    pub tx_hash: B256,
+    /// ID of the request, for request events.
+    pub request_id: Option<U256>,
+    /// Client of the request, as encoded in the request ID.
+    pub client: Option<Address>,
+    /// Prover locking the request, or the sender of the fulfilling transaction.
+    pub prover: Option<Address>,
+    /// Depositing account, or the stake recipient of a slashed request.
+    pub account: Option<Address>,
+    /// Deposited value, or the stake burned for a slashed request.
+    pub value: Option<U256>,
+}
+
+/// Filter for querying indexed events. Unset fields match any event.
+#[derive(Clone, Debug, Default)]
+pub struct EventFilter {
+    /// Request ID to match.
+    pub request_id: Option<U256>,
+    /// Client address to match.
+    pub client: Option<Address>,
+    /// Prover address to match.
+    pub prover: Option<Address>,
+    /// Account address to match.
+    pub account: Option<Address>,
+    /// Minimum block timestamp, inclusive.
+    pub from_timestamp: Option<u64>,
+    /// Maximum block timestamp, inclusive.
+    pub to_timestamp: Option<u64>,
+    /// Event kinds to match. Empty matches all kinds.
+    pub kinds: Vec<EventKind>,
+}
+
+impl EventFilter {
+    /// Matches events for the given request ID.
+    pub fn with_request_id(self, request_id: U256) -> Self {
+        Self { request_id: Some(request_id), ..self }
+    }
+
+    /// Matches events for requests from the given client.
+    pub fn with_client(self, client: Address) -> Self {
+        Self { client: Some(client), ..self }
+    }
+
+    /// Matches events for the given prover.
+    pub fn with_prover(self, prover: Address) -> Self {
+        Self { prover: Some(prover), ..self }
+    }
+
+    /// Matches events for the given account.
+    pub fn with_account(self, account: Address) -> Self {
+        Self { account: Some(account), ..self }
+    }
+
+    /// Matches events emitted between the given timestamps, inclusive.
+    pub fn with_time_range(self, from_timestamp: u64, to_timestamp: u64) -> Self {
+        Self { from_timestamp: Some(from_timestamp), to_timestamp: Some(to_timestamp), ..self }
+    }
+
+    /// Matches events of the given kinds.
+    pub fn with_kinds(self, kinds: impl IntoIterator<Item = EventKind>) -> Self {
+        Self { kinds: kinds.into_iter().collect(), ..self }
+    }
+}
+
+/// Indexer following the BoundlessMarket events into an [IndexerDb].
+pub struct MarketIndexer<P> {
+    market: BoundlessMarketService<P>,
+    db: IndexerDb,
+    block_range: u64,
+    confirmations: u64,
+    start_block: Option<u64>,
+}
+
+impl<P> MarketIndexer<P>
+where
+    P: Provider<Ethereum> + 'static + Clone,
+{
+    /// Creates a new indexer for the given market, storing events in the given database.
+    pub fn new(market: BoundlessMarketService<P>, db: IndexerDb) -> Self {
+        Self {
+            market,
+            db,
+            block_range: DEFAULT_BLOCK_RANGE,
+            confirmations: DEFAULT_CONFIRMATIONS,
+            start_block: None,
+        }
+    }
+
+    /// Sets the number of blocks queried at once.
+    pub fn with_block_range(self, block_range: u64) -> Self {
+        Self { block_range: block_range.max(1), ..self }
+    }
+
+    /// Sets the number of blocks to wait on top of a block before indexing it.
+    pub fn with_confirmations(self, confirmations: u64) -> Self {
+        Self { confirmations, ..self }
+    }
+
+    /// Sets the block to start indexing from, when no checkpoint is stored.
+    ///
+    /// Defaults to the latest block at the time of the first sync.
+    pub fn with_start_block(self, start_block: u64) -> Self {
+        Self { start_block: Some(start_block), ..self }
+    }
+
+    /// Returns the underlying database.
+    pub fn db(&self) -> &IndexerDb {
+        &self.db
+    }
+
+    /// Indexes all blocks from the last checkpoint up to the latest confirmed block.
+    ///
+    /// If the last checkpoint is no longer part of the chain, the index is first rewound to the
+    /// most recent checkpoint still on the chain. Returns the latest confirmed block.
+    pub async fn sync(&self) -> Result<u64, IndexerError> {
+        let latest_block = self
+            .market
+            .instance()
+            .provider()
+            .get_block_number()
+            .await?
+            .saturating_sub(self.confirmations);
+        let mut from_block = match self.db.get_last_block().await? {
+            Some(last_block) => self.rewind_reorged(last_block).await? + 1,
+            None => self.start_block.unwrap_or(latest_block),
+        };
+
+        while from_block <= latest_block {
+            let to_block = (from_block + self.block_range - 1).min(latest_block);
+            // The hash is fetched before the logs, so that a reorg in between leaves a
+            // checkpoint that no longer matches the chain, and the range is indexed again.
+            let to_block_hash = self.block_hash(to_block).await?.context("block not found")?;
+            let events = self.fetch_events(from_block, to_block).await?;
+            tracing::debug!(
+                "Indexed {} events from block {} to block {}",
+                events.len(),
+                from_block,
+                to_block
+            );
+            self.db.add_events(&events, to_block, to_block_hash).await?;
+            from_block = to_block + 1;
+        }
+
+        Ok(latest_block)
+    }
+
+    /// Checks the stored checkpoints against the chain, newest first, and rewinds the index to
+    /// the first one still on the chain.
+    ///
+    /// Returns the last indexed block after rewinding.
+    async fn rewind_reorged(&self, last_block: u64) -> Result<u64, IndexerError> {
+        let checkpoints = self.db.get_checkpoints().await?;
+        if checkpoints.is_empty() {
+            return Ok(last_block);
+        }
+        for (block, hash) in checkpoints {
+            if self.block_hash(block).await? != Some(hash) {
+                continue;
+            }
+            if block != last_block {
+                tracing::warn!(
+                    "Reorg detected after block {block}, removing events up to block {last_block}"
+                );
+                self.db.rewind(block).await?;
+            }
+            return Ok(block);
+        }
+        Err(anyhow::anyhow!(
+            "Reorg deeper than the stored checkpoints detected before block {last_block}"
+        )
+        .into())
+    }
+
+    async fn block_hash(&self, block_number: u64) -> Result<Option<B256>, IndexerError> {
+        let block = self
+            .market
+            .instance()
+            .provider()
+            .get_block_by_number(BlockNumberOrTag::Number(block_number))
+            .await?;
+        Ok(block.map(|block| block.header.hash))
+    }
+
+    /// Keeps the index up to date, syncing at the given interval.
+    pub async fn run(self, interval: Duration) -> Result<(), IndexerError> {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.sync().await {
+                match err {
+                    IndexerError::DatabaseError(_) => return Err(err),
+                    _ => tracing::warn!("Failed to sync market events: {err:?}"),
+                }
+            }
+        }
+    }
+
+    /// Returns the indexed events matching the filter, in chain order.
+    pub async fn events(&self, filter: &EventFilter) -> Result<Vec<IndexedEvent>, IndexerError> {
+        Ok(self.db.get_events(filter).await?)
+    }
+
+    /// Returns the indexed events for the given request ID, in chain order.
+    pub async fn request_events(
+        &self,
+        request_id: U256,
+    ) -> Result<Vec<IndexedEvent>, IndexerError> {
+        self.events(&EventFilter::default().with_request_id(request_id)).await
+    }
+
+    async fn fetch_events(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<IndexedEvent>, IndexerError> {
+        let provider = self.market.instance().provider();
+        let filter = Filter::new()
+            .address(*self.market.instance().address())
+            .event_signature(vec![
+                IBoundlessMarket::RequestSubmitted::SIGNATURE_HASH,
+                IBoundlessMarket::RequestLocked::SIGNATURE_HASH,
+                IBoundlessMarket::RequestFulfilled::SIGNATURE_HASH,
+                IBoundlessMarket::ProofDelivered::SIGNATURE_HASH,
+                IBoundlessMarket::ProverSlashed::SIGNATURE_HASH,
+                IBoundlessMarket::Deposit::SIGNATURE_HASH,
+            ])
+            .from_block(from_block)
+            .to_block(to_block);
+        let logs = provider.get_logs(&filter).await?;
+
+        let mut timestamps = HashMap::new();
+        let mut senders = HashMap::new();
+        let mut events = Vec::with_capacity(logs.len());
+        for log in logs {
+            let block_number = log.block_number.context("block number is none")?;
+            let block_timestamp = match log.block_timestamp {
+                Some(timestamp) => timestamp,
+                None => match timestamps.get(&block_number) {
+                    Some(timestamp) => *timestamp,
+                    None => {
+                        let timestamp = provider
+                            .get_block_by_number(BlockNumberOrTag::Number(block_number))
+                            .await?
+                            .context("failed to get block")?
+                            .header
+                            .timestamp();
+                        timestamps.insert(block_number, timestamp);
+                        timestamp
+                    }
+                },
+            };
+            let tx_hash = log.transaction_hash.context("tx hash is none")?;
+
+            let mut event = IndexedEvent {
+                kind: EventKind::Deposit,
+                block_number,
+                log_index: log.log_index.context("log index is none")?,
+                block_timestamp,
+                tx_hash,
+                request_id: None,
+                client: None,
+                prover: None,
+                account: None,
+                value: None,
+            };
+            match decode_log(&log)? {
+                MarketEvent::Submitted(id) => {
+                    event.kind = EventKind::Submitted;
+                    event.request_id = Some(id);
+                }
+                MarketEvent::Locked(id, prover) => {
+                    event.kind = EventKind::Locked;
+                    event.request_id = Some(id);
+                    event.prover = Some(prover);
+                }
+                MarketEvent::Fulfilled(id) => {
+                    event.kind = EventKind::Fulfilled;
+                    event.request_id = Some(id);
+                }
+                MarketEvent::ProofDelivered(id) => {
+                    event.kind = EventKind::ProofDelivered;
+                    event.request_id = Some(id);
+                }
+                MarketEvent::Slashed(id, stake_burned, stake_recipient) => {
+                    event.kind = EventKind::Slashed;
+                    event.request_id = Some(id);
+                    event.account = Some(stake_recipient);
+                    event.value = Some(stake_burned);
+                }
+                MarketEvent::Deposit(account, value) => {
+                    event.account = Some(account);
+                    event.value = Some(value);
+                }
+            }
+            if matches!(event.kind, EventKind::Fulfilled | EventKind::ProofDelivered) {
+                // Fulfillments are batched, so the sender is looked up once per transaction.
+                let sender = match senders.get(&tx_hash) {
+                    Some(sender) => *sender,
+                    None => {
+                        let sender = provider
+                            .get_transaction_by_hash(tx_hash)
+                            .await?
+                            .context("Transaction not found")?
+                            .from();
+                        senders.insert(tx_hash, sender);
+                        sender
+                    }
+                };
+                event.prover = Some(sender);
+            }
+            event.client = event.request_id.map(|id| RequestId::from_lossy(id).addr);
+            events.push(event);
+        }
+        Ok(events)
+    }
+}
+
+enum MarketEvent {
+    Submitted(U256),
+    Locked(U256, Address),
+    Fulfilled(U256),
+    ProofDelivered(U256),
+    Slashed(U256, U256, Address),
+    Deposit(Address, U256),
+}
+
+fn decode_log(log: &Log) -> Result<MarketEvent, MarketError> {
+    use IBoundlessMarket::IBoundlessMarketEvents as Events;
+
+    let event = Events::decode_log(&log.inner, true).context("failed to decode event")?;
+    let event = match event.data {
+        Events::RequestSubmitted(data) => MarketEvent::Submitted(data.requestId),
+        Events::RequestLocked(data) => MarketEvent::Locked(data.requestId, data.prover),
+        Events::RequestFulfilled(data) => MarketEvent::Fulfilled(data.requestId),
+        Events::ProofDelivered(data) => MarketEvent::ProofDelivered(data.requestId),
+        Events::ProverSlashed(data) => {
+            MarketEvent::Slashed(data.requestId, data.stakeBurned, data.stakeRecipient)
+        }
+        Events::Deposit(data) => MarketEvent::Deposit(data.account, data.value),
+        _ => {
+            return Err(anyhow::anyhow!("unexpected event in log {:?}", log.transaction_hash).into())
+        }
+    };
+    Ok(event)
+}
@@ -12,14 +12,29 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
+
+use alloy_primitives::Address;
+use anyhow::ensure;
 use bytemuck::Pod;
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use k256::{ecdh::EphemeralSecret, elliptic_curve::sec1::ToEncodedPoint};
 use risc0_zkvm::serde::to_vec;
-use risc0_zkvm::ExecutorEnv;
+use risc0_zkvm::{ExecutorEnv, Receipt};
 use rmp_serde;
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 
 use crate::contracts::Input;
 
+pub use k256::{PublicKey, SecretKey};
+
+/// Domain separation string used when deriving the per-recipient key wrapping key.
+const KEY_WRAP_INFO: &[u8] = b"boundless-input-encryption-v1";
+
 // Input version.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
@@ -30,6 +45,12 @@ enum Version {
     // MessagePack encoded version based on [InputV1].
     #[default]
     V1 = 1,
+    // MessagePack encoded version, with env vars, args, segment limit and assumptions.
+    //
+    // Uses the same encoding as V1, but is only decoded by provers that honour the extra fields.
+    V2 = 2,
+    // MessagePack encoded [EncryptedEnv], wrapping an encoded [GuestEnv] of any other version.
+    Encrypted = 0x80,
 }
 
 impl From<Version> for u8 {
@@ -45,6 +66,8 @@ impl TryFrom<u8> for Version {
         match v {
             v if v == Version::V0 as u8 => Ok(Version::V0),
             v if v == Version::V1 as u8 => Ok(Version::V1),
+            v if v == Version::V2 as u8 => Ok(Version::V2),
+            v if v == Version::Encrypted as u8 => Ok(Version::Encrypted),
             _ => Err(Error::UnsupportedVersion(v as u64)),
         }
     }
@@ -69,6 +92,21 @@ pub enum Error {
     /// Encoded input buffer is empty, which is an invalid encoding.
     #[error("Cannot decode empty buffer as input")]
     EmptyEncodedInput,
+    /// The input is encrypted and must be decrypted with [GuestEnv::decrypt].
+    #[error("Input is encrypted")]
+    Encrypted,
+    /// The input is encrypted, but not to the given key.
+    #[error("Input is not encrypted to key for {0}")]
+    NotRecipient(Address),
+    /// Encrypting the input failed.
+    #[error("Input encryption failed")]
+    Encryption,
+    /// Decrypting the input failed, e.g. because the ciphertext was tampered with.
+    #[error("Input decryption failed")]
+    Decryption,
+    /// A plaintext [GuestEnv] was requested for an input with recipients to encrypt it to.
+    #[error("Input has recipients and must be built encrypted with InputBuilder::build_vec")]
+    RecipientsSet,
 }
 
 /// Structured input used by the Boundless prover to execute the guest for the proof request.
@@ -88,6 +126,26 @@ pub struct GuestEnv {
     /// be read. If the guest uses `env::read`, this should be encoded using the default RISC Zero
     /// codec. [InputBuilder::write] will encode the data given using the default codec.
     pub stdin: Vec<u8>,
+    /// Frames to be provided to the guest on stdin after [GuestEnv::stdin].
+    ///
+    /// Each frame is preceded by its length as a little-endian u32, such that the guest can read
+    /// it with a single call to `env::read_frame`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stdin_frames: Vec<Vec<u8>>,
+    /// Environment variables to be provided to the guest.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env_vars: BTreeMap<String, String>,
+    /// Arguments to be provided to the guest, as read by `env::args`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Hint for the maximum segment size, as a power of two, to use for execution and proving.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segment_limit_po2: Option<u32>,
+    /// URLs of the receipts for the assumptions made by the guest (i.e. calls to `env::verify`).
+    ///
+    /// Each URL must resolve to a bincode serialized [Receipt].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assumptions: Vec<String>,
 }
 
 impl GuestEnv {
@@ -97,19 +155,203 @@ impl GuestEnv {
             return Err(Error::EmptyEncodedInput);
         }
         match Version::try_from(bytes[0])? {
-            Version::V0 => Ok(Self { stdin: bytes[1..].to_vec() }),
-            Version::V1 => Ok(rmp_serde::from_read(&bytes[1..])?),
+            Version::V0 => Ok(Self { stdin: bytes[1..].to_vec(), ..Default::default() }),
+            Version::V1 | Version::V2 => Ok(rmp_serde::from_read(&bytes[1..])?),
+            Version::Encrypted => Err(Error::Encrypted),
         }
     }
 
+    /// Returns true if the given encoded input is an encrypted [GuestEnv].
+    pub fn is_encrypted(bytes: &[u8]) -> bool {
+        bytes.first() == Some(&(Version::Encrypted as u8))
+    }
+
+    /// Encode and encrypt the [GuestEnv] such that only the holders of the secret keys
+    /// corresponding to the given public keys can decode it.
+    ///
+    /// The environment is encrypted once under a random content key, which is then wrapped for
+    /// each recipient using an ECDH key agreement with a fresh ephemeral secp256k1 key.
+    pub fn encrypt(&self, recipients: &[PublicKey]) -> Result<Vec<u8>, Error> {
+        let content_key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = ChaCha20Poly1305::new(&content_key)
+            .encrypt(&nonce, self.encode()?.as_slice())
+            .map_err(|_| Error::Encryption)?;
+
+        let recipients = recipients
+            .iter()
+            .map(|public_key| {
+                let ephemeral = EphemeralSecret::random(&mut OsRng);
+                let ephemeral_key =
+                    ephemeral.public_key().to_encoded_point(true).as_bytes().to_vec();
+                let wrap_key =
+                    derive_wrap_key(&ephemeral.diffie_hellman(public_key), &ephemeral_key);
+                // Each wrapping key is only ever used once, so a fixed nonce is safe here.
+                let wrapped_key = ChaCha20Poly1305::new(&wrap_key)
+                    .encrypt(&Nonce::default(), content_key.as_slice())
+                    .map_err(|_| Error::Encryption)?;
+                Ok(Recipient {
+                    address: public_key_address(public_key),
+                    ephemeral_key,
+                    wrapped_key,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let envelope = EncryptedEnv { recipients, nonce: nonce.to_vec(), ciphertext };
+        let mut encoded = vec![Version::Encrypted.into()];
+        encoded.extend_from_slice(&rmp_serde::to_vec_named(&envelope)?);
+        Ok(encoded)
+    }
+
+    /// Decrypt and decode an encrypted [GuestEnv] using the given secret key.
+    ///
+    /// Inputs that are not encrypted are decoded as with [GuestEnv::decode].
+    pub fn decrypt(bytes: &[u8], secret_key: &SecretKey) -> Result<Self, Error> {
+        if !Self::is_encrypted(bytes) {
+            return Self::decode(bytes);
+        }
+        let envelope = EncryptedEnv::decode(bytes)?;
+        let public_key = secret_key.public_key();
+        let address = public_key_address(&public_key);
+        let recipient = envelope
+            .recipients
+            .iter()
+            .find(|recipient| recipient.address == address)
+            .ok_or(Error::NotRecipient(address))?;
+
+        let ephemeral_key =
+            PublicKey::from_sec1_bytes(&recipient.ephemeral_key).map_err(|_| Error::Decryption)?;
+        let shared_secret =
+            k256::ecdh::diffie_hellman(secret_key.to_nonzero_scalar(), ephemeral_key.as_affine());
+        let wrap_key = derive_wrap_key(&shared_secret, &recipient.ephemeral_key);
+        let content_key = ChaCha20Poly1305::new(&wrap_key)
+            .decrypt(&Nonce::default(), recipient.wrapped_key.as_slice())
+            .map_err(|_| Error::Decryption)?;
+        if content_key.len() != 32 || envelope.nonce.len() != 12 {
+            return Err(Error::Decryption);
+        }
+        let plaintext = ChaCha20Poly1305::new(Key::from_slice(&content_key))
+            .decrypt(Nonce::from_slice(&envelope.nonce), envelope.ciphertext.as_slice())
+            .map_err(|_| Error::Decryption)?;
+
+        Self::decode(&plaintext)
+    }
+
+    /// Returns the addresses of the keys an encrypted [GuestEnv] is encrypted to.
+    pub fn recipients(bytes: &[u8]) -> Result<Vec<Address>, Error> {
+        Ok(EncryptedEnv::decode(bytes)?.recipients.into_iter().map(|r| r.address).collect())
+    }
+
     /// Encode the [GuestEnv] for inclusion in a proof request.
+    ///
+    /// Environments that only set `stdin` are encoded as V1, such that they can be decoded by
+    /// provers that do not support the V2 fields.
     pub fn encode(&self) -> Result<Vec<u8>, Error> {
+        let version = match self.is_stdin_only() {
+            true => Version::V1,
+            false => Version::V2,
+        };
         let mut encoded = Vec::<u8>::new();
         // Push the version as the first byte to indicate the message version.
-        encoded.push(Version::V1.into());
+        encoded.push(version.into());
         encoded.extend_from_slice(&rmp_serde::to_vec_named(&self)?);
         Ok(encoded)
     }
+
+    /// Returns true if the environment only sets `stdin`, and none of the V2 fields.
+    pub fn is_stdin_only(&self) -> bool {
+        self.stdin_frames.is_empty()
+            && self.env_vars.is_empty()
+            && self.args.is_empty()
+            && self.segment_limit_po2.is_none()
+            && self.assumptions.is_empty()
+    }
+
+    /// Returns the bytes read by the guest from stdin, i.e. [GuestEnv::stdin] followed by each of
+    /// the [GuestEnv::stdin_frames] with its length header.
+    pub fn stdin_bytes(&self) -> Vec<u8> {
+        let mut stdin = self.stdin.clone();
+        for frame in &self.stdin_frames {
+            stdin.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            stdin.extend_from_slice(frame);
+        }
+        stdin
+    }
+
+    /// Create an [ExecutorEnv] from this [GuestEnv], using the given receipts for the referenced
+    /// assumptions.
+    ///
+    /// The receipts must be the ones fetched from [GuestEnv::assumptions], in the same order.
+    pub fn into_executor_env<'a>(
+        self,
+        assumptions: Vec<Receipt>,
+    ) -> anyhow::Result<ExecutorEnv<'a>> {
+        ensure!(
+            assumptions.len() == self.assumptions.len(),
+            "expected {} assumption receipts, got {}",
+            self.assumptions.len(),
+            assumptions.len()
+        );
+
+        let mut builder = ExecutorEnv::builder();
+        builder
+            .write_slice(&self.stdin_bytes())
+            .env_vars(self.env_vars.into_iter().collect())
+            .args(&self.args);
+        if let Some(po2) = self.segment_limit_po2 {
+            builder.segment_limit_po2(po2);
+        }
+        for receipt in assumptions {
+            builder.add_assumption(receipt);
+        }
+        builder.build()
+    }
+}
+
+/// Encrypted envelope around an encoded [GuestEnv].
+#[derive(Serialize, Deserialize)]
+struct EncryptedEnv {
+    recipients: Vec<Recipient>,
+    nonce: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
+
+impl EncryptedEnv {
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.is_empty() {
+            return Err(Error::EmptyEncodedInput);
+        }
+        match Version::try_from(bytes[0])? {
+            Version::Encrypted => Ok(rmp_serde::from_read(&bytes[1..])?),
+            version => Err(Error::UnsupportedVersion(version as u64)),
+        }
+    }
+}
+
+/// Content key of an [EncryptedEnv], wrapped for a single recipient.
+#[derive(Serialize, Deserialize)]
+struct Recipient {
+    /// Address derived from the recipient public key, used to find the entry to unwrap.
+    address: Address,
+    /// SEC1 compressed ephemeral public key used for the key agreement.
+    ephemeral_key: Vec<u8>,
+    wrapped_key: Vec<u8>,
+}
+
+fn derive_wrap_key(shared_secret: &k256::ecdh::SharedSecret, ephemeral_key: &[u8]) -> Key {
+    let mut wrap_key = Key::default();
+    shared_secret
+        .extract::<Sha256>(Some(ephemeral_key))
+        .expand(KEY_WRAP_INFO, &mut wrap_key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    wrap_key
+}
+
+/// Returns the Ethereum address of the given public key.
+pub fn public_key_address(public_key: &PublicKey) -> Address {
+    let point = public_key.to_encoded_point(false);
+    Address::from_raw_public_key(&point.as_bytes()[1..])
 }
 
 impl TryFrom<GuestEnv> for ExecutorEnv<'_> {
@@ -118,8 +360,11 @@ impl TryFrom<GuestEnv> for ExecutorEnv<'_> {
     /// Create an [ExecutorEnv], which can be used for execution and proving through the
     /// [risc0_zkvm] [Prover][risc0_zkvm::Prover] and [Executor][risc0_zkvm::Executor] traits, from
     /// the given [GuestEnv].
+    ///
+    /// Fails if the [GuestEnv] references any assumptions, which must be fetched and provided with
+    /// [GuestEnv::into_executor_env] instead.
     fn try_from(env: GuestEnv) -> Result<Self, Self::Error> {
-        ExecutorEnv::builder().write_slice(&env.stdin).build()
+        env.into_executor_env(Vec::new())
     }
 }
 
@@ -134,27 +379,119 @@ pub struct InputBuilder {
     ///
     /// See [GuestEnv::stdin]
     pub stdin: Vec<u8>,
+    /// Frames to be provided to the guest on stdin after all other stdin data.
+    ///
+    /// See [GuestEnv::stdin_frames]
+    pub stdin_frames: Vec<Vec<u8>>,
+    /// Public keys to encrypt the input to.
+    ///
+    /// If empty, the input is encoded in the clear and readable by anyone.
+    pub recipients: Vec<PublicKey>,
+    /// Environment variables to be provided to the guest.
+    ///
+    /// See [GuestEnv::env_vars]
+    pub env_vars: BTreeMap<String, String>,
+    /// Arguments to be provided to the guest.
+    ///
+    /// See [GuestEnv::args]
+    pub args: Vec<String>,
+    /// Segment limit hint.
+    ///
+    /// See [GuestEnv::segment_limit_po2]
+    pub segment_limit_po2: Option<u32>,
+    /// URLs of assumption receipts.
+    ///
+    /// See [GuestEnv::assumptions]
+    pub assumptions: Vec<String>,
 }
 
 impl InputBuilder {
     /// Create a new input builder.
     pub fn new() -> Self {
-        Self { stdin: Vec::new() }
+        Self::default()
+    }
+
+    /// Add a frame to be provided to the guest on stdin, after all the data written to stdin.
+    ///
+    /// Unlike [InputBuilder::write_frame], the frame is kept separate from the stdin data in the
+    /// encoded [GuestEnv].
+    pub fn with_stdin_frame(self, payload: impl Into<Vec<u8>>) -> Self {
+        let mut stdin_frames = self.stdin_frames;
+        stdin_frames.push(payload.into());
+        Self { stdin_frames, ..self }
+    }
+
+    /// Set an environment variable to be provided to the guest.
+    pub fn with_env_var(self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let mut env_vars = self.env_vars;
+        env_vars.insert(name.into(), value.into());
+        Self { env_vars, ..self }
+    }
+
+    /// Set the arguments to be provided to the guest.
+    pub fn with_args<I: IntoIterator<Item = S>, S: Into<String>>(self, args: I) -> Self {
+        Self { args: args.into_iter().map(Into::into).collect(), ..self }
+    }
+
+    /// Set the segment limit hint, as a power of two.
+    pub fn with_segment_limit_po2(self, po2: u32) -> Self {
+        Self { segment_limit_po2: Some(po2), ..self }
+    }
+
+    /// Add the URL of a bincode serialized [Receipt] for an assumption made by the guest.
+    pub fn with_assumption(self, receipt_url: impl Into<String>) -> Self {
+        let mut assumptions = self.assumptions;
+        assumptions.push(receipt_url.into());
+        Self { assumptions, ..self }
+    }
+
+    /// Encrypt the input to the given prover public key.
+    ///
+    /// Can be called multiple times to allow any one of several provers to decrypt the input.
+    /// Provers that do not hold one of the recipient keys will not be able to fulfill the request.
+    pub fn with_recipient(self, public_key: PublicKey) -> Self {
+        let mut recipients = self.recipients;
+        recipients.push(public_key);
+        Self { recipients, ..self }
     }
 
     /// Build the [GuestEnv] for inclusion in a proof request.
+    ///
+    /// Fails with [Error::RecipientsSet] if any recipients are set, as the [GuestEnv] would not be
+    /// encrypted. Use [InputBuilder::build_vec] or [InputBuilder::build_inline] instead.
     pub fn build_env(self) -> Result<GuestEnv, Error> {
-        Ok(GuestEnv { stdin: self.stdin })
+        if !self.recipients.is_empty() {
+            return Err(Error::RecipientsSet);
+        }
+        Ok(self.into_env())
     }
 
     /// Build the and encode [GuestEnv] for inclusion in a proof request.
+    ///
+    /// If any recipients are set, the encoded [GuestEnv] is encrypted to them.
     pub fn build_vec(self) -> Result<Vec<u8>, Error> {
-        self.build_env()?.encode()
+        let recipients = self.recipients.clone();
+        let env = self.into_env();
+        match recipients.is_empty() {
+            true => env.encode(),
+            false => env.encrypt(&recipients),
+        }
+    }
+
+    fn into_env(self) -> GuestEnv {
+        GuestEnv {
+            stdin: self.stdin,
+            stdin_frames: self.stdin_frames,
+            env_vars: self.env_vars,
+            args: self.args,
+            segment_limit_po2: self.segment_limit_po2,
+            assumptions: self.assumptions,
+        }
     }
 
     /// Build and encode the [GuestEnv] into an inline [Input] for inclusion in a proof request.
     pub fn build_inline(self) -> Result<Input, Error> {
-        Ok(Input::inline(self.build_env()?.encode()?))
+        Ok(Input::inline(self.build_vec()?))
     }
 
     /// Write input data.
@@ -239,8 +576,16 @@ mod tests {
         let parsed = GuestEnv::decode(&bytes)?;
         assert_eq!(parsed.stdin, vec![1, 2, 3]);
 
+        // Test V2
+        let v2 = InputBuilder::new().write_slice(&[1u8, 2, 3]).with_args(["guest"]);
+        let bytes = v2.build_vec()?;
+        assert_eq!(bytes[0], 2);
+        let parsed = GuestEnv::decode(&bytes)?;
+        assert_eq!(parsed.stdin, vec![1, 2, 3]);
+        assert_eq!(parsed.args, vec!["guest".to_string()]);
+
         // Test unsupported version
-        let bytes = vec![2u8, 1, 2, 3];
+        let bytes = vec![3u8, 1, 2, 3];
         let parsed = GuestEnv::decode(&bytes);
         assert!(parsed.is_err());
 
@@ -256,4 +601,95 @@ mod tests {
         assert_eq!(env, decoded_env);
         Ok(())
     }
+
+    #[test]
+    fn test_encode_decode_env_v2() -> Result<(), Error> {
+        let env = InputBuilder::new()
+            .write_slice(&[1u8, 2, 3])
+            .with_env_var("RUST_LOG", "info")
+            .with_args(["guest", "--verbose"])
+            .with_segment_limit_po2(20)
+            .with_assumption("https://example.com/receipt.bin")
+            .build_env()?;
+        assert!(!env.is_stdin_only());
+
+        let decoded_env = GuestEnv::decode(&env.encode()?)?;
+        assert_eq!(env, decoded_env);
+
+        // A stdin only env is still encoded as V1, and decodes the same as before.
+        let env = InputBuilder::new().write_slice(&[1u8, 2, 3]).build_env()?;
+        let bytes = env.encode()?;
+        assert_eq!(bytes[0], 1);
+        assert_eq!(GuestEnv::decode(&bytes)?, env);
+        Ok(())
+    }
+
+    #[test]
+    fn test_stdin_frames() -> Result<(), Error> {
+        let env = InputBuilder::new()
+            .write_slice(&[1u8, 2, 3])
+            .with_stdin_frame([4u8, 5])
+            .with_stdin_frame([6u8])
+            .build_env()?;
+        assert!(!env.is_stdin_only());
+        assert_eq!(
+            env.stdin_bytes(),
+            InputBuilder::new()
+                .write_slice(&[1u8, 2, 3])
+                .write_frame(&[4u8, 5])
+                .write_frame(&[6u8])
+                .stdin
+        );
+
+        let bytes = env.encode()?;
+        assert_eq!(bytes[0], 2);
+        assert_eq!(GuestEnv::decode(&bytes)?, env);
+        Ok(())
+    }
+
+    #[test]
+    fn test_executor_env_assumptions() -> Result<(), Error> {
+        let env =
+            InputBuilder::new().with_assumption("https://example.com/receipt.bin").build_env()?;
+        assert!(ExecutorEnv::try_from(env.clone()).is_err());
+        assert!(env.into_executor_env(Vec::new()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_env() -> Result<(), Error> {
+        let key_a = SecretKey::random(&mut OsRng);
+        let key_b = SecretKey::random(&mut OsRng);
+        let other = SecretKey::random(&mut OsRng);
+
+        let bytes = InputBuilder::new()
+            .write_slice(&[1u8, 2, 3])
+            .with_recipient(key_a.public_key())
+            .with_recipient(key_b.public_key())
+            .build_vec()?;
+        assert!(GuestEnv::is_encrypted(&bytes));
+        assert!(matches!(GuestEnv::decode(&bytes), Err(Error::Encrypted)));
+        assert_eq!(
+            GuestEnv::recipients(&bytes)?,
+            vec![public_key_address(&key_a.public_key()), public_key_address(&key_b.public_key())]
+        );
+
+        assert_eq!(GuestEnv::decrypt(&bytes, &key_a)?.stdin, vec![1, 2, 3]);
+        assert_eq!(GuestEnv::decrypt(&bytes, &key_b)?.stdin, vec![1, 2, 3]);
+        assert!(matches!(GuestEnv::decrypt(&bytes, &other), Err(Error::NotRecipient(_))));
+
+        // Tampering with the ciphertext must be detected.
+        let mut tampered = bytes.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(GuestEnv::decrypt(&tampered, &key_a).is_err());
+
+        // Unencrypted inputs decrypt as a plain decode.
+        let plain = InputBuilder::new().write_slice(&[4u8]).build_vec()?;
+        assert_eq!(GuestEnv::decrypt(&plain, &other)?.stdin, vec![4]);
+
+        // An input with recipients is never built in the clear.
+        let builder = InputBuilder::new().write_slice(&[4u8]).with_recipient(key_a.public_key());
+        assert!(matches!(builder.build_env(), Err(Error::RecipientsSet)));
+        Ok(())
+    }
 }
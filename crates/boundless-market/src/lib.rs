@@ -23,6 +23,10 @@
 #[cfg(not(target_os = "zkvm"))]
 pub use alloy;
 
+#[cfg(not(target_os = "zkvm"))]
+/// Alerts module for checking balances against thresholds and delivering alerts to pluggable
+/// sinks.
+pub mod alerts;
 #[cfg(not(target_os = "zkvm"))]
 /// A ProviderLayer module.
 ///
@@ -32,27 +36,38 @@ pub mod balance_alerts_layer;
 #[cfg(not(target_os = "zkvm"))]
 /// Client module for interacting with the Boundless Market API.
 pub mod client;
+#[cfg(not(target_os = "zkvm"))]
+/// Clock module for estimating the current time of the chain.
+pub mod clock;
 /// Contracts module for interacting with the Boundless Market smart contracts.
 pub mod contracts;
 #[cfg(not(target_os = "zkvm"))]
+/// Deployments module for looking up the contracts of known Boundless deployments.
+pub mod deployments;
+#[cfg(not(target_os = "zkvm"))]
+/// Fulfillments module for retrieving proofs from the chain and from proof archives.
+pub mod fulfillments;
+#[cfg(all(feature = "indexer", not(target_os = "zkvm")))]
+/// Indexer module for following the market events into a local store.
+pub mod indexer;
+#[cfg(not(target_os = "zkvm"))]
 /// Input module for serializing input.
 pub mod input;
 #[cfg(not(target_os = "zkvm"))]
 /// Order stream client module for submitting requests off-chain.
 pub mod order_stream_client;
 #[cfg(not(target_os = "zkvm"))]
+/// Request template module for building proof requests from YAML, JSON or TOML files.
+pub mod request_template;
+#[cfg(not(target_os = "zkvm"))]
 /// Selector module implementing utility functions for supported selectors.
 pub mod selector;
 #[cfg(not(target_os = "zkvm"))]
+/// Signer module for signing requests and transactions with local, remote or KMS keys.
+pub mod signer;
+#[cfg(not(target_os = "zkvm"))]
+/// Simulator module for running market strategies offline against an in-process market.
+pub mod simulator;
+#[cfg(not(target_os = "zkvm"))]
 /// Storage module for interacting with the storage provider.
 pub mod storage;
-
-/// A very small utility function to get the current unix timestamp.
-// TODO(#379): Avoid drift relative to the chain's timestamps.
-#[cfg(not(target_os = "zkvm"))]
-pub(crate) fn now_timestamp() -> u64 {
-    std::time::SystemTime::now()
-        .duration_since(std::time::SystemTime::UNIX_EPOCH)
-        .unwrap()
-        .as_secs()
-}
@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use alloy::{
-    primitives::{Address, PrimitiveSignature, U256},
+    primitives::{Address, PrimitiveSignature, SignatureError, U256},
     signers::{Error as SignerErr, Signer},
 };
 use alloy_primitives::B256;
@@ -25,7 +25,7 @@ use futures_util::{SinkExt, Stream, StreamExt};
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use siwe::Message as SiweMsg;
-use std::{error::Error, pin::Pin};
+use std::{error::Error, pin::Pin, time::Duration};
 use thiserror::Error;
 use time::OffsetDateTime;
 use tokio::net::TcpStream;
@@ -35,12 +35,23 @@ use tokio_tungstenite::{
 };
 use utoipa::ToSchema;
 
-use crate::contracts::{eip712_domain, ProofRequest, RequestError};
+use crate::{
+    contracts::{eip712_domain, ProofRequest, RequestError, RequestId},
+    fulfillments::FulfillmentData,
+};
 
 /// Order stream submission API path.
 pub const ORDER_SUBMISSION_PATH: &str = "/api/v1/submit_order";
+/// Order stream batch submission API path.
+pub const ORDER_BATCH_SUBMISSION_PATH: &str = "/api/v1/submit_orders";
+/// Order stream cancellation API path.
+pub const ORDER_CANCEL_PATH: &str = "/api/v1/cancel_order";
 /// Order stream order list API path.
 pub const ORDER_LIST_PATH: &str = "/api/v1/orders";
+/// Order stream fulfillment submission API path.
+pub const FULFILLMENT_SUBMISSION_PATH: &str = "/api/v1/submit_fulfillment";
+/// Order stream fulfillment API path.
+pub const FULFILLMENT_PATH: &str = "/api/v1/fulfillments";
 /// Order stream nonce API path.
 pub const AUTH_GET_NONCE: &str = "/api/v1/nonce/";
 /// Order stream health check API path.
@@ -108,6 +119,85 @@ pub struct OrderData {
     pub created_at: DateTime<Utc>,
 }
 
+#[allow(missing_docs)]
+mod cancellation {
+    alloy::sol! {
+        struct OrderCancellation {
+            uint256 requestId;
+            bytes32 requestDigest;
+        }
+    }
+}
+use cancellation::OrderCancellation;
+
+/// Signed message retracting an open order from the order stream
+///
+/// The message is signed by the client of the request over the EIP-712 domain of the market.
+/// Brokers receiving it stop pricing and locking the order, unless they already locked it.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, PartialEq)]
+pub struct CancelOrderMsg {
+    /// Request ID of the cancelled order
+    #[schema(value_type = Object)]
+    pub request_id: U256,
+    /// Request digest of the cancelled order
+    #[schema(value_type = Object)]
+    pub request_digest: B256,
+    /// Signature of the request client
+    #[schema(value_type = Object)]
+    pub signature: PrimitiveSignature,
+}
+
+impl CancelOrderMsg {
+    /// Creates a cancellation for the order with the given request ID and digest
+    pub async fn new(
+        request_id: U256,
+        request_digest: B256,
+        signer: &(impl Signer + ?Sized),
+        market_address: Address,
+        chain_id: u64,
+    ) -> Result<Self, OrderError> {
+        let hash = Self::signing_hash(request_id, request_digest, market_address, chain_id);
+        let signature = signer.sign_hash(&hash).await.map_err(OrderError::InvalidSignature)?;
+        Ok(Self { request_id, request_digest, signature })
+    }
+
+    /// Verify the cancellation is signed by the client of the request
+    pub fn verify(&self, market_address: Address, chain_id: u64) -> Result<(), OrderError> {
+        let hash =
+            Self::signing_hash(self.request_id, self.request_digest, market_address, chain_id);
+        let addr =
+            self.signature.recover_address_from_prehash(&hash).map_err(RequestError::from)?;
+        if addr != RequestId::from_lossy(self.request_id).addr {
+            return Err(RequestError::from(SignatureError::FromBytes("Address mismatch")).into());
+        }
+        Ok(())
+    }
+
+    fn signing_hash(
+        request_id: U256,
+        request_digest: B256,
+        market_address: Address,
+        chain_id: u64,
+    ) -> B256 {
+        let domain = eip712_domain(market_address, chain_id);
+        OrderCancellation { requestId: request_id, requestDigest: request_digest }
+            .eip712_signing_hash(&domain.alloy_struct())
+    }
+}
+
+/// Message received from the order stream websocket
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum OrderStreamEvent {
+    /// A new order was submitted
+    Order(OrderData),
+    /// An open order was cancelled by its client
+    Cancellation {
+        /// Signed cancellation message
+        cancellation: CancelOrderMsg,
+    },
+}
+
 /// Nonce object for authentication to order-stream websocket
 #[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
 pub struct Nonce {
@@ -125,6 +215,27 @@ pub struct SubmitOrderRes {
     pub request_id: U256,
 }
 
+/// Fulfillment of an order, submitted by the prover after delivering the proof on-chain
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, PartialEq)]
+pub struct FulfillmentSubmission {
+    /// Request ID of the fulfilled order
+    #[schema(value_type = Object)]
+    pub request_id: U256,
+    /// Journal and seal of the fulfillment
+    #[schema(value_type = Object)]
+    pub fulfillment: FulfillmentData,
+}
+
+/// Result of a single order in a batch submission
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+pub struct SubmitOrdersRes {
+    /// Request ID of the order
+    #[schema(value_type = Object)]
+    pub request_id: U256,
+    /// Error message, if the order was rejected
+    pub error: Option<String>,
+}
+
 impl Order {
     /// Create a new Order
     pub fn new(request: ProofRequest, request_digest: B256, signature: PrimitiveSignature) -> Self {
@@ -161,7 +272,7 @@ pub struct AuthMsg {
 
 impl AuthMsg {
     /// Creates a new authentication message from a nonce, origin, signer
-    pub async fn new(nonce: Nonce, origin: &Url, signer: &impl Signer) -> Result<Self> {
+    pub async fn new(nonce: Nonce, origin: &Url, signer: &(impl Signer + ?Sized)) -> Result<Self> {
         let message = format!(
             "{} wants you to sign in with your Ethereum account:\n{}\n\nBoundless Order Stream\n\nURI: {}\nVersion: 1\nChain ID: 1\nNonce: {}\nIssued At: {}",
             origin.authority(), signer.address(), origin, nonce.nonce, Utc::now().to_rfc3339(),
@@ -218,7 +329,7 @@ impl Client {
     pub async fn submit_request(
         &self,
         request: &ProofRequest,
-        signer: &impl Signer,
+        signer: &(impl Signer + ?Sized),
     ) -> Result<Order> {
         let url = self.base_url.join(ORDER_SUBMISSION_PATH)?;
         let signature =
@@ -251,6 +362,115 @@ impl Client {
         Ok(order)
     }
 
+    /// Submit multiple proof requests to the order stream server in a single call
+    ///
+    /// Returns the result of each request, in order. Requests failing local validation are not
+    /// sent to the server.
+    pub async fn submit_requests(
+        &self,
+        requests: &[ProofRequest],
+        signer: &(impl Signer + ?Sized),
+    ) -> Result<Vec<Result<Order>>> {
+        let url = self.base_url.join(ORDER_BATCH_SUBMISSION_PATH)?;
+        let domain = eip712_domain(self.boundless_market_address, self.chain_id);
+
+        let mut results = Vec::with_capacity(requests.len());
+        let mut orders = Vec::with_capacity(requests.len());
+        for request in requests {
+            let signature =
+                request.sign_request(signer, self.boundless_market_address, self.chain_id).await?;
+            let request_digest = request.eip712_signing_hash(&domain.alloy_struct());
+            let order = Order { request: request.clone(), request_digest, signature };
+            match order.validate(self.boundless_market_address, self.chain_id) {
+                Ok(()) => {
+                    orders.push(order.clone());
+                    results.push(Ok(order));
+                }
+                Err(err) => results.push(Err(err.into())),
+            }
+        }
+        if orders.is_empty() {
+            return Ok(results);
+        }
+
+        let response = self
+            .client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .json(&orders)
+            .send()
+            .await?;
+
+        // Check for any errors in the response
+        if let Err(err) = response.error_for_status_ref() {
+            let error_message = match response.json::<serde_json::Value>().await {
+                Ok(json_body) => {
+                    json_body["msg"].as_str().unwrap_or("Unknown server error").to_string()
+                }
+                Err(_) => "Failed to read server error message".to_string(),
+            };
+
+            return Err(anyhow::Error::new(err).context(error_message));
+        }
+
+        let submitted: Vec<SubmitOrdersRes> = response.json().await?;
+        anyhow::ensure!(
+            submitted.len() == orders.len(),
+            "server returned {} results for {} orders",
+            submitted.len(),
+            orders.len()
+        );
+        let mut submitted = submitted.into_iter();
+        for result in results.iter_mut().filter(|result| result.is_ok()) {
+            if let Some(error) = submitted.next().and_then(|res| res.error) {
+                *result = Err(anyhow::anyhow!(error));
+            }
+        }
+        Ok(results)
+    }
+
+    /// Cancel the open order of a proof request on the order stream server
+    ///
+    /// The signer must be the client of the request. Brokers are notified of the cancellation,
+    /// but an order that was already locked on-chain can no longer be retracted.
+    pub async fn cancel_request(
+        &self,
+        request: &ProofRequest,
+        signer: &(impl Signer + ?Sized),
+    ) -> Result<CancelOrderMsg> {
+        let url = self.base_url.join(ORDER_CANCEL_PATH)?;
+        let domain = eip712_domain(self.boundless_market_address, self.chain_id);
+        let cancellation = CancelOrderMsg::new(
+            request.id,
+            request.eip712_signing_hash(&domain.alloy_struct()),
+            signer,
+            self.boundless_market_address,
+            self.chain_id,
+        )
+        .await?;
+        let response = self
+            .client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .json(&cancellation)
+            .send()
+            .await?;
+
+        // Check for any errors in the response
+        if let Err(err) = response.error_for_status_ref() {
+            let error_message = match response.json::<serde_json::Value>().await {
+                Ok(json_body) => {
+                    json_body["msg"].as_str().unwrap_or("Unknown server error").to_string()
+                }
+                Err(_) => "Failed to read server error message".to_string(),
+            };
+
+            return Err(anyhow::Error::new(err).context(error_message));
+        }
+
+        Ok(cancellation)
+    }
+
     /// Fetch an order from the order stream server.
     ///
     /// If multiple orders are found, the `request_digest` must be provided to select the correct order.
@@ -291,6 +511,70 @@ impl Client {
         }
     }
 
+    /// Submit the fulfillment of an order to the order stream server
+    ///
+    /// The server only accepts fulfillments of its orders that verify against the market's
+    /// verifier, and serves them to clients that cannot recover them from the chain.
+    pub async fn submit_fulfillment(
+        &self,
+        request_id: U256,
+        fulfillment: &FulfillmentData,
+    ) -> Result<()> {
+        let url = self.base_url.join(FULFILLMENT_SUBMISSION_PATH)?;
+        let submission = FulfillmentSubmission { request_id, fulfillment: fulfillment.clone() };
+        let response = self
+            .client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .json(&submission)
+            .send()
+            .await?;
+
+        // Check for any errors in the response
+        if let Err(err) = response.error_for_status_ref() {
+            let error_message = match response.json::<serde_json::Value>().await {
+                Ok(json_body) => {
+                    json_body["msg"].as_str().unwrap_or("Unknown server error").to_string()
+                }
+                Err(_) => "Failed to read server error message".to_string(),
+            };
+
+            return Err(anyhow::Error::new(err).context(error_message));
+        }
+
+        Ok(())
+    }
+
+    /// Fetch the fulfillment of an order from the order stream server, if it has one
+    pub async fn fetch_fulfillment(&self, request_id: U256) -> Result<Option<FulfillmentData>> {
+        let url = self.base_url.join(&format!("{FULFILLMENT_PATH}/{request_id}"))?;
+        let response = self.client.get(url).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            anyhow::bail!("Http error {} fetching fulfillment", response.status())
+        }
+
+        Ok(Some(response.json().await?))
+    }
+
+    /// List the open orders with an order stream id of at least `offset`, in id order.
+    ///
+    /// At most `limit` orders are returned, and the server may cap the limit further.
+    pub async fn list_orders(&self, offset: i64, limit: u64) -> Result<Vec<OrderData>> {
+        let mut url = self.base_url.join(ORDER_LIST_PATH)?;
+        url.query_pairs_mut()
+            .append_pair("offset", &offset.to_string())
+            .append_pair("limit", &limit.to_string());
+        let res = self.client.get(url).send().await?;
+        if !res.status().is_success() {
+            anyhow::bail!("Http error {} listing orders", res.status())
+        }
+
+        Ok(res.json().await?)
+    }
+
     /// Get the nonce from the order stream service for websocket auth
     pub async fn get_nonce(&self, address: Address) -> Result<Nonce> {
         let url = self.base_url.join(AUTH_GET_NONCE)?.join(&address.to_string())?;
@@ -311,7 +595,7 @@ impl Client {
     /// Only one connection per address is allowed.
     pub async fn connect_async(
         &self,
-        signer: &impl Signer,
+        signer: &(impl Signer + ?Sized),
     ) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
         let nonce = self
             .get_nonce(signer.address())
@@ -370,9 +654,64 @@ impl Client {
     }
 }
 
+/// Configuration of the reconnects of [reconnecting_order_stream_events]
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ReconnectConfig {
+    /// Delay before the first reconnect attempt, doubled on each consecutive failure
+    pub initial_backoff: Duration,
+    /// Maximum delay between reconnect attempts
+    pub max_backoff: Duration,
+    /// Maximum number of consecutive reconnect attempts, after which the stream ends, or `None`
+    /// to retry forever
+    pub max_attempts: Option<u32>,
+    /// Number of orders to fetch per page when backfilling missed orders
+    pub backfill_page_size: u64,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            max_attempts: None,
+            backfill_page_size: 100,
+        }
+    }
+}
+
+impl ReconnectConfig {
+    /// Set the delay before the first reconnect attempt
+    pub fn with_initial_backoff(self, initial_backoff: Duration) -> Self {
+        Self { initial_backoff, ..self }
+    }
+
+    /// Set the maximum delay between reconnect attempts
+    pub fn with_max_backoff(self, max_backoff: Duration) -> Self {
+        Self { max_backoff, ..self }
+    }
+
+    /// Set the maximum number of consecutive reconnect attempts
+    pub fn with_max_attempts(self, max_attempts: u32) -> Self {
+        Self { max_attempts: Some(max_attempts), ..self }
+    }
+
+    /// Set the number of orders to fetch per page when backfilling
+    pub fn with_backfill_page_size(self, backfill_page_size: u64) -> Self {
+        Self { backfill_page_size, ..self }
+    }
+
+    /// Delay before the given reconnect attempt, starting at 1
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
 /// Stream of Order messages from a WebSocket
 ///
-/// This function takes a WebSocket stream and returns a stream of `Order` messages.
+/// This function takes a WebSocket stream and returns a stream of `Order` messages. Order
+/// cancellations are skipped, use [order_stream_events] to receive them.
 /// Example usage:
 /// ```no_run
 /// use alloy::signers::Signer;
@@ -391,8 +730,25 @@ impl Client {
 /// ```
 #[allow(clippy::type_complexity)]
 pub fn order_stream(
-    mut socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
 ) -> Pin<Box<dyn Stream<Item = Result<OrderData, Box<dyn Error + Send + Sync>>> + Send>> {
+    Box::pin(order_stream_events(socket).filter_map(|event| async move {
+        match event {
+            Ok(OrderStreamEvent::Order(order)) => Some(Ok(order)),
+            Ok(OrderStreamEvent::Cancellation { .. }) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }))
+}
+
+/// Stream of order stream events from a WebSocket
+///
+/// Like [order_stream], but also yields the cancellations of open orders. Cancellations should
+/// be verified with [CancelOrderMsg::verify] before being acted upon.
+#[allow(clippy::type_complexity)]
+pub fn order_stream_events(
+    mut socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+) -> Pin<Box<dyn Stream<Item = Result<OrderStreamEvent, Box<dyn Error + Send + Sync>>> + Send>> {
     Box::pin(stream! {
         // Create a ping interval - configurable via environment variable
         let ping_duration = match std::env::var("ORDER_STREAM_CLIENT_PING_MS") {
@@ -419,8 +775,8 @@ pub fn order_stream(
                 msg_result = socket.next() => {
                     match msg_result {
                         Some(Ok(tungstenite::Message::Text(msg))) => {
-                            match serde_json::from_str::<OrderData>(&msg) {
-                                Ok(order) => yield Ok(order),
+                            match serde_json::from_str::<OrderStreamEvent>(&msg) {
+                                Ok(event) => yield Ok(event),
                                 Err(err) => yield Err(Box::new(err) as Box<dyn Error + Send + Sync>),
                             }
                         }
@@ -491,6 +847,140 @@ pub fn order_stream(
     })
 }
 
+/// Number of order ids retained for deduplicating the orders of [reconnecting_order_stream_events]
+const SEEN_ORDERS_WINDOW: i64 = 10_000;
+
+/// Stream of order stream events that survives connection failures
+///
+/// Like [order_stream_events], but connects the socket itself, and reconnects with exponential
+/// backoff whenever the connection fails or closes. Each connection is authenticated with a
+/// fresh nonce.
+///
+/// The stream tracks the highest id of the orders it yielded as a cursor, starting from `cursor`
+/// if given. After each connect, orders missed while disconnected are backfilled through
+/// [Client::list_orders] before the new live orders. Since orders are not necessarily announced
+/// in id order, the backfill starts one page below the cursor, and the ids of yielded orders are
+/// tracked so that no order is yielded twice. Callers may persist the id of the last received
+/// order and pass it as `cursor` to resume after a restart, or pass `Some(0)` to backfill all
+/// open orders. Without a cursor, orders submitted before the first order is received are not
+/// backfilled.
+///
+/// Connection errors are logged rather than yielded, and the stream only ends once
+/// [ReconnectConfig::max_attempts] consecutive reconnect attempts failed.
+#[allow(clippy::type_complexity)]
+pub fn reconnecting_order_stream_events<S>(
+    client: Client,
+    signer: S,
+    cursor: Option<i64>,
+    config: ReconnectConfig,
+) -> Pin<Box<dyn Stream<Item = Result<OrderStreamEvent, Box<dyn Error + Send + Sync>>> + Send>>
+where
+    S: Signer + Send + Sync + 'static,
+{
+    Box::pin(stream! {
+        let mut cursor = cursor;
+        let mut seen = SeenOrders::default();
+        let mut attempt = 0u32;
+        'connect: loop {
+            if attempt > 0 {
+                if config.max_attempts.is_some_and(|max| attempt > max) {
+                    tracing::error!("Giving up on order stream after {attempt} failed attempts");
+                    break;
+                }
+                let backoff = config.backoff(attempt);
+                tracing::info!("Reconnecting to order stream in {backoff:?} (attempt {attempt})");
+                tokio::time::sleep(backoff).await;
+            }
+            attempt += 1;
+
+            let socket = match client.connect_async(&signer).await {
+                Ok(socket) => socket,
+                Err(err) => {
+                    tracing::warn!("Failed to connect to order stream: {err:?}");
+                    continue;
+                }
+            };
+
+            // Backfill after connecting, so that no order falls in between. Orders received
+            // both ways are deduplicated by their ids.
+            if let Some(start) = cursor {
+                let overlap = i64::try_from(config.backfill_page_size).unwrap_or(i64::MAX);
+                let mut offset = start.saturating_sub(overlap).max(0) + 1;
+                let mut backfilled = 0;
+                loop {
+                    let page = match client.list_orders(offset, config.backfill_page_size).await {
+                        Ok(page) => page,
+                        Err(err) => {
+                            // Retry rather than risk missing the orders of the gap.
+                            tracing::warn!("Failed to backfill orders from {offset}: {err:?}");
+                            continue 'connect;
+                        }
+                    };
+                    let done = (page.len() as u64) < config.backfill_page_size;
+                    for order in page {
+                        offset = offset.max(order.id + 1);
+                        if !seen.insert(order.id) {
+                            continue;
+                        }
+                        cursor = cursor.max(Some(order.id));
+                        backfilled += 1;
+                        yield Ok(OrderStreamEvent::Order(order));
+                    }
+                    if done {
+                        break;
+                    }
+                }
+                if backfilled > 0 {
+                    tracing::info!("Backfilled {backfilled} orders missed by the order stream");
+                }
+            }
+
+            let mut events = order_stream_events(socket);
+            while let Some(event) = events.next().await {
+                match event {
+                    Ok(OrderStreamEvent::Order(order)) => {
+                        // Only reset the backoff once the connection proved to be usable.
+                        attempt = 1;
+                        if !seen.insert(order.id) {
+                            tracing::debug!("Skipping already received order {}", order.id);
+                            continue;
+                        }
+                        cursor = cursor.max(Some(order.id));
+                        yield Ok(OrderStreamEvent::Order(order));
+                    }
+                    Ok(event) => {
+                        attempt = 1;
+                        yield Ok(event);
+                    }
+                    Err(err) => tracing::warn!("Order stream connection failed: {err:?}"),
+                }
+            }
+            tracing::warn!("Order stream connection closed");
+        }
+    })
+}
+
+/// Ids of the orders yielded by [reconnecting_order_stream_events], within a window below the
+/// highest id
+#[derive(Default)]
+struct SeenOrders(std::collections::BTreeSet<i64>);
+
+impl SeenOrders {
+    /// Records the id, returning false if it was already seen
+    fn insert(&mut self, id: i64) -> bool {
+        if !self.0.insert(id) {
+            return false;
+        }
+        if let Some(&max) = self.0.last() {
+            let min = max.saturating_sub(SEEN_ORDERS_WINDOW);
+            while self.0.first().is_some_and(|&first| first < min) {
+                self.0.pop_first();
+            }
+        }
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -505,6 +995,80 @@ mod tests {
         auth_msg.verify("localhost:8585", &nonce.nonce).await.unwrap();
     }
 
+    #[tokio::test]
+    async fn cancel_msg_verify() {
+        let signer = LocalSigner::random();
+        let other = LocalSigner::random();
+        let market = Address::repeat_byte(1);
+        let request_id = RequestId::u256(signer.address(), 1);
+        let digest = B256::repeat_byte(2);
+
+        let cancellation =
+            CancelOrderMsg::new(request_id, digest, &signer, market, 1).await.unwrap();
+        cancellation.verify(market, 1).unwrap();
+        cancellation.verify(market, 2).unwrap_err();
+
+        let forged = CancelOrderMsg::new(request_id, digest, &other, market, 1).await.unwrap();
+        forged.verify(market, 1).unwrap_err();
+
+        // Cancellations are distinguished from orders on the websocket.
+        let json = serde_json::to_string(&OrderStreamEvent::Cancellation {
+            cancellation: cancellation.clone(),
+        })
+        .unwrap();
+        let OrderStreamEvent::Cancellation { cancellation: parsed } =
+            serde_json::from_str(&json).unwrap()
+        else {
+            panic!("expected a cancellation");
+        };
+        assert_eq!(parsed, cancellation);
+    }
+
+    #[test]
+    fn reconnect_backoff() {
+        let config = ReconnectConfig::default()
+            .with_initial_backoff(Duration::from_secs(1))
+            .with_max_backoff(Duration::from_secs(10));
+        let backoffs: Vec<_> = (1..=6).map(|attempt| config.backoff(attempt).as_secs()).collect();
+        assert_eq!(backoffs, [1, 2, 4, 8, 10, 10]);
+        assert_eq!(config.backoff(u32::MAX), Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn list_orders() {
+        let server = httpmock::MockServer::start();
+        let list_mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path(ORDER_LIST_PATH)
+                .query_param("offset", "5")
+                .query_param("limit", "10");
+            then.status(200).json_body(serde_json::json!([]));
+        });
+
+        let client = Client::new(server.base_url().parse().unwrap(), Address::ZERO, 1);
+        assert!(client.list_orders(5, 10).await.unwrap().is_empty());
+        list_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn reconnect_gives_up() {
+        let server = httpmock::MockServer::start();
+        let nonce_mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path_contains(AUTH_GET_NONCE);
+            then.status(500);
+        });
+
+        let client = Client::new(server.base_url().parse().unwrap(), Address::ZERO, 1);
+        let config = ReconnectConfig::default()
+            .with_initial_backoff(Duration::from_millis(1))
+            .with_max_attempts(2);
+        let mut stream =
+            reconnecting_order_stream_events(client, LocalSigner::random(), Some(1), config);
+        assert!(stream.next().await.is_none());
+        // The initial attempt, and two retries, each with a fresh nonce.
+        nonce_mock.assert_hits(3);
+    }
+
     #[tokio::test]
     #[should_panic(expected = "Message domain does not match")]
     async fn auth_msg_bad_origin() {
@@ -524,4 +1088,18 @@ mod tests {
         let auth_msg = AuthMsg::new(nonce.clone(), &origin, &signer).await.unwrap();
         auth_msg.verify("localhost:8585", "BAD_NONCE").await.unwrap();
     }
+
+    #[test]
+    fn seen_orders() {
+        let mut seen = SeenOrders::default();
+        assert!(seen.insert(11));
+        // Orders announced out of id order are not dropped.
+        assert!(seen.insert(10));
+        assert!(!seen.insert(11));
+        assert!(!seen.insert(10));
+
+        // Ids far below the highest one are forgotten.
+        assert!(seen.insert(11 + SEEN_ORDERS_WINDOW + 1));
+        assert!(seen.insert(10));
+    }
 }
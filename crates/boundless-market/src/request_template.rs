@@ -0,0 +1,972 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use alloy::{
+    network::Ethereum,
+    primitives::{
+        aliases::U96,
+        utils::{format_ether, parse_ether},
+        Address, Bytes, B256, U256,
+    },
+    providers::Provider,
+};
+use clap::ValueEnum;
+use risc0_zkvm::{compute_image_id, default_executor, sha::Digestible, Digest};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::{
+    client::{Client, ClientError},
+    clock::Clock,
+    contracts::{Callback, Input, Offer, Predicate, ProofRequest, RequestError, Requirements},
+    input::{GuestEnv, InputBuilder},
+    selector::ProofType,
+    storage::StorageProvider,
+};
+
+/// Maximum depth of nested includes, which also bounds include cycles.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Sections and fields of the template holding paths, which are resolved relative to the
+/// template file that defines them.
+const PATH_FIELDS: [(&str, &str); 2] = [("image", "path"), ("input", "file")];
+
+/// Default ramp up period, used if the template does not set one.
+const DEFAULT_RAMP_UP_PERIOD: Duration = Duration::from_secs(240);
+/// Default lock timeout, used if the template does not set one.
+const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(900);
+/// Default timeout, used if the template does not set one.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1800);
+
+/// Errors that can occur when loading or building a [RequestTemplate].
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum TemplateError {
+    /// Failed to read a file referenced by the template.
+    #[error("failed to read {0}: {1}")]
+    Io(PathBuf, #[source] std::io::Error),
+
+    /// Failed to parse a template file.
+    #[error("failed to parse {0}: {1}")]
+    Parse(PathBuf, String),
+
+    /// The template file extension is not one of `yaml`, `yml`, `json` or `toml`.
+    #[error("unsupported template format: {0}")]
+    UnsupportedFormat(PathBuf),
+
+    /// The includes are nested too deeply, which is usually caused by an include cycle.
+    #[error("template includes are nested too deeply at {0}; is there an include cycle?")]
+    IncludeDepth(PathBuf),
+
+    /// A `${name}` reference to a variable that is not defined.
+    #[error("undefined template variable: {0}")]
+    UndefinedVariable(String),
+
+    /// The template is well-formed, but its values are invalid or inconsistent.
+    #[error("invalid template: {0}")]
+    Invalid(String),
+
+    /// Failed to build the guest input.
+    #[error("input error: {0}")]
+    Input(#[from] crate::input::Error),
+
+    /// Failed to execute the guest, which is needed to price the request or compute the journal.
+    #[error("failed to execute guest: {0}")]
+    Execution(#[source] anyhow::Error),
+
+    /// Failed to upload the image or input.
+    #[error("client error: {0}")]
+    Client(#[from] ClientError),
+
+    /// The resulting request is invalid.
+    #[error("invalid request: {0}")]
+    Request(#[from] RequestError),
+}
+
+/// A typed template for a [ProofRequest], loaded from a YAML, JSON or TOML file.
+///
+/// Unlike a serialized [ProofRequest], a template refers to the image and input by path, gives
+/// prices in ether (optionally per mcycle, in which case the guest is executed to count cycles)
+/// and timeouts as human readable durations (e.g. `15m` or `1h30m`).
+///
+/// Templates may `include` other templates, relative to the including file, whose fields act as
+/// defaults. String values may reference `${name}` variables, defined in the `variables` table of
+/// the template (or its includes) or given when loading the template.
+///
+/// ```yaml
+/// include: [defaults.yaml]
+/// variables:
+///   guest: echo
+/// image:
+///   path: target/guests/${guest}
+/// input:
+///   text: hello
+/// predicate:
+///   type: prefix_match
+///   prefix: "0x68656c6c6f"
+/// offer:
+///   min_price_per_mcycle: 0.001
+///   max_price_per_mcycle: 0.002
+///   lock_timeout: 15m
+///   timeout: 30m
+/// ```
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+pub struct RequestTemplate {
+    /// Template files to include. Fields set in the including template take precedence.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<PathBuf>,
+    /// Variables to substitute for `${name}` in the string values of the template.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+    /// The guest image.
+    #[serde(default)]
+    pub image: ImageTemplate,
+    /// The guest input.
+    #[serde(default)]
+    pub input: InputTemplate,
+    /// The predicate on the journal. Defaults to the digest of the journal from executing the guest.
+    #[serde(default)]
+    pub predicate: PredicateTemplate,
+    /// Optional callback to call on fulfillment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub callback: Option<CallbackTemplate>,
+    /// Proof type to request, e.g. `any` or `groth16`. Defaults to `any`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof_type: Option<String>,
+    /// The offer.
+    #[serde(default)]
+    pub offer: OfferTemplate,
+
+    /// Directory relative to which the paths in the template are resolved.
+    #[serde(skip)]
+    base_dir: PathBuf,
+}
+
+/// The guest image of a [RequestTemplate].
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+pub struct ImageTemplate {
+    /// Path to the guest ELF, relative to the template file defining it. The image is uploaded
+    /// with the client storage provider, unless a URL is given.
+    pub path: Option<PathBuf>,
+    /// URL of the guest ELF. Requires either the path or the ID to be set.
+    pub url: Option<String>,
+    /// Image ID of the guest. Computed from the ELF if a path is given.
+    pub id: Option<B256>,
+}
+
+/// The guest input of a [RequestTemplate]. At most one of `file`, `hex` and `text` may be set.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+pub struct InputTemplate {
+    /// Path to a file with the input bytes, relative to the template file defining it.
+    pub file: Option<PathBuf>,
+    /// Input bytes, hex encoded.
+    pub hex: Option<Bytes>,
+    /// Input bytes, as a UTF-8 string.
+    pub text: Option<String>,
+    /// Encode the input with the RISC Zero serde codec, rather than writing it as is.
+    #[serde(default)]
+    pub encode: bool,
+    /// Include the input inline in the request, rather than uploading it.
+    #[serde(default)]
+    pub inline: bool,
+}
+
+/// The predicate of a [RequestTemplate].
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+#[non_exhaustive]
+pub enum PredicateTemplate {
+    /// Match the digest of the journal produced by executing the guest with the input.
+    #[default]
+    ExecutionDigest,
+    /// Match the given journal digest.
+    DigestMatch {
+        /// The journal digest.
+        digest: B256,
+    },
+    /// Match the given journal prefix.
+    PrefixMatch {
+        /// The journal prefix, hex encoded.
+        prefix: Bytes,
+    },
+    /// Match the bytes of the journal at the given offset against the value, in the bits set in
+    /// the mask.
+    MaskedMatch {
+        /// Offset of the matched bytes in the journal.
+        offset: u32,
+        /// The mask, hex encoded, of the same length as the value.
+        mask: Bytes,
+        /// The value, hex encoded.
+        value: Bytes,
+    },
+    /// Match any one of the given journal digests.
+    AnyDigestMatch {
+        /// The journal digests.
+        digests: Vec<B256>,
+    },
+    /// Bound the length of the journal, inclusive of both bounds.
+    LengthRange {
+        /// Minimum journal length, in bytes.
+        min_length: u32,
+        /// Maximum journal length, in bytes.
+        max_length: u32,
+    },
+}
+
+/// The callback of a [RequestTemplate].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+pub struct CallbackTemplate {
+    /// Address of the callback contract.
+    pub address: Address,
+    /// Gas limit of the callback.
+    pub gas_limit: u64,
+}
+
+/// The offer of a [RequestTemplate].
+///
+/// Each of the min and max price must be given either as a total or per mcycle.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+pub struct OfferTemplate {
+    /// Minimum price, in ether.
+    pub min_price: Option<EtherAmount>,
+    /// Maximum price, in ether.
+    pub max_price: Option<EtherAmount>,
+    /// Minimum price per mcycle, in ether.
+    pub min_price_per_mcycle: Option<EtherAmount>,
+    /// Maximum price per mcycle, in ether.
+    pub max_price_per_mcycle: Option<EtherAmount>,
+    /// Lock stake, in ether. Defaults to zero.
+    pub lock_stake: Option<EtherAmount>,
+    /// Delay from the time the request is built to the start of bidding. If not set, the client
+    /// bidding start delay applies when the request is submitted.
+    pub bidding_start_delay: Option<HumanDuration>,
+    /// Ramp up period. Defaults to 4 minutes.
+    pub ramp_up_period: Option<HumanDuration>,
+    /// Lock timeout. Defaults to 15 minutes.
+    pub lock_timeout: Option<HumanDuration>,
+    /// Timeout. Defaults to 30 minutes.
+    pub timeout: Option<HumanDuration>,
+}
+
+/// An amount of ether, given in a template as a decimal string or number (e.g. `0.001`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EtherAmount(pub U256);
+
+/// A duration, given in a template as a number of seconds or a string such as `90s`, `15m`,
+/// `1h30m` or `2d`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HumanDuration(pub Duration);
+
+/// Template values that may be given either as a string or as a number.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrNumber {
+    String(String),
+    Number(serde_json::Number),
+}
+
+impl fmt::Display for StringOrNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StringOrNumber::String(s) => s.fmt(f),
+            StringOrNumber::Number(n) => n.fmt(f),
+        }
+    }
+}
+
+impl FromStr for EtherAmount {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_ether(s.trim()).map(Self).map_err(|err| format!("invalid ether amount {s:?}: {err}"))
+    }
+}
+
+impl<'de> Deserialize<'de> for EtherAmount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        StringOrNumber::deserialize(deserializer)?
+            .to_string()
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for EtherAmount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format_ether(self.0))
+    }
+}
+
+impl FromStr for HumanDuration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid duration {s:?}");
+        if let Ok(secs) = s.trim().parse::<u64>() {
+            return Ok(Self(Duration::from_secs(secs)));
+        }
+
+        let mut total = 0u64;
+        let mut parsed = false;
+        let mut digits = String::new();
+        for c in s.chars().filter(|c| !c.is_whitespace()) {
+            if c.is_ascii_digit() {
+                digits.push(c);
+                continue;
+            }
+            let unit = match c {
+                's' => 1,
+                'm' => 60,
+                'h' => 60 * 60,
+                'd' => 24 * 60 * 60,
+                _ => return Err(invalid()),
+            };
+            let value: u64 = digits.parse().map_err(|_| invalid())?;
+            total =
+                value.checked_mul(unit).and_then(|v| total.checked_add(v)).ok_or_else(invalid)?;
+            digits.clear();
+            parsed = true;
+        }
+        if !digits.is_empty() || !parsed {
+            return Err(invalid());
+        }
+        Ok(Self(Duration::from_secs(total)))
+    }
+}
+
+impl<'de> Deserialize<'de> for HumanDuration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        StringOrNumber::deserialize(deserializer)?
+            .to_string()
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for HumanDuration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{}s", self.0.as_secs()))
+    }
+}
+
+impl HumanDuration {
+    fn as_secs_u32(&self, field: &str) -> Result<u32, TemplateError> {
+        u32::try_from(self.0.as_secs())
+            .map_err(|_| TemplateError::Invalid(format!("offer {field} is too long")))
+    }
+}
+
+impl RequestTemplate {
+    /// Loads the template at the given path, resolving its includes and variables.
+    ///
+    /// The format is determined from the file extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TemplateError> {
+        Self::load_with_variables(path, &BTreeMap::new())
+    }
+
+    /// Loads the template at the given path, with the given variables taking precedence over the
+    /// ones defined in the template.
+    pub fn load_with_variables(
+        path: impl AsRef<Path>,
+        variables: &BTreeMap<String, String>,
+    ) -> Result<Self, TemplateError> {
+        let path = path.as_ref();
+        let mut path_dirs = BTreeMap::new();
+        let mut value = load_value(path, 0, &mut path_dirs)?;
+
+        let mut all_variables = BTreeMap::new();
+        if let Some(Value::Object(defined)) = value.get("variables") {
+            for (name, var) in defined {
+                let var = match var {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                all_variables.insert(name.clone(), var);
+            }
+        }
+        all_variables.extend(variables.clone());
+        if let Value::Object(map) = &mut value {
+            for (key, field) in map.iter_mut().filter(|(key, _)| *key != "variables") {
+                substitute(field, &all_variables).map_err(|err| match err {
+                    TemplateError::UndefinedVariable(name) => {
+                        TemplateError::UndefinedVariable(format!("{name} (in {key})"))
+                    }
+                    err => err,
+                })?;
+            }
+            map.insert(
+                "variables".to_string(),
+                serde_json::to_value(&all_variables).expect("variables are valid json"),
+            );
+        }
+        for ((section, field), dir) in path_dirs {
+            if let Some(Value::String(field_path)) =
+                value.get_mut(section).and_then(|section| section.get_mut(field))
+            {
+                *field_path = dir.join(&*field_path).to_string_lossy().into_owned();
+            }
+        }
+
+        serde_json::from_value(value)
+            .map_err(|err| TemplateError::Parse(path.to_path_buf(), err.to_string()))
+    }
+
+    /// Sets the directory relative to which relative image and input paths are resolved.
+    ///
+    /// The paths of a loaded template are already resolved relative to the template file that
+    /// defines them, so this only applies to relative paths set after loading.
+    pub fn with_base_dir(self, base_dir: impl Into<PathBuf>) -> Self {
+        Self { base_dir: base_dir.into(), ..self }
+    }
+
+    fn resolve(&self, path: &Path) -> PathBuf {
+        self.base_dir.join(path)
+    }
+
+    fn read(&self, path: &Path) -> Result<Vec<u8>, TemplateError> {
+        let path = self.resolve(path);
+        std::fs::read(&path).map_err(|err| TemplateError::Io(path, err))
+    }
+
+    /// Builds the [GuestEnv] described by the input section of the template.
+    pub fn build_env(&self) -> Result<GuestEnv, TemplateError> {
+        let input = &self.input;
+        let data = match (&input.file, &input.hex, &input.text) {
+            (Some(file), None, None) => self.read(file)?,
+            (None, Some(hex), None) => hex.to_vec(),
+            (None, None, Some(text)) => text.as_bytes().to_vec(),
+            (None, None, None) => Vec::new(),
+            _ => {
+                return Err(TemplateError::Invalid(
+                    "at most one of input file, hex and text may be set".into(),
+                ))
+            }
+        };
+        let builder = match input.encode {
+            true => InputBuilder::new().write(&data)?,
+            false => InputBuilder::new().write_slice(&data),
+        };
+        Ok(builder.build_env()?)
+    }
+
+    /// Builds the [Offer] described by the template, given the cycle count of the guest if any
+    /// of the prices are given per mcycle.
+    ///
+    /// The bidding start, if the template sets a delay, is computed from the given clock.
+    pub fn build_offer(
+        &self,
+        cycles: Option<u64>,
+        clock: &dyn Clock,
+    ) -> Result<Offer, TemplateError> {
+        let offer = &self.offer;
+        let price = |total: Option<EtherAmount>, per_mcycle: Option<EtherAmount>, name: &str| match (
+            total, per_mcycle, cycles,
+        ) {
+            (Some(total), None, _) => Ok(total.0),
+            (None, Some(per_mcycle), Some(cycles)) => {
+                Ok((per_mcycle.0 * U256::from(cycles)).div_ceil(U256::from(1_000_000)))
+            }
+            (None, Some(_), None) => Err(TemplateError::Invalid(format!(
+                "offer {name}_per_mcycle requires the cycle count of the guest"
+            ))),
+            (Some(_), Some(_), _) => Err(TemplateError::Invalid(format!(
+                "only one of offer {name} and {name}_per_mcycle may be set"
+            ))),
+            (None, None, _) => Err(TemplateError::Invalid(format!(
+                "one of offer {name} or {name}_per_mcycle must be set"
+            ))),
+        };
+
+        let bidding_start = match offer.bidding_start_delay {
+            Some(delay) => clock.now() + delay.0.as_secs(),
+            None => 0,
+        };
+        let duration = |value: Option<HumanDuration>, default: Duration, name: &str| {
+            value.unwrap_or(HumanDuration(default)).as_secs_u32(name)
+        };
+
+        Ok(Offer {
+            minPrice: price(offer.min_price, offer.min_price_per_mcycle, "min_price")?,
+            maxPrice: price(offer.max_price, offer.max_price_per_mcycle, "max_price")?,
+            biddingStart: bidding_start,
+            rampUpPeriod: duration(offer.ramp_up_period, DEFAULT_RAMP_UP_PERIOD, "ramp_up_period")?,
+            lockTimeout: duration(offer.lock_timeout, DEFAULT_LOCK_TIMEOUT, "lock_timeout")?,
+            timeout: duration(offer.timeout, DEFAULT_TIMEOUT, "timeout")?,
+            lockStake: offer.lock_stake.unwrap_or_default().0,
+        })
+    }
+
+    fn needs_execution(&self) -> bool {
+        matches!(self.predicate, PredicateTemplate::ExecutionDigest)
+            || self.offer.min_price_per_mcycle.is_some()
+            || self.offer.max_price_per_mcycle.is_some()
+    }
+
+    /// Builds the [ProofRequest] described by the template, uploading the image and input with
+    /// the storage provider of the given client as needed.
+    ///
+    /// If the request ID is not set, or the bidding start delay is not set in the template, they
+    /// are filled in by the client on submission. All other fields are checked with
+    /// [ProofRequest::validate].
+    pub async fn build_request<P, S>(
+        &self,
+        client: &Client<P, S>,
+    ) -> Result<ProofRequest, TemplateError>
+    where
+        P: Provider<Ethereum> + 'static + Clone,
+        S: StorageProvider,
+    {
+        let elf = self.image.path.as_deref().map(|path| self.read(path)).transpose()?;
+        let image_id = match (&elf, self.image.id) {
+            (Some(elf), id) => {
+                let computed = B256::from(<[u8; 32]>::from(
+                    compute_image_id(elf).map_err(TemplateError::Execution)?,
+                ));
+                if id.is_some_and(|id| id != computed) {
+                    return Err(TemplateError::Invalid(format!(
+                        "image id does not match the image at the given path ({computed})"
+                    )));
+                }
+                computed
+            }
+            (None, Some(id)) => id,
+            (None, None) => {
+                return Err(TemplateError::Invalid("one of image path or id must be set".into()))
+            }
+        };
+        let image_url = match (&self.image.url, &elf) {
+            (Some(url), _) => url.clone(),
+            (None, Some(elf)) => client.upload_image(elf).await?.to_string(),
+            (None, None) => {
+                return Err(TemplateError::Invalid("one of image path or url must be set".into()))
+            }
+        };
+
+        let env = self.build_env()?;
+        let session = match self.needs_execution() {
+            true => {
+                let elf = elf.as_ref().ok_or_else(|| {
+                    TemplateError::Invalid(
+                        "image path must be set to execute the guest for the predicate or price"
+                            .into(),
+                    )
+                })?;
+                let (env, elf) = (env.clone(), elf.clone());
+                // Executing the guest can take a while, so keep it off the async runtime.
+                let session = tokio::task::spawn_blocking(move || {
+                    default_executor().execute(env.try_into()?, &elf)
+                })
+                .await
+                .map_err(|err| TemplateError::Execution(err.into()))?
+                .map_err(TemplateError::Execution)?;
+                Some(session)
+            }
+            false => None,
+        };
+
+        let predicate = match (&self.predicate, &session) {
+            (PredicateTemplate::ExecutionDigest, Some(session)) => {
+                Predicate::digest_match(session.journal.digest())
+            }
+            (PredicateTemplate::ExecutionDigest, None) => unreachable!("guest was not executed"),
+            (PredicateTemplate::DigestMatch { digest }, _) => Predicate::digest_match(digest.0),
+            (PredicateTemplate::PrefixMatch { prefix }, _) => {
+                Predicate::prefix_match(prefix.clone())
+            }
+            (PredicateTemplate::MaskedMatch { offset, mask, value }, _) => {
+                if mask.len() != value.len() {
+                    return Err(TemplateError::Invalid(
+                        "predicate mask and value must be of the same length".into(),
+                    ));
+                }
+                Predicate::masked_match(*offset, mask, value)
+            }
+            (PredicateTemplate::AnyDigestMatch { digests }, _) => {
+                if digests.is_empty() {
+                    return Err(TemplateError::Invalid(
+                        "predicate digests must not be empty".into(),
+                    ));
+                }
+                Predicate::any_digest_match(digests.iter().map(|digest| digest.0))
+            }
+            (PredicateTemplate::LengthRange { min_length, max_length }, _) => {
+                if min_length > max_length {
+                    return Err(TemplateError::Invalid(
+                        "predicate min_length must not exceed max_length".into(),
+                    ));
+                }
+                Predicate::length_range(*min_length, *max_length)
+            }
+        };
+        let cycles = session
+            .as_ref()
+            .map(|session| session.segments.iter().map(|segment| 1 << segment.po2).sum::<u64>());
+        let offer = self.build_offer(cycles, client.clock.as_ref())?;
+
+        let mut requirements = Requirements::new(Digest::from_bytes(image_id.0), predicate);
+        if let Some(callback) = &self.callback {
+            requirements = requirements.with_callback(Callback {
+                addr: callback.address,
+                gasLimit: U96::from(callback.gas_limit),
+            });
+        }
+        if let Some(proof_type) = &self.proof_type {
+            let proof_type = ProofType::from_str(proof_type, true)
+                .map_err(|err| TemplateError::Invalid(format!("invalid proof type: {err}")))?;
+            if proof_type == ProofType::Groth16 {
+                requirements = requirements.with_groth16_proof();
+            }
+        }
+
+        let encoded = env.encode()?;
+        let input = match self.input.inline {
+            true => Input::inline(encoded),
+            false => Input::url(client.upload_input(&encoded).await?),
+        };
+
+        let request = ProofRequest::builder()
+            .with_image_url(image_url)
+            .with_input(input)
+            .with_requirements(requirements)
+            .with_offer(offer)
+            .build()?;
+
+        // The bidding start may be left to the client, so only check the rest of the request.
+        let mut check = request.clone();
+        if check.offer.biddingStart == 0 {
+            check.offer.biddingStart = client.clock.now();
+        }
+        check.validate()?;
+
+        Ok(request)
+    }
+}
+
+/// Parses the template file at the given path, and merges in its includes.
+///
+/// Records in `path_dirs` the directory of the file defining each of the [PATH_FIELDS] that is
+/// set in the merged template.
+fn load_value(
+    path: &Path,
+    depth: usize,
+    path_dirs: &mut BTreeMap<(&'static str, &'static str), PathBuf>,
+) -> Result<Value, TemplateError> {
+    if depth > MAX_INCLUDE_DEPTH {
+        return Err(TemplateError::IncludeDepth(path.to_path_buf()));
+    }
+    let contents =
+        std::fs::read_to_string(path).map_err(|err| TemplateError::Io(path.to_path_buf(), err))?;
+    let parse_err = |err: String| TemplateError::Parse(path.to_path_buf(), err);
+    let mut value: Value = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("yaml" | "yml") => {
+            serde_yaml::from_str(&contents).map_err(|e| parse_err(e.to_string()))?
+        }
+        Some("json") => serde_json::from_str(&contents).map_err(|e| parse_err(e.to_string()))?,
+        Some("toml") => toml::from_str(&contents).map_err(|e| parse_err(e.to_string()))?,
+        _ => return Err(TemplateError::UnsupportedFormat(path.to_path_buf())),
+    };
+
+    let includes: Vec<PathBuf> = match value.as_object_mut().and_then(|map| map.remove("include")) {
+        Some(includes) => serde_json::from_value(includes)
+            .map_err(|err| parse_err(format!("invalid include list: {err}")))?,
+        None => Vec::new(),
+    };
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut merged = Value::Object(Default::default());
+    for include in includes {
+        merge(&mut merged, load_value(&dir.join(include), depth + 1, path_dirs)?);
+    }
+    for (section, field) in PATH_FIELDS {
+        if value.get(section).and_then(|section| section.get(field)).is_some() {
+            path_dirs.insert((section, field), dir.to_path_buf());
+        }
+    }
+    merge(&mut merged, value);
+    Ok(merged)
+}
+
+/// Deep merges `over` into `base`, with the values in `over` taking precedence.
+fn merge(base: &mut Value, over: Value) {
+    match (base, over) {
+        (Value::Object(base), Value::Object(over)) => {
+            for (key, value) in over {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, over) => *base = over,
+    }
+}
+
+/// Substitutes `${name}` references in all the strings of the given value.
+fn substitute(
+    value: &mut Value,
+    variables: &BTreeMap<String, String>,
+) -> Result<(), TemplateError> {
+    match value {
+        Value::String(s) => {
+            let mut result = String::with_capacity(s.len());
+            let mut rest = s.as_str();
+            while let Some(start) = rest.find("${") {
+                let end = rest[start..].find('}').ok_or_else(|| {
+                    TemplateError::Invalid(format!("unterminated variable in {s:?}"))
+                })?;
+                let name = &rest[start + 2..start + end];
+                let var = variables
+                    .get(name)
+                    .ok_or_else(|| TemplateError::UndefinedVariable(name.to_string()))?;
+                result.push_str(&rest[..start]);
+                result.push_str(var);
+                rest = &rest[start + end + 1..];
+            }
+            result.push_str(rest);
+            *s = result;
+        }
+        Value::Array(values) => {
+            for value in values {
+                substitute(value, variables)?;
+            }
+        }
+        Value::Object(map) => {
+            for value in map.values_mut() {
+                substitute(value, variables)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FakeClock, SystemClock};
+
+    fn write(dir: &Path, name: &str, contents: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn human_duration() {
+        let parse = |s: &str| s.parse::<HumanDuration>().map(|d| d.0.as_secs());
+        assert_eq!(parse("300"), Ok(300));
+        assert_eq!(parse("90s"), Ok(90));
+        assert_eq!(parse("15m"), Ok(900));
+        assert_eq!(parse("1h30m"), Ok(5400));
+        assert_eq!(parse("2d"), Ok(172800));
+        assert!(parse("").is_err());
+        assert!(parse("10").is_ok());
+        assert!(parse("10x").is_err());
+        assert!(parse("1h30").is_err());
+    }
+
+    #[test]
+    fn load_yaml_with_includes_and_variables() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "defaults.toml",
+            r#"
+            [variables]
+            greeting = "hello"
+
+            [offer]
+            min_price = "0.001"
+            max_price = 0.002
+            lock_timeout = "10m"
+            "#,
+        );
+        let path = write(
+            dir.path(),
+            "request.yaml",
+            r#"
+include: [defaults.toml]
+variables:
+  name: world
+image:
+  id: "0x0101010101010101010101010101010101010101010101010101010101010101"
+  url: https://example.com/${name}.elf
+input:
+  text: ${greeting} ${name}
+  inline: true
+predicate:
+  type: prefix_match
+  prefix: "0x68656c6c6f"
+offer:
+  max_price: 0.003
+  timeout: 1h
+"#,
+        );
+
+        let template = RequestTemplate::load(&path).unwrap();
+        assert_eq!(template.image.url.as_deref(), Some("https://example.com/world.elf"));
+        assert_eq!(template.input.text.as_deref(), Some("hello world"));
+        assert_eq!(template.build_env().unwrap().stdin, b"hello world");
+
+        let offer = template.build_offer(None, &SystemClock).unwrap();
+        assert_eq!(offer.minPrice, parse_ether("0.001").unwrap());
+        assert_eq!(offer.maxPrice, parse_ether("0.003").unwrap());
+        assert_eq!(offer.lockTimeout, 600);
+        assert_eq!(offer.timeout, 3600);
+        assert_eq!(offer.rampUpPeriod, DEFAULT_RAMP_UP_PERIOD.as_secs() as u32);
+        assert_eq!(offer.biddingStart, 0);
+
+        let mut delayed = template.clone();
+        delayed.offer.bidding_start_delay = Some(HumanDuration(Duration::from_secs(30)));
+        let offer = delayed.build_offer(None, &FakeClock::new(1_000)).unwrap();
+        assert_eq!(offer.biddingStart, 1_030);
+
+        // Variables given on load take precedence.
+        let vars = BTreeMap::from([("name".to_string(), "there".to_string())]);
+        let template = RequestTemplate::load_with_variables(&path, &vars).unwrap();
+        assert_eq!(template.input.text.as_deref(), Some("hello there"));
+    }
+
+    #[test]
+    fn load_json_per_mcycle_offer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            dir.path(),
+            "request.json",
+            r#"{
+                "image": { "path": "guest.elf" },
+                "offer": { "min_price_per_mcycle": "0.001", "max_price_per_mcycle": "0.002" }
+            }"#,
+        );
+        let template = RequestTemplate::load(&path).unwrap();
+        assert!(template.needs_execution());
+        assert!(matches!(template.build_offer(None, &SystemClock), Err(TemplateError::Invalid(_))));
+
+        let offer = template.build_offer(Some(2_000_000), &SystemClock).unwrap();
+        assert_eq!(offer.minPrice, parse_ether("0.002").unwrap());
+        assert_eq!(offer.maxPrice, parse_ether("0.004").unwrap());
+    }
+
+    #[test]
+    fn paths_relative_to_defining_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("common")).unwrap();
+        write(&dir.path().join("common"), "input.bin", "from common");
+        write(
+            &dir.path().join("common"),
+            "defaults.yaml",
+            "image:\n  path: guest.elf\ninput:\n  file: input.bin\n",
+        );
+        let path = write(dir.path(), "request.yaml", "include: [common/defaults.yaml]\n");
+
+        let template = RequestTemplate::load(&path).unwrap();
+        assert_eq!(template.image.path, Some(dir.path().join("common").join("guest.elf")));
+        assert_eq!(template.build_env().unwrap().stdin, b"from common");
+
+        // A path set in the including file is relative to it.
+        write(dir.path(), "input.bin", "from request");
+        let path = write(
+            dir.path(),
+            "override.yaml",
+            "include: [common/defaults.yaml]\ninput:\n  file: input.bin\n",
+        );
+        let template = RequestTemplate::load(&path).unwrap();
+        assert_eq!(template.image.path, Some(dir.path().join("common").join("guest.elf")));
+        assert_eq!(template.build_env().unwrap().stdin, b"from request");
+    }
+
+    #[test]
+    fn load_predicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            dir.path(),
+            "request.yaml",
+            r#"
+predicate:
+  type: masked_match
+  offset: 4
+  mask: "0xff00"
+  value: "0x4100"
+"#,
+        );
+        let template = RequestTemplate::load(&path).unwrap();
+        assert!(matches!(
+            template.predicate,
+            PredicateTemplate::MaskedMatch { offset: 4, ref mask, ref value }
+                if mask.as_ref() == [0xff, 0x00] && value.as_ref() == [0x41, 0x00]
+        ));
+
+        let path = write(
+            dir.path(),
+            "any.toml",
+            r#"
+            [predicate]
+            type = "any_digest_match"
+            digests = ["0x0101010101010101010101010101010101010101010101010101010101010101"]
+            "#,
+        );
+        let template = RequestTemplate::load(&path).unwrap();
+        assert!(matches!(
+            template.predicate,
+            PredicateTemplate::AnyDigestMatch { ref digests } if digests.len() == 1
+        ));
+
+        let path = write(
+            dir.path(),
+            "length.json",
+            r#"{ "predicate": { "type": "length_range", "min_length": 1, "max_length": 64 } }"#,
+        );
+        let template = RequestTemplate::load(&path).unwrap();
+        assert!(matches!(
+            template.predicate,
+            PredicateTemplate::LengthRange { min_length: 1, max_length: 64 }
+        ));
+    }
+
+    #[test]
+    fn load_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(dir.path(), "a.yaml", "include: [b.yaml]\n");
+        write(dir.path(), "b.yaml", "include: [a.yaml]\n");
+        assert!(matches!(RequestTemplate::load(&path), Err(TemplateError::IncludeDepth(_))));
+
+        let path = write(dir.path(), "undefined.yaml", "input:\n  text: ${missing}\n");
+        assert!(matches!(RequestTemplate::load(&path), Err(TemplateError::UndefinedVariable(_))));
+
+        let path = write(dir.path(), "unknown.yaml", "imageUrl: https://example.com\n");
+        assert!(matches!(RequestTemplate::load(&path), Err(TemplateError::Parse(..))));
+
+        let path = write(dir.path(), "request.txt", "");
+        assert!(matches!(RequestTemplate::load(&path), Err(TemplateError::UnsupportedFormat(_))));
+    }
+}
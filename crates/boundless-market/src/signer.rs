@@ -0,0 +1,627 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Signers for requests and transactions, configured from a URI.
+//!
+//! The signing methods of the SDK accept any alloy [Signer], so that requests can be signed with
+//! keys that never leave a KMS or a remote signing service. Binaries configured from the command
+//! line or the environment describe where the key lives with a [SignerUri]:
+//!
+//! - a hex encoded private key, e.g. `0xac09...`;
+//! - an encrypted JSON keystore, with its password read from a file or an environment variable,
+//!   e.g. `keystore:/path/to/key.json?password-file=/path/to/password` or
+//!   `keystore:key.json?password-env=KEYSTORE_PASSWORD`;
+//! - a [RemoteSigner] served over HTTPS, with an optional bearer token read from a file or an
+//!   environment variable, e.g. `https://signer.example.com/keys/1?token-env=SIGNER_TOKEN`. Plain
+//!   HTTP is only accepted for loopback hosts;
+//! - an AWS KMS key, e.g. `aws-kms:alias/boundless`, using the AWS credentials of the
+//!   environment. Requires the `aws-kms` feature.
+
+use std::{path::PathBuf, str::FromStr, time::Duration};
+
+use alloy::{
+    consensus::SignableTransaction,
+    network::TxSigner,
+    primitives::{Address, Bytes, ChainId, PrimitiveSignature, B256},
+    signers::{
+        local::{LocalSignerError, PrivateKeySigner},
+        Signer,
+    },
+};
+use async_trait::async_trait;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use url::{Host, Url};
+
+/// Timeout of connecting to a [RemoteSigner].
+const REMOTE_SIGNER_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Timeout of a request to a [RemoteSigner], including connecting.
+const REMOTE_SIGNER_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Errors that can occur when configuring a signer.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum SignerError {
+    /// The signer URI is malformed.
+    #[error("invalid signer URI: {0}")]
+    InvalidUri(String),
+
+    /// The environment variable holding a keystore password or token is not set.
+    #[error("secret variable {0} not set")]
+    MissingSecret(String),
+
+    /// The keystore could not be decrypted.
+    #[error("keystore error {0}")]
+    KeystoreError(#[from] LocalSignerError),
+
+    /// IO error.
+    #[error("IO error {0}")]
+    IoError(#[from] std::io::Error),
+
+    /// HTTP error.
+    #[error("HTTP error {0}")]
+    HttpError(#[from] reqwest::Error),
+
+    /// The signer is not supported by this build.
+    #[error("{0} signers require the {1} feature")]
+    Unsupported(&'static str, &'static str),
+
+    /// AWS KMS error.
+    #[cfg(feature = "aws-kms")]
+    #[error("AWS KMS error {0}")]
+    AwsKmsError(#[from] alloy::signers::aws::AwsSignerError),
+}
+
+/// Source of a secret, such as the password of a keystore or the token of a remote signer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SecretSource {
+    /// Read from a file, ignoring a trailing newline.
+    File(PathBuf),
+    /// Read from an environment variable.
+    Env(String),
+}
+
+impl SecretSource {
+    /// Reads the secret.
+    pub async fn read(&self) -> Result<String, SignerError> {
+        match self {
+            Self::File(path) => {
+                let secret = tokio::fs::read_to_string(path).await?;
+                Ok(secret.trim_end_matches(['\n', '\r']).to_string())
+            }
+            Self::Env(var) => {
+                std::env::var(var).map_err(|_| SignerError::MissingSecret(var.clone()))
+            }
+        }
+    }
+}
+
+/// Location of a signing key.
+///
+/// See the [module documentation](self) for the supported formats.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum SignerUri {
+    /// A raw private key.
+    PrivateKey(PrivateKeySigner),
+    /// An encrypted JSON keystore file.
+    Keystore {
+        /// Path of the keystore file.
+        path: PathBuf,
+        /// Source of the password.
+        password: SecretSource,
+    },
+    /// A [RemoteSigner] at the given URL.
+    Remote {
+        /// Base URL of the service.
+        url: Url,
+        /// Source of the bearer token, if the service requires one.
+        token: Option<SecretSource>,
+    },
+    /// An AWS KMS key, by key ID, ARN or alias.
+    AwsKms(String),
+}
+
+impl FromStr for SignerUri {
+    type Err = SignerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(key_id) = s.strip_prefix("aws-kms:") {
+            let key_id = key_id.trim_start_matches("//");
+            if key_id.is_empty() {
+                return Err(SignerError::InvalidUri("missing AWS KMS key ID".into()));
+            }
+            return Ok(Self::AwsKms(key_id.to_string()));
+        }
+        if let Some(rest) = s.strip_prefix("keystore:") {
+            let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+            let path = path.strip_prefix("//").unwrap_or(path);
+            if path.is_empty() {
+                return Err(SignerError::InvalidUri("missing keystore path".into()));
+            }
+            let mut password = None;
+            for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+                match key.as_ref() {
+                    "password-file" => {
+                        password = Some(SecretSource::File(value.into_owned().into()))
+                    }
+                    "password-env" => password = Some(SecretSource::Env(value.into_owned())),
+                    _ => {
+                        return Err(SignerError::InvalidUri(format!(
+                            "unknown keystore parameter {key}"
+                        )))
+                    }
+                }
+            }
+            let password = password.ok_or_else(|| {
+                SignerError::InvalidUri(
+                    "keystore URI must set password-file or password-env".into(),
+                )
+            })?;
+            return Ok(Self::Keystore { path: path.into(), password });
+        }
+        if s.starts_with("http://") || s.starts_with("https://") {
+            let mut url = Url::parse(s).map_err(|err| SignerError::InvalidUri(err.to_string()))?;
+            let mut token = None;
+            let mut query = Vec::new();
+            for (key, value) in url.query_pairs() {
+                match key.as_ref() {
+                    "token-file" => token = Some(SecretSource::File(value.into_owned().into())),
+                    "token-env" => token = Some(SecretSource::Env(value.into_owned())),
+                    _ => query.push((key.into_owned(), value.into_owned())),
+                }
+            }
+            // Keep the token parameters out of the URL of the requests to the service.
+            if query.is_empty() {
+                url.set_query(None);
+            } else {
+                url.query_pairs_mut().clear().extend_pairs(query);
+            }
+            return Ok(Self::Remote { url, token });
+        }
+        // Do not echo the input, as it is likely a malformed private key.
+        PrivateKeySigner::from_str(s).map(Self::PrivateKey).map_err(|_| {
+            SignerError::InvalidUri(
+                "expected a private key, or a keystore:, http(s):// or aws-kms: URI".into(),
+            )
+        })
+    }
+}
+
+impl From<PrivateKeySigner> for SignerUri {
+    fn from(signer: PrivateKeySigner) -> Self {
+        Self::PrivateKey(signer)
+    }
+}
+
+impl SignerUri {
+    /// Creates the signer, decrypting the keystore or connecting to the remote signer or KMS.
+    pub async fn signer(&self) -> Result<AnySigner, SignerError> {
+        match self {
+            Self::PrivateKey(signer) => Ok(AnySigner::Local(signer.clone())),
+            Self::Keystore { path, password } => {
+                let password = password.read().await?;
+                Ok(AnySigner::Local(PrivateKeySigner::decrypt_keystore(path, password)?))
+            }
+            Self::Remote { url, token } => {
+                let token = match token {
+                    Some(token) => Some(token.read().await?),
+                    None => None,
+                };
+                Ok(AnySigner::Remote(RemoteSigner::connect(url.clone(), token).await?))
+            }
+            #[cfg(feature = "aws-kms")]
+            Self::AwsKms(key_id) => {
+                let config = aws_config::load_from_env().await;
+                let client = aws_sdk_kms::Client::new(&config);
+                let signer =
+                    alloy::signers::aws::AwsSigner::new(client, key_id.clone(), None).await?;
+                Ok(AnySigner::AwsKms(signer))
+            }
+            #[cfg(not(feature = "aws-kms"))]
+            Self::AwsKms(_) => Err(SignerError::Unsupported("AWS KMS", "aws-kms")),
+        }
+    }
+}
+
+/// Command line arguments selecting the signer of a binary, either a private key or a
+/// [SignerUri].
+#[derive(Clone, Debug, Parser)]
+#[group(required = true, multiple = false)]
+pub struct SignerArgs {
+    /// Private key of the wallet
+    #[arg(long, env, hide_env_values = true)]
+    pub private_key: Option<PrivateKeySigner>,
+    /// URI of the signer, as an alternative to the private key
+    ///
+    /// One of `keystore:<path>?password-file=<path>`, `keystore:<path>?password-env=<var>`,
+    /// the `https://` URL of a remote signer, optionally with `token-file=<path>` or
+    /// `token-env=<var>`, or `aws-kms:<key ID>`.
+    #[arg(long, env, hide_env_values = true)]
+    pub signer_uri: Option<SignerUri>,
+}
+
+impl SignerArgs {
+    /// Creates the signer.
+    pub async fn signer(&self) -> Result<AnySigner, SignerError> {
+        match (&self.private_key, &self.signer_uri) {
+            (Some(private_key), _) => Ok(AnySigner::Local(private_key.clone())),
+            (None, Some(uri)) => uri.signer().await,
+            (None, None) => Err(SignerError::InvalidUri("no private key or signer URI set".into())),
+        }
+    }
+}
+
+impl From<PrivateKeySigner> for SignerArgs {
+    fn from(private_key: PrivateKeySigner) -> Self {
+        Self { private_key: Some(private_key), signer_uri: None }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct AddressResponse {
+    address: Address,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SignRequest {
+    hash: B256,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SignResponse {
+    signature: Bytes,
+}
+
+/// A signer holding its key in a remote service, reached over HTTP.
+///
+/// The service signs 32-byte hashes, and implements the following endpoints, relative to its
+/// base URL:
+///
+/// - `GET address`, returning `{"address": "0x..."}`;
+/// - `POST sign` with `{"hash": "0x..."}`, returning `{"signature": "0x..."}`, the 65-byte
+///   signature of the hash.
+///
+/// Transactions are hashed locally, and only their hash is sent to the service. The signatures
+/// returned by the service are checked against its address.
+///
+/// Requests carry the bearer token of the service, if it has one. As the token and the hashes
+/// must not be sent in the clear, the service must be served over HTTPS unless it runs on a
+/// loopback host.
+#[derive(Clone)]
+pub struct RemoteSigner {
+    client: reqwest::Client,
+    base_url: Url,
+    token: Option<String>,
+    address: Address,
+    chain_id: Option<ChainId>,
+}
+
+impl std::fmt::Debug for RemoteSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteSigner")
+            .field("base_url", &self.base_url)
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .field("address", &self.address)
+            .field("chain_id", &self.chain_id)
+            .finish()
+    }
+}
+
+impl RemoteSigner {
+    /// Connects to the service at the given URL, fetching the address of its key.
+    ///
+    /// The token, if any, is sent as a bearer token with every request.
+    pub async fn connect(base_url: Url, token: Option<String>) -> Result<Self, SignerError> {
+        if base_url.scheme() != "https" && !is_loopback(&base_url) {
+            return Err(SignerError::InvalidUri(format!(
+                "remote signer at {} must use https",
+                base_url.host_str().unwrap_or_default()
+            )));
+        }
+        let client = reqwest::Client::builder()
+            .connect_timeout(REMOTE_SIGNER_CONNECT_TIMEOUT)
+            .timeout(REMOTE_SIGNER_REQUEST_TIMEOUT)
+            .build()?;
+        let signer = Self { client, base_url, token, address: Address::ZERO, chain_id: None };
+        let AddressResponse { address } = signer
+            .authorize(signer.client.get(endpoint(&signer.base_url, "address")))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(Self { address, ..signer })
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    async fn request_signature(&self, hash: &B256) -> Result<Bytes, reqwest::Error> {
+        let response = self
+            .authorize(self.client.post(endpoint(&self.base_url, "sign")))
+            .json(&SignRequest { hash: *hash })
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(response.json::<SignResponse>().await?.signature)
+    }
+}
+
+fn is_loopback(url: &Url) -> bool {
+    match url.host() {
+        Some(Host::Domain(domain)) => domain == "localhost",
+        Some(Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+fn endpoint(base_url: &Url, name: &str) -> Url {
+    let mut url = base_url.clone();
+    url.path_segments_mut().expect("signer URL cannot be a base").pop_if_empty().push(name);
+    url
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    async fn sign_hash(&self, hash: &B256) -> alloy::signers::Result<PrimitiveSignature> {
+        let signature = self.request_signature(hash).await.map_err(alloy::signers::Error::other)?;
+        let signature = PrimitiveSignature::try_from(signature.as_ref())
+            .map_err(alloy::signers::Error::other)?;
+        let signer =
+            signature.recover_address_from_prehash(hash).map_err(alloy::signers::Error::other)?;
+        if signer != self.address {
+            return Err(alloy::signers::Error::other(format!(
+                "remote signer returned a signature by {signer}, expected {}",
+                self.address
+            )));
+        }
+        Ok(signature)
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> Option<ChainId> {
+        self.chain_id
+    }
+
+    fn set_chain_id(&mut self, chain_id: Option<ChainId>) {
+        self.chain_id = chain_id;
+    }
+}
+
+#[async_trait]
+impl TxSigner<PrimitiveSignature> for RemoteSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &mut dyn SignableTransaction<PrimitiveSignature>,
+    ) -> alloy::signers::Result<PrimitiveSignature> {
+        if let Some(chain_id) = self.chain_id {
+            if !tx.set_chain_id_checked(chain_id) {
+                return Err(alloy::signers::Error::other(format!(
+                    "transaction chain ID does not match the signer chain ID {chain_id}"
+                )));
+            }
+        }
+        self.sign_hash(&tx.signature_hash()).await
+    }
+}
+
+/// A signer created from a [SignerUri].
+///
+/// Signs both requests, as a [Signer], and transactions, as a [TxSigner], so it can be used for
+/// the wallet of a provider, e.g. with `EthereumWallet::from(signer.clone())`.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum AnySigner {
+    /// A local private key.
+    Local(PrivateKeySigner),
+    /// A remote signer.
+    Remote(RemoteSigner),
+    /// An AWS KMS key.
+    #[cfg(feature = "aws-kms")]
+    AwsKms(alloy::signers::aws::AwsSigner),
+}
+
+impl From<PrivateKeySigner> for AnySigner {
+    fn from(signer: PrivateKeySigner) -> Self {
+        Self::Local(signer)
+    }
+}
+
+#[async_trait]
+impl Signer for AnySigner {
+    async fn sign_hash(&self, hash: &B256) -> alloy::signers::Result<PrimitiveSignature> {
+        match self {
+            Self::Local(signer) => signer.sign_hash(hash).await,
+            Self::Remote(signer) => signer.sign_hash(hash).await,
+            #[cfg(feature = "aws-kms")]
+            Self::AwsKms(signer) => signer.sign_hash(hash).await,
+        }
+    }
+
+    fn address(&self) -> Address {
+        match self {
+            Self::Local(signer) => Signer::address(signer),
+            Self::Remote(signer) => Signer::address(signer),
+            #[cfg(feature = "aws-kms")]
+            Self::AwsKms(signer) => Signer::address(signer),
+        }
+    }
+
+    fn chain_id(&self) -> Option<ChainId> {
+        match self {
+            Self::Local(signer) => signer.chain_id(),
+            Self::Remote(signer) => signer.chain_id(),
+            #[cfg(feature = "aws-kms")]
+            Self::AwsKms(signer) => signer.chain_id(),
+        }
+    }
+
+    fn set_chain_id(&mut self, chain_id: Option<ChainId>) {
+        match self {
+            Self::Local(signer) => signer.set_chain_id(chain_id),
+            Self::Remote(signer) => signer.set_chain_id(chain_id),
+            #[cfg(feature = "aws-kms")]
+            Self::AwsKms(signer) => signer.set_chain_id(chain_id),
+        }
+    }
+}
+
+#[async_trait]
+impl TxSigner<PrimitiveSignature> for AnySigner {
+    fn address(&self) -> Address {
+        Signer::address(self)
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &mut dyn SignableTransaction<PrimitiveSignature>,
+    ) -> alloy::signers::Result<PrimitiveSignature> {
+        match self {
+            Self::Local(signer) => signer.sign_transaction(tx).await,
+            Self::Remote(signer) => signer.sign_transaction(tx).await,
+            #[cfg(feature = "aws-kms")]
+            Self::AwsKms(signer) => signer.sign_transaction(tx).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contracts::{Input, Offer, Predicate, ProofRequest, RequestId, Requirements};
+    use alloy::{consensus::TxLegacy, primitives::TxKind};
+    use boundless_market_test_utils::spawn_remote_signer;
+    use risc0_zkvm::sha::Digest;
+
+    #[test]
+    fn parse_signer_uri() {
+        let key = PrivateKeySigner::random();
+        let uri: SignerUri = hex::encode(key.to_bytes()).parse().unwrap();
+        assert!(matches!(uri, SignerUri::PrivateKey(signer) if signer.address() == key.address()));
+
+        let uri: SignerUri = "keystore:///keys/a.json?password-env=PASSWORD".parse().unwrap();
+        assert!(matches!(
+            uri,
+            SignerUri::Keystore { path, password: SecretSource::Env(var) }
+                if path == PathBuf::from("/keys/a.json") && var == "PASSWORD"
+        ));
+        assert!("keystore:a.json".parse::<SignerUri>().is_err());
+
+        let uri: SignerUri = "https://signer.example.com/keys/1".parse().unwrap();
+        assert!(matches!(
+            uri,
+            SignerUri::Remote { url, token: None } if url.path() == "/keys/1"
+        ));
+
+        // The token parameters are stripped from the URL, other parameters are kept.
+        let uri: SignerUri =
+            "https://signer.example.com/keys/1?region=eu&token-env=TOKEN".parse().unwrap();
+        assert!(matches!(
+            uri,
+            SignerUri::Remote { url, token: Some(SecretSource::Env(var)) }
+                if url.query() == Some("region=eu") && var == "TOKEN"
+        ));
+
+        let uri: SignerUri = "aws-kms:alias/boundless".parse().unwrap();
+        assert!(matches!(uri, SignerUri::AwsKms(key_id) if key_id == "alias/boundless"));
+
+        let err = "0xnotakey".parse::<SignerUri>().unwrap_err();
+        assert!(!err.to_string().contains("notakey"));
+    }
+
+    #[tokio::test]
+    async fn keystore_signer() {
+        let dir = tempfile::tempdir().unwrap();
+        let (key, name) =
+            PrivateKeySigner::new_keystore(dir.path(), &mut rand::thread_rng(), "secret", None)
+                .unwrap();
+        let password_file = dir.path().join("password");
+        std::fs::write(&password_file, "secret\n").unwrap();
+
+        let uri: SignerUri = format!(
+            "keystore:{}?password-file={}",
+            dir.path().join(name).display(),
+            password_file.display()
+        )
+        .parse()
+        .unwrap();
+        let signer = uri.signer().await.unwrap();
+        assert_eq!(Signer::address(&signer), key.address());
+    }
+
+    #[tokio::test]
+    async fn remote_signer() {
+        let key = PrivateKeySigner::random();
+        let (url, _server) = spawn_remote_signer(key.clone(), Some("secret")).await.unwrap();
+
+        // Requests without the token are rejected.
+        let uri = SignerUri::Remote { url: url.clone(), token: None };
+        assert!(matches!(uri.signer().await, Err(SignerError::HttpError(_))));
+
+        let dir = tempfile::tempdir().unwrap();
+        let token_file = dir.path().join("token");
+        std::fs::write(&token_file, "secret\n").unwrap();
+        let uri = SignerUri::Remote { url, token: Some(SecretSource::File(token_file)) };
+        let signer = uri.signer().await.unwrap();
+        assert_eq!(Signer::address(&signer), key.address());
+        assert!(!format!("{signer:?}").contains("secret"));
+
+        // Requests signed remotely verify as signed by the key.
+        let request = ProofRequest::new(
+            RequestId::new(key.address(), 1),
+            Requirements::new(Digest::ZERO, Predicate::prefix_match(vec![])),
+            "https://example.com/guest",
+            Input::inline(vec![]),
+            Offer::default(),
+        );
+        let market = Address::repeat_byte(1);
+        let signature = request.sign_request(&signer, market, 1).await.unwrap();
+        request.verify_signature(&signature.as_bytes().into(), market, 1).unwrap();
+
+        // Transactions are hashed locally and signed remotely.
+        let mut tx = TxLegacy {
+            chain_id: Some(1),
+            to: TxKind::Call(market),
+            gas_limit: 21_000,
+            ..Default::default()
+        };
+        let signature = TxSigner::sign_transaction(&signer, &mut tx).await.unwrap();
+        assert_eq!(
+            signature.recover_address_from_prehash(&tx.signature_hash()).unwrap(),
+            key.address()
+        );
+    }
+
+    #[tokio::test]
+    async fn remote_signer_requires_https() {
+        let url = Url::parse("http://signer.example.com/keys/1").unwrap();
+        let err = RemoteSigner::connect(url, None).await.unwrap_err();
+        assert!(matches!(err, SignerError::InvalidUri(_)));
+    }
+}
@@ -0,0 +1,1153 @@
+// Copyright 2025 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An in-process simulation of the Boundless Market.
+//!
+//! [SimMarket] implements the request, lock, fulfill and slash semantics of `BoundlessMarket.sol`
+//! over an in-memory ledger, with a [FakeClock] standing in for block timestamps and a
+//! [MockVerifier] standing in for proof verification. [Simulation] drives a set of virtual
+//! provers, each with its own [ProverStrategy], over a stream of synthetic requests, which lets
+//! pricing and locking strategies be evaluated over thousands of orders in seconds, without anvil
+//! or a contract deployment.
+//!
+//! Services that act on the market through the [MarketBackend] trait can run against a
+//! [SimMarketClient] in place of a [BoundlessMarketService] deployed on a chain.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use alloy::{
+    network::Ethereum,
+    primitives::{Address, Bytes, U256},
+    providers::Provider,
+};
+use anyhow::Context;
+use async_trait::async_trait;
+
+use crate::{
+    clock::{Clock, FakeClock},
+    contracts::{
+        boundless_market::{BoundlessMarketService, MarketError},
+        ProofRequest, RequestError, RequestStatus,
+    },
+};
+
+/// Share of a slashed stake that is burned, in basis points, as in `BoundlessMarket.sol`.
+pub const SLASHING_BURN_BPS: u64 = 7500;
+
+/// Address holding the market treasury, standing in for the market contract itself.
+pub const TREASURY: Address = Address::ZERO;
+
+/// Errors returned by the [SimMarket], mirroring the reverts of `BoundlessMarket.sol`.
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum SimError {
+    /// The request is malformed.
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
+
+    /// The request is already locked.
+    #[error("request is locked 0x{0:x}")]
+    RequestIsLocked(U256),
+
+    /// The request is already fulfilled.
+    #[error("request is fulfilled 0x{0:x}")]
+    RequestIsFulfilled(U256),
+
+    /// The lock deadline of the request has passed.
+    #[error("request lock is expired 0x{0:x} (lock deadline {1})")]
+    RequestLockIsExpired(U256, u64),
+
+    /// The deadline of the request has passed.
+    #[error("request is expired 0x{0:x} (deadline {1})")]
+    RequestIsExpired(U256, u64),
+
+    /// The account does not have enough balance or stake.
+    #[error("insufficient balance for {0}")]
+    InsufficientBalance(Address),
+
+    /// The request is not locked.
+    #[error("request is not locked 0x{0:x}")]
+    RequestIsNotLocked(U256),
+
+    /// The request is already slashed.
+    #[error("request is slashed 0x{0:x}")]
+    RequestIsSlashed(U256),
+
+    /// The request has not expired yet, so it cannot be slashed.
+    #[error("request is not expired 0x{0:x} (deadline {1})")]
+    RequestIsNotExpired(U256, u64),
+
+    /// The fulfilled request differs from the locked request with the same ID.
+    #[error("fulfillment does not match the locked request 0x{0:x}")]
+    InvalidRequestFulfillment(U256),
+
+    /// The verifier rejected the proof.
+    #[error("verification failed for request 0x{0:x}")]
+    VerificationFailed(U256),
+}
+
+impl From<RequestError> for SimError {
+    fn from(err: RequestError) -> Self {
+        SimError::InvalidRequest(err.to_string())
+    }
+}
+
+/// Verifier accepting a proof of a request if the journal satisfies the request predicate.
+///
+/// Seals are not simulated, so the image ID is taken as given.
+#[derive(Clone, Debug, Default)]
+pub struct MockVerifier {
+    reject: HashSet<U256>,
+}
+
+impl MockVerifier {
+    /// Rejects all proofs of the given request, e.g. to simulate a faulty prover.
+    pub fn with_rejected(mut self, request_id: U256) -> Self {
+        self.reject.insert(request_id);
+        self
+    }
+
+    /// Verifies a proof of the request with the given journal.
+    pub fn verify(&self, request: &ProofRequest, journal: &[u8]) -> bool {
+        !self.reject.contains(&request.id) && request.requirements.predicate.eval(journal)
+    }
+}
+
+/// An event emitted by the [SimMarket], mirroring the events of `BoundlessMarket.sol`.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum SimEvent {
+    /// A request was submitted.
+    RequestSubmitted {
+        /// The request ID.
+        request_id: U256,
+    },
+    /// A request was locked.
+    RequestLocked {
+        /// The request ID.
+        request_id: U256,
+        /// The prover that locked the request.
+        prover: Address,
+        /// The price paid by the client.
+        price: U256,
+    },
+    /// A request was fulfilled.
+    RequestFulfilled {
+        /// The request ID.
+        request_id: U256,
+        /// The prover that fulfilled the request.
+        prover: Address,
+        /// The payment to the prover.
+        payment: U256,
+    },
+    /// A request was fulfilled, but the prover was not paid.
+    PaymentRequirementsFailed {
+        /// The request ID.
+        request_id: U256,
+        /// Why the prover was not paid.
+        reason: SimError,
+    },
+    /// The prover of a request was slashed.
+    ProverSlashed {
+        /// The request ID.
+        request_id: U256,
+        /// The burned stake.
+        burned: U256,
+        /// The stake transferred to the recipient.
+        transferred: U256,
+        /// The recipient of the transferred stake.
+        recipient: Address,
+    },
+}
+
+/// Balances of an account in the [SimMarket].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SimAccount {
+    /// Balance, in wei.
+    pub balance: U256,
+    /// Stake balance, in stake tokens.
+    pub stake_balance: U256,
+}
+
+/// Payment state of a lock.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Paid {
+    No,
+    BeforeLockDeadline,
+    AfterLockDeadline,
+}
+
+#[derive(Clone, Debug)]
+struct Lock {
+    request: ProofRequest,
+    prover: Address,
+    price: U256,
+    stake: U256,
+    paid: Paid,
+    slashed: bool,
+}
+
+/// Result of a successful call to [SimMarket::fulfill].
+#[derive(Clone, Debug, PartialEq)]
+pub struct FulfillOutcome {
+    /// Payment to the prover.
+    pub payment: U256,
+    /// Why the prover was not paid, if it was not.
+    ///
+    /// As in the contract, the request is still marked as fulfilled.
+    pub payment_error: Option<SimError>,
+}
+
+/// In-memory implementation of the `BoundlessMarket` contract.
+///
+/// Method names follow [BoundlessMarketService](crate::contracts::boundless_market::BoundlessMarketService),
+/// with the caller given explicitly. Client signatures are not simulated.
+#[derive(Clone, Debug)]
+pub struct SimMarket {
+    clock: FakeClock,
+    verifier: MockVerifier,
+    accounts: HashMap<Address, SimAccount>,
+    submitted: BTreeMap<U256, ProofRequest>,
+    locks: HashMap<U256, Lock>,
+    fulfilled: HashSet<U256>,
+    events: Vec<(u64, SimEvent)>,
+    burned: U256,
+}
+
+impl SimMarket {
+    /// Creates an empty market using the given clock.
+    pub fn new(clock: FakeClock) -> Self {
+        Self {
+            clock,
+            verifier: MockVerifier::default(),
+            accounts: HashMap::new(),
+            submitted: BTreeMap::new(),
+            locks: HashMap::new(),
+            fulfilled: HashSet::new(),
+            events: Vec::new(),
+            burned: U256::ZERO,
+        }
+    }
+
+    /// Sets the verifier.
+    pub fn with_verifier(self, verifier: MockVerifier) -> Self {
+        Self { verifier, ..self }
+    }
+
+    /// Returns the clock of the market.
+    pub fn clock(&self) -> &FakeClock {
+        &self.clock
+    }
+
+    /// Returns the events emitted so far, with their timestamps.
+    pub fn events(&self) -> &[(u64, SimEvent)] {
+        &self.events
+    }
+
+    /// Returns the total burned stake.
+    pub fn burned(&self) -> U256 {
+        self.burned
+    }
+
+    fn emit(&mut self, event: SimEvent) {
+        self.events.push((self.clock.now(), event));
+    }
+
+    fn account(&mut self, address: Address) -> &mut SimAccount {
+        self.accounts.entry(address).or_default()
+    }
+
+    /// Deposits funds into the account.
+    pub fn deposit(&mut self, address: Address, value: U256) {
+        self.account(address).balance += value;
+    }
+
+    /// Withdraws funds from the account.
+    pub fn withdraw(&mut self, address: Address, value: U256) -> Result<(), SimError> {
+        let account = self.account(address);
+        account.balance =
+            account.balance.checked_sub(value).ok_or(SimError::InsufficientBalance(address))?;
+        Ok(())
+    }
+
+    /// Deposits stake into the account.
+    pub fn deposit_stake(&mut self, address: Address, value: U256) {
+        self.account(address).stake_balance += value;
+    }
+
+    /// Withdraws stake from the account.
+    pub fn withdraw_stake(&mut self, address: Address, value: U256) -> Result<(), SimError> {
+        let account = self.account(address);
+        account.stake_balance = account
+            .stake_balance
+            .checked_sub(value)
+            .ok_or(SimError::InsufficientBalance(address))?;
+        Ok(())
+    }
+
+    /// Returns the balance of the account.
+    pub fn balance_of(&self, address: Address) -> U256 {
+        self.accounts.get(&address).map(|account| account.balance).unwrap_or_default()
+    }
+
+    /// Returns the stake balance of the account.
+    pub fn balance_of_stake(&self, address: Address) -> U256 {
+        self.accounts.get(&address).map(|account| account.stake_balance).unwrap_or_default()
+    }
+
+    /// Submits a request, making it visible through [SimMarket::open_requests].
+    pub fn submit_request(&mut self, request: ProofRequest) -> Result<(), SimError> {
+        request.validate()?;
+        let request_id = request.id;
+        self.submitted.insert(request_id, request);
+        self.emit(SimEvent::RequestSubmitted { request_id });
+        Ok(())
+    }
+
+    /// Returns the submitted requests that can currently be locked.
+    pub fn open_requests(&self) -> impl Iterator<Item = &ProofRequest> {
+        let now = self.clock.now();
+        self.submitted.values().filter(move |request| {
+            !self.locks.contains_key(&request.id)
+                && !self.fulfilled.contains(&request.id)
+                && now <= request.offer.lock_deadline()
+        })
+    }
+
+    /// Returns the submitted request with the given ID.
+    pub fn request(&self, request_id: U256) -> Option<&ProofRequest> {
+        self.submitted.get(&request_id)
+    }
+
+    /// Returns the prover holding the lock on the request, if it is locked.
+    pub fn locked_by(&self, request_id: U256) -> Option<Address> {
+        self.locks.get(&request_id).map(|lock| lock.prover)
+    }
+
+    /// Returns whether the request is fulfilled.
+    pub fn is_fulfilled(&self, request_id: U256) -> bool {
+        self.fulfilled.contains(&request_id)
+    }
+
+    /// Returns the status of the request, as
+    /// [BoundlessMarketService::get_status](crate::contracts::boundless_market::BoundlessMarketService::get_status).
+    pub fn get_status(&self, request_id: U256, expires_at: Option<u64>) -> RequestStatus {
+        let now = self.clock.now();
+        if self.fulfilled.contains(&request_id) {
+            return RequestStatus::Fulfilled;
+        }
+        if expires_at.is_some_and(|expires_at| now > expires_at) {
+            return RequestStatus::Expired;
+        }
+        match self.locks.get(&request_id) {
+            Some(lock) if now > lock.request.offer.deadline() => RequestStatus::Expired,
+            Some(_) => RequestStatus::Locked,
+            None => RequestStatus::Unknown,
+        }
+    }
+
+    fn validate(&self, request: &ProofRequest) -> Result<(), SimError> {
+        let offer = &request.offer;
+        if offer.minPrice > offer.maxPrice
+            || offer.rampUpPeriod > offer.lockTimeout
+            || offer.lockTimeout > offer.timeout
+            || offer.timeout - offer.lockTimeout > (1 << 24) - 1
+        {
+            return Err(SimError::InvalidRequest("invalid offer".into()));
+        }
+        if offer.deadline() < self.clock.now() {
+            return Err(SimError::RequestIsExpired(request.id, offer.deadline()));
+        }
+        Ok(())
+    }
+
+    fn price(&self, request: &ProofRequest) -> U256 {
+        request.offer.price_at(self.clock.now()).unwrap_or_default()
+    }
+
+    /// Locks the request to the prover, charging the client the current price and the prover
+    /// the lock stake.
+    ///
+    /// Returns the price paid by the client.
+    pub fn lock_request(
+        &mut self,
+        request: &ProofRequest,
+        prover: Address,
+    ) -> Result<U256, SimError> {
+        self.validate(request)?;
+        let request_id = request.id;
+        if self.locks.contains_key(&request_id) {
+            return Err(SimError::RequestIsLocked(request_id));
+        }
+        if self.fulfilled.contains(&request_id) {
+            return Err(SimError::RequestIsFulfilled(request_id));
+        }
+        let lock_deadline = request.offer.lock_deadline();
+        if self.clock.now() > lock_deadline {
+            return Err(SimError::RequestLockIsExpired(request_id, lock_deadline));
+        }
+
+        let price = self.price(request);
+        let client = request.client_address();
+        let stake = request.offer.lockStake;
+        if self.balance_of(client) < price {
+            return Err(SimError::InsufficientBalance(client));
+        }
+        if self.balance_of_stake(prover) < stake {
+            return Err(SimError::InsufficientBalance(prover));
+        }
+        self.account(client).balance -= price;
+        self.account(prover).stake_balance -= stake;
+
+        self.locks.insert(
+            request_id,
+            Lock { request: request.clone(), prover, price, stake, paid: Paid::No, slashed: false },
+        );
+        self.emit(SimEvent::RequestLocked { request_id, prover, price });
+        Ok(price)
+    }
+
+    /// Fulfills the request with a proof of the given journal, and pays the prover if eligible.
+    ///
+    /// As in the contract, a proof of a request locked by another prover still fulfills the
+    /// request, but the prover is not paid, and a request whose lock expired can be fulfilled by
+    /// any prover, at the current price of the request.
+    pub fn fulfill(
+        &mut self,
+        request: &ProofRequest,
+        prover: Address,
+        journal: &[u8],
+    ) -> Result<FulfillOutcome, SimError> {
+        let request_id = request.id;
+        if !self.verifier.verify(request, journal) {
+            return Err(SimError::VerificationFailed(request_id));
+        }
+
+        let now = self.clock.now();
+        let res = match self.locks.get(&request_id).cloned() {
+            Some(lock) if now <= lock.request.offer.lock_deadline() => {
+                self.fulfill_locked(lock, request, prover)?
+            }
+            Some(lock) => self.fulfill_was_locked(lock, request, prover)?,
+            None => self.fulfill_never_locked(request, prover),
+        };
+
+        match &res {
+            Ok(payment) => {
+                self.emit(SimEvent::RequestFulfilled { request_id, prover, payment: *payment })
+            }
+            Err(reason) => self
+                .emit(SimEvent::PaymentRequirementsFailed { request_id, reason: reason.clone() }),
+        }
+        Ok(match res {
+            Ok(payment) => FulfillOutcome { payment, payment_error: None },
+            Err(err) => FulfillOutcome { payment: U256::ZERO, payment_error: Some(err) },
+        })
+    }
+
+    fn fulfill_locked(
+        &mut self,
+        lock: Lock,
+        request: &ProofRequest,
+        prover: Address,
+    ) -> Result<Result<U256, SimError>, SimError> {
+        let request_id = request.id;
+        if lock.paid != Paid::No {
+            return Ok(Err(SimError::RequestIsFulfilled(request_id)));
+        }
+        if lock.request != *request {
+            return Err(SimError::InvalidRequestFulfillment(request_id));
+        }
+        self.fulfilled.insert(request_id);
+
+        // While the request is locked, only the locker is eligible for payment.
+        if lock.prover != prover {
+            return Ok(Err(SimError::RequestIsLocked(request_id)));
+        }
+        self.locks.get_mut(&request_id).unwrap().paid = Paid::BeforeLockDeadline;
+        let account = self.account(prover);
+        account.balance += lock.price;
+        account.stake_balance += lock.stake;
+        Ok(Ok(lock.price))
+    }
+
+    fn fulfill_was_locked(
+        &mut self,
+        lock: Lock,
+        request: &ProofRequest,
+        prover: Address,
+    ) -> Result<Result<U256, SimError>, SimError> {
+        let request_id = request.id;
+        if lock.paid != Paid::No {
+            return Ok(Err(SimError::RequestIsFulfilled(request_id)));
+        }
+        self.fulfilled.insert(request_id);
+        // Pricing the request checks that it has not expired.
+        self.validate(request)?;
+
+        // The client was charged the lock price already, so settle the difference with the
+        // current price of the fulfilled request.
+        let price = self.price(request);
+        let client = request.client_address();
+        if price > lock.price {
+            let owed = price - lock.price;
+            if self.balance_of(client) < owed {
+                return Ok(Err(SimError::InsufficientBalance(client)));
+            }
+            self.account(client).balance -= owed;
+        } else {
+            self.account(client).balance += lock.price - price;
+        }
+
+        let lock = self.locks.get_mut(&request_id).unwrap();
+        lock.paid = Paid::AfterLockDeadline;
+        // As in the contract, the lock now records the prover that fulfilled the request.
+        lock.prover = prover;
+        self.account(prover).balance += price;
+        Ok(Ok(price))
+    }
+
+    fn fulfill_never_locked(
+        &mut self,
+        request: &ProofRequest,
+        prover: Address,
+    ) -> Result<U256, SimError> {
+        let request_id = request.id;
+        if self.fulfilled.contains(&request_id) {
+            return Err(SimError::RequestIsFulfilled(request_id));
+        }
+        self.validate(request)?;
+        let price = self.price(request);
+
+        self.fulfilled.insert(request_id);
+        let client = request.client_address();
+        if self.balance_of(client) < price {
+            return Err(SimError::InsufficientBalance(client));
+        }
+        self.account(client).balance -= price;
+        self.account(prover).balance += price;
+        Ok(price)
+    }
+
+    /// Slashes the prover of a request that was not fulfilled before its lock deadline.
+    ///
+    /// Part of the stake is burned. The rest goes to the prover that fulfilled the request after
+    /// the lock deadline, if any, or otherwise to the treasury, with the price refunded to the
+    /// client.
+    pub fn slash(&mut self, request_id: U256) -> Result<(), SimError> {
+        let now = self.clock.now();
+        let lock = self.locks.get(&request_id).ok_or(SimError::RequestIsNotLocked(request_id))?;
+        if lock.slashed {
+            return Err(SimError::RequestIsSlashed(request_id));
+        }
+        if lock.paid == Paid::BeforeLockDeadline {
+            return Err(SimError::RequestIsFulfilled(request_id));
+        }
+        let deadline = lock.request.offer.deadline();
+        if now <= deadline {
+            return Err(SimError::RequestIsNotExpired(request_id, deadline));
+        }
+
+        let lock = lock.clone();
+        self.locks.get_mut(&request_id).unwrap().slashed = true;
+        let burned = lock.stake * U256::from(SLASHING_BURN_BPS) / U256::from(10000);
+        let transferred = lock.stake - burned;
+        let recipient = match lock.paid {
+            Paid::AfterLockDeadline => lock.prover,
+            _ => {
+                let client = lock.request.client_address();
+                self.account(client).balance += lock.price;
+                TREASURY
+            }
+        };
+        self.account(recipient).stake_balance += transferred;
+        self.burned += burned;
+        self.emit(SimEvent::ProverSlashed { request_id, burned, transferred, recipient });
+        Ok(())
+    }
+
+    /// Returns the IDs of the locked requests that can currently be slashed.
+    pub fn slashable_requests(&self) -> Vec<U256> {
+        let now = self.clock.now();
+        self.locks
+            .iter()
+            .filter(|(_, lock)| {
+                !lock.slashed
+                    && lock.paid != Paid::BeforeLockDeadline
+                    && now > lock.request.offer.deadline()
+            })
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
+
+impl From<SimError> for MarketError {
+    fn from(err: SimError) -> Self {
+        match err {
+            SimError::RequestIsLocked(request_id) => MarketError::RequestIsLocked(request_id),
+            SimError::RequestIsFulfilled(request_id) => MarketError::RequestIsFulfilled(request_id),
+            SimError::RequestLockIsExpired(request_id, lock_deadline) => {
+                MarketError::RequestLockIsExpired { request_id, lock_deadline }
+            }
+            SimError::RequestIsExpired(request_id, deadline) => {
+                MarketError::RequestIsExpired { request_id, deadline }
+            }
+            SimError::InsufficientBalance(address) => MarketError::InsufficientBalance(address),
+            SimError::RequestIsNotLocked(request_id) => MarketError::RequestIsNotLocked(request_id),
+            SimError::RequestIsSlashed(request_id) => MarketError::RequestIsSlashed(request_id),
+            SimError::RequestIsNotExpired(request_id, deadline) => {
+                MarketError::RequestIsNotExpired { request_id, deadline }
+            }
+            err => MarketError::Error(err.into()),
+        }
+    }
+}
+
+/// Market operations used by a prover to pick and lock requests.
+///
+/// Implemented by [BoundlessMarketService] for a deployed market, and by [SimMarketClient] for a
+/// [SimMarket], such that services can be tested against either.
+#[async_trait]
+pub trait MarketBackend: Send + Sync {
+    /// Returns the address of the prover acting on the market.
+    fn caller(&self) -> Address;
+
+    /// Returns the current timestamp of the market, i.e. of the latest block.
+    async fn timestamp(&self) -> Result<u64, MarketError>;
+
+    /// Returns the status of the request, as
+    /// [BoundlessMarketService::get_status].
+    async fn get_status(
+        &self,
+        request_id: U256,
+        expires_at: Option<u64>,
+    ) -> Result<RequestStatus, MarketError>;
+
+    /// Locks the request to the caller, returning the price it was locked at.
+    async fn lock_request(
+        &self,
+        request: &ProofRequest,
+        client_sig: &Bytes,
+        priority_gas: Option<u64>,
+    ) -> Result<U256, MarketError>;
+
+    /// Returns the stake balance of the account.
+    async fn balance_of_stake(&self, account: Address) -> Result<U256, MarketError>;
+}
+
+#[async_trait]
+impl<P> MarketBackend for BoundlessMarketService<P>
+where
+    P: Provider<Ethereum> + 'static,
+{
+    fn caller(&self) -> Address {
+        BoundlessMarketService::caller(self)
+    }
+
+    async fn timestamp(&self) -> Result<u64, MarketError> {
+        self.get_latest_block_timestamp().await
+    }
+
+    async fn get_status(
+        &self,
+        request_id: U256,
+        expires_at: Option<u64>,
+    ) -> Result<RequestStatus, MarketError> {
+        BoundlessMarketService::get_status(self, request_id, expires_at).await
+    }
+
+    async fn lock_request(
+        &self,
+        request: &ProofRequest,
+        client_sig: &Bytes,
+        priority_gas: Option<u64>,
+    ) -> Result<U256, MarketError> {
+        let lock_block =
+            BoundlessMarketService::lock_request(self, request, client_sig, priority_gas).await?;
+        let lock_timestamp = self
+            .instance()
+            .provider()
+            .get_block_by_number(lock_block.into())
+            .await
+            .with_context(|| format!("failed to get block {lock_block}"))?
+            .with_context(|| format!("failed to get block {lock_block}: block not found"))?
+            .header
+            .timestamp;
+        request.offer.price_at(lock_timestamp)
+    }
+
+    async fn balance_of_stake(&self, account: Address) -> Result<U256, MarketError> {
+        BoundlessMarketService::balance_of_stake(self, account).await
+    }
+}
+
+/// [MarketBackend] acting on a shared [SimMarket] as the given prover.
+///
+/// Client signatures and gas are not simulated, and are ignored.
+#[derive(Clone, Debug)]
+pub struct SimMarketClient {
+    market: Arc<Mutex<SimMarket>>,
+    caller: Address,
+}
+
+impl SimMarketClient {
+    /// Creates a client of the shared market, acting as the given prover.
+    pub fn new(market: Arc<Mutex<SimMarket>>, caller: Address) -> Self {
+        Self { market, caller }
+    }
+
+    /// Returns the shared market.
+    pub fn market(&self) -> &Arc<Mutex<SimMarket>> {
+        &self.market
+    }
+}
+
+#[async_trait]
+impl MarketBackend for SimMarketClient {
+    fn caller(&self) -> Address {
+        self.caller
+    }
+
+    async fn timestamp(&self) -> Result<u64, MarketError> {
+        Ok(self.market.lock().unwrap().clock().now())
+    }
+
+    async fn get_status(
+        &self,
+        request_id: U256,
+        expires_at: Option<u64>,
+    ) -> Result<RequestStatus, MarketError> {
+        Ok(self.market.lock().unwrap().get_status(request_id, expires_at))
+    }
+
+    async fn lock_request(
+        &self,
+        request: &ProofRequest,
+        _client_sig: &Bytes,
+        _priority_gas: Option<u64>,
+    ) -> Result<U256, MarketError> {
+        Ok(self.market.lock().unwrap().lock_request(request, self.caller)?)
+    }
+
+    async fn balance_of_stake(&self, account: Address) -> Result<U256, MarketError> {
+        Ok(self.market.lock().unwrap().balance_of_stake(account))
+    }
+}
+
+/// Strategy of a virtual prover in a [Simulation].
+pub trait ProverStrategy: Send {
+    /// Decides whether to lock the open request, given its current price.
+    fn should_lock(&mut self, request: &ProofRequest, price: U256, now: u64) -> bool;
+
+    /// Decides whether to fulfill the open request without locking it, given its current price.
+    ///
+    /// This is how provers pick up requests whose lock expired. Defaults to never.
+    fn should_fulfill(&mut self, _request: &ProofRequest, _price: U256, _now: u64) -> bool {
+        false
+    }
+
+    /// Returns the number of seconds needed to prove the request.
+    fn proving_time(&mut self, request: &ProofRequest) -> u64;
+
+    /// Returns the number of requests the prover can prove at once. Defaults to one.
+    fn capacity(&self) -> usize {
+        1
+    }
+}
+
+struct VirtualProver {
+    address: Address,
+    strategy: Box<dyn ProverStrategy>,
+    /// Requests being proven, with the time the proof is ready.
+    jobs: Vec<(u64, U256)>,
+}
+
+/// Summary of the outcome of a [Simulation], per prover.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProverReport {
+    /// Number of requests locked.
+    pub locked: usize,
+    /// Number of requests fulfilled.
+    pub fulfilled: usize,
+    /// Number of locks slashed.
+    pub slashed: usize,
+    /// Total payments received.
+    pub earnings: U256,
+}
+
+/// Drives virtual provers over a [SimMarket] with a fixed time step.
+///
+/// At each step, every prover with free capacity is offered the open requests, completed proofs
+/// are delivered, and expired locks are slashed. Provers are offered requests in a rotating order,
+/// so that no prover always wins races for the same request.
+pub struct Simulation {
+    market: SimMarket,
+    provers: Vec<VirtualProver>,
+    journals: HashMap<U256, Vec<u8>>,
+    step: u64,
+    round: usize,
+}
+
+impl Simulation {
+    /// Creates a simulation over the given market, advancing the clock by `step` seconds per step.
+    pub fn new(market: SimMarket, step: u64) -> Self {
+        Self { market, provers: Vec::new(), journals: HashMap::new(), step, round: 0 }
+    }
+
+    /// Adds a virtual prover with the given address, stake and strategy.
+    pub fn with_prover(
+        mut self,
+        address: Address,
+        stake: U256,
+        strategy: impl ProverStrategy + 'static,
+    ) -> Self {
+        self.market.deposit_stake(address, stake);
+        self.provers.push(VirtualProver {
+            address,
+            strategy: Box::new(strategy),
+            jobs: Vec::new(),
+        });
+        self
+    }
+
+    /// Returns the market.
+    pub fn market(&self) -> &SimMarket {
+        &self.market
+    }
+
+    /// Returns the market, e.g. to fund clients.
+    pub fn market_mut(&mut self) -> &mut SimMarket {
+        &mut self.market
+    }
+
+    /// Submits a request, along with the journal its provers will produce.
+    pub fn submit(&mut self, request: ProofRequest, journal: Vec<u8>) -> Result<(), SimError> {
+        self.journals.insert(request.id, journal);
+        self.market.submit_request(request)
+    }
+
+    /// Advances the clock by one step and lets every prover act.
+    pub fn step(&mut self) {
+        self.market.clock().advance(self.step);
+        let now = self.market.clock().now();
+        let count = self.provers.len();
+        self.round = self.round.wrapping_add(1);
+
+        for i in 0..count {
+            let prover = &mut self.provers[(i + self.round) % count];
+            let mut done = Vec::new();
+            prover.jobs.retain(|(ready_at, request_id)| {
+                let ready = *ready_at <= now;
+                if ready {
+                    done.push(*request_id);
+                }
+                !ready
+            });
+            let address = prover.address;
+            for request_id in done {
+                let request = self.market.request(request_id).unwrap().clone();
+                let journal = &self.journals[&request_id];
+                if let Err(err) = self.market.fulfill(&request, address, journal) {
+                    tracing::debug!("Simulated prover {address} failed to fulfill: {err}");
+                }
+            }
+
+            let prover = &mut self.provers[(i + self.round) % count];
+            let open: Vec<ProofRequest> = self.market.open_requests().cloned().collect();
+            for request in open {
+                if prover.jobs.len() >= prover.strategy.capacity() {
+                    break;
+                }
+                let price = self.market.price(&request);
+                if prover.strategy.should_lock(&request, price, now)
+                    && self.market.lock_request(&request, prover.address).is_ok()
+                {
+                    let ready_at = now + prover.strategy.proving_time(&request);
+                    prover.jobs.push((ready_at, request.id));
+                }
+            }
+
+            // Requests whose lock expired can be fulfilled by anyone.
+            let expired: Vec<ProofRequest> = self
+                .market
+                .locks
+                .values()
+                .filter(|lock| {
+                    lock.paid == Paid::No
+                        && now > lock.request.offer.lock_deadline()
+                        && now <= lock.request.offer.deadline()
+                        && !prover.jobs.iter().any(|(_, id)| *id == lock.request.id)
+                })
+                .map(|lock| lock.request.clone())
+                .collect();
+            for request in expired {
+                if prover.jobs.len() >= prover.strategy.capacity() {
+                    break;
+                }
+                let price = self.market.price(&request);
+                if prover.strategy.should_fulfill(&request, price, now) {
+                    let ready_at = now + prover.strategy.proving_time(&request);
+                    prover.jobs.push((ready_at, request.id));
+                }
+            }
+        }
+
+        for request_id in self.market.slashable_requests() {
+            if let Err(err) = self.market.slash(request_id) {
+                tracing::debug!("Failed to slash simulated request 0x{request_id:x}: {err}");
+            }
+        }
+    }
+
+    /// Steps the simulation until the clock reaches the given timestamp.
+    pub fn run_until(&mut self, timestamp: u64) {
+        while self.market.clock().now() < timestamp {
+            self.step();
+        }
+    }
+
+    /// Summarizes the outcome of the simulation so far, per prover address.
+    pub fn report(&self) -> HashMap<Address, ProverReport> {
+        let mut reports: HashMap<Address, ProverReport> =
+            self.provers.iter().map(|prover| (prover.address, ProverReport::default())).collect();
+        let mut lockers = HashMap::new();
+        for (_, event) in self.market.events() {
+            match event {
+                SimEvent::RequestLocked { request_id, prover, .. } => {
+                    lockers.insert(*request_id, *prover);
+                    reports.entry(*prover).or_default().locked += 1;
+                }
+                SimEvent::RequestFulfilled { prover, payment, .. } => {
+                    let report = reports.entry(*prover).or_default();
+                    report.fulfilled += 1;
+                    report.earnings += *payment;
+                }
+                SimEvent::ProverSlashed { request_id, .. } => {
+                    if let Some(locker) = lockers.get(request_id) {
+                        reports.entry(*locker).or_default().slashed += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+        reports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contracts::{Input, Offer, Predicate, RequestId, Requirements};
+    use alloy::primitives::B256;
+    use risc0_zkvm::sha::Digest;
+
+    const START: u64 = 1_000_000;
+
+    fn request(client: Address, index: u32, journal: &[u8], lock_stake: u64) -> ProofRequest {
+        ProofRequest::new(
+            RequestId::new(client, index),
+            Requirements::new(
+                Digest::from(B256::repeat_byte(1).0),
+                Predicate::prefix_match(journal.to_vec()),
+            ),
+            "https://example.com/guest",
+            Input::inline(vec![]),
+            Offer {
+                minPrice: U256::from(100),
+                maxPrice: U256::from(200),
+                biddingStart: START,
+                rampUpPeriod: 100,
+                lockTimeout: 300,
+                timeout: 600,
+                lockStake: U256::from(lock_stake),
+            },
+        )
+    }
+
+    fn market() -> (SimMarket, Address, Address, Address) {
+        let mut market = SimMarket::new(FakeClock::new(START));
+        let (client, alice, bob) =
+            (Address::repeat_byte(1), Address::repeat_byte(2), Address::repeat_byte(3));
+        market.deposit(client, U256::from(10_000));
+        market.deposit_stake(alice, U256::from(1_000));
+        market.deposit_stake(bob, U256::from(1_000));
+        (market, client, alice, bob)
+    }
+
+    #[test]
+    fn lock_and_fulfill() {
+        let (mut market, client, alice, bob) = market();
+        let req = request(client, 1, b"hello", 400);
+        market.submit_request(req.clone()).unwrap();
+
+        market.clock().advance(50);
+        assert_eq!(market.lock_request(&req, alice).unwrap(), U256::from(150));
+        assert_eq!(market.lock_request(&req, bob), Err(SimError::RequestIsLocked(req.id)));
+        assert_eq!(market.balance_of(client), U256::from(9_850));
+        assert_eq!(market.balance_of_stake(alice), U256::from(600));
+        assert_eq!(market.get_status(req.id, None), RequestStatus::Locked);
+
+        assert_eq!(market.fulfill(&req, alice, b"bye"), Err(SimError::VerificationFailed(req.id)));
+        // Another prover can fulfill a locked request, but is not paid.
+        let outcome = market.fulfill(&req, bob, b"hello world").unwrap();
+        assert_eq!(outcome.payment_error, Some(SimError::RequestIsLocked(req.id)));
+        assert_eq!(market.get_status(req.id, None), RequestStatus::Fulfilled);
+
+        let outcome = market.fulfill(&req, alice, b"hello world").unwrap();
+        assert_eq!(outcome, FulfillOutcome { payment: U256::from(150), payment_error: None });
+        assert_eq!(market.balance_of(alice), U256::from(150));
+        assert_eq!(market.balance_of_stake(alice), U256::from(1_000));
+
+        market.clock().advance(1_000);
+        assert_eq!(market.slash(req.id), Err(SimError::RequestIsFulfilled(req.id)));
+    }
+
+    #[test]
+    fn lock_checks() {
+        let (mut market, client, alice, _) = market();
+        let req = request(client, 1, b"hello", 2_000);
+        assert_eq!(market.lock_request(&req, alice), Err(SimError::InsufficientBalance(alice)));
+
+        let req = request(client, 2, b"hello", 0);
+        market.clock().advance(301);
+        assert_eq!(
+            market.lock_request(&req, alice),
+            Err(SimError::RequestLockIsExpired(req.id, START + 300))
+        );
+        market.clock().advance(300);
+        assert_eq!(
+            market.lock_request(&req, alice),
+            Err(SimError::RequestIsExpired(req.id, START + 600))
+        );
+    }
+
+    #[test]
+    fn slash_unfulfilled() {
+        let (mut market, client, alice, _) = market();
+        let req = request(client, 1, b"hello", 400);
+        market.lock_request(&req, alice).unwrap();
+        assert_eq!(market.slash(req.id), Err(SimError::RequestIsNotExpired(req.id, START + 600)));
+
+        market.clock().advance(601);
+        assert_eq!(market.slashable_requests(), [req.id]);
+        market.slash(req.id).unwrap();
+        assert_eq!(market.slash(req.id), Err(SimError::RequestIsSlashed(req.id)));
+        // The client is refunded, and the unburned stake goes to the treasury.
+        assert_eq!(market.balance_of(client), U256::from(10_000));
+        assert_eq!(market.balance_of_stake(TREASURY), U256::from(100));
+        assert_eq!(market.burned(), U256::from(300));
+    }
+
+    #[test]
+    fn fulfill_after_lock_expired() {
+        let (mut market, client, alice, bob) = market();
+        let req = request(client, 1, b"hello", 400);
+        market.lock_request(&req, alice).unwrap();
+
+        market.clock().advance(301);
+        // The price is zero after the lock deadline, so the client is refunded in full.
+        let outcome = market.fulfill(&req, bob, b"hello").unwrap();
+        assert_eq!(outcome, FulfillOutcome { payment: U256::ZERO, payment_error: None });
+        assert_eq!(market.balance_of(client), U256::from(10_000));
+
+        market.clock().advance(300);
+        market.slash(req.id).unwrap();
+        // The prover that fulfilled the request receives the unburned stake.
+        assert_eq!(market.balance_of_stake(bob), U256::from(1_100));
+        assert_eq!(market.balance_of_stake(alice), U256::from(600));
+    }
+
+    #[tokio::test]
+    async fn market_backend() {
+        let (market, client, alice, bob) = market();
+        let market = Arc::new(Mutex::new(market));
+        let (alice_client, bob_client) =
+            (SimMarketClient::new(market.clone(), alice), SimMarketClient::new(market, bob));
+        let req = request(client, 1, b"hello", 400);
+        alice_client.market().lock().unwrap().submit_request(req.clone()).unwrap();
+        alice_client.market().lock().unwrap().clock().advance(50);
+
+        let backend: &dyn MarketBackend = &alice_client;
+        assert_eq!(backend.timestamp().await.unwrap(), START + 50);
+        assert_eq!(backend.get_status(req.id, None).await.unwrap(), RequestStatus::Unknown);
+        assert_eq!(backend.lock_request(&req, &Bytes::new(), None).await.unwrap(), U256::from(150));
+        assert_eq!(backend.get_status(req.id, None).await.unwrap(), RequestStatus::Locked);
+        assert_eq!(backend.balance_of_stake(alice).await.unwrap(), U256::from(600));
+
+        let err = bob_client.lock_request(&req, &Bytes::new(), None).await.unwrap_err();
+        assert!(matches!(err, MarketError::RequestIsLocked(id) if id == req.id));
+    }
+
+    struct Greedy {
+        min_price: U256,
+        proving_time: u64,
+        capacity: usize,
+    }
+
+    impl ProverStrategy for Greedy {
+        fn should_lock(&mut self, _request: &ProofRequest, price: U256, _now: u64) -> bool {
+            price >= self.min_price
+        }
+
+        fn should_fulfill(&mut self, _request: &ProofRequest, _price: U256, _now: u64) -> bool {
+            true
+        }
+
+        fn proving_time(&mut self, _request: &ProofRequest) -> u64 {
+            self.proving_time
+        }
+
+        fn capacity(&self) -> usize {
+            self.capacity
+        }
+    }
+
+    #[test]
+    fn simulate_many_orders() {
+        let clock = FakeClock::new(START);
+        let client = Address::repeat_byte(1);
+        let mut market = SimMarket::new(clock);
+        market.deposit(client, U256::from(1_000_000_000));
+        let (fast, slow) = (Address::repeat_byte(2), Address::repeat_byte(3));
+        let mut sim = Simulation::new(market, 10)
+            .with_prover(
+                fast,
+                U256::from(1_000_000),
+                Greedy { min_price: U256::from(150), proving_time: 60, capacity: 1_000 },
+            )
+            // Too slow to meet the lock deadline, so its locks get slashed.
+            .with_prover(
+                slow,
+                U256::from(1_000_000),
+                Greedy { min_price: U256::ZERO, proving_time: 400, capacity: 20 },
+            );
+
+        for index in 0..2_000u32 {
+            let mut req = request(client, index, b"journal", 100);
+            req.offer.biddingStart = START + index as u64;
+            sim.submit(req, b"journal".to_vec()).unwrap();
+        }
+        sim.run_until(START + 3_000);
+
+        let report = sim.report();
+        assert_eq!(report[&fast].fulfilled + report[&slow].fulfilled, 2_000);
+        assert!(report[&fast].earnings > U256::ZERO);
+        assert_eq!(report[&slow].slashed, report[&slow].locked);
+        assert_eq!(report[&fast].slashed, 0);
+        assert!(sim.market().slashable_requests().is_empty());
+    }
+}
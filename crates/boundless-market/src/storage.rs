@@ -16,6 +16,7 @@
 //! accessible to provers.
 
 use std::{
+    collections::BTreeMap,
     env::VarError,
     fmt::Debug,
     path::PathBuf,
@@ -40,10 +41,11 @@ use clap::{Parser, ValueEnum};
 use httpmock::MockServer;
 use reqwest::{
     multipart::{Form, Part},
-    Url,
+    Method, StatusCode, Url,
 };
 use sha2::{Digest as _, Sha256};
 use tempfile::TempDir;
+use tokio::sync::Mutex;
 use url::ParseError;
 
 #[async_trait]
@@ -76,6 +78,8 @@ pub enum BuiltinStorageProvider {
     Pinata(PinataStorageProvider),
     /// Temporary file storage provider, used for local testing.
     File(TempFileStorageProvider),
+    /// HTTP PUT storage provider, e.g. a WebDAV server.
+    Http(HttpStorageProvider),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -91,6 +95,9 @@ pub enum BuiltinStorageProviderError {
     /// Error type for the temporary file storage provider.
     #[error("temp file storage provider error")]
     File(#[from] TempFileStorageProviderError),
+    /// Error type for the HTTP storage provider.
+    #[error("HTTP storage provider error")]
+    Http(#[from] HttpStorageProviderError),
     /// Error type for an invalid storage provider.
     #[error("Invalid storage provider: {0}")]
     InvalidProvider(String),
@@ -109,18 +116,23 @@ pub enum StorageProviderType {
     Pinata,
     /// Temporary file storage provider.
     File,
+    /// HTTP PUT storage provider.
+    Http,
 }
 
 #[derive(Clone, Debug, Parser)]
 /// Configuration for the storage provider.
 pub struct StorageProviderConfig {
-    /// Storage provider to use [possible values: s3, pinata, file]
+    /// Storage provider to use [possible values: s3, pinata, file, http]
     ///
     /// - For 's3', the following options are required:
     ///   --s3-access-key, --s3-secret-key, --s3-bucket, --s3-url, --aws-region
     /// - For 'pinata', the following option is required:
     ///   --pinata-jwt (optionally, you can specify --pinata-api-url, --ipfs-gateway-url)
-    /// - For 'file', no additional options are required (optionally, you can specify --file-path)    
+    /// - For 'file', no additional options are required (optionally, you can specify --file-path)
+    /// - For 'http', the following option is required:
+    ///   --http-storage-url (optionally, you can specify --http-storage-public-url,
+    ///   --http-storage-auth-token)
     #[arg(long, env, value_enum, default_value_t = StorageProviderType::Pinata)]
     pub storage_provider: StorageProviderType,
 
@@ -159,6 +171,17 @@ pub struct StorageProviderConfig {
     /// Path for file storage provider
     #[arg(long)]
     pub file_path: Option<PathBuf>,
+
+    // **HTTP Storage Provider Options**
+    /// Base URL files are uploaded to with PUT requests
+    #[arg(long, env, required_if_eq("storage_provider", "http"))]
+    pub http_storage_url: Option<Url>,
+    /// Base URL files are publicly accessible at, if different from the upload URL
+    #[arg(long, env, requires("http_storage_url"))]
+    pub http_storage_public_url: Option<Url>,
+    /// Bearer token for authenticating uploads
+    #[arg(long, env, requires("http_storage_url"))]
+    pub http_storage_auth_token: Option<String>,
 }
 
 impl StorageProviderConfig {
@@ -176,6 +199,9 @@ impl StorageProviderConfig {
             pinata_api_url: None,
             ipfs_gateway_url: None,
             file_path: None,
+            http_storage_url: None,
+            http_storage_public_url: None,
+            http_storage_auth_token: None,
         }
     }
 }
@@ -189,6 +215,7 @@ impl StorageProvider for BuiltinStorageProvider {
             Self::S3(provider) => provider.upload_image(elf).await?,
             Self::Pinata(provider) => provider.upload_image(elf).await?,
             Self::File(provider) => provider.upload_image(elf).await?,
+            Self::Http(provider) => provider.upload_image(elf).await?,
         })
     }
 
@@ -197,6 +224,7 @@ impl StorageProvider for BuiltinStorageProvider {
             Self::S3(provider) => provider.upload_input(input).await?,
             Self::Pinata(provider) => provider.upload_input(input).await?,
             Self::File(provider) => provider.upload_input(input).await?,
+            Self::Http(provider) => provider.upload_input(input).await?,
         })
     }
 }
@@ -206,7 +234,9 @@ impl StorageProvider for BuiltinStorageProvider {
 /// If the environment variable `RISC0_DEV_MODE` is set, a temporary file storage provider is used.
 /// Otherwise, the following environment variables are checked in order:
 /// - `PINATA_JWT`, `PINATA_API_URL`, `IPFS_GATEWAY_URL`: Pinata storage provider;
-/// - `S3_ACCESS`, `S3_SECRET`, `S3_BUCKET`, `S3_URL`, `AWS_REGION`: S3 storage provider.
+/// - `S3_ACCESS`, `S3_SECRET`, `S3_BUCKET`, `S3_URL`, `AWS_REGION`: S3 storage provider;
+/// - `HTTP_STORAGE_URL`, `HTTP_STORAGE_PUBLIC_URL`, `HTTP_STORAGE_AUTH_TOKEN`: HTTP storage
+///   provider.
 pub async fn storage_provider_from_env(
 ) -> Result<BuiltinStorageProvider, BuiltinStorageProviderError> {
     if risc0_zkvm::is_dev_mode() {
@@ -221,6 +251,10 @@ pub async fn storage_provider_from_env(
         return Ok(BuiltinStorageProvider::S3(provider));
     }
 
+    if let Ok(provider) = HttpStorageProvider::from_env() {
+        return Ok(BuiltinStorageProvider::Http(provider));
+    }
+
     Err(BuiltinStorageProviderError::NoProvider)
 }
 
@@ -241,6 +275,10 @@ pub async fn storage_provider_from_config(
             let provider = TempFileStorageProvider::from_config(config)?;
             Ok(BuiltinStorageProvider::File(provider))
         }
+        StorageProviderType::Http => {
+            let provider = HttpStorageProvider::from_config(config)?;
+            Ok(BuiltinStorageProvider::Http(provider))
+        }
     }
 }
 
@@ -680,6 +718,299 @@ impl StorageProvider for TempFileStorageProvider {
     }
 }
 
+#[derive(Clone, Debug)]
+/// Storage provider that uploads ELFs and inputs with HTTP PUT requests.
+///
+/// This works with any server accepting PUT requests, such as a WebDAV server, so that storage can
+/// be self-hosted. Files are stored under content-addressed paths, and uploads are skipped if a
+/// HEAD request shows the file is already present.
+pub struct HttpStorageProvider {
+    client: reqwest::Client,
+    upload_url: Url,
+    public_url: Url,
+    auth_token: Option<String>,
+}
+
+#[derive(thiserror::Error, Debug)]
+/// Error type for the HTTP storage provider.
+pub enum HttpStorageProviderError {
+    /// Error type for reqwest errors.
+    #[error("request error: {0}")]
+    Reqwest(#[from] reqwest::Error),
+
+    /// Error type for URL parsing errors.
+    #[error("url parse error: {0}")]
+    UrlParse(#[from] url::ParseError),
+
+    /// Error type for environment variable errors.
+    #[error("environment variable error: {0}")]
+    EnvVar(#[from] VarError),
+
+    /// Error type for missing configuration parameters.
+    #[error("missing config parameter: {0}")]
+    Config(String),
+
+    /// Error type for other errors.
+    #[error("{0}")]
+    Other(#[from] anyhow::Error),
+}
+
+impl HttpStorageProvider {
+    /// Creates a new HTTP storage provider from the environment variables.
+    pub fn from_env() -> Result<Self, HttpStorageProviderError> {
+        let upload_url = Url::parse(&std::env::var("HTTP_STORAGE_URL")?)?;
+        let public_url = match std::env::var("HTTP_STORAGE_PUBLIC_URL") {
+            Ok(url) => Some(Url::parse(&url)?),
+            Err(VarError::NotPresent) => None,
+            Err(e) => return Err(e.into()),
+        };
+        let auth_token = match std::env::var("HTTP_STORAGE_AUTH_TOKEN") {
+            Ok(token) => Some(token),
+            Err(VarError::NotPresent) => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self::from_parts(upload_url, public_url, auth_token))
+    }
+
+    /// Creates a new HTTP storage provider from the given parts.
+    ///
+    /// Files are uploaded under `upload_url`, and are accessible under `public_url`, which
+    /// defaults to the upload URL.
+    pub fn from_parts(
+        upload_url: Url,
+        public_url: Option<Url>,
+        auth_token: Option<String>,
+    ) -> Self {
+        let public_url = public_url.unwrap_or_else(|| upload_url.clone());
+        Self {
+            client: reqwest::Client::new(),
+            upload_url: with_trailing_slash(upload_url),
+            public_url: with_trailing_slash(public_url),
+            auth_token,
+        }
+    }
+
+    /// Creates a new HTTP storage provider from the given configuration.
+    pub fn from_config(config: &StorageProviderConfig) -> Result<Self, HttpStorageProviderError> {
+        let upload_url = config
+            .http_storage_url
+            .clone()
+            .ok_or_else(|| HttpStorageProviderError::Config("http_storage_url".to_string()))?;
+
+        Ok(Self::from_parts(
+            upload_url,
+            config.http_storage_public_url.clone(),
+            config.http_storage_auth_token.clone(),
+        ))
+    }
+
+    fn request(&self, method: Method, url: Url) -> reqwest::RequestBuilder {
+        let request = self.client.request(method, url);
+        match &self.auth_token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    async fn upload(
+        &self,
+        data: impl AsRef<[u8]>,
+        dir: &str,
+        filename: &str,
+    ) -> Result<Url, HttpStorageProviderError> {
+        let key = format!("{dir}/{filename}");
+        let upload_url = self.upload_url.join(&key)?;
+        let public_url = self.public_url.join(&key)?;
+
+        let head = self.request(Method::HEAD, upload_url.clone()).send().await?;
+        if head.status().is_success() {
+            tracing::debug!("Skipping upload of {key}, already present");
+            return Ok(public_url);
+        }
+
+        let put = || self.request(Method::PUT, upload_url.clone()).body(data.as_ref().to_vec());
+        let response = put().send().await?;
+        if response.status() == StatusCode::CONFLICT {
+            // WebDAV servers reject uploads to a missing collection, so create it and retry.
+            let mkcol = Method::from_bytes(b"MKCOL").context("invalid method")?;
+            let collection = self.upload_url.join(&format!("{dir}/"))?;
+            let response = self.request(mkcol, collection).send().await?;
+            if response.status() != StatusCode::METHOD_NOT_ALLOWED {
+                response.error_for_status()?;
+            }
+            put().send().await?.error_for_status()?;
+        } else {
+            response.error_for_status()?;
+        }
+
+        Ok(public_url)
+    }
+}
+
+fn with_trailing_slash(mut url: Url) -> Url {
+    if !url.path().ends_with('/') {
+        url.set_path(&format!("{}/", url.path()));
+    }
+    url
+}
+
+#[async_trait]
+impl StorageProvider for HttpStorageProvider {
+    type Error = HttpStorageProviderError;
+
+    async fn upload_image(&self, elf: &[u8]) -> Result<Url, Self::Error> {
+        let image_id = risc0_zkvm::compute_image_id(elf)?;
+        self.upload(elf, "image", &image_id.to_string()).await
+    }
+
+    async fn upload_input(&self, input: &[u8]) -> Result<Url, Self::Error> {
+        let digest = Sha256::digest(input);
+        self.upload(input, "input", &hex::encode(digest.as_slice())).await
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Storage provider wrapper that skips uploading content that was already uploaded.
+///
+/// Images are identified by their image ID, and inputs by their SHA-256 digest. The URLs of
+/// uploaded content are recorded in a manifest, which can be persisted to a local file so that
+/// uploads are reused across runs. Before a URL is reused, the content is checked to still be
+/// available, with a HEAD request for HTTP URLs.
+pub struct ContentAddressedStorageProvider<S> {
+    inner: S,
+    client: reqwest::Client,
+    manifest: Arc<Mutex<BTreeMap<String, String>>>,
+    manifest_path: Option<PathBuf>,
+    verify: bool,
+}
+
+#[derive(thiserror::Error, Debug)]
+/// Error type for the content-addressed storage provider.
+pub enum ContentAddressedStorageError<E: Debug> {
+    /// Error from the wrapped storage provider.
+    #[error("storage provider error: {0:?}")]
+    Provider(E),
+
+    /// Error type for IO errors on the manifest file.
+    #[error("manifest IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Error type for manifest (de)serialization errors.
+    #[error("manifest JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// Error type for other errors.
+    #[error("{0}")]
+    Other(#[from] anyhow::Error),
+}
+
+impl<S: StorageProvider> ContentAddressedStorageProvider<S> {
+    /// Wraps the given storage provider, with an in-memory manifest.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            client: reqwest::Client::new(),
+            manifest: Default::default(),
+            manifest_path: None,
+            verify: true,
+        }
+    }
+
+    /// Persists the manifest to the given file, loading the uploads it records if it exists.
+    pub async fn with_manifest(
+        self,
+        path: impl Into<PathBuf>,
+    ) -> Result<Self, ContentAddressedStorageError<S::Error>> {
+        let path = path.into();
+        let manifest = match tokio::fs::read(&path).await {
+            Ok(contents) => serde_json::from_slice(&contents)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Self { manifest: Arc::new(Mutex::new(manifest)), manifest_path: Some(path), ..self })
+    }
+
+    /// Sets whether recorded content is checked to still be available before being reused.
+    ///
+    /// Enabled by default.
+    pub fn with_verification(self, verify: bool) -> Self {
+        Self { verify, ..self }
+    }
+
+    /// Returns the wrapped storage provider.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    async fn lookup(&self, key: &str) -> Option<Url> {
+        let url = Url::parse(self.manifest.lock().await.get(key)?).ok()?;
+        if self.verify && !self.is_available(&url).await {
+            tracing::debug!("Recorded upload of {key} is no longer available: {url}");
+            return None;
+        }
+        Some(url)
+    }
+
+    async fn is_available(&self, url: &Url) -> bool {
+        match url.scheme() {
+            "http" | "https" => match self.client.head(url.clone()).send().await {
+                Ok(response) => response.status().is_success(),
+                Err(_) => false,
+            },
+            "file" => url.to_file_path().map(|path| path.exists()).unwrap_or(false),
+            // Other schemes, such as s3://, cannot be checked without credentials.
+            _ => true,
+        }
+    }
+
+    async fn record(
+        &self,
+        key: String,
+        url: &Url,
+    ) -> Result<(), ContentAddressedStorageError<S::Error>> {
+        let mut manifest = self.manifest.lock().await;
+        manifest.insert(key, url.to_string());
+        if let Some(path) = &self.manifest_path {
+            tokio::fs::write(path, serde_json::to_vec_pretty(&*manifest)?).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<S> StorageProvider for ContentAddressedStorageProvider<S>
+where
+    S: StorageProvider + Send + Sync,
+    S::Error: Send,
+{
+    type Error = ContentAddressedStorageError<S::Error>;
+
+    async fn upload_image(&self, elf: &[u8]) -> Result<Url, Self::Error> {
+        let key = format!("image/{}", risc0_zkvm::compute_image_id(elf)?);
+        if let Some(url) = self.lookup(&key).await {
+            tracing::debug!("Skipping upload of {key}, already uploaded to {url}");
+            return Ok(url);
+        }
+        let url =
+            self.inner.upload_image(elf).await.map_err(ContentAddressedStorageError::Provider)?;
+        self.record(key, &url).await?;
+        Ok(url)
+    }
+
+    async fn upload_input(&self, input: &[u8]) -> Result<Url, Self::Error> {
+        let key = format!("input/{}", hex::encode(Sha256::digest(input).as_slice()));
+        if let Some(url) = self.lookup(&key).await {
+            tracing::debug!("Skipping upload of {key}, already uploaded to {url}");
+            return Ok(url);
+        }
+        let url =
+            self.inner.upload_input(input).await.map_err(ContentAddressedStorageError::Provider)?;
+        self.record(key, &url).await?;
+        Ok(url)
+    }
+}
+
 /// A `StorageProvider` implementation for testing using [MockServer].
 ///
 /// This provider doesn't actually upload files to a real storage system. Instead, it:
@@ -766,6 +1097,78 @@ mod tests {
         println!("Input URL: {}", input_url);
     }
 
+    #[tokio::test]
+    async fn test_http_storage_provider() {
+        let server = MockServer::start();
+        let base_url = Url::parse(&server.url("/dav")).unwrap();
+        let provider = HttpStorageProvider::from_parts(base_url, None, Some("token".into()));
+
+        let input_data = b"test input data";
+        let input_path = format!("/dav/input/{}", hex::encode(Sha256::digest(input_data)));
+        let head = server.mock(|when, then| {
+            when.method(httpmock::Method::HEAD).path(&input_path);
+            then.status(404);
+        });
+        let put = server.mock(|when, then| {
+            when.method(httpmock::Method::PUT)
+                .path(&input_path)
+                .header("authorization", "Bearer token")
+                .body(String::from_utf8_lossy(input_data));
+            then.status(201);
+        });
+
+        let input_url = provider.upload_input(input_data).await.unwrap();
+        assert_eq!(input_url.path(), input_path);
+        head.assert();
+        put.assert();
+
+        // Content already present is not uploaded again.
+        let image_data = guest_util::ECHO_ELF;
+        let image_id = risc0_zkvm::compute_image_id(image_data).unwrap();
+        let image_path = format!("/dav/image/{image_id}");
+        server.mock(|when, then| {
+            when.method(httpmock::Method::HEAD).path(&image_path);
+            then.status(200);
+        });
+        let put = server.mock(|when, then| {
+            when.method(httpmock::Method::PUT).path(&image_path);
+            then.status(201);
+        });
+
+        let image_url = provider.upload_image(image_data).await.unwrap();
+        assert_eq!(image_url.path(), image_path);
+        put.assert_hits(0);
+    }
+
+    #[tokio::test]
+    async fn test_content_addressed_storage_provider() {
+        let manifest_dir = tempfile::tempdir().unwrap();
+        let manifest_path = manifest_dir.path().join("manifest.json");
+        let storage = ContentAddressedStorageProvider::new(MockStorageProvider::start())
+            .with_manifest(&manifest_path)
+            .await
+            .unwrap();
+
+        let input_data = b"test input data";
+        let input_url = storage.upload_input(input_data).await.unwrap();
+        // The mock server serves the upload with GET only, so skip the availability check.
+        let storage = storage.with_verification(false);
+        assert_eq!(storage.upload_input(input_data).await.unwrap(), input_url);
+        assert_ne!(storage.upload_input(b"other input").await.unwrap(), input_url);
+
+        // Uploads recorded in the manifest are reused across instances.
+        let storage = ContentAddressedStorageProvider::new(MockStorageProvider::start())
+            .with_manifest(&manifest_path)
+            .await
+            .unwrap()
+            .with_verification(false);
+        assert_eq!(storage.upload_input(input_data).await.unwrap(), input_url);
+
+        // Unavailable content is uploaded again.
+        let storage = storage.with_verification(true);
+        assert_ne!(storage.upload_input(input_data).await.unwrap(), input_url);
+    }
+
     #[tokio::test]
     async fn test_mock_storage_provider() {
         // Create our mock storage provider
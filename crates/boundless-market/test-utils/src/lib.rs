@@ -12,17 +12,25 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
 use alloy::{
     network::EthereumWallet,
     node_bindings::AnvilInstance,
     primitives::{Address, Bytes, FixedBytes},
     providers::{ext::AnvilApi, Provider, ProviderBuilder, WalletProvider},
-    signers::local::PrivateKeySigner,
+    signers::{local::PrivateKeySigner, SignerSync},
     sol_types::SolCall,
 };
 use alloy_primitives::{B256, U256};
 use alloy_sol_types::{Eip712Domain, SolStruct, SolValue};
 use anyhow::{Context, Ok, Result};
+use axum::{
+    extract::State,
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
 use boundless_market::contracts::{
     boundless_market::BoundlessMarketService,
     bytecode::*,
@@ -44,6 +52,9 @@ use risc0_zkvm::{
     FakeReceipt, Groth16ReceiptVerifierParameters, InnerReceipt, Journal, MaybePruned, Receipt,
     ReceiptClaim,
 };
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use url::Url;
 
 pub struct TestCtx<P> {
     pub verifier_address: Address,
@@ -428,3 +439,80 @@ pub fn mock_singleton(
 
     (to_b256(set_builder_root), set_builder_seal.into(), fulfillment, assessor_seal.into())
 }
+
+#[derive(Serialize, Deserialize)]
+struct RemoteAddressResponse {
+    address: Address,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RemoteSignRequest {
+    hash: B256,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RemoteSignResponse {
+    signature: Bytes,
+}
+
+/// Spawns a local stand-in for a remote signing service, as used by
+/// [boundless_market::signer::RemoteSigner], signing with the given key.
+///
+/// If a token is given, requests without it as their bearer token are rejected.
+///
+/// Returns the URL of the service, and the handle of the server task.
+pub async fn spawn_remote_signer(
+    signer: PrivateKeySigner,
+    token: Option<&str>,
+) -> Result<(Url, JoinHandle<()>)> {
+    struct RemoteSignerState {
+        signer: PrivateKeySigner,
+        token: Option<String>,
+    }
+
+    impl RemoteSignerState {
+        fn authorize(&self, headers: &HeaderMap) -> Result<(), (StatusCode, String)> {
+            let Some(token) = &self.token else {
+                return Ok(());
+            };
+            let authorization = headers.get(AUTHORIZATION).and_then(|value| value.to_str().ok());
+            if authorization != Some(format!("Bearer {token}").as_str()) {
+                return Err((StatusCode::UNAUTHORIZED, "invalid token".into()));
+            }
+            Ok(())
+        }
+    }
+
+    async fn address(
+        State(state): State<Arc<RemoteSignerState>>,
+        headers: HeaderMap,
+    ) -> Result<Json<RemoteAddressResponse>, (StatusCode, String)> {
+        state.authorize(&headers)?;
+        Ok(Json(RemoteAddressResponse { address: state.signer.address() }))
+    }
+
+    async fn sign(
+        State(state): State<Arc<RemoteSignerState>>,
+        headers: HeaderMap,
+        Json(request): Json<RemoteSignRequest>,
+    ) -> Result<Json<RemoteSignResponse>, (StatusCode, String)> {
+        state.authorize(&headers)?;
+        state
+            .signer
+            .sign_hash_sync(&request.hash)
+            .map(|signature| Json(RemoteSignResponse { signature: signature.as_bytes().into() }))
+            .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))
+    }
+
+    let state = RemoteSignerState { signer, token: token.map(str::to_string) };
+    let app = Router::new()
+        .route("/address", get(address))
+        .route("/sign", post(sign))
+        .with_state(Arc::new(state));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let url = Url::parse(&format!("http://{}/", listener.local_addr()?))?;
+    let handle = tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    Ok((url, handle))
+}
@@ -12,25 +12,38 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
+
 use alloy::{
     consensus::Transaction,
     node_bindings::Anvil,
-    primitives::{aliases::U160, utils::parse_ether, Address, U256},
-    providers::Provider,
+    primitives::{aliases::U160, utils::parse_ether, Address, Bytes, U256},
+    providers::{ext::AnvilApi, Provider, ProviderBuilder},
     sol_types::eip712_domain,
 };
 use alloy_sol_types::SolCall;
+#[cfg(feature = "indexer")]
+use boundless_market::indexer::{EventFilter, EventKind, IndexerDb, MarketIndexer};
 use boundless_market::{
+    alerts::{AlertLevel, BalanceMonitor, BalanceSource, BalanceWatch},
+    client::{Client, RequestEvent},
+    clock::{ChainClock, Clock},
     contracts::{
-        hit_points::default_allowance, AssessorReceipt, IBoundlessMarket, Offer, Predicate,
-        PredicateType, ProofRequest, RequestId, RequestStatus, Requirements,
+        boundless_market::MarketError,
+        hit_points::default_allowance,
+        offer_builder::{OfferBuilder, PricingTier},
+        AssessorReceipt, IBoundlessMarket, Offer, Predicate, PredicateType, ProofRequest,
+        RequestId, RequestStatus, Requirements,
     },
     input::InputBuilder,
+    storage::BuiltinStorageProvider,
 };
 use boundless_market_test_utils::{create_test_ctx, mock_singleton, TestCtx};
+use futures_util::StreamExt;
 use guest_assessor::{ASSESSOR_GUEST_ID, ASSESSOR_GUEST_PATH};
 use guest_set_builder::{SET_BUILDER_ID, SET_BUILDER_PATH};
 use guest_util::ECHO_ID;
+use risc0_ethereum_contracts::event_query::EventQueryConfig;
 use risc0_zkvm::sha::Digest;
 use tracing_test::traced_test;
 
@@ -92,7 +105,11 @@ async fn test_deposit_withdraw() {
     );
 
     // Withdraw when balance is zero
-    assert!(ctx.prover_market.withdraw(parse_ether("2").unwrap()).await.is_err());
+    let err = ctx.prover_market.withdraw(parse_ether("2").unwrap()).await.unwrap_err();
+    assert!(
+        matches!(err, MarketError::InsufficientBalance(addr) if addr == ctx.prover_signer.address()),
+        "unexpected error: {err:?}"
+    );
 }
 
 #[tokio::test]
@@ -101,7 +118,7 @@ async fn test_deposit_withdraw_stake() {
     // Setup anvil
     let anvil = Anvil::new().spawn();
 
-    let mut ctx = create_test_ctx(
+    let ctx = create_test_ctx(
         &anvil,
         SET_BUILDER_ID,
         format!("file://{SET_BUILDER_PATH}"),
@@ -114,8 +131,13 @@ async fn test_deposit_withdraw_stake() {
     let deposit = U256::from(10);
 
     // set stake balance alerts
-    ctx.prover_market =
-        ctx.prover_market.with_stake_balance_alert(&Some(U256::from(10)), &Some(U256::from(5)));
+    let monitor = BalanceMonitor::new(ctx.prover_provider.clone())
+        .with_market_address(ctx.boundless_market_address)
+        .with_watch(
+            BalanceWatch::new(BalanceSource::Stake, ctx.prover_signer.address())
+                .with_warn_below(U256::from(10))
+                .with_error_below(U256::from(5)),
+        );
 
     // Approve and deposit stake
     ctx.prover_market.approve_deposit_stake(deposit).await.unwrap();
@@ -128,6 +150,7 @@ async fn test_deposit_withdraw_stake() {
         ctx.prover_market.balance_of_stake(ctx.prover_signer.address()).await.unwrap(),
         U256::from(20)
     );
+    assert!(monitor.check().await.unwrap().is_empty());
 
     // Withdraw prover balances in chunks to observe alerts
 
@@ -136,12 +159,14 @@ async fn test_deposit_withdraw_stake() {
         ctx.prover_market.balance_of_stake(ctx.prover_signer.address()).await.unwrap(),
         U256::from(9)
     );
+    assert_eq!(monitor.check().await.unwrap()[0].level, AlertLevel::Warning);
 
     ctx.prover_market.withdraw_stake(U256::from(5)).await.unwrap();
     assert_eq!(
         ctx.prover_market.balance_of_stake(ctx.prover_signer.address()).await.unwrap(),
         U256::from(4)
     );
+    assert_eq!(monitor.check().await.unwrap()[0].level, AlertLevel::Error);
 
     ctx.prover_market.withdraw_stake(U256::from(4)).await.unwrap();
     assert_eq!(
@@ -150,7 +175,32 @@ async fn test_deposit_withdraw_stake() {
     );
 
     // Withdraw when balance is zero
-    assert!(ctx.prover_market.withdraw_stake(U256::from(20)).await.is_err());
+    let err = ctx.prover_market.withdraw_stake(U256::from(20)).await.unwrap_err();
+    assert!(
+        matches!(err, MarketError::InsufficientBalance(addr) if addr == ctx.prover_signer.address()),
+        "unexpected error: {err:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_chain_clock() {
+    let anvil = Anvil::new().spawn();
+    let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+
+    // Move the chain a day ahead of the system clock.
+    let chain_time = now_timestamp() + 24 * 60 * 60;
+    provider.anvil_set_next_block_timestamp(chain_time).await.unwrap();
+    provider.anvil_mine(Some(1), None).await.unwrap();
+
+    let clock = ChainClock::new(provider.clone());
+    let now = clock.sync().await.unwrap();
+    assert!((chain_time..chain_time + 5).contains(&now));
+    assert!((chain_time..chain_time + 5).contains(&clock.now()));
+
+    provider.anvil_mine(Some(2), Some(12)).await.unwrap();
+    clock.sync().await.unwrap();
+    assert_eq!(clock.latest_block(), Some(3));
+    assert!(clock.now() >= now);
 }
 
 #[tokio::test]
@@ -180,6 +230,166 @@ async fn test_submit_request() {
     assert!(log.requestId == request_id);
 }
 
+#[tokio::test]
+async fn test_submit_requests() {
+    // Setup anvil
+    let anvil = Anvil::new().spawn();
+
+    let ctx = create_test_ctx(
+        &anvil,
+        SET_BUILDER_ID,
+        format!("file://{SET_BUILDER_PATH}"),
+        ASSESSOR_GUEST_ID,
+        format!("file://{ASSESSOR_GUEST_PATH}"),
+    )
+    .await
+    .unwrap();
+
+    let client: Client<_, BuiltinStorageProvider> =
+        Client::new(ctx.customer_market.clone(), ctx.set_verifier.clone());
+    assert!(ctx.customer_market.supports_multicall().await.unwrap());
+
+    // Requests without an ID are assigned one.
+    let mut unassigned = new_request(2, &ctx).await;
+    unassigned.id = U256::ZERO;
+    let requests = [new_request(1, &ctx).await, unassigned, new_request(3, &ctx).await];
+    let submitted =
+        client.submit_requests_with_signer(&requests, &ctx.customer_signer).await.unwrap();
+    assert_eq!(submitted.len(), 3);
+    assert_eq!(submitted[0].0, requests[0].id);
+    assert_ne!(submitted[1].0, U256::ZERO);
+    assert_eq!(submitted[2].0, requests[2].id);
+
+    // The total max price is deposited at once.
+    let balance = ctx.customer_market.balance_of(ctx.customer_signer.address()).await.unwrap();
+    assert_eq!(balance, requests[0].offer.maxPrice * U256::from(3));
+
+    // All requests are submitted in a single transaction.
+    let logs = ctx.customer_market.instance().RequestSubmitted_filter().query().await.unwrap();
+    let submitted_ids: Vec<_> = logs.iter().map(|(log, _)| log.requestId).collect();
+    assert_eq!(submitted_ids, submitted.iter().map(|(id, _)| *id).collect::<Vec<_>>());
+    assert!(logs.iter().all(|(_, log)| log.transaction_hash == logs[0].1.transaction_hash));
+}
+
+#[tokio::test]
+async fn test_resubmit_request() {
+    // Setup anvil
+    let anvil = Anvil::new().spawn();
+
+    let ctx = create_test_ctx(
+        &anvil,
+        SET_BUILDER_ID,
+        format!("file://{SET_BUILDER_PATH}"),
+        ASSESSOR_GUEST_ID,
+        format!("file://{ASSESSOR_GUEST_PATH}"),
+    )
+    .await
+    .unwrap();
+
+    let client: Client<_, BuiltinStorageProvider> =
+        Client::new(ctx.customer_market.clone(), ctx.set_verifier.clone());
+
+    let request = new_request(1, &ctx).await;
+    let (request_id, _) =
+        client.submit_request_with_signer(&request, &ctx.customer_signer).await.unwrap();
+
+    // The resubmitted request keeps its ID, with a higher price.
+    let offer = request.offer.clone().with_price_bump(50);
+    let (resubmitted_id, _) = client
+        .resubmit_request_with_signer(&request, offer.clone(), &ctx.customer_signer)
+        .await
+        .unwrap();
+    assert_eq!(resubmitted_id, request_id);
+
+    let logs = ctx.customer_market.instance().RequestSubmitted_filter().query().await.unwrap();
+    assert_eq!(logs.len(), 2);
+    let (resubmitted, client_sig) = ctx
+        .customer_market
+        .get_submitted_request(request_id, logs[1].1.transaction_hash)
+        .await
+        .unwrap();
+    assert_eq!(resubmitted.offer.maxPrice, offer.maxPrice);
+    assert!(resubmitted.offer.biddingStart >= request.offer.biddingStart);
+
+    // Once locked, the request can no longer be resubmitted.
+    ctx.prover_market
+        .deposit_stake_with_permit(default_allowance(), &ctx.prover_signer)
+        .await
+        .unwrap();
+    ctx.prover_market.lock_request(&resubmitted, &client_sig, None).await.unwrap();
+    client
+        .resubmit_request_with_signer(&resubmitted, offer.with_price_bump(50), &ctx.customer_signer)
+        .await
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn test_offer_builder_history() {
+    // Setup anvil
+    let anvil = Anvil::new().spawn();
+
+    let ctx = create_test_ctx(
+        &anvil,
+        SET_BUILDER_ID,
+        format!("file://{SET_BUILDER_PATH}"),
+        ASSESSOR_GUEST_ID,
+        format!("file://{ASSESSOR_GUEST_PATH}"),
+    )
+    .await
+    .unwrap();
+    ctx.prover_market
+        .deposit_stake_with_permit(default_allowance(), &ctx.prover_signer)
+        .await
+        .unwrap();
+
+    // Lock two echo requests at the end of their ramp-up, then a request for another image at
+    // the start of its ramp-up, each in its own block.
+    for idx in 1..=3 {
+        let mut request = new_request(idx, &ctx).await;
+        request.offer.biddingStart -= 10;
+        if idx == 3 {
+            request.requirements = request.requirements.with_image_id(Digest::ZERO);
+            request.offer.rampUpPeriod = 90;
+        }
+        ctx.customer_market.submit_request(&request, &ctx.customer_signer).await.unwrap();
+        let client_sig: Bytes = request
+            .sign_request(
+                &ctx.customer_signer,
+                *ctx.customer_market.instance().address(),
+                anvil.chain_id(),
+            )
+            .await
+            .unwrap()
+            .as_bytes()
+            .into();
+        ctx.prover_market.lock_request(&request, &client_sig, None).await.unwrap();
+    }
+
+    // Query the events one block at a time.
+    let market = ctx.customer_market.clone().with_event_query_config(EventQueryConfig::new(100, 1));
+    let builder =
+        OfferBuilder::new(PricingTier::Standard).with_cycles(1_000_000).with_history_blocks(100);
+
+    // The median lock was at the end of the ramp-up.
+    let offer = builder.clone().build(&market).await.unwrap();
+    assert_eq!(offer.minPrice, offer.maxPrice);
+    let offer = builder.clone().with_history_image_id(ECHO_ID).build(&market).await.unwrap();
+    assert_eq!(offer.minPrice, offer.maxPrice);
+
+    // Only the most recent lock is sampled, at the start of its ramp-up.
+    let offer = builder.clone().with_max_history_samples(1).build(&market).await.unwrap();
+    assert!(offer.minPrice < offer.maxPrice);
+
+    // The most recent lock is not of the echo image, so no lock is sampled.
+    let offer = builder
+        .with_history_image_id(ECHO_ID)
+        .with_max_history_samples(1)
+        .build(&market)
+        .await
+        .unwrap();
+    assert_eq!(offer.minPrice, U256::from(1_000_000_000_000_000u64));
+}
+
 #[tokio::test]
 #[traced_test]
 async fn test_e2e() {
@@ -549,3 +759,206 @@ async fn test_e2e_no_payment() {
     //assert_eq!(journal, fulfillment.journal);
     //assert_eq!(seal, fulfillment.seal);
 }
+
+#[cfg(feature = "indexer")]
+#[tokio::test]
+async fn test_indexer() {
+    // Setup anvil
+    let anvil = Anvil::new().spawn();
+
+    let ctx = create_test_ctx(
+        &anvil,
+        SET_BUILDER_ID,
+        format!("file://{SET_BUILDER_PATH}"),
+        ASSESSOR_GUEST_ID,
+        format!("file://{ASSESSOR_GUEST_PATH}"),
+    )
+    .await
+    .unwrap();
+
+    let eip712_domain = eip712_domain! {
+        name: "IBoundlessMarket",
+        version: "1",
+        chain_id: anvil.chain_id(),
+        verifying_contract: *ctx.customer_market.instance().address(),
+    };
+
+    let db = IndexerDb::new("sqlite::memory:").await.unwrap();
+    let indexer = MarketIndexer::new(ctx.customer_market.clone(), db)
+        .with_start_block(0)
+        .with_block_range(2)
+        .with_confirmations(0);
+
+    let request = new_request(1, &ctx).await;
+    let request_id =
+        ctx.customer_market.submit_request(&request, &ctx.customer_signer).await.unwrap();
+    let client_sig: Bytes = request
+        .sign_request(
+            &ctx.customer_signer,
+            *ctx.customer_market.instance().address(),
+            anvil.chain_id(),
+        )
+        .await
+        .unwrap()
+        .as_bytes()
+        .into();
+
+    // Index up to the submission, then resume from the checkpoint after the fulfillment.
+    let last_block = indexer.sync().await.unwrap();
+    assert_eq!(indexer.db().get_last_block().await.unwrap(), Some(last_block));
+
+    ctx.prover_market
+        .deposit_stake_with_permit(default_allowance(), &ctx.prover_signer)
+        .await
+        .unwrap();
+    ctx.prover_market.lock_request(&request, &client_sig, None).await.unwrap();
+    let (root, set_verifier_seal, fulfillment, assessor_seal) =
+        mock_singleton(&request, eip712_domain, ctx.prover_signer.address());
+    ctx.set_verifier.submit_merkle_root(root, set_verifier_seal).await.unwrap();
+    let assessor_fill = AssessorReceipt {
+        seal: assessor_seal,
+        selectors: vec![],
+        prover: ctx.prover_signer.address(),
+        callbacks: vec![],
+    };
+    ctx.prover_market.fulfill(&fulfillment, assessor_fill).await.unwrap();
+    assert!(indexer.sync().await.unwrap() > last_block);
+
+    let kinds: Vec<_> =
+        indexer.request_events(request_id).await.unwrap().into_iter().map(|e| e.kind).collect();
+    assert_eq!(
+        kinds,
+        [EventKind::Submitted, EventKind::Locked, EventKind::Fulfilled, EventKind::ProofDelivered]
+    );
+
+    let prover_events = indexer
+        .events(&EventFilter::default().with_prover(ctx.prover_signer.address()))
+        .await
+        .unwrap();
+    assert_eq!(prover_events.len(), 3);
+    let client_events = indexer
+        .events(&EventFilter::default().with_client(ctx.customer_signer.address()))
+        .await
+        .unwrap();
+    assert_eq!(client_events.len(), 4);
+}
+
+#[cfg(feature = "indexer")]
+#[tokio::test]
+async fn test_indexer_reorg() {
+    // Setup anvil
+    let anvil = Anvil::new().spawn();
+
+    let ctx = create_test_ctx(
+        &anvil,
+        SET_BUILDER_ID,
+        format!("file://{SET_BUILDER_PATH}"),
+        ASSESSOR_GUEST_ID,
+        format!("file://{ASSESSOR_GUEST_PATH}"),
+    )
+    .await
+    .unwrap();
+
+    let db = IndexerDb::new("sqlite::memory:").await.unwrap();
+    let indexer = MarketIndexer::new(ctx.customer_market.clone(), db)
+        .with_start_block(0)
+        .with_confirmations(0);
+
+    // Index the deployment, so a checkpoint before the snapshot is recorded.
+    indexer.sync().await.unwrap();
+    let snapshot = ctx.customer_provider.anvil_snapshot().await.unwrap();
+    let request = new_request(1, &ctx).await;
+    let request_id =
+        ctx.customer_market.submit_request(&request, &ctx.customer_signer).await.unwrap();
+    indexer.sync().await.unwrap();
+    assert!(!indexer.request_events(request_id).await.unwrap().is_empty());
+
+    // Replace the submission with a deposit, so the blocks after the snapshot change.
+    assert!(ctx.customer_provider.anvil_revert(snapshot).await.unwrap());
+    ctx.customer_market.deposit(parse_ether("0.1").unwrap()).await.unwrap();
+    ctx.customer_provider.anvil_mine(Some(2), None).await.unwrap();
+
+    indexer.sync().await.unwrap();
+    assert!(indexer.request_events(request_id).await.unwrap().is_empty());
+    let deposits = indexer
+        .events(
+            &EventFilter::default()
+                .with_account(ctx.customer_signer.address())
+                .with_kinds([EventKind::Deposit]),
+        )
+        .await
+        .unwrap();
+    assert!(deposits.iter().any(|event| event.value == Some(parse_ether("0.1").unwrap())));
+}
+
+#[tokio::test]
+async fn test_watch_request() {
+    // Setup anvil
+    let anvil = Anvil::new().spawn();
+
+    let ctx = create_test_ctx(
+        &anvil,
+        SET_BUILDER_ID,
+        format!("file://{SET_BUILDER_PATH}"),
+        ASSESSOR_GUEST_ID,
+        format!("file://{ASSESSOR_GUEST_PATH}"),
+    )
+    .await
+    .unwrap();
+
+    let eip712_domain = eip712_domain! {
+        name: "IBoundlessMarket",
+        version: "1",
+        chain_id: anvil.chain_id(),
+        verifying_contract: *ctx.customer_market.instance().address(),
+    };
+
+    let request = new_request(1, &ctx).await;
+    let request_id =
+        ctx.customer_market.submit_request(&request, &ctx.customer_signer).await.unwrap();
+    let client_sig: Bytes = request
+        .sign_request(
+            &ctx.customer_signer,
+            *ctx.customer_market.instance().address(),
+            anvil.chain_id(),
+        )
+        .await
+        .unwrap()
+        .as_bytes()
+        .into();
+
+    ctx.prover_market
+        .deposit_stake_with_permit(default_allowance(), &ctx.prover_signer)
+        .await
+        .unwrap();
+    ctx.prover_market.lock_request(&request, &client_sig, None).await.unwrap();
+
+    let client: Client<_, BuiltinStorageProvider> =
+        Client::new(ctx.customer_market.clone(), ctx.set_verifier.clone());
+    let mut events = Box::pin(client.watch_request(request_id, Duration::from_millis(100)));
+
+    // Past events are replayed when the watch starts.
+    assert_eq!(events.next().await, Some(RequestEvent::Submitted));
+    let Some(RequestEvent::Locked { prover, price }) = events.next().await else {
+        panic!("expected a lock event");
+    };
+    assert_eq!(prover, ctx.prover_signer.address());
+    assert!(price.unwrap() >= request.offer.minPrice);
+
+    let (root, set_verifier_seal, fulfillment, assessor_seal) =
+        mock_singleton(&request, eip712_domain, ctx.prover_signer.address());
+    ctx.set_verifier.submit_merkle_root(root, set_verifier_seal).await.unwrap();
+    let assessor_fill = AssessorReceipt {
+        seal: assessor_seal,
+        selectors: vec![],
+        prover: ctx.prover_signer.address(),
+        callbacks: vec![],
+    };
+    ctx.prover_market.fulfill(&fulfillment, assessor_fill).await.unwrap();
+
+    assert_eq!(
+        events.next().await,
+        Some(RequestEvent::Fulfilled { journal: fulfillment.journal, seal: fulfillment.seal })
+    );
+    assert_eq!(events.next().await, None);
+}
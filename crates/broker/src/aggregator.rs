@@ -2,24 +2,30 @@
 //
 // All rights reserved.
 
+use std::collections::{HashMap, HashSet};
+
 use alloy::primitives::{utils, Address, U256};
 use anyhow::{bail, Context, Result};
 use boundless_assessor::{AssessorInput, Fulfillment};
-use boundless_market::{contracts::eip712_domain, input::InputBuilder};
+use boundless_market::{
+    clock::{Clock, ClockObj},
+    contracts::eip712_domain,
+    input::InputBuilder,
+};
 use chrono::Utc;
 use risc0_aggregation::GuestState;
+use risc0_ethereum_contracts::encode_seal;
 use risc0_zkvm::{
     sha::{Digest, Digestible},
-    ReceiptClaim,
+    Receipt, ReceiptClaim,
 };
 
 use crate::{
     config::ConfigLock,
     db::{AggregationOrder, DbObj},
-    now_timestamp,
-    provers::{self, ProverObj},
+    provers::{self, ProofResult, ProverError, ProverObj},
     task::{RetryRes, RetryTask, SupervisorErr},
-    AggregationState, Batch, BatchStatus,
+    AggregationCheckpoint, AggregationState, Batch, BatchStatus,
 };
 
 #[derive(Clone)]
@@ -32,6 +38,7 @@ pub struct AggregatorService {
     market_addr: Address,
     prover_addr: Address,
     chain_id: u64,
+    clock: ClockObj,
 }
 
 impl AggregatorService {
@@ -47,6 +54,7 @@ impl AggregatorService {
         prover_addr: Address,
         config: ConfigLock,
         prover: ProverObj,
+        clock: ClockObj,
     ) -> Result<Self> {
         prover
             .upload_image(&set_builder_guest_id.to_string(), set_builder_guest)
@@ -67,15 +75,26 @@ impl AggregatorService {
             market_addr,
             prover_addr,
             chain_id,
+            clock,
         })
     }
 
-    async fn prove_set_builder(
-        &self,
-        aggregation_state: Option<&AggregationState>,
-        proofs: &[String],
-        finalize: bool,
-    ) -> Result<AggregationState> {
+    /// Wait on a proof that was started by an earlier run of an aggregation step.
+    ///
+    /// Returns `None` if the proof needs to be started again, because it failed or because the
+    /// prover no longer knows about it (e.g. the in-memory prover after a restart).
+    async fn resume_proof(&self, proof_id: &str) -> Result<Option<ProofResult>> {
+        match self.prover.wait_for_stark(proof_id).await {
+            Ok(proof_res) => Ok(Some(proof_res)),
+            Err(err @ (ProverError::NotFound(_) | ProverError::ProvingFailed(_))) => {
+                tracing::warn!("Unable to resume proof {proof_id}, restarting: {err}");
+                Ok(None)
+            }
+            Err(err) => Err(err).with_context(|| format!("Failed to wait for proof {proof_id}")),
+        }
+    }
+
+    async fn get_claims(&self, proofs: &[String]) -> Result<Vec<ReceiptClaim>> {
         // TODO(#268): Handle failure to get an individual order.
         let mut claims = Vec::<ReceiptClaim>::with_capacity(proofs.len());
         for proof_id in proofs {
@@ -92,10 +111,19 @@ impl AggregatorService {
                 .with_context(|| format!("Receipt for {proof_id} claims pruned"))?;
             claims.push(claim);
         }
+        Ok(claims)
+    }
 
+    async fn start_set_builder(
+        &self,
+        aggregation_state: Option<&AggregationState>,
+        proofs: &[String],
+        claims: Vec<ReceiptClaim>,
+        finalize: bool,
+    ) -> Result<String> {
         let input = aggregation_state
             .map_or(GuestState::initial(self.set_builder_guest_id), |s| s.guest_state.clone())
-            .into_input(claims.clone(), finalize)
+            .into_input(claims, finalize)
             .context("Failed to build set builder input")?;
 
         // Gather the proof IDs for the assumptions we will need: any pending proofs, and the proof
@@ -121,15 +149,51 @@ impl AggregatorService {
         // the proving cluster is overloaded
 
         tracing::debug!("Starting proving of set-builder");
-        let proof_res = self
+        let proof_id = self
             .prover
-            .prove_and_monitor_stark(
-                &self.set_builder_guest_id.to_string(),
-                &input_id,
-                assumption_ids,
-            )
+            .prove_stark(&self.set_builder_guest_id.to_string(), &input_id, assumption_ids)
             .await
-            .context("Failed to prove set-builder")?;
+            .context("Failed to start set-builder proof")?;
+
+        Ok(proof_id)
+    }
+
+    async fn prove_set_builder(
+        &self,
+        batch_id: usize,
+        aggregation_state: Option<&AggregationState>,
+        proofs: &[String],
+        checkpoint: &mut AggregationCheckpoint,
+    ) -> Result<AggregationState> {
+        let claims = self.get_claims(proofs).await?;
+
+        let mut proof_res = None;
+        if let Some(proof_id) = checkpoint.set_builder_proof_id.clone() {
+            tracing::info!("Resuming set-builder proof {proof_id} for batch {batch_id}");
+            proof_res = self.resume_proof(&proof_id).await?;
+        }
+        let proof_res = match proof_res {
+            Some(proof_res) => proof_res,
+            None => {
+                let proof_id = self
+                    .start_set_builder(
+                        aggregation_state,
+                        proofs,
+                        claims.clone(),
+                        checkpoint.finalize,
+                    )
+                    .await?;
+                checkpoint.set_builder_proof_id = Some(proof_id.clone());
+                self.db
+                    .set_batch_checkpoint(batch_id, checkpoint)
+                    .await
+                    .context("Failed to checkpoint set-builder proof")?;
+                self.prover
+                    .wait_for_stark(&proof_id)
+                    .await
+                    .context("Failed to prove set-builder")?
+            }
+        };
         tracing::debug!(
             "completed proving of set-builder cycles: {} time: {}",
             proof_res.stats.total_cycles,
@@ -156,10 +220,11 @@ impl AggregatorService {
             proof_id: proof_res.id,
             claim_digests,
             groth16_proof_id: None,
+            groth16_seal: None,
         })
     }
 
-    async fn prove_assessor(&self, order_ids: &[U256]) -> Result<String> {
+    async fn start_assessor(&self, order_ids: &[U256]) -> Result<String> {
         let mut fills = vec![];
         let mut assumptions = vec![];
 
@@ -191,7 +256,6 @@ impl AggregatorService {
             })
         }
 
-        let order_count = fills.len();
         let input = AssessorInput {
             fills,
             domain: eip712_domain(self.market_addr, self.chain_id),
@@ -202,15 +266,43 @@ impl AggregatorService {
         let input_id =
             self.prover.upload_input(stdin).await.context("Failed to upload assessor input")?;
 
-        let proof_res = self
+        let proof_id = self
             .prover
-            .prove_and_monitor_stark(&self.assessor_guest_id.to_string(), &input_id, assumptions)
+            .prove_stark(&self.assessor_guest_id.to_string(), &input_id, assumptions)
             .await
-            .context("Failed to prove assesor stark")?;
+            .context("Failed to start assessor proof")?;
+
+        Ok(proof_id)
+    }
+
+    async fn prove_assessor(
+        &self,
+        batch_id: usize,
+        order_ids: &[U256],
+        checkpoint: &mut AggregationCheckpoint,
+    ) -> Result<String> {
+        if let Some(proof_id) = checkpoint.assessor_proof_id.clone() {
+            tracing::info!("Resuming assessor proof {proof_id} for batch {batch_id}");
+            if let Some(proof_res) = self.resume_proof(&proof_id).await? {
+                return Ok(proof_res.id);
+            }
+        }
+
+        let proof_id = self.start_assessor(order_ids).await?;
+        // Any set-builder proof started previously depends on the assessor proof being replaced.
+        checkpoint.assessor_proof_id = Some(proof_id.clone());
+        checkpoint.set_builder_proof_id = None;
+        self.db
+            .set_batch_checkpoint(batch_id, checkpoint)
+            .await
+            .context("Failed to checkpoint assessor proof")?;
+
+        let proof_res =
+            self.prover.wait_for_stark(&proof_id).await.context("Failed to prove assesor stark")?;
 
         tracing::debug!(
             "Assessor proof completed, count: {} cycles: {} time: {}",
-            order_count,
+            order_ids.len(),
             proof_res.stats.total_cycles,
             proof_res.elapsed_time
         );
@@ -250,12 +342,18 @@ impl AggregatorService {
     ///
     /// Checks current min-deadline, batch timer, and current block.
     async fn check_finalize(
-        &mut self,
+        &self,
         batch_id: usize,
         batch: &Batch,
         pending_orders: &[AggregationOrder],
     ) -> Result<bool> {
-        let (conf_batch_size, conf_batch_time, conf_batch_fees, conf_max_journal_bytes) = {
+        let (
+            conf_batch_size,
+            conf_max_batch_size,
+            conf_batch_time,
+            conf_batch_fees,
+            conf_max_journal_bytes,
+        ) = {
             let config = self.config.lock_all().context("Failed to lock config")?;
 
             // TODO: Move this parse into config
@@ -267,6 +365,7 @@ impl AggregatorService {
             };
             (
                 config.batcher.min_batch_size,
+                config.batcher.max_batch_size,
                 config.batcher.batch_max_time,
                 batch_max_fees,
                 config.batcher.batch_max_journal_bytes,
@@ -283,6 +382,16 @@ impl AggregatorService {
         // Finalize the batch whenever it exceeds a target size.
         // Add any pending jobs into the batch along with the finalization run.
         let batch_size = batch.orders.len() + pending_orders.len();
+        if let Some(max_batch_size) = conf_max_batch_size {
+            if batch_size >= max_batch_size as usize {
+                tracing::debug!(
+                    "Finalizing batch {batch_id}: max size hit {} - {}",
+                    batch_size,
+                    max_batch_size
+                );
+                return Ok(true);
+            }
+        }
         if let Some(batch_target_size) = conf_batch_size {
             if batch_size >= batch_target_size as usize {
                 tracing::debug!(
@@ -353,7 +462,7 @@ impl AggregatorService {
             let config = self.config.lock_all().context("Failed to lock config")?;
             config.batcher.block_deadline_buffer_secs
         };
-        let now = now_timestamp();
+        let now = self.clock.now();
 
         let deadline = pending_orders
             .iter()
@@ -379,20 +488,18 @@ impl AggregatorService {
     }
 
     async fn aggregate_proofs(
-        &mut self,
+        &self,
         batch_id: usize,
         batch: &Batch,
-        new_proofs: &[AggregationOrder],
-        groth16_proofs: &[AggregationOrder],
-        finalize: bool,
+        mut checkpoint: AggregationCheckpoint,
     ) -> Result<String> {
-        let assessor_proof_id = if finalize {
+        let assessor_proof_id = if checkpoint.finalize {
             let assessor_order_ids: Vec<U256> = batch
                 .orders
                 .iter()
                 .copied()
-                .chain(new_proofs.iter().map(|p| p.order_id))
-                .chain(groth16_proofs.iter().map(|p| p.order_id))
+                .chain(checkpoint.new_proofs.iter().map(|p| p.order_id))
+                .chain(checkpoint.groth16_proofs.iter().map(|p| p.order_id))
                 .collect();
 
             tracing::debug!(
@@ -400,8 +507,10 @@ impl AggregatorService {
                 assessor_order_ids
             );
 
-            let assessor_proof_id =
-                self.prove_assessor(&assessor_order_ids).await.with_context(|| {
+            let assessor_proof_id = self
+                .prove_assessor(batch_id, &assessor_order_ids, &mut checkpoint)
+                .await
+                .with_context(|| {
                     format!("Failed to prove assessor with orders {:x?}", assessor_order_ids)
                 })?;
 
@@ -410,16 +519,21 @@ impl AggregatorService {
             None
         };
 
-        let proof_ids: Vec<String> = new_proofs
+        let proof_ids: Vec<String> = checkpoint
+            .new_proofs
             .iter()
-            .cloned()
             .map(|proof| proof.proof_id.clone())
             .chain(assessor_proof_id.iter().cloned())
             .collect();
 
         tracing::debug!("Running set builder for {batch_id} with proofs {:x?}", proof_ids);
         let aggregation_state = self
-            .prove_set_builder(batch.aggregation_state.as_ref(), &proof_ids, finalize)
+            .prove_set_builder(
+                batch_id,
+                batch.aggregation_state.as_ref(),
+                &proof_ids,
+                &mut checkpoint,
+            )
             .await
             .context("Failed to prove set builder for batch {batch_id}")?;
 
@@ -429,7 +543,7 @@ impl AggregatorService {
             .update_batch(
                 batch_id,
                 &aggregation_state,
-                &[new_proofs, groth16_proofs].concat(),
+                &[checkpoint.new_proofs, checkpoint.groth16_proofs].concat(),
                 assessor_proof_id,
             )
             .await
@@ -438,66 +552,130 @@ impl AggregatorService {
         Ok(aggregation_state.proof_id)
     }
 
-    async fn aggregate(&mut self) -> Result<()> {
-        // Get the current batch. This aggregator service works on one batch at a time, including
-        // any proofs ready for aggregation into the current batch.
-        let batch_id =
-            self.db.get_current_batch().await.context("Failed to get current batch ID")?;
-        let batch = self.db.get_batch(batch_id).await.context("Failed to get batch")?;
-
-        let (aggregation_proof_id, compress) = match batch.status {
-            BatchStatus::Aggregating => {
-                // Fetch all proofs that are pending aggregation from the DB.
-                let new_proofs = self
-                    .db
-                    .get_aggregation_proofs()
+    /// Compress the aggregation proof of a batch, recording the Groth16 proof ID and seal.
+    ///
+    /// The Groth16 proof ID is recorded as soon as the compression starts, so that a compression
+    /// interrupted by a restart is resumed by passing its ID as `started`.
+    async fn compress_batch(
+        &self,
+        batch_id: usize,
+        aggregation_proof_id: &str,
+        started: Option<String>,
+    ) -> Result<String> {
+        let compress_proof_id = match started {
+            Some(compress_proof_id) => {
+                tracing::info!(
+                    "Resuming groth16 compression proof {compress_proof_id} for batch {batch_id}"
+                );
+                compress_proof_id
+            }
+            None => {
+                tracing::debug!("Starting groth16 compression proof for batch {batch_id}");
+                let compress_proof_id = self
+                    .prover
+                    .start_compress(aggregation_proof_id)
                     .await
-                    .context("Failed to get pending agg proofs from DB")?;
-                // Fetch all groth16 proofs that are ready to be submitted from the DB.
-                let new_groth16_proofs = self
-                    .db
-                    .get_groth16_proofs()
+                    .context("Failed to start compression")?;
+                self.db
+                    .set_batch_groth16_proof_id(batch_id, &compress_proof_id)
                     .await
-                    .context("Failed to get groth16 proofs from DB")?;
-
-                // Finalize the current batch before adding any new orders if the finalization conditions
-                // are already met.
-                let finalize = self
-                    .check_finalize(
-                        batch_id,
-                        &batch,
-                        &[new_proofs.clone(), new_groth16_proofs.clone()].concat(),
-                    )
-                    .await?;
+                    .context("Failed to checkpoint groth16 proof ID")?;
+                compress_proof_id
+            }
+        };
+        self.prover
+            .wait_for_compress(&compress_proof_id)
+            .await
+            .context("Failed to complete compression")?;
+        tracing::debug!("Completed groth16 compression for batch {batch_id}");
 
-                // If we don't need to finalize, and there are no new proofs, there is no work to do.
-                if !finalize && new_proofs.is_empty() {
-                    tracing::trace!("No aggregation work to do for batch {batch_id}");
-                    return Ok(());
-                }
+        let receipt = self
+            .prover
+            .get_compressed_receipt(&compress_proof_id)
+            .await
+            .context("Failed to fetch g16 receipt")?
+            .context("Groth16 receipt missing")?;
+        let receipt: Receipt =
+            bincode::deserialize(&receipt).context("Failed to deserialize g16 receipt")?;
+        let seal = encode_seal(&receipt).context("Failed to encode g16 receipt seal")?;
 
-                let aggregation_proof_id = self
-                    .aggregate_proofs(batch_id, &batch, &new_proofs, &new_groth16_proofs, finalize)
-                    .await?;
-                (aggregation_proof_id, finalize)
+        self.db
+            .set_batch_groth16_seal(batch_id, &compress_proof_id, &seal.into())
+            .await
+            .context("Failed to checkpoint groth16 proof")?;
+
+        Ok(compress_proof_id)
+    }
+
+    /// Run the next aggregation step for a batch, adding the given orders to it.
+    async fn aggregate_batch(
+        &self,
+        batch_id: usize,
+        batch: Batch,
+        new_proofs: Vec<AggregationOrder>,
+        groth16_proofs: Vec<AggregationOrder>,
+    ) -> Result<()> {
+        let (aggregation_proof_id, compress, groth16_proof_id, compressed) = match batch.status {
+            BatchStatus::Aggregating => {
+                // Resume an aggregation step that was interrupted, e.g. by a restart, with the
+                // same orders and any proofs that were already started.
+                let checkpoint = match batch.checkpoint.clone() {
+                    Some(checkpoint) => {
+                        tracing::info!("Resuming aggregation of batch {batch_id} from checkpoint");
+                        checkpoint
+                    }
+                    None => {
+                        // Finalize the current batch before adding any new orders if the
+                        // finalization conditions are already met.
+                        let finalize = self
+                            .check_finalize(
+                                batch_id,
+                                &batch,
+                                &[new_proofs.clone(), groth16_proofs.clone()].concat(),
+                            )
+                            .await?;
+
+                        // If we don't need to finalize, and there are no new proofs, there is no
+                        // work to do.
+                        if !finalize && new_proofs.is_empty() {
+                            tracing::trace!("No aggregation work to do for batch {batch_id}");
+                            return Ok(());
+                        }
+
+                        AggregationCheckpoint {
+                            new_proofs,
+                            groth16_proofs,
+                            finalize,
+                            assessor_proof_id: None,
+                            set_builder_proof_id: None,
+                        }
+                    }
+                };
+
+                let finalize = checkpoint.finalize;
+                let aggregation_proof_id =
+                    self.aggregate_proofs(batch_id, &batch, checkpoint).await?;
+                (aggregation_proof_id, finalize, None, false)
             }
             BatchStatus::PendingCompression => {
                 let Some(aggregation_state) = batch.aggregation_state else {
                     bail!("Batch {batch_id} in inconsistent state: status is PendingCompression but aggregation_state is None");
                 };
-                (aggregation_state.proof_id, true)
+                // Resume from the Groth16 proof recorded before an interruption, if any.
+                let compressed = aggregation_state.groth16_seal.is_some();
+                (aggregation_state.proof_id, true, aggregation_state.groth16_proof_id, compressed)
             }
             status => bail!("Unexpected batch status {status:?}"),
         };
 
         if compress {
-            tracing::debug!("Starting groth16 compression proof for batch {batch_id}");
-            let compress_proof_id = self
-                .prover
-                .compress(&aggregation_proof_id)
-                .await
-                .context("Failed to complete compression")?;
-            tracing::debug!("Completed groth16 compression for batch {batch_id}");
+            let compress_proof_id = match groth16_proof_id {
+                Some(compress_proof_id) if compressed => {
+                    tracing::info!("Resuming batch {batch_id} from its recorded groth16 proof");
+                    compress_proof_id
+                }
+                started => self.compress_batch(batch_id, &aggregation_proof_id, started).await?,
+            };
 
             self.db
                 .complete_batch(batch_id, compress_proof_id)
@@ -507,6 +685,111 @@ impl AggregatorService {
 
         Ok(())
     }
+
+    async fn aggregate(&mut self) -> Result<()> {
+        let (max_batch_size, max_concurrent_batches) = {
+            let config = self.config.lock_all().context("Failed to lock config")?;
+            (
+                config.batcher.max_batch_size.map(|size| size as usize),
+                config.batcher.max_concurrent_batches.max(1) as usize,
+            )
+        };
+
+        // Get the open batches, making sure there is at least one. The aggregator works on up to
+        // `max_concurrent_batches` batches at a time, adding the proofs ready for aggregation to
+        // them.
+        self.db.get_current_batch().await.context("Failed to get current batch ID")?;
+        let mut batches = self.db.get_open_batches().await.context("Failed to get open batches")?;
+
+        // Orders in an interrupted aggregation step will be resumed with that step, and must not
+        // be assigned to another batch.
+        let in_flight: HashSet<U256> = batches
+            .iter()
+            .filter_map(|(_, batch)| batch.checkpoint.as_ref())
+            .flat_map(|checkpoint| checkpoint.new_proofs.iter().chain(&checkpoint.groth16_proofs))
+            .map(|order| order.order_id)
+            .collect();
+
+        // Fetch all proofs that are pending aggregation from the DB.
+        let new_proofs = self
+            .db
+            .get_aggregation_proofs()
+            .await
+            .context("Failed to get pending agg proofs from DB")?;
+        // Fetch all groth16 proofs that are ready to be submitted from the DB.
+        let new_groth16_proofs =
+            self.db.get_groth16_proofs().await.context("Failed to get groth16 proofs from DB")?;
+
+        // Pending orders, soonest deadline first, and whether each needs aggregation.
+        let mut pending: Vec<(AggregationOrder, bool)> = new_proofs
+            .into_iter()
+            .map(|order| (order, true))
+            .chain(new_groth16_proofs.into_iter().map(|order| (order, false)))
+            .filter(|(order, _)| !in_flight.contains(&order.order_id))
+            .collect();
+        pending.sort_by_key(|(order, _)| order.expiration);
+
+        let accepts_orders =
+            |batch: &Batch| batch.status == BatchStatus::Aggregating && batch.checkpoint.is_none();
+        let capacity = |batch: &Batch| {
+            max_batch_size.map_or(usize::MAX, |max| max.saturating_sub(batch.orders.len()))
+        };
+
+        // Open more batches while the pending orders do not fit in the open ones.
+        loop {
+            let open_capacity = batches
+                .iter()
+                .filter(|(_, batch)| accepts_orders(batch))
+                .fold(0usize, |sum, (_, batch)| sum.saturating_add(capacity(batch)));
+            if pending.len() <= open_capacity || batches.len() >= max_concurrent_batches {
+                break;
+            }
+            let batch_id = self.db.new_batch().await.context("Failed to create new batch")?;
+            tracing::debug!("Opened batch {batch_id} for {} pending orders", pending.len());
+            let batch = self.db.get_batch(batch_id).await.context("Failed to get batch")?;
+            batches.push((batch_id, batch));
+        }
+
+        // Assign the orders with the soonest deadlines to the batches with the soonest deadlines,
+        // as those are the first to be finalized.
+        let mut targets: Vec<&(usize, Batch)> =
+            batches.iter().filter(|(_, batch)| accepts_orders(batch)).collect();
+        targets.sort_by_key(|(batch_id, batch)| (batch.deadline.unwrap_or(u64::MAX), *batch_id));
+        let mut assignments =
+            HashMap::<usize, (Vec<AggregationOrder>, Vec<AggregationOrder>)>::new();
+        let mut pending = pending.into_iter();
+        for (batch_id, batch) in targets {
+            let assignment = assignments.entry(*batch_id).or_default();
+            for (order, aggregate) in pending.by_ref().take(capacity(batch)) {
+                if aggregate {
+                    assignment.0.push(order);
+                } else {
+                    assignment.1.push(order);
+                }
+            }
+        }
+        let waiting = pending.len();
+        if waiting > 0 {
+            tracing::debug!("{waiting} orders waiting for capacity in an open batch");
+        }
+
+        // Run the aggregation steps for each batch concurrently.
+        let batch_ids: Vec<usize> = batches.iter().map(|(batch_id, _)| *batch_id).collect();
+        let results = futures::future::join_all(batches.into_iter().map(|(batch_id, batch)| {
+            let (new_proofs, groth16_proofs) = assignments.remove(&batch_id).unwrap_or_default();
+            self.aggregate_batch(batch_id, batch, new_proofs, groth16_proofs)
+        }))
+        .await;
+
+        let mut res = Ok(());
+        for (batch_id, batch_res) in batch_ids.into_iter().zip(results) {
+            if let Err(err) = batch_res {
+                tracing::error!("Failed to aggregate batch {batch_id}: {err:?}");
+                res = Err(err);
+            }
+        }
+        res
+    }
 }
 
 impl RetryTask for AggregatorService {
@@ -551,12 +834,18 @@ mod tests {
         providers::{ext::AnvilApi, Provider, ProviderBuilder},
         signers::local::PrivateKeySigner,
     };
-    use boundless_market::contracts::{
-        Input, InputType, Offer, Predicate, PredicateType, ProofRequest, RequestId, Requirements,
+    use boundless_market::{
+        clock::SystemClock,
+        contracts::{
+            Input, InputType, Offer, Predicate, PredicateType, ProofRequest, RequestId,
+            Requirements,
+        },
+        input::GuestEnv,
     };
     use guest_assessor::{ASSESSOR_GUEST_ELF, ASSESSOR_GUEST_ID};
     use guest_set_builder::{SET_BUILDER_ELF, SET_BUILDER_ID};
     use guest_util::{ECHO_ELF, ECHO_ID};
+    use risc0_zkvm::Receipt;
     use tracing_test::traced_test;
 
     #[tokio::test]
@@ -607,6 +896,7 @@ mod tests {
             prover_addr,
             config,
             prover,
+            Arc::new(SystemClock),
         )
         .await
         .unwrap();
@@ -654,6 +944,9 @@ mod tests {
             client_sig: client_sig.into(),
             lock_price: Some(U256::from(min_price)),
             error_msg: None,
+            offchain: false,
+            callback_gas: None,
+            assumption_ids: vec![],
         };
         let order_id = U256::from(order.request.id);
         db.add_order(order_id, order.clone()).await.unwrap();
@@ -697,6 +990,9 @@ mod tests {
             client_sig,
             lock_price: Some(U256::from(min_price)),
             error_msg: None,
+            offchain: false,
+            callback_gas: None,
+            assumption_ids: vec![],
         };
         let order_id = U256::from(order.request.id);
         db.add_order(order_id, order.clone()).await.unwrap();
@@ -759,6 +1055,7 @@ mod tests {
             prover_addr,
             config,
             prover,
+            Arc::new(SystemClock),
         )
         .await
         .unwrap();
@@ -806,6 +1103,9 @@ mod tests {
             lock_price: Some(U256::from(min_price)),
             error_msg: None,
             request: order_request,
+            offchain: false,
+            callback_gas: None,
+            assumption_ids: vec![],
         };
         let order_id = U256::from(order.request.id);
         db.add_order(order_id, order.clone()).await.unwrap();
@@ -864,6 +1164,9 @@ mod tests {
             lock_price: Some(U256::from(min_price)),
             error_msg: None,
             request: order_request,
+            offchain: false,
+            callback_gas: None,
+            assumption_ids: vec![],
         };
         let order_id = U256::from(order.request.id);
         db.add_order(order_id, order.clone()).await.unwrap();
@@ -923,6 +1226,7 @@ mod tests {
             prover_addr,
             config,
             prover,
+            Arc::new(SystemClock),
         )
         .await
         .unwrap();
@@ -969,6 +1273,9 @@ mod tests {
             client_sig: client_sig.into(),
             lock_price: Some(U256::from(min_price)),
             error_msg: None,
+            offchain: false,
+            callback_gas: None,
+            assumption_ids: vec![],
         };
         let order_id = U256::from(order.request.id);
         db.add_order(order_id, order.clone()).await.unwrap();
@@ -1031,6 +1338,7 @@ mod tests {
             signer.address(),
             config.clone(),
             prover,
+            Arc::new(SystemClock),
         )
         .await
         .unwrap();
@@ -1077,6 +1385,9 @@ mod tests {
             client_sig: client_sig.into(),
             lock_price: Some(U256::from(min_price)),
             error_msg: None,
+            offchain: false,
+            callback_gas: None,
+            assumption_ids: vec![],
         };
         let order_id = U256::from(order.request.id);
         db.add_order(order_id, order.clone()).await.unwrap();
@@ -1147,6 +1458,7 @@ mod tests {
             signer.address(),
             config.clone(),
             prover,
+            Arc::new(SystemClock),
         )
         .await
         .unwrap();
@@ -1193,6 +1505,9 @@ mod tests {
             client_sig: client_sig.into(),
             lock_price: Some(U256::from(min_price)),
             error_msg: None,
+            offchain: false,
+            callback_gas: None,
+            assumption_ids: vec![],
         };
 
         // add first order and aggregate
@@ -1214,4 +1529,401 @@ mod tests {
         assert_eq!(batch.orders.len(), 2);
         assert_eq!(batch.status, BatchStatus::PendingSubmission);
     }
+
+    /// Aggregation stage at which [KillProver] simulates the broker being killed.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum KillStage {
+        Assessor,
+        SetBuilder,
+        Compression,
+    }
+
+    /// Prover that fails once at the given stage, after the proof has been started on the inner
+    /// prover, as if the broker was killed while waiting on the proving backend.
+    ///
+    /// At [KillStage::Compression], waiting on the compression hangs instead, and
+    /// [KillProver::compressing] is notified so that the waiting task can be cancelled, see
+    /// [kill_and_resume].
+    struct KillProver {
+        inner: DefaultProver,
+        kill_stage: std::sync::Mutex<Option<KillStage>>,
+        /// Image ID and proof ID of every started STARK proof.
+        started: std::sync::Mutex<Vec<(String, String)>>,
+        /// Number of started compressions.
+        compressions: std::sync::Mutex<usize>,
+        /// Notified when waiting on a compression hangs.
+        compressing: tokio::sync::Notify,
+    }
+
+    impl KillProver {
+        fn new(inner: DefaultProver, kill_stage: Option<KillStage>) -> Self {
+            Self {
+                inner,
+                kill_stage: std::sync::Mutex::new(kill_stage),
+                started: Default::default(),
+                compressions: Default::default(),
+                compressing: Default::default(),
+            }
+        }
+
+        fn started_count(&self, image_id: Digest) -> usize {
+            let image_id = image_id.to_string();
+            self.started.lock().unwrap().iter().filter(|(id, _)| *id == image_id).count()
+        }
+
+        fn kill(&self, stage: KillStage) -> Result<(), ProverError> {
+            let mut kill_stage = self.kill_stage.lock().unwrap();
+            if *kill_stage == Some(stage) {
+                *kill_stage = None;
+                return Err(ProverError::Other(anyhow::anyhow!("killed during {stage:?}")));
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Prover for KillProver {
+        async fn upload_input(&self, input: Vec<u8>) -> Result<String, ProverError> {
+            self.inner.upload_input(input).await
+        }
+        async fn upload_env(&self, env: GuestEnv) -> Result<String, ProverError> {
+            self.inner.upload_env(env).await
+        }
+        async fn upload_receipt(&self, receipt: Vec<u8>) -> Result<String, ProverError> {
+            self.inner.upload_receipt(receipt).await
+        }
+        async fn upload_image(&self, image_id: &str, image: Vec<u8>) -> Result<(), ProverError> {
+            self.inner.upload_image(image_id, image).await
+        }
+        async fn preflight(
+            &self,
+            image_id: &str,
+            input_id: &str,
+            assumptions: Vec<String>,
+            executor_limit: Option<u64>,
+        ) -> Result<ProofResult, ProverError> {
+            self.inner.preflight(image_id, input_id, assumptions, executor_limit).await
+        }
+        async fn prove_stark(
+            &self,
+            image_id: &str,
+            input_id: &str,
+            assumptions: Vec<String>,
+        ) -> Result<String, ProverError> {
+            let proof_id = self.inner.prove_stark(image_id, input_id, assumptions).await?;
+            self.started.lock().unwrap().push((image_id.to_string(), proof_id.clone()));
+            Ok(proof_id)
+        }
+        async fn wait_for_stark(&self, proof_id: &str) -> Result<ProofResult, ProverError> {
+            let image_id = self
+                .started
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|(_, id)| id == proof_id)
+                .map(|(image_id, _)| image_id.clone());
+            if image_id == Some(Digest::from(ASSESSOR_GUEST_ID).to_string()) {
+                self.kill(KillStage::Assessor)?;
+            } else if image_id == Some(Digest::from(SET_BUILDER_ID).to_string()) {
+                self.kill(KillStage::SetBuilder)?;
+            }
+            self.inner.wait_for_stark(proof_id).await
+        }
+        async fn get_receipt(&self, proof_id: &str) -> Result<Option<Receipt>, ProverError> {
+            self.inner.get_receipt(proof_id).await
+        }
+        async fn get_preflight_journal(
+            &self,
+            proof_id: &str,
+        ) -> Result<Option<Vec<u8>>, ProverError> {
+            self.inner.get_preflight_journal(proof_id).await
+        }
+        async fn get_journal(&self, proof_id: &str) -> Result<Option<Vec<u8>>, ProverError> {
+            self.inner.get_journal(proof_id).await
+        }
+        async fn start_compress(&self, proof_id: &str) -> Result<String, ProverError> {
+            *self.compressions.lock().unwrap() += 1;
+            self.inner.start_compress(proof_id).await
+        }
+        async fn wait_for_compress(&self, compress_proof_id: &str) -> Result<(), ProverError> {
+            if self.kill(KillStage::Compression).is_err() {
+                self.compressing.notify_one();
+                std::future::pending::<()>().await;
+            }
+            self.inner.wait_for_compress(compress_proof_id).await
+        }
+        async fn get_compressed_receipt(
+            &self,
+            proof_id: &str,
+        ) -> Result<Option<Vec<u8>>, ProverError> {
+            self.inner.get_compressed_receipt(proof_id).await
+        }
+    }
+
+    const TEST_CHAIN_ID: u64 = 1;
+
+    async fn new_aggregator(db: DbObj, prover: ProverObj, config: ConfigLock) -> AggregatorService {
+        AggregatorService::new(
+            db,
+            TEST_CHAIN_ID,
+            Digest::from(SET_BUILDER_ID),
+            SET_BUILDER_ELF.to_vec(),
+            Digest::from(ASSESSOR_GUEST_ID),
+            ASSESSOR_GUEST_ELF.to_vec(),
+            Address::ZERO,
+            Address::ZERO,
+            config,
+            prover,
+            Arc::new(SystemClock),
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Add a proven order that is pending aggregation, returning its ID.
+    async fn add_proven_order(db: &DbObj, prover: &KillProver, timeout: u32) -> U256 {
+        let image_id = Digest::from(ECHO_ID);
+        let image_id_str = image_id.to_string();
+        prover.upload_image(&image_id_str, ECHO_ELF.to_vec()).await.unwrap();
+        let input_id = prover
+            .upload_input(encode_input(&vec![0x41, 0x41, 0x41, 0x41]).unwrap())
+            .await
+            .unwrap();
+        let proof_res =
+            prover.inner.prove_and_monitor_stark(&image_id_str, &input_id, vec![]).await.unwrap();
+
+        let customer_signer = PrivateKeySigner::random();
+        let order_request = ProofRequest::new(
+            RequestId::new(customer_signer.address(), 0),
+            Requirements::new(
+                image_id,
+                Predicate { predicateType: PredicateType::PrefixMatch, data: Default::default() },
+            ),
+            "http://risczero.com/image",
+            Input { inputType: InputType::Inline, data: Default::default() },
+            Offer {
+                minPrice: U256::from(2),
+                maxPrice: U256::from(4),
+                biddingStart: now_timestamp(),
+                timeout,
+                lockTimeout: timeout,
+                rampUpPeriod: 1,
+                lockStake: U256::from(10),
+            },
+        );
+        let client_sig = order_request
+            .sign_request(&customer_signer, Address::ZERO, TEST_CHAIN_ID)
+            .await
+            .unwrap()
+            .as_bytes();
+
+        let order = Order {
+            status: OrderStatus::PendingAgg,
+            updated_at: Utc::now(),
+            target_timestamp: None,
+            image_id: Some(image_id_str),
+            input_id: Some(input_id),
+            proof_id: Some(proof_res.id),
+            compressed_proof_id: None,
+            expire_timestamp: Some(order_request.expires_at()),
+            client_sig: client_sig.into(),
+            lock_price: Some(U256::from(2)),
+            error_msg: None,
+            request: order_request,
+            offchain: false,
+            callback_gas: None,
+            assumption_ids: vec![],
+        };
+        let order_id = U256::from(order.request.id);
+        db.add_order(order_id, order).await.unwrap();
+        order_id
+    }
+
+    /// Kill the aggregator at the given stage of finalizing a batch, then check that a new
+    /// aggregator completes the batch without restarting proofs that were already started.
+    ///
+    /// At [KillStage::Compression], the aggregation task is cancelled while it waits on the
+    /// compression.
+    async fn kill_and_resume(stage: KillStage) {
+        let db: DbObj = Arc::new(SqliteDb::new("sqlite::memory:").await.unwrap());
+        let config = ConfigLock::default();
+        config.load_write().unwrap().batcher.min_batch_size = Some(1);
+
+        let prover = Arc::new(KillProver::new(DefaultProver::new(), Some(stage)));
+        let order_id = add_proven_order(&db, &prover, 1200).await;
+        let batch_id = db.get_current_batch().await.unwrap();
+
+        let mut aggregator = new_aggregator(db.clone(), prover.clone(), config.clone()).await;
+        if stage == KillStage::Compression {
+            tokio::select! {
+                res = aggregator.aggregate() => panic!("aggregation was not interrupted: {res:?}"),
+                _ = prover.compressing.notified() => {}
+            }
+        } else {
+            aggregator.aggregate().await.unwrap_err();
+        }
+
+        let batch = db.get_batch(batch_id).await.unwrap();
+        match stage {
+            KillStage::Assessor => {
+                assert_eq!(batch.status, BatchStatus::Aggregating);
+                let checkpoint = batch.checkpoint.unwrap();
+                assert!(checkpoint.assessor_proof_id.is_some());
+                assert!(checkpoint.set_builder_proof_id.is_none());
+            }
+            KillStage::SetBuilder => {
+                assert_eq!(batch.status, BatchStatus::Aggregating);
+                let checkpoint = batch.checkpoint.unwrap();
+                assert!(checkpoint.assessor_proof_id.is_some());
+                assert!(checkpoint.set_builder_proof_id.is_some());
+            }
+            KillStage::Compression => {
+                assert_eq!(batch.status, BatchStatus::PendingCompression);
+                assert!(batch.checkpoint.is_none());
+                let aggregation_state = batch.aggregation_state.unwrap();
+                assert!(aggregation_state.groth16_proof_id.is_some());
+                assert!(aggregation_state.groth16_seal.is_none());
+            }
+        }
+
+        // Restart the aggregator.
+        drop(aggregator);
+        let mut aggregator = new_aggregator(db.clone(), prover.clone(), config).await;
+        aggregator.aggregate().await.unwrap();
+
+        assert_eq!(prover.started_count(Digest::from(ASSESSOR_GUEST_ID)), 1);
+        assert_eq!(prover.started_count(Digest::from(SET_BUILDER_ID)), 1);
+        assert_eq!(*prover.compressions.lock().unwrap(), 1);
+
+        let db_order = db.get_order(order_id).await.unwrap().unwrap();
+        assert_eq!(db_order.status, OrderStatus::PendingSubmission);
+        let (complete_batch_id, batch) = db.get_complete_batch().await.unwrap().unwrap();
+        assert_eq!(complete_batch_id, batch_id);
+        assert_eq!(batch.orders, vec![order_id]);
+        assert!(batch.checkpoint.is_none());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn resume_killed_assessor() {
+        kill_and_resume(KillStage::Assessor).await;
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn resume_killed_set_builder() {
+        kill_and_resume(KillStage::SetBuilder).await;
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn resume_killed_compression() {
+        kill_and_resume(KillStage::Compression).await;
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn resume_lost_proof() {
+        let db: DbObj = Arc::new(SqliteDb::new("sqlite::memory:").await.unwrap());
+        let config = ConfigLock::default();
+        config.load_write().unwrap().batcher.min_batch_size = Some(1);
+
+        let prover = Arc::new(KillProver::new(DefaultProver::new(), None));
+        let order_id = add_proven_order(&db, &prover, 1200).await;
+
+        // Checkpoint referencing an assessor proof the prover does not know about, as with the
+        // in-memory prover after a restart.
+        let new_proofs = db.get_aggregation_proofs().await.unwrap();
+        let batch_id = db.get_current_batch().await.unwrap();
+        let checkpoint = AggregationCheckpoint {
+            new_proofs,
+            groth16_proofs: vec![],
+            finalize: true,
+            assessor_proof_id: Some("stark_lost".to_string()),
+            set_builder_proof_id: Some("stark_lost_set_builder".to_string()),
+        };
+        db.set_batch_checkpoint(batch_id, &checkpoint).await.unwrap();
+
+        let mut aggregator = new_aggregator(db.clone(), prover.clone(), config).await;
+        aggregator.aggregate().await.unwrap();
+        assert!(logs_contain("Unable to resume proof stark_lost"));
+
+        assert_eq!(prover.started_count(Digest::from(ASSESSOR_GUEST_ID)), 1);
+        assert_eq!(prover.started_count(Digest::from(SET_BUILDER_ID)), 1);
+        let (_, batch) = db.get_complete_batch().await.unwrap().unwrap();
+        assert_eq!(batch.orders, vec![order_id]);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn aggregate_parallel_batches() {
+        let db: DbObj = Arc::new(SqliteDb::new("sqlite::memory:").await.unwrap());
+        let config = ConfigLock::default();
+        {
+            let mut config = config.load_write().unwrap();
+            config.batcher.min_batch_size = Some(10);
+            config.batcher.max_batch_size = Some(1);
+            config.batcher.max_concurrent_batches = 2;
+        }
+
+        let prover = Arc::new(KillProver::new(DefaultProver::new(), None));
+        let late_order_id = add_proven_order(&db, &prover, 1200).await;
+        let early_order_id = add_proven_order(&db, &prover, 600).await;
+
+        let mut aggregator = new_aggregator(db.clone(), prover.clone(), config).await;
+        aggregator.aggregate().await.unwrap();
+        assert!(logs_contain("max size hit"));
+
+        // Each order fills a batch to the max size, so both batches are finalized, with the set
+        // builder proofs for each running concurrently.
+        assert!(db.get_open_batches().await.unwrap().is_empty());
+        assert_eq!(prover.started_count(Digest::from(SET_BUILDER_ID)), 2);
+        assert_eq!(prover.started_count(Digest::from(ASSESSOR_GUEST_ID)), 2);
+
+        // The order with the earliest deadline is assigned first.
+        let (first_id, first_batch) = db.get_complete_batch().await.unwrap().unwrap();
+        let (second_id, second_batch) = db.get_complete_batch().await.unwrap().unwrap();
+        let (first_batch, second_batch) = if first_id < second_id {
+            (first_batch, second_batch)
+        } else {
+            (second_batch, first_batch)
+        };
+        assert_eq!(first_batch.orders, vec![early_order_id]);
+        assert_eq!(second_batch.orders, vec![late_order_id]);
+        for order_id in [early_order_id, late_order_id] {
+            let db_order = db.get_order(order_id).await.unwrap().unwrap();
+            assert_eq!(db_order.status, OrderStatus::PendingSubmission);
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn orders_wait_for_batch_capacity() {
+        let db: DbObj = Arc::new(SqliteDb::new("sqlite::memory:").await.unwrap());
+        let config = ConfigLock::default();
+        {
+            let mut config = config.load_write().unwrap();
+            config.batcher.min_batch_size = Some(10);
+            config.batcher.max_batch_size = Some(2);
+        }
+
+        let prover = Arc::new(KillProver::new(DefaultProver::new(), None));
+        let mut order_ids = vec![];
+        for timeout in [600, 900, 1200] {
+            order_ids.push(add_proven_order(&db, &prover, timeout).await);
+        }
+
+        let mut aggregator = new_aggregator(db.clone(), prover.clone(), config).await;
+        aggregator.aggregate().await.unwrap();
+        assert!(logs_contain("1 orders waiting for capacity in an open batch"));
+
+        let (_, batch) = db.get_complete_batch().await.unwrap().unwrap();
+        assert_eq!(batch.orders, order_ids[..2]);
+
+        // The remaining order is added to a new batch on the next run.
+        aggregator.aggregate().await.unwrap();
+        let open = db.get_open_batches().await.unwrap();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].1.orders, order_ids[2..]);
+    }
 }
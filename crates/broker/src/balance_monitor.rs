@@ -0,0 +1,158 @@
+// Copyright (c) 2025 RISC Zero, Inc.
+//
+// All rights reserved.
+
+use std::{sync::Arc, time::Duration};
+
+use alloy::{
+    network::Ethereum,
+    primitives::{utils::parse_ether, Address},
+    providers::Provider,
+};
+use anyhow::{Context, Result};
+use boundless_market::{
+    alerts::{
+        AlertSink, BalanceMonitor, BalanceSource, BalanceWatch, CommandSink, TracingSink,
+        WebhookSink,
+    },
+    contracts::boundless_market::BoundlessMarketService,
+};
+
+use crate::{
+    config::{ConfigLock, MarketConf},
+    task::{RetryRes, RetryTask, SupervisorErr},
+};
+
+/// Periodically checks the wallet, market, stake and hit points balances of the broker against
+/// the configured thresholds, and delivers alerts to the configured sinks.
+///
+/// The thresholds, sinks and interval are read from the config before every check, so changes to
+/// the config apply without a restart.
+#[derive(Clone)]
+pub struct BalanceMonitorService<P> {
+    config: ConfigLock,
+    provider: P,
+    market_addr: Address,
+    prover_addr: Address,
+}
+
+impl<P> BalanceMonitorService<P>
+where
+    P: Provider<Ethereum> + 'static + Clone,
+{
+    pub fn new(
+        config: ConfigLock,
+        provider: P,
+        market_addr: Address,
+        prover_addr: Address,
+    ) -> Self {
+        Self { config, provider, market_addr, prover_addr }
+    }
+
+    /// Returns the watches of the balances with thresholds configured.
+    fn watches(market: &MarketConf, prover_addr: Address) -> Result<Vec<BalanceWatch>> {
+        let parse = |value: &Option<String>| value.as_deref().map(parse_ether).transpose();
+        let watches = [
+            (
+                BalanceSource::Native,
+                &market.balance_warn_threshold,
+                &market.balance_error_threshold,
+            ),
+            (
+                BalanceSource::Market,
+                &market.market_balance_warn_threshold,
+                &market.market_balance_error_threshold,
+            ),
+            (
+                BalanceSource::Stake,
+                &market.stake_balance_warn_threshold,
+                &market.stake_balance_error_threshold,
+            ),
+            (
+                BalanceSource::HitPoints,
+                &market.hp_balance_warn_threshold,
+                &market.hp_balance_error_threshold,
+            ),
+        ];
+        let mut configured = Vec::new();
+        for (source, warn, error) in watches {
+            if warn.is_none() && error.is_none() {
+                continue;
+            }
+            configured.push(
+                BalanceWatch::new(source, prover_addr)
+                    .with_warn_below(
+                        parse(warn).with_context(|| format!("Invalid {source} warn threshold"))?,
+                    )
+                    .with_error_below(
+                        parse(error)
+                            .with_context(|| format!("Invalid {source} error threshold"))?,
+                    ),
+            );
+        }
+        Ok(configured)
+    }
+
+    /// Returns the sinks to deliver alerts to.
+    fn sinks(market: &MarketConf) -> Result<Vec<Arc<dyn AlertSink>>> {
+        let mut sinks: Vec<Arc<dyn AlertSink>> = vec![Arc::new(TracingSink)];
+        if let Some(url) = &market.alert_webhook_url {
+            sinks.push(Arc::new(WebhookSink::new(
+                url.parse().context("Invalid alert webhook URL")?,
+            )));
+        }
+        if let Some((program, args)) = market.alert_command.as_ref().and_then(|c| c.split_first()) {
+            sinks.push(Arc::new(CommandSink::new(program, args)));
+        }
+        Ok(sinks)
+    }
+
+    /// Applies the current config to the monitor, returning the interval until the next check.
+    fn reconfigure(&self, monitor: &mut BalanceMonitor<P>) -> Result<Duration> {
+        let config = self.config.lock_all().context("Failed to read config")?;
+        let market = &config.market;
+        let watches = Self::watches(market, self.prover_addr)?;
+        let sinks = Self::sinks(market)?;
+        monitor.set_watches(watches);
+        monitor.set_sinks(sinks);
+        Ok(Duration::from_secs(market.balance_check_interval))
+    }
+
+    async fn monitor_balances(&self) -> Result<()> {
+        let hit_points_addr =
+            BoundlessMarketService::new(self.market_addr, self.provider.clone(), self.prover_addr)
+                .stake_token_address()
+                .await
+                .context("Failed to get the stake token address")?;
+        let mut monitor = BalanceMonitor::new(self.provider.clone())
+            .with_market_address(self.market_addr)
+            .with_hit_points_address(hit_points_addr);
+
+        loop {
+            let interval = match self.reconfigure(&mut monitor) {
+                Ok(interval) => interval,
+                Err(err) => {
+                    // Keep checking with the last valid config until the config is fixed.
+                    tracing::warn!("Failed to apply balance monitor config: {err:?}");
+                    Duration::from_secs(MarketConf::default().balance_check_interval)
+                }
+            };
+            // Failures are logged in check, and retried on the next interval.
+            let _ = monitor.check().await;
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+impl<P> RetryTask for BalanceMonitorService<P>
+where
+    P: Provider<Ethereum> + 'static + Clone,
+{
+    fn spawn(&self) -> RetryRes {
+        let service = self.clone();
+        Box::pin(async move {
+            tracing::info!("Starting balance monitor");
+            service.monitor_balances().await.map_err(SupervisorErr::Recover)
+        })
+    }
+}
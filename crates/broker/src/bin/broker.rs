@@ -3,18 +3,14 @@
 // All rights reserved.
 
 use alloy::{
-    primitives::utils::parse_ether,
     providers::{network::EthereumWallet, ProviderBuilder, WalletProvider},
     rpc::client::RpcClient,
     transports::layers::RetryBackoffLayer,
 };
 use alloy_chains::NamedChain;
 use anyhow::{Context, Result};
-use boundless_market::{
-    balance_alerts_layer::{BalanceAlertConfig, BalanceAlertLayer},
-    contracts::boundless_market::BoundlessMarketService,
-};
-use broker::{Args, Broker, Config, CustomRetryPolicy};
+use boundless_market::contracts::boundless_market::BoundlessMarketService;
+use broker::{Args, Broker, CustomRetryPolicy};
 use clap::Parser;
 use tracing_subscriber::fmt::format::FmtSpan;
 
@@ -26,9 +22,9 @@ async fn main() -> Result<()> {
         .init();
 
     let args = Args::parse();
-    let config = Config::load(&args.config_file).await?;
 
-    let wallet = EthereumWallet::from(args.private_key.clone());
+    let signer = args.signer.signer().await.context("Failed to create signer")?;
+    let wallet = EthereumWallet::from(signer.clone());
 
     let retry_layer = RetryBackoffLayer::new_with_policy(
         args.rpc_retry_max,
@@ -37,43 +33,28 @@ async fn main() -> Result<()> {
         CustomRetryPolicy,
     );
     let client = RpcClient::builder().layer(retry_layer).http(args.rpc_url.clone());
-    let balance_alerts_layer = BalanceAlertLayer::new(BalanceAlertConfig {
-        watch_address: wallet.default_signer().address(),
-        warn_threshold: config
-            .market
-            .balance_warn_threshold
-            .map(|s| parse_ether(&s))
-            .transpose()?,
-        error_threshold: config
-            .market
-            .balance_error_threshold
-            .map(|s| parse_ether(&s))
-            .transpose()?,
-    });
 
-    let provider = ProviderBuilder::new()
-        .layer(balance_alerts_layer)
-        .wallet(wallet)
-        .with_chain(NamedChain::Sepolia)
-        .on_client(client);
+    let provider =
+        ProviderBuilder::new().wallet(wallet).with_chain(NamedChain::Sepolia).on_client(client);
+
+    let deposit_amount = args.deposit_amount;
+    let broker = Broker::new(args, provider.clone(), signer.clone()).await?;
 
     // TODO: Move this code somewhere else / monitor our balanceOf and top it up as needed
-    if let Some(deposit_amount) = args.deposit_amount.as_ref() {
+    if let Some(deposit_amount) = deposit_amount {
         let boundless_market = BoundlessMarketService::new(
-            args.boundless_market_address,
+            broker.deployment().boundless_market_address,
             provider.clone(),
             provider.default_signer_address(),
         );
 
         tracing::info!("pre-depositing {deposit_amount} HP into the market contract");
         boundless_market
-            .deposit_stake_with_permit(*deposit_amount, &args.private_key)
+            .deposit_stake_with_permit(deposit_amount, &signer)
             .await
             .context("Failed to deposit to market")?;
     }
 
-    let broker = Broker::new(args, provider).await?;
-
     broker.start_service().await.context("Broker service failed")?;
 
     Ok(())
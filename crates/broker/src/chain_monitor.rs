@@ -11,6 +11,7 @@ use tokio::sync::{watch, Notify, RwLock};
 
 use alloy::{eips::BlockNumberOrTag, providers::Provider};
 use anyhow::{Context, Result};
+use boundless_market::clock::{ChainClock, ClockObj};
 
 use crate::task::{RetryRes, RetryTask, SupervisorErr};
 
@@ -22,6 +23,7 @@ pub struct ChainMonitorService<P> {
     gas_price: watch::Sender<u128>,
     update_notifier: Arc<Notify>,
     next_update: Arc<RwLock<Instant>>,
+    clock: ChainClock<Arc<P>>,
 }
 
 impl<P: Provider> ChainMonitorService<P> {
@@ -30,6 +32,9 @@ impl<P: Provider> ChainMonitorService<P> {
         let (gas_price, _) = watch::channel(0);
         let (block_timestamp, _) = watch::channel(0);
 
+        let clock = ChainClock::new(provider.clone());
+        clock.sync().await.context("failed to sync chain clock")?;
+
         Ok(Self {
             provider,
             block_number,
@@ -37,9 +42,20 @@ impl<P: Provider> ChainMonitorService<P> {
             gas_price,
             update_notifier: Arc::new(Notify::new()),
             next_update: Arc::new(RwLock::new(Instant::now())),
+            clock,
         })
     }
 
+    /// Returns a clock following the timestamps of the blocks seen by the chain monitor.
+    ///
+    /// Deadlines of orders should be checked against this clock rather than the system clock.
+    pub fn clock(&self) -> ClockObj
+    where
+        P: 'static,
+    {
+        Arc::new(self.clock.clone())
+    }
+
     /// Returns the latest block number, triggering an update if enough time has passed
     pub async fn current_block_number(&self) -> Result<u64> {
         if Instant::now() > *self.next_update.read().await {
@@ -126,6 +142,7 @@ where
                     .map_err(SupervisorErr::Recover)?;
                 let _ = self_clone.block_number.send_replace(block.header.number);
                 let _ = self_clone.block_timestamp.send_replace(block.header.timestamp);
+                self_clone.clock.observe(block.header.number, block.header.timestamp);
 
                 let gas_price = gas_price_res
                     .context("failed to get gas price")
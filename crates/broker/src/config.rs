@@ -44,9 +44,25 @@ mod defaults {
         250_000
     }
 
+    pub const fn balance_check_interval() -> u64 {
+        60
+    }
+
     pub const fn max_submission_attempts() -> u32 {
         3
     }
+
+    pub const fn max_concurrent_batches() -> u32 {
+        1
+    }
+
+    pub const fn min_segment_limit_po2() -> u32 {
+        13
+    }
+
+    pub const fn max_segment_limit_po2() -> u32 {
+        22
+    }
 }
 /// All configuration related to markets mechanics
 #[derive(Debug, Deserialize, Serialize)]
@@ -118,12 +134,35 @@ pub struct MarketConf {
     /// Balance warning threshold (in native token)
     /// if the submitter balance drops below this the broker will issue error logs
     pub balance_error_threshold: Option<String>,
+    /// Market balance warning threshold (in native token)
+    /// if the balance deposited in the market drops below this the broker will issue warning logs
+    pub market_balance_warn_threshold: Option<String>,
+    /// Market balance error threshold (in native token)
+    /// if the balance deposited in the market drops below this the broker will issue error logs
+    pub market_balance_error_threshold: Option<String>,
     /// Stake balance warning threshold (in stake tokens)
     /// if the stake balance drops below this the broker will issue warning logs
     pub stake_balance_warn_threshold: Option<String>,
     /// Stake balance error threshold (in stake tokens)
     /// if the stake balance drops below this the broker will issue error logs
     pub stake_balance_error_threshold: Option<String>,
+    /// Hit points balance warning threshold (in stake tokens)
+    /// if the hit points held by the broker wallet drop below this the broker will issue warning
+    /// logs
+    pub hp_balance_warn_threshold: Option<String>,
+    /// Hit points balance error threshold (in stake tokens)
+    /// if the hit points held by the broker wallet drop below this the broker will issue error logs
+    pub hp_balance_error_threshold: Option<String>,
+    /// Interval in seconds between checks of the balances against the thresholds
+    #[serde(default = "defaults::balance_check_interval")]
+    pub balance_check_interval: u64,
+    /// Optional webhook URL to post balance alerts to, as JSON
+    pub alert_webhook_url: Option<String>,
+    /// Optional command, and its arguments, to run for each balance alert
+    ///
+    /// The alert is passed in the `BOUNDLESS_ALERT` (JSON) and `BOUNDLESS_ALERT_MESSAGE`
+    /// environment variables
+    pub alert_command: Option<Vec<String>>,
     /// Max concurrent locks
     ///
     /// Maximum number of concurrent lockin requests that can be processed at once
@@ -131,6 +170,16 @@ pub struct MarketConf {
     /// Optional cache directory for storing downloaded images and inputs
     /// if not set, files will be re-downloaded every time
     pub cache_dir: Option<PathBuf>,
+    /// Optional path to a file holding the hex encoded secp256k1 secret key used to decrypt
+    /// encrypted request inputs
+    ///
+    /// The `INPUT_DECRYPTION_KEY` environment variable takes precedence over this file. Orders
+    /// with inputs encrypted to other keys are skipped
+    pub input_decryption_key_file: Option<PathBuf>,
+    /// Optional directory to archive the journal and seal of fulfilled orders
+    ///
+    /// Serving this directory over HTTP lets requestors fetch proofs without an archive node
+    pub fulfillment_archive_dir: Option<PathBuf>,
 }
 
 impl Default for MarketConf {
@@ -155,10 +204,19 @@ impl Default for MarketConf {
             groth16_verify_gas_estimate: defaults::groth16_verify_gas_estimate(),
             balance_warn_threshold: None,
             balance_error_threshold: None,
+            market_balance_warn_threshold: None,
+            market_balance_error_threshold: None,
             stake_balance_warn_threshold: None,
             stake_balance_error_threshold: None,
+            hp_balance_warn_threshold: None,
+            hp_balance_error_threshold: None,
+            balance_check_interval: defaults::balance_check_interval(),
+            alert_webhook_url: None,
+            alert_command: None,
             max_concurrent_locks: None,
             cache_dir: None,
+            input_decryption_key_file: None,
+            fulfillment_archive_dir: None,
         }
     }
 }
@@ -199,6 +257,16 @@ pub struct ProverConf {
     ///
     /// None indicates there are infinite number of retries.
     pub max_critical_task_retries: Option<u32>,
+    /// Min segment limit po2 accepted from the env of an order
+    ///
+    /// Orders whose env sets a smaller segment limit po2 are skipped
+    #[serde(default = "defaults::min_segment_limit_po2")]
+    pub min_segment_limit_po2: u32,
+    /// Max segment limit po2 accepted from the env of an order
+    ///
+    /// Orders whose env sets a larger segment limit po2 are skipped
+    #[serde(default = "defaults::max_segment_limit_po2")]
+    pub max_segment_limit_po2: u32,
 }
 
 impl Default for ProverConf {
@@ -214,6 +282,8 @@ impl Default for ProverConf {
             set_builder_guest_path: None,
             assessor_set_guest_path: None,
             max_critical_task_retries: None,
+            min_segment_limit_po2: defaults::min_segment_limit_po2(),
+            max_segment_limit_po2: defaults::max_segment_limit_po2(),
         }
     }
 }
@@ -226,6 +296,17 @@ pub struct BatcherConfig {
     /// Batch size (in proofs) before publishing
     #[serde(alias = "batch_size")]
     pub min_batch_size: Option<u64>,
+    /// Max batch size (in proofs)
+    ///
+    /// Once a batch reaches this size it is finalized, and further orders are assigned to
+    /// another batch
+    pub max_batch_size: Option<u64>,
+    /// Number of batches that can be aggregated in parallel
+    ///
+    /// Orders are assigned to open batches by deadline, and the set-builder proofs for each
+    /// batch run concurrently on the prover backend
+    #[serde(default = "defaults::max_concurrent_batches")]
+    pub max_concurrent_batches: u32,
     /// Max combined journal size (in bytes) that once exceeded will trigger a publish
     #[serde(default = "defaults::batch_max_journal_bytes")]
     pub batch_max_journal_bytes: usize,
@@ -258,6 +339,8 @@ impl Default for BatcherConfig {
         Self {
             batch_max_time: None,
             min_batch_size: Some(2),
+            max_batch_size: None,
+            max_concurrent_batches: defaults::max_concurrent_batches(),
             batch_max_journal_bytes: defaults::batch_max_journal_bytes(),
             batch_max_fees: None,
             block_deadline_buffer_secs: 120,
@@ -482,6 +565,8 @@ proof_retry_sleep_ms = 500
 [batcher]
 batch_max_time = 300
 batch_size = 3
+max_batch_size = 10
+max_concurrent_batches = 2
 block_deadline_buffer_secs = 120
 txn_timeout = 45
 batch_poll_time_ms = 1200
@@ -533,6 +618,8 @@ error = ?"#;
         assert_eq!(config.batcher.block_deadline_buffer_secs, 120);
         assert_eq!(config.batcher.txn_timeout, None);
         assert_eq!(config.batcher.batch_poll_time_ms, None);
+        assert_eq!(config.batcher.max_batch_size, None);
+        assert_eq!(config.batcher.max_concurrent_batches, 1);
     }
 
     #[tokio::test]
@@ -586,6 +673,8 @@ error = ?"#;
             assert_eq!(config.batcher.txn_timeout, Some(45));
             assert_eq!(config.batcher.batch_poll_time_ms, Some(1200));
             assert_eq!(config.batcher.min_batch_size, Some(3));
+            assert_eq!(config.batcher.max_batch_size, Some(10));
+            assert_eq!(config.batcher.max_concurrent_batches, 2);
             assert!(config.batcher.single_txn_fulfill);
         }
         tracing::debug!("closing...");
@@ -112,6 +112,9 @@ fn generate_test_order(id: u32) -> Order {
         client_sig: vec![].into(),
         lock_price: Some(U256::from(10)),
         error_msg: None,
+        offchain: false,
+        callback_gas: None,
+        assumption_ids: vec![],
     }
 }
 
@@ -208,7 +211,7 @@ proptest! {
                                         db.set_order_proof_id(U256::from(id), &proof_id).await.unwrap();
                                     },
                                     ExistingOrderOperation::SetImageInputIds { image_id, input_id } => {
-                                        db.set_image_input_ids(U256::from(id), &image_id, &input_id).await.unwrap();
+                                        db.set_image_input_ids(U256::from(id), &image_id, &input_id, &[]).await.unwrap();
                                     },
                                     ExistingOrderOperation::SetAggregationStatus => {
                                         db.set_aggregation_status(U256::from(id), OrderStatus::PendingAgg).await.unwrap();
@@ -278,6 +281,7 @@ proptest! {
                                                 guest_state: GuestState::initial([1u32; 8]),
                                                 claim_digests: vec![],
                                                 groth16_proof_id: None,
+                                                groth16_seal: None,
                                                 proof_id,
                                             };
 
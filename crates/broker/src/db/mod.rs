@@ -4,16 +4,19 @@
 
 use std::{default::Default, str::FromStr, sync::Arc};
 
-use alloy::primitives::{ruint::ParseError as RuintParseErr, B256, U256};
+use alloy::primitives::{ruint::ParseError as RuintParseErr, Bytes, B256, U256};
 use async_trait::async_trait;
 use chrono::Utc;
+use serde::{Deserialize, Serialize};
 use sqlx::{
     sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions},
     Row,
 };
 use thiserror::Error;
 
-use crate::{AggregationState, Batch, BatchStatus, Order, OrderStatus, ProofRequest};
+use crate::{
+    AggregationCheckpoint, AggregationState, Batch, BatchStatus, Order, OrderStatus, ProofRequest,
+};
 use tracing::instrument;
 
 #[cfg(test)]
@@ -55,6 +58,12 @@ pub enum DbError {
     #[error("Failed to set last block")]
     SetBlockFail,
 
+    #[error("Invalid callback gas limit: {0}")]
+    BadCallbackGas(u64),
+
+    #[error("Invalid order stream cursor: {0}")]
+    BadOffchainCursor(String),
+
     #[error("Invalid order id: {0} missing field: {1}")]
     InvalidOrder(String, &'static str),
 
@@ -63,7 +72,7 @@ pub enum DbError {
 }
 
 /// Struct containing the information about an order used by the aggregation worker.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct AggregationOrder {
     pub order_id: U256,
     pub proof_id: String,
@@ -98,8 +107,30 @@ pub trait BrokerDb {
     async fn set_order_complete(&self, id: U256) -> Result<(), DbError>;
     async fn set_order_status(&self, id: U256, status: OrderStatus) -> Result<(), DbError>;
     async fn skip_order(&self, id: U256) -> Result<(), DbError>;
+    /// Skip an order that was cancelled by its client, unless the broker already started pricing
+    /// it, as the picker may lock it concurrently. Returns whether the order was skipped.
+    async fn cancel_order(&self, id: U256) -> Result<bool, DbError>;
+    /// Replace an order not yet picked for pricing, or skipped, with a new version of the
+    /// request, such as one with a higher price, resetting it for pricing. Returns whether the
+    /// order was replaced.
+    async fn replace_order(&self, id: U256, order: Order) -> Result<bool, DbError>;
+    /// Add an order, or replace the stored version of the request if it was resubmitted under the
+    /// same ID with another offer. Returns whether the order was added or replaced.
+    async fn add_or_replace_order(&self, id: U256, order: Order) -> Result<bool, DbError> {
+        match self.get_order(id).await? {
+            None => {
+                self.add_order(id, order).await?;
+                Ok(true)
+            }
+            Some(existing) if existing.request == order.request => Ok(false),
+            Some(_) => self.replace_order(id, order).await,
+        }
+    }
     async fn get_last_block(&self) -> Result<Option<u64>, DbError>;
     async fn set_last_block(&self, block_numb: u64) -> Result<(), DbError>;
+    /// Get the highest order stream id of the offchain orders received, if any.
+    async fn get_offchain_cursor(&self) -> Result<Option<i64>, DbError>;
+    async fn set_offchain_cursor(&self, cursor: i64) -> Result<(), DbError>;
     async fn get_pending_lock_orders(
         &self,
         end_timestamp: u64,
@@ -123,15 +154,37 @@ pub trait BrokerDb {
         id: U256,
         image_id: &str,
         input_id: &str,
+        assumption_ids: &[String],
     ) -> Result<(), DbError>;
+    async fn set_order_callback_gas(&self, id: U256, callback_gas: u64) -> Result<(), DbError>;
     async fn set_aggregation_status(&self, id: U256, status: OrderStatus) -> Result<(), DbError>;
     async fn get_aggregation_proofs(&self) -> Result<Vec<AggregationOrder>, DbError>;
     async fn get_groth16_proofs(&self) -> Result<Vec<AggregationOrder>, DbError>;
+    /// Record the ID of the Groth16 compression proof of a batch as soon as it is started, so
+    /// that the compression is resumed rather than restarted after an interruption.
+    async fn set_batch_groth16_proof_id(
+        &self,
+        batch_id: usize,
+        g16_proof_id: &str,
+    ) -> Result<(), DbError>;
+    /// Record the Groth16 proof of a batch that is pending compression, along with its seal.
+    ///
+    /// The batch stays pending compression until [BrokerDb::complete_batch] is called.
+    async fn set_batch_groth16_seal(
+        &self,
+        batch_id: usize,
+        g16_proof_id: &str,
+        seal: &Bytes,
+    ) -> Result<(), DbError>;
     async fn complete_batch(&self, batch_id: usize, g16_proof_id: String) -> Result<(), DbError>;
     async fn get_complete_batch(&self) -> Result<Option<(usize, Batch)>, DbError>;
     async fn set_batch_submitted(&self, batch_id: usize) -> Result<(), DbError>;
     async fn set_batch_failure(&self, batch_id: usize, err: String) -> Result<(), DbError>;
     async fn get_current_batch(&self) -> Result<usize, DbError>;
+    /// Create a new, empty batch in the aggregating state.
+    async fn new_batch(&self) -> Result<usize, DbError>;
+    /// Get all batches that are still being aggregated or compressed, ordered by ID.
+    async fn get_open_batches(&self) -> Result<Vec<(usize, Batch)>, DbError>;
 
     /// Update a batch with the results of an aggregation step.
     ///
@@ -146,6 +199,15 @@ pub trait BrokerDb {
     ) -> Result<(), DbError>;
     async fn get_batch(&self, batch_id: usize) -> Result<Batch, DbError>;
 
+    /// Record the progress of an aggregation step that has not yet completed.
+    ///
+    /// The checkpoint is cleared by [BrokerDb::update_batch] once the step completes.
+    async fn set_batch_checkpoint(
+        &self,
+        batch_id: usize,
+        checkpoint: &AggregationCheckpoint,
+    ) -> Result<(), DbError>;
+
     #[cfg(test)]
     async fn add_batch(&self, batch_id: usize, batch: Batch) -> Result<(), DbError>;
     #[cfg(test)]
@@ -155,6 +217,7 @@ pub trait BrokerDb {
 pub type DbObj = Arc<dyn BrokerDb + Send + Sync>;
 
 const SQL_BLOCK_KEY: i64 = 0;
+const SQL_OFFCHAIN_CURSOR_KEY: i64 = 1;
 
 pub struct SqliteDb {
     pool: SqlitePool,
@@ -189,17 +252,6 @@ impl SqliteDb {
     pub async fn from(pool: SqlitePool) -> Result<Self, DbError> {
         Ok(Self { pool })
     }
-
-    async fn new_batch(&self) -> Result<usize, DbError> {
-        let batch = Batch { start_time: Utc::now(), ..Default::default() };
-
-        let res: i64 = sqlx::query_scalar("INSERT INTO batches (data) VALUES ($1) RETURNING id")
-            .bind(sqlx::types::Json(&batch))
-            .fetch_one(&self.pool)
-            .await?;
-
-        Ok(res as usize)
-    }
 }
 
 #[derive(sqlx::FromRow)]
@@ -507,6 +559,43 @@ impl BrokerDb for SqliteDb {
         Ok(())
     }
 
+    #[instrument(level = "trace", skip_all, fields(id = %format!("{id:x}")))]
+    async fn cancel_order(&self, id: U256) -> Result<bool, DbError> {
+        let res = sqlx::query(
+            r#"
+            UPDATE orders
+            SET data = json_set(
+                       json_set(data,
+                       '$.status', $1),
+                       '$.updated_at', $2)
+            WHERE
+                id = $3 AND data->>'status' = $4"#,
+        )
+        .bind(OrderStatus::Skipped)
+        .bind(Utc::now().timestamp())
+        .bind(format!("{id:x}"))
+        .bind(OrderStatus::New)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected() != 0)
+    }
+
+    #[instrument(level = "trace", skip_all, fields(id = %format!("{id:x}")))]
+    async fn replace_order(&self, id: U256, order: Order) -> Result<bool, DbError> {
+        let res = sqlx::query(
+            "UPDATE orders SET data = $1 WHERE id = $2 AND data->>'status' IN ($3, $4)",
+        )
+        .bind(sqlx::types::Json(&order))
+        .bind(format!("{id:x}"))
+        .bind(OrderStatus::New)
+        .bind(OrderStatus::Skipped)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected() != 0)
+    }
+
     #[instrument(level = "trace", skip_all)]
     async fn get_last_block(&self) -> Result<Option<u64>, DbError> {
         // TODO: query_as, seems to not work correctly here
@@ -539,6 +628,33 @@ impl BrokerDb for SqliteDb {
         Ok(())
     }
 
+    #[instrument(level = "trace", skip_all)]
+    async fn get_offchain_cursor(&self) -> Result<Option<i64>, DbError> {
+        let res = sqlx::query("SELECT block FROM last_block WHERE id = $1")
+            .bind(SQL_OFFCHAIN_CURSOR_KEY)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        let Some(row) = res else {
+            return Ok(None);
+        };
+
+        let cursor_str: String = row.try_get("block")?;
+
+        Ok(Some(cursor_str.parse().map_err(|_err| DbError::BadOffchainCursor(cursor_str))?))
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    async fn set_offchain_cursor(&self, cursor: i64) -> Result<(), DbError> {
+        sqlx::query("REPLACE INTO last_block (id, block) VALUES ($1, $2)")
+            .bind(SQL_OFFCHAIN_CURSOR_KEY)
+            .bind(cursor.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
     #[instrument(level = "trace", skip_all)]
     async fn get_pending_lock_orders(
         &self,
@@ -691,21 +807,50 @@ impl BrokerDb for SqliteDb {
         id: U256,
         image_id: &str,
         input_id: &str,
+        assumption_ids: &[String],
     ) -> Result<(), DbError> {
         let res = sqlx::query(
             r#"
             UPDATE orders
             SET data = json_set(
+                       json_set(
                        json_set(
                        json_set(data,
                        '$.image_id', $1),
                        '$.input_id', $2),
-                       '$.updated_at', $3)
+                       '$.assumption_ids', json($3)),
+                       '$.updated_at', $4)
             WHERE
-                id = $4"#,
+                id = $5"#,
         )
         .bind(image_id)
         .bind(input_id)
+        .bind(serde_json::to_string(assumption_ids)?)
+        .bind(Utc::now().timestamp())
+        .bind(format!("{id:x}"))
+        .execute(&self.pool)
+        .await?;
+
+        if res.rows_affected() == 0 {
+            return Err(DbError::OrderNotFound(id));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, fields(id = %format!("{id:x}")))]
+    async fn set_order_callback_gas(&self, id: U256, callback_gas: u64) -> Result<(), DbError> {
+        let res = sqlx::query(
+            r#"
+            UPDATE orders
+            SET data = json_set(
+                       json_set(data,
+                       '$.callback_gas', $1),
+                       '$.updated_at', $2)
+            WHERE
+                id = $3"#,
+        )
+        .bind(i64::try_from(callback_gas).map_err(|_| DbError::BadCallbackGas(callback_gas))?)
         .bind(Utc::now().timestamp())
         .bind(format!("{id:x}"))
         .execute(&self.pool)
@@ -824,6 +969,71 @@ impl BrokerDb for SqliteDb {
         Ok(agg_orders)
     }
 
+    #[instrument(level = "trace", skip_all)]
+    async fn set_batch_groth16_proof_id(
+        &self,
+        batch_id: usize,
+        g16_proof_id: &str,
+    ) -> Result<(), DbError> {
+        let batch = self.get_batch(batch_id).await?;
+        if batch.aggregation_state.is_none() {
+            return Err(DbError::BatchAggregationStateIsNone(batch_id));
+        }
+
+        let res = sqlx::query(
+            r#"
+            UPDATE batches
+            SET data = json_set(data, '$.aggregation_state.groth16_proof_id', $1)
+            WHERE
+                id = $2"#,
+        )
+        .bind(g16_proof_id)
+        .bind(batch_id as i64)
+        .execute(&self.pool)
+        .await?;
+
+        if res.rows_affected() == 0 {
+            return Err(DbError::BatchNotFound(batch_id));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all)]
+    async fn set_batch_groth16_seal(
+        &self,
+        batch_id: usize,
+        g16_proof_id: &str,
+        seal: &Bytes,
+    ) -> Result<(), DbError> {
+        let batch = self.get_batch(batch_id).await?;
+        if batch.aggregation_state.is_none() {
+            return Err(DbError::BatchAggregationStateIsNone(batch_id));
+        }
+
+        let res = sqlx::query(
+            r#"
+            UPDATE batches
+            SET data = json_set(
+                       json_set(data,
+                       '$.aggregation_state.groth16_proof_id', $1),
+                       '$.aggregation_state.groth16_seal', $2)
+            WHERE
+                id = $3"#,
+        )
+        .bind(g16_proof_id)
+        .bind(seal.to_string())
+        .bind(batch_id as i64)
+        .execute(&self.pool)
+        .await?;
+
+        if res.rows_affected() == 0 {
+            return Err(DbError::BatchNotFound(batch_id));
+        }
+
+        Ok(())
+    }
+
     #[instrument(level = "trace", skip_all)]
     async fn complete_batch(&self, batch_id: usize, g16_proof_id: String) -> Result<(), DbError> {
         let batch = self.get_batch(batch_id).await?;
@@ -952,6 +1162,30 @@ impl BrokerDb for SqliteDb {
         }
     }
 
+    #[instrument(level = "trace", skip(self))]
+    async fn new_batch(&self) -> Result<usize, DbError> {
+        let batch = Batch { start_time: Utc::now(), ..Default::default() };
+
+        let res: i64 = sqlx::query_scalar("INSERT INTO batches (data) VALUES ($1) RETURNING id")
+            .bind(sqlx::types::Json(&batch))
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(res as usize)
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    async fn get_open_batches(&self) -> Result<Vec<(usize, Batch)>, DbError> {
+        let batches: Vec<DbBatch> =
+            sqlx::query_as("SELECT * FROM batches WHERE data->>'status' IN ($1, $2) ORDER BY id")
+                .bind(BatchStatus::Aggregating)
+                .bind(BatchStatus::PendingCompression)
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(batches.into_iter().map(|batch| (batch.id as usize, batch.data)).collect())
+    }
+
     #[instrument(level = "trace", skip(self, aggreagtion_state, orders, assessor_proof_id))]
     async fn update_batch(
         &self,
@@ -989,12 +1223,14 @@ impl BrokerDb for SqliteDb {
             r#"
             UPDATE batches
             SET
-                data = json_set(
+                data = json_remove(
+                       json_set(
                        json_set(
                        json_set(data,
                        '$.deadline', $1),
                        '$.fees', $2),
-                       '$.aggregation_state', json($3))
+                       '$.aggregation_state', json($3)),
+                       '$.checkpoint')
             WHERE
                 id = $4"#,
         )
@@ -1091,6 +1327,31 @@ impl BrokerDb for SqliteDb {
         }
     }
 
+    #[instrument(level = "trace", skip(self, checkpoint))]
+    async fn set_batch_checkpoint(
+        &self,
+        batch_id: usize,
+        checkpoint: &AggregationCheckpoint,
+    ) -> Result<(), DbError> {
+        let res = sqlx::query(
+            r#"
+            UPDATE batches
+            SET data = json_set(data, '$.checkpoint', json($1))
+            WHERE
+                id = $2"#,
+        )
+        .bind(sqlx::types::Json(checkpoint))
+        .bind(batch_id as i64)
+        .execute(&self.pool)
+        .await?;
+
+        if res.rows_affected() == 0 {
+            return Err(DbError::BatchNotFound(batch_id));
+        }
+
+        Ok(())
+    }
+
     #[cfg(test)]
     async fn add_batch(&self, batch_id: usize, batch: Batch) -> Result<(), DbError> {
         let res = sqlx::query("INSERT INTO batches (id, data) VALUES ($1, $2)")
@@ -1175,6 +1436,9 @@ mod tests {
             client_sig: Bytes::new(),
             lock_price: None,
             error_msg: None,
+            offchain: false,
+            callback_gas: None,
+            assumption_ids: vec![],
         }
     }
 
@@ -1435,6 +1699,70 @@ mod tests {
         assert_eq!(db_order.status, OrderStatus::Skipped);
     }
 
+    #[sqlx::test]
+    async fn cancel_order(pool: SqlitePool) {
+        let db: DbObj = Arc::new(SqliteDb::from(pool).await.unwrap());
+        let id = U256::ZERO;
+        let mut order = create_order();
+        db.add_order(id, order.clone()).await.unwrap();
+
+        assert!(db.cancel_order(id).await.unwrap());
+        assert_eq!(db.get_order(id).await.unwrap().unwrap().status, OrderStatus::Skipped);
+
+        // Orders the broker started pricing, locking or proving are not cancelled.
+        for (i, status) in [OrderStatus::Pricing, OrderStatus::Locking, OrderStatus::PendingProving]
+            .into_iter()
+            .enumerate()
+        {
+            let started_id = U256::from(i + 1);
+            order.status = status;
+            db.add_order(started_id, order.clone()).await.unwrap();
+            assert!(!db.cancel_order(started_id).await.unwrap());
+            assert_eq!(db.get_order(started_id).await.unwrap().unwrap().status, status);
+        }
+        assert!(!db.cancel_order(U256::from(10)).await.unwrap());
+    }
+
+    #[sqlx::test]
+    async fn replace_order(pool: SqlitePool) {
+        let db: DbObj = Arc::new(SqliteDb::from(pool).await.unwrap());
+        let id = U256::ZERO;
+        let order = create_order();
+        db.add_order(id, order.clone()).await.unwrap();
+
+        let mut bumped = create_order();
+        bumped.request.offer.maxPrice = U256::from(4);
+        db.set_order_status(id, OrderStatus::Pricing).await.unwrap();
+        assert!(!db.replace_order(id, bumped.clone()).await.unwrap());
+
+        db.skip_order(id).await.unwrap();
+        assert!(db.replace_order(id, bumped).await.unwrap());
+        let db_order = db.get_order(id).await.unwrap().unwrap();
+        assert_eq!(db_order.status, OrderStatus::New);
+        assert_eq!(db_order.request.offer.maxPrice, U256::from(4));
+    }
+
+    #[sqlx::test]
+    async fn add_or_replace_order(pool: SqlitePool) {
+        let db: DbObj = Arc::new(SqliteDb::from(pool).await.unwrap());
+        let id = U256::ZERO;
+        let order = create_order();
+        assert!(db.add_or_replace_order(id, order.clone()).await.unwrap());
+
+        // Seeing the same request again does not reset a skipped order.
+        db.skip_order(id).await.unwrap();
+        assert!(!db.add_or_replace_order(id, order).await.unwrap());
+        assert_eq!(db.get_order(id).await.unwrap().unwrap().status, OrderStatus::Skipped);
+
+        // A resubmission with another offer replaces it.
+        let mut bumped = create_order();
+        bumped.request.offer.maxPrice = U256::from(4);
+        assert!(db.add_or_replace_order(id, bumped).await.unwrap());
+        let db_order = db.get_order(id).await.unwrap().unwrap();
+        assert_eq!(db_order.status, OrderStatus::New);
+        assert_eq!(db_order.request.offer.maxPrice, U256::from(4));
+    }
+
     #[sqlx::test]
     async fn set_get_block(pool: SqlitePool) {
         let db: DbObj = Arc::new(SqliteDb::from(pool).await.unwrap());
@@ -1452,6 +1780,20 @@ mod tests {
         assert_eq!(block_numb, db_block);
     }
 
+    #[sqlx::test]
+    async fn set_get_offchain_cursor(pool: SqlitePool) {
+        let db: DbObj = Arc::new(SqliteDb::from(pool).await.unwrap());
+
+        assert_eq!(db.get_offchain_cursor().await.unwrap(), None);
+        db.set_offchain_cursor(42).await.unwrap();
+        assert_eq!(db.get_offchain_cursor().await.unwrap(), Some(42));
+
+        // Stored apart from the last block.
+        db.set_last_block(20).await.unwrap();
+        assert_eq!(db.get_offchain_cursor().await.unwrap(), Some(42));
+        assert_eq!(db.get_last_block().await.unwrap(), Some(20));
+    }
+
     #[sqlx::test]
     async fn get_pending_lock_orders(pool: SqlitePool) {
         let db: DbObj = Arc::new(SqliteDb::from(pool).await.unwrap());
@@ -1537,12 +1879,31 @@ mod tests {
 
         let image_id = "test_img";
         let input_id = "test_input";
-        db.set_image_input_ids(id, image_id, input_id).await.unwrap();
+        let assumption_ids = vec!["test_receipt".to_string()];
+        db.set_image_input_ids(id, image_id, input_id, &assumption_ids).await.unwrap();
 
         let db_order = db.get_order(id).await.unwrap().unwrap();
 
         assert_eq!(db_order.image_id, Some(image_id.into()));
         assert_eq!(db_order.input_id, Some(input_id.into()));
+        assert_eq!(db_order.assumption_ids, assumption_ids);
+    }
+
+    #[sqlx::test]
+    async fn set_order_callback_gas(pool: SqlitePool) {
+        let db: DbObj = Arc::new(SqliteDb::from(pool).await.unwrap());
+
+        let id = U256::ZERO;
+        let order = create_order();
+        db.add_order(id, order.clone()).await.unwrap();
+
+        db.set_order_callback_gas(id, 50_000).await.unwrap();
+
+        let db_order = db.get_order(id).await.unwrap().unwrap();
+        assert_eq!(db_order.callback_gas, Some(50_000));
+
+        let err = db.set_order_callback_gas(id, u64::MAX).await.unwrap_err();
+        assert!(matches!(err, DbError::BadCallbackGas(u64::MAX)));
     }
 
     #[sqlx::test]
@@ -1664,6 +2025,7 @@ mod tests {
                 guest_state: GuestState::initial([1u32; 8]),
                 claim_digests: vec![],
                 groth16_proof_id: None,
+                groth16_seal: None,
                 proof_id: "a".to_string(),
             }),
             ..Default::default()
@@ -1678,6 +2040,61 @@ mod tests {
         assert_eq!(db_batch.aggregation_state.unwrap().groth16_proof_id.unwrap(), g16_proof_id);
     }
 
+    #[sqlx::test]
+    async fn set_batch_groth16_seal(pool: SqlitePool) {
+        let db: DbObj = Arc::new(SqliteDb::from(pool).await.unwrap());
+
+        let batch_id = 1;
+        let batch = Batch {
+            status: BatchStatus::PendingCompression,
+            aggregation_state: Some(AggregationState {
+                guest_state: GuestState::initial([1u32; 8]),
+                claim_digests: vec![],
+                groth16_proof_id: None,
+                groth16_seal: None,
+                proof_id: "a".to_string(),
+            }),
+            ..Default::default()
+        };
+        db.add_batch(batch_id, batch).await.unwrap();
+
+        let seal = Bytes::from(vec![1, 2, 3, 4]);
+        db.set_batch_groth16_seal(batch_id, "Testg16", &seal).await.unwrap();
+
+        let db_batch = db.get_batch(batch_id).await.unwrap();
+        assert_eq!(db_batch.status, BatchStatus::PendingCompression);
+        let aggregation_state = db_batch.aggregation_state.unwrap();
+        assert_eq!(aggregation_state.groth16_proof_id.unwrap(), "Testg16");
+        assert_eq!(aggregation_state.groth16_seal.unwrap(), seal);
+    }
+
+    #[sqlx::test]
+    async fn set_batch_groth16_proof_id(pool: SqlitePool) {
+        let db: DbObj = Arc::new(SqliteDb::from(pool).await.unwrap());
+
+        let batch_id = 1;
+        let batch = Batch {
+            status: BatchStatus::PendingCompression,
+            aggregation_state: Some(AggregationState {
+                guest_state: GuestState::initial([1u32; 8]),
+                claim_digests: vec![],
+                groth16_proof_id: None,
+                groth16_seal: None,
+                proof_id: "a".to_string(),
+            }),
+            ..Default::default()
+        };
+        db.add_batch(batch_id, batch).await.unwrap();
+
+        db.set_batch_groth16_proof_id(batch_id, "Testg16").await.unwrap();
+
+        let db_batch = db.get_batch(batch_id).await.unwrap();
+        assert_eq!(db_batch.status, BatchStatus::PendingCompression);
+        let aggregation_state = db_batch.aggregation_state.unwrap();
+        assert_eq!(aggregation_state.groth16_proof_id.unwrap(), "Testg16");
+        assert_eq!(aggregation_state.groth16_seal, None);
+    }
+
     #[sqlx::test]
     async fn get_complete_batch(pool: SqlitePool) {
         let db: DbObj = Arc::new(SqliteDb::from(pool).await.unwrap());
@@ -1755,6 +2172,7 @@ mod tests {
             proof_id: "c".to_string(),
             claim_digests: claim_digests.clone(),
             groth16_proof_id: None,
+            groth16_seal: None,
         };
 
         let base_fees = U256::from(10);
@@ -1782,4 +2200,68 @@ mod tests {
         assert_eq!(&agg_state.proof_id, "c");
         assert_eq!(&agg_state.claim_digests, &claim_digests);
     }
+
+    #[sqlx::test]
+    async fn set_batch_checkpoint(pool: SqlitePool) {
+        let db: DbObj = Arc::new(SqliteDb::from(pool).await.unwrap());
+        db.add_order(U256::from(11), create_order()).await.unwrap();
+
+        let batch_id = db.get_current_batch().await.unwrap();
+        let checkpoint = AggregationCheckpoint {
+            new_proofs: vec![AggregationOrder {
+                proof_id: "a".to_string(),
+                order_id: U256::from(11),
+                expiration: 20,
+                fee: U256::from(5),
+            }],
+            groth16_proofs: vec![],
+            finalize: true,
+            assessor_proof_id: Some("b".to_string()),
+            set_builder_proof_id: None,
+        };
+        db.set_batch_checkpoint(batch_id, &checkpoint).await.unwrap();
+
+        let db_checkpoint = db.get_batch(batch_id).await.unwrap().checkpoint.unwrap();
+        assert_eq!(db_checkpoint.new_proofs, checkpoint.new_proofs);
+        assert_eq!(db_checkpoint.assessor_proof_id.as_deref(), Some("b"));
+        assert_eq!(db_checkpoint.set_builder_proof_id, None);
+        assert!(db_checkpoint.finalize);
+
+        // Completing the aggregation step clears the checkpoint.
+        let agg_state = AggregationState {
+            guest_state: GuestState::initial([3u32; 8]),
+            proof_id: "c".to_string(),
+            claim_digests: vec![],
+            groth16_proof_id: None,
+            groth16_seal: None,
+        };
+        db.update_batch(batch_id, &agg_state, &checkpoint.new_proofs, Some("b".to_string()))
+            .await
+            .unwrap();
+        let db_batch = db.get_batch(batch_id).await.unwrap();
+        assert!(db_batch.checkpoint.is_none());
+        assert_eq!(db_batch.status, BatchStatus::PendingCompression);
+
+        assert!(matches!(
+            db.set_batch_checkpoint(batch_id + 1, &checkpoint).await,
+            Err(DbError::BatchNotFound(_))
+        ));
+    }
+
+    #[sqlx::test]
+    async fn get_open_batches(pool: SqlitePool) {
+        let db: DbObj = Arc::new(SqliteDb::from(pool).await.unwrap());
+        assert!(db.get_open_batches().await.unwrap().is_empty());
+
+        let batch_1 = db.get_current_batch().await.unwrap();
+        let batch_2 = db.new_batch().await.unwrap();
+        let batch_3 = db.new_batch().await.unwrap();
+        db.set_batch_status(batch_2, BatchStatus::PendingCompression).await.unwrap();
+        db.set_batch_status(batch_3, BatchStatus::Complete).await.unwrap();
+
+        let open: Vec<_> = db.get_open_batches().await.unwrap();
+        let open_ids: Vec<_> = open.iter().map(|(id, _)| *id).collect();
+        assert_eq!(open_ids, vec![batch_1, batch_2]);
+        assert_eq!(open[1].1.status, BatchStatus::PendingCompression);
+    }
 }
@@ -2,28 +2,33 @@
 //
 // All rights reserved.
 
-use std::{path::PathBuf, sync::Arc, time::SystemTime};
+use std::{path::PathBuf, sync::Arc};
 
 use crate::config::ConfigLock;
-use crate::storage::create_uri_handler;
+use crate::storage::{
+    create_input_handler, create_uri_handler, decrypt_input, input_decryption_key,
+};
 use alloy::{
     network::Ethereum,
     primitives::{Address, Bytes, U256},
     providers::{Provider, WalletProvider},
-    signers::local::PrivateKeySigner,
+    signers::Signer,
 };
 use anyhow::{ensure, Context, Result};
 use boundless_market::{
     contracts::{boundless_market::BoundlessMarketService, InputType, ProofRequest},
+    deployments::{Deployment, Deployments},
     input::GuestEnv,
     order_stream_client::Client as OrderStreamClient,
     selector::is_groth16_selector,
+    signer::{AnySigner, SignerArgs},
+    simulator::MarketBackend,
 };
 use chrono::{serde::ts_seconds, DateTime, Utc};
 use clap::Parser;
 pub use config::Config;
 use config::ConfigWatcher;
-use db::{DbObj, SqliteDb};
+use db::{AggregationOrder, DbObj, SqliteDb};
 use provers::ProverObj;
 use risc0_ethereum_contracts::set_verifier::SetVerifierService;
 use risc0_zkvm::sha::Digest;
@@ -34,6 +39,7 @@ use tokio::task::JoinSet;
 use url::Url;
 
 pub(crate) mod aggregator;
+pub(crate) mod balance_monitor;
 pub(crate) mod chain_monitor;
 pub(crate) mod config;
 pub(crate) mod db;
@@ -61,21 +67,39 @@ pub struct Args {
     pub rpc_url: Url,
 
     /// Order stream server URL
+    ///
+    /// Setting this value enables receiving offchain orders from the order stream
     #[clap(long, env)]
     pub order_stream_url: Option<Url>,
 
-    /// wallet key
-    #[clap(long, env)]
-    pub private_key: PrivateKeySigner,
+    /// Receive offchain orders from the order stream of the deployment on the chain
+    ///
+    /// Offchain orders are only received if this flag or the order stream URL is set
+    #[clap(long, env, conflicts_with = "order_stream_url")]
+    pub deployment_order_stream: bool,
+
+    /// wallet signer, as a private key or a signer URI
+    #[clap(flatten)]
+    pub signer: SignerArgs,
 
     /// Boundless market address
+    ///
+    /// Defaults to the address of the deployment on the chain, if known
     #[clap(long, env)]
-    pub boundless_market_address: Address,
+    pub boundless_market_address: Option<Address>,
 
     /// Risc zero Set verifier address
-    // TODO: Get this from the market contract via view call
+    ///
+    /// Defaults to the set verifier registered in the verifier router of the market for the set
+    /// builder image, or to the address of the deployment on the chain, if known
     #[clap(long, env)]
-    set_verifier_address: Address,
+    pub set_verifier_address: Option<Address>,
+
+    /// Deployments file path
+    ///
+    /// TOML file overriding or extending the known deployments, e.g. for a local devnet
+    #[clap(long, env = "BOUNDLESS_DEPLOYMENTS")]
+    pub deployments_file: Option<PathBuf>,
 
     /// local prover API (Bento)
     ///
@@ -124,6 +148,96 @@ pub struct Args {
     pub rpc_retry_cu: u64,
 }
 
+/// Returns the image ID of the set builder configured in the broker config, or of the set builder
+/// bundled with the broker.
+fn set_builder_image_id(config: &ConfigLock) -> Result<Digest> {
+    let set_builder_path = {
+        let config = config.lock_all().context("Failed to lock config")?;
+        config.prover.set_builder_guest_path.clone()
+    };
+    match set_builder_path {
+        Some(path) => {
+            let elf_buf = std::fs::read(path).context("Failed to read set-builder path")?;
+            risc0_zkvm::compute_image_id(&elf_buf).context("Failed to compute set-builder imageId")
+        }
+        None => Ok(Digest::from(guest_set_builder::SET_BUILDER_ID)),
+    }
+}
+
+/// Resolves the contract addresses to use, from the arguments or from the known deployment on the
+/// chain of the provider.
+///
+/// Unless given in the arguments, the set verifier is read from the verifier router of the market
+/// contract, as the verifier registered for the given set builder image ID. The set verifier of
+/// the known deployment is only used if the router has none registered.
+async fn resolve_deployment<P>(
+    args: &Args,
+    provider: &P,
+    set_builder_image_id: Digest,
+) -> Result<Deployment>
+where
+    P: Provider<Ethereum> + Clone,
+{
+    let chain_id = provider.get_chain_id().await.context("Failed to get chain ID")?;
+    let deployments = match &args.deployments_file {
+        Some(path) => Deployments::default()
+            .with_overrides_from_file(path)
+            .with_context(|| format!("Failed to load deployments file {}", path.display()))?,
+        None => Deployments::default(),
+    };
+    let mut deployment = match (deployments.resolve(chain_id), args.boundless_market_address) {
+        (Ok(mut deployment), Some(market)) => {
+            if market != deployment.boundless_market_address {
+                // The order stream of the deployment only serves its own market.
+                deployment.order_stream_url = None;
+            }
+            deployment.boundless_market_address = market;
+            deployment
+        }
+        (Ok(deployment), None) => deployment,
+        (Err(_), Some(market)) => Deployment::new(chain_id, market, Address::ZERO),
+        (Err(err), None) => return Err(err.into()),
+    };
+
+    if let Some(set_verifier) = args.set_verifier_address {
+        deployment.set_verifier_address = set_verifier;
+        return Ok(deployment);
+    }
+    let market = BoundlessMarketService::new(
+        deployment.boundless_market_address,
+        provider.clone(),
+        Address::ZERO,
+    );
+    match market.set_verifier_address(set_builder_image_id).await {
+        Ok(set_verifier) => {
+            if deployment.set_verifier_address != Address::ZERO
+                && deployment.set_verifier_address != set_verifier
+            {
+                tracing::warn!(
+                    "Set verifier {set_verifier} of the market differs from the known deployment \
+                     set verifier {}, using the one of the market",
+                    deployment.set_verifier_address
+                );
+            }
+            deployment.set_verifier_address = set_verifier;
+        }
+        Err(err) if deployment.set_verifier_address != Address::ZERO => {
+            tracing::warn!(
+                "Failed to read the set verifier for set builder {set_builder_image_id} from the \
+                 market, using the known deployment set verifier {}: {err:?}",
+                deployment.set_verifier_address
+            );
+        }
+        Err(err) => {
+            return Err(err.context(format!(
+                "Failed to read the set verifier for set builder {set_builder_image_id} from the \
+                 market, set --set-verifier-address"
+            )));
+        }
+    }
+    Ok(deployment)
+}
+
 /// Status of a order as it moves through the lifecycle
 #[derive(Clone, Copy, sqlx::Type, Debug, PartialEq, Serialize, Deserialize)]
 enum OrderStatus {
@@ -174,6 +288,11 @@ struct Order {
     ///
     ///  Populated after preflight
     input_id: Option<String>,
+    /// Prover IDs of the assumption receipts referenced by the input
+    ///
+    /// Populated after preflight
+    #[serde(default)]
+    assumption_ids: Vec<String>,
     /// Proof Id
     ///
     /// Populated after proof completion
@@ -192,6 +311,13 @@ struct Order {
     lock_price: Option<U256>,
     /// Failure message
     error_msg: Option<String>,
+    /// Whether the order was received from the order stream
+    #[serde(default)]
+    offchain: bool,
+    /// Gas used by the request callback in simulation
+    ///
+    /// Populated during order picking
+    callback_gas: Option<u64>,
 }
 
 impl Order {
@@ -203,12 +329,15 @@ impl Order {
             target_timestamp: None,
             image_id: None,
             input_id: None,
+            assumption_ids: vec![],
             proof_id: None,
             compressed_proof_id: None,
             expire_timestamp: None,
             client_sig,
             lock_price: None,
             error_msg: None,
+            offchain: false,
+            callback_gas: None,
         }
     }
     pub fn is_groth16(&self) -> bool {
@@ -238,6 +367,32 @@ struct AggregationState {
     /// Proof ID for the Groth16 proof that compresses the root of the aggregation tree.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub groth16_proof_id: Option<String>,
+    /// Encoded seal of the Groth16 proof.
+    ///
+    /// Recorded as soon as compression completes, so that the proof is not compressed again
+    /// after a restart, and the batch can be submitted without the proving backend.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub groth16_seal: Option<Bytes>,
+}
+
+/// An aggregation step that is in progress for a batch.
+///
+/// Recorded as soon as a proof for the step is started on the proving backend, so that after a
+/// restart the step is resumed with the same orders and the started proofs are reused.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct AggregationCheckpoint {
+    /// Orders with proofs being added to the aggregation tree.
+    pub new_proofs: Vec<AggregationOrder>,
+    /// Orders with groth16 proofs, which are included in the assessor but not the tree.
+    pub groth16_proofs: Vec<AggregationOrder>,
+    /// Whether this step finalizes the batch.
+    pub finalize: bool,
+    /// Proof ID for the assessor proof, once started.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assessor_proof_id: Option<String>,
+    /// Proof ID for the set builder proof, once started.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub set_builder_proof_id: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Default, Clone)]
@@ -251,6 +406,9 @@ struct Batch {
     /// proof ID for the receipt that attests to the correctness of this state.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub aggregation_state: Option<AggregationState>,
+    /// Aggregation step in progress, cleared once its result is recorded in the aggregation state.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkpoint: Option<AggregationCheckpoint>,
     /// When the batch was initially created.
     pub start_time: DateTime<Utc>,
     /// The deadline for the batch, which is the earliest deadline for any order in the batch.
@@ -264,23 +422,63 @@ struct Batch {
 
 pub struct Broker<P> {
     args: Args,
+    signer: AnySigner,
+    deployment: Deployment,
     provider: Arc<P>,
     db: DbObj,
     config_watcher: ConfigWatcher,
+    market: Option<Arc<dyn MarketBackend>>,
 }
 
 impl<P> Broker<P>
 where
     P: Provider<Ethereum> + 'static + Clone + WalletProvider,
 {
-    pub async fn new(args: Args, provider: P) -> Result<Self> {
+    /// Creates the broker, signing with the given signer, which must be the signer of the wallet
+    /// of the provider.
+    pub async fn new(args: Args, provider: P, signer: AnySigner) -> Result<Self> {
         let config_watcher =
             ConfigWatcher::new(&args.config_file).await.context("Failed to load broker config")?;
 
         let db: DbObj =
             Arc::new(SqliteDb::new(&args.db_url).await.context("Failed to connect to sqlite DB")?);
 
-        Ok(Self { args, db, provider: Arc::new(provider), config_watcher })
+        ensure!(
+            signer.address() == provider.default_signer_address(),
+            "Signer address {} does not match the wallet address {}",
+            signer.address(),
+            provider.default_signer_address()
+        );
+
+        let set_builder_image_id = set_builder_image_id(&config_watcher.config)?;
+        let deployment = resolve_deployment(&args, &provider, set_builder_image_id).await?;
+        tracing::info!(
+            "Using market {} and set verifier {} on chain {}",
+            deployment.boundless_market_address,
+            deployment.set_verifier_address,
+            deployment.chain_id
+        );
+
+        Ok(Self {
+            args,
+            signer,
+            deployment,
+            db,
+            provider: Arc::new(provider),
+            config_watcher,
+            market: None,
+        })
+    }
+
+    /// Picks and locks orders on the given market, such as a simulated market, in place of the
+    /// deployed one.
+    pub fn with_market(self, market: Arc<dyn MarketBackend>) -> Self {
+        Self { market: Some(market), ..self }
+    }
+
+    /// Returns the deployment the broker is running against.
+    pub fn deployment(&self) -> &Deployment {
+        &self.deployment
     }
 
     async fn get_assessor_image(&self) -> Result<(Digest, Vec<u8>)> {
@@ -297,7 +495,7 @@ where
             Ok((img_id, elf_buf))
         } else {
             let boundless_market = BoundlessMarketService::new(
-                self.args.boundless_market_address,
+                self.deployment.boundless_market_address,
                 self.provider.clone(),
                 Address::ZERO,
             );
@@ -329,7 +527,7 @@ where
             Ok((img_id, elf_buf))
         } else {
             let set_verifier_contract = SetVerifierService::new(
-                self.args.set_verifier_address,
+                self.deployment.set_verifier_address,
                 self.provider.clone(),
                 Address::ZERO,
             );
@@ -381,11 +579,11 @@ where
         // spin up a supervisor for the market monitor
         let market_monitor = Arc::new(market_monitor::MarketMonitor::new(
             loopback_blocks,
-            self.args.boundless_market_address,
+            self.deployment.boundless_market_address,
             self.provider.clone(),
             self.db.clone(),
             chain_monitor.clone(),
-            self.args.private_key.address(),
+            self.signer.address(),
         ));
 
         let block_times =
@@ -402,18 +600,59 @@ where
             Ok(())
         });
 
+        // spin up a supervisor for the balance monitor
+        let balance_monitor = Arc::new(balance_monitor::BalanceMonitorService::new(
+            config.clone(),
+            self.provider.clone(),
+            self.deployment.boundless_market_address,
+            self.signer.address(),
+        ));
+        let cloned_config = config.clone();
+        supervisor_tasks.spawn(async move {
+            Supervisor::new(balance_monitor, cloned_config)
+                .spawn()
+                .await
+                .context("Failed to start balance monitor")?;
+            Ok(())
+        });
+
         let chain_id = self.provider.get_chain_id().await.context("Failed to get chain ID")?;
-        let client =
-            self.args.order_stream_url.clone().map(|url| {
-                OrderStreamClient::new(url, self.args.boundless_market_address, chain_id)
-            });
+        let order_stream_url = match self.args.order_stream_url.clone() {
+            Some(url) => Some(url),
+            None if self.args.deployment_order_stream => {
+                let url = self.deployment.order_stream_url()?;
+                ensure!(
+                    url.is_some(),
+                    "No order stream known for the deployment on chain {chain_id}, set \
+                     --order-stream-url"
+                );
+                url
+            }
+            None => {
+                if let Some(url) = self.deployment.order_stream_url()? {
+                    tracing::warn!(
+                        "Offchain orders are disabled, pass --deployment-order-stream to receive \
+                         them from the order stream of the deployment at {url}"
+                    );
+                }
+                None
+            }
+        };
+        match &order_stream_url {
+            Some(url) => tracing::info!("Receiving offchain orders from order stream {url}"),
+            None => tracing::info!("Receiving onchain orders only"),
+        }
+        let client = order_stream_url.map(|url| {
+            OrderStreamClient::new(url, self.deployment.boundless_market_address, chain_id)
+        });
         // spin up a supervisor for the offchain market monitor
-        if let Some(client) = client {
+        if let Some(client) = &client {
             let offchain_market_monitor =
                 Arc::new(offchain_market_monitor::OffchainMarketMonitor::new(
                     self.db.clone(),
                     client.clone(),
-                    self.args.private_key.clone(),
+                    self.signer.clone(),
+                    chain_monitor.clock(),
                 ));
             let cloned_config = config.clone();
             supervisor_tasks.spawn(async move {
@@ -429,7 +668,7 @@ where
         let prover: provers::ProverObj = if risc0_zkvm::is_dev_mode() {
             tracing::warn!("WARNING: Running the Broker in dev mode does not generate valid receipts. \
             Receipts generated from this process are invalid and should never be used in production.");
-            Arc::new(provers::DefaultProver::new())
+            Arc::new(provers::DefaultProver::new().with_config(config.clone()))
         } else if let (Some(bonsai_api_key), Some(bonsai_api_url)) =
             (self.args.bonsai_api_key.as_ref(), self.args.bonsai_api_url.as_ref())
         {
@@ -446,18 +685,22 @@ where
                     .context("Failed to initialize Bento client")?,
             )
         } else {
-            Arc::new(provers::DefaultProver::new())
+            Arc::new(provers::DefaultProver::new().with_config(config.clone()))
         };
 
         // Spin up the order picker to pre-flight and find orders to lock
-        let order_picker = Arc::new(order_picker::OrderPicker::new(
+        let mut order_picker = order_picker::OrderPicker::new(
             self.db.clone(),
             config.clone(),
             prover.clone(),
-            self.args.boundless_market_address,
+            self.deployment.boundless_market_address,
             self.provider.clone(),
             chain_monitor.clone(),
-        ));
+        );
+        if let Some(market) = &self.market {
+            order_picker = order_picker.with_market(market.clone());
+        }
+        let order_picker = Arc::new(order_picker);
         let cloned_config = config.clone();
         supervisor_tasks.spawn(async move {
             Supervisor::new(order_picker, cloned_config)
@@ -467,14 +710,18 @@ where
             Ok(())
         });
 
-        let order_monitor = Arc::new(order_monitor::OrderMonitor::new(
+        let mut order_monitor = order_monitor::OrderMonitor::new(
             self.db.clone(),
             self.provider.clone(),
             chain_monitor.clone(),
             config.clone(),
             block_times,
-            self.args.boundless_market_address,
-        )?);
+            self.deployment.boundless_market_address,
+        )?;
+        if let Some(market) = &self.market {
+            order_monitor = order_monitor.with_market(market.clone());
+        }
+        let order_monitor = Arc::new(order_monitor);
         let cloned_config = config.clone();
         supervisor_tasks.spawn(async move {
             Supervisor::new(order_monitor, cloned_config)
@@ -502,7 +749,7 @@ where
         let set_builder_img_data = self.get_set_builder_image().await?;
         let assessor_img_data = self.get_assessor_image().await?;
 
-        let prover_addr = self.args.private_key.address();
+        let prover_addr = self.signer.address();
         let aggregator = Arc::new(
             aggregator::AggregatorService::new(
                 self.db.clone(),
@@ -511,10 +758,11 @@ where
                 set_builder_img_data.1,
                 assessor_img_data.0,
                 assessor_img_data.1,
-                self.args.boundless_market_address,
+                self.deployment.boundless_market_address,
                 prover_addr,
                 config.clone(),
                 prover.clone(),
+                chain_monitor.clock(),
             )
             .await
             .context("Failed to initialize aggregator service")?,
@@ -535,9 +783,11 @@ where
             config.clone(),
             prover.clone(),
             self.provider.clone(),
-            self.args.set_verifier_address,
-            self.args.boundless_market_address,
+            self.deployment.set_verifier_address,
+            self.deployment.boundless_market_address,
             set_builder_img_data.0,
+            assessor_img_data.0,
+            client,
         )?);
         let cloned_config = config.clone();
         supervisor_tasks.spawn(async move {
@@ -612,48 +862,64 @@ async fn upload_image_uri(
     Ok(image_id)
 }
 
+/// Uploads the input of an order to the prover, along with the receipts of the assumptions it
+/// references. Returns the input ID and the assumption IDs to pass when proving.
 async fn upload_input_uri(
     prover: &ProverObj,
     order: &Order,
     config: &ConfigLock,
-) -> Result<String> {
-    Ok(match order.request.input.inputType {
-        InputType::Inline => prover
-            .upload_input(
-                GuestEnv::decode(&order.request.input.data)
-                    .with_context(|| "Failed to decode input")?
-                    .stdin,
-            )
-            .await
-            .context("Failed to upload input data")?,
+) -> Result<(String, Vec<String>)> {
+    let env = match order.request.input.inputType {
+        InputType::Inline => {
+            let key = input_decryption_key(config)?;
+            let input_data = decrypt_input(order.request.input.data.to_vec(), key.as_ref())
+                .context("Failed to decrypt input")?;
+            GuestEnv::decode(&input_data).with_context(|| "Failed to decode input")?
+        }
 
         InputType::Url => {
             let input_uri_str =
                 std::str::from_utf8(&order.request.input.data).context("input url is not utf8")?;
             tracing::debug!("Input URI string: {input_uri_str}");
             let input_uri =
-                create_uri_handler(input_uri_str, config).await.context("URL handling failed")?;
+                create_input_handler(input_uri_str, config).await.context("URL handling failed")?;
 
-            let input_data = GuestEnv::decode(
+            GuestEnv::decode(
                 &input_uri
                     .fetch()
                     .await
                     .with_context(|| format!("Failed to fetch input URI: {input_uri_str}"))?,
             )
             .with_context(|| format!("Failed to decode input from URI: {input_uri_str}"))?
-            .stdin;
-
-            prover.upload_input(input_data).await.context("Failed to upload input")?
         }
         //???
         _ => anyhow::bail!("Invalid input type: {:?}", order.request.input.inputType),
-    })
+    };
+
+    // Assumptions are uploaded to the prover as receipts, to be passed by ID when proving.
+    let mut assumption_ids = Vec::with_capacity(env.assumptions.len());
+    for receipt_uri_str in &env.assumptions {
+        let receipt_uri =
+            create_uri_handler(receipt_uri_str, config).await.context("URL handling failed")?;
+        let receipt = receipt_uri
+            .fetch()
+            .await
+            .with_context(|| format!("Failed to fetch assumption receipt: {receipt_uri_str}"))?;
+        assumption_ids
+            .push(prover.upload_receipt(receipt).await.context("Failed to upload receipt")?);
+    }
+
+    let input_id = prover.upload_env(env).await.context("Failed to upload input")?;
+    Ok((input_id, assumption_ids))
 }
 
-/// A very small utility function to get the current unix timestamp in seconds.
-// TODO(#379): Avoid drift relative to the chain's timestamps.
+/// Current unix timestamp of the system clock, used to build test orders.
+///
+/// Services check deadlines against the chain clock of the [chain_monitor::ChainMonitorService].
+#[cfg(test)]
 pub(crate) fn now_timestamp() -> u64 {
-    SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs()
+    use boundless_market::clock::{Clock, SystemClock};
+    SystemClock.now()
 }
 
 #[cfg(feature = "test-utils")]
@@ -691,11 +957,13 @@ pub mod test_utils {
             let args = Args {
                 db_url: "sqlite::memory:".into(),
                 config_file: config_file.path().to_path_buf(),
-                boundless_market_address: ctx.boundless_market_address,
-                set_verifier_address: ctx.set_verifier_address,
+                boundless_market_address: Some(ctx.boundless_market_address),
+                set_verifier_address: Some(ctx.set_verifier_address),
+                deployments_file: None,
                 rpc_url,
                 order_stream_url: None,
-                private_key: ctx.prover_signer.clone(),
+                deployment_order_stream: false,
+                signer: ctx.prover_signer.clone().into(),
                 bento_api_url: None,
                 bonsai_api_key: None,
                 bonsai_api_url: None,
@@ -713,7 +981,8 @@ pub mod test_utils {
         }
 
         pub async fn build(self) -> Result<(Broker<P>, NamedTempFile)> {
-            Ok((Broker::new(self.args, self.provider).await?, self.config_file))
+            let signer = self.args.signer.signer().await?;
+            Ok((Broker::new(self.args, self.provider, signer).await?, self.config_file))
         }
     }
 }
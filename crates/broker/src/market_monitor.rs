@@ -10,13 +10,14 @@ use alloy::{
     primitives::{Address, U256},
     providers::Provider,
     rpc::types::{Filter, Log},
-    sol,
-    sol_types::{SolCall, SolEvent},
+    sol_types::SolEvent,
 };
 
 use anyhow::{Context, Result};
 use boundless_market::contracts::{
-    boundless_market::BoundlessMarketService, IBoundlessMarket, RequestId, RequestStatus,
+    boundless_market::{find_submitted_request, BoundlessMarketService},
+    smart_contract_requestor::{ERC1271_MAGIC_VALUE, IERC1271},
+    IBoundlessMarket, RequestId, RequestStatus,
 };
 use futures_util::StreamExt;
 
@@ -38,15 +39,6 @@ pub struct MarketMonitor<P> {
     prover_addr: Address,
 }
 
-sol! {
-    #[sol(rpc)]
-    interface IERC1271 {
-        function isValidSignature(bytes32 hash, bytes memory signature) external view returns (bytes4 magicValue);
-    }
-}
-
-const ERC1271_MAGIC_VALUE: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
-
 impl<P> MarketMonitor<P>
 where
     P: Provider<Ethereum> + 'static + Clone,
@@ -136,19 +128,18 @@ where
                 .get_transaction_by_hash(tx_hash)
                 .await?
                 .context("Missing transaction data")?;
-            let calldata = IBoundlessMarket::submitRequestCall::abi_decode(tx_data.input(), true)
+            let calldata = find_submitted_request(tx_data.input(), request_id)
                 .context("Failed to decode calldata")?;
-            let order_exists = match db.order_exists(request_id).await {
-                Ok(val) => val,
+            // A request resubmitted under the same ID with another offer supersedes the stored
+            // one, so only requests already stored as-is are skipped.
+            match db.get_order(request_id).await {
+                Ok(Some(order)) if order.request == calldata.request => continue,
+                Ok(_) => {}
                 Err(err) => {
                     tracing::error!("Failed to check if order exists in db: {err:?}");
                     continue;
                 }
             };
-            // TODO(#162) Handle the case where multiple requests share an ID.
-            if order_exists {
-                continue;
-            }
 
             let req_status =
                 match market.get_status(request_id, Some(calldata.request.expires_at())).await {
@@ -169,17 +160,21 @@ where
             }
 
             tracing::info!("Found open order: {}", calldata.request.id);
-            if let Err(err) = db
-                .add_order(
+            match db
+                .add_or_replace_order(
                     request_id,
                     Order::new(calldata.request.clone(), calldata.clientSignature.clone()),
                 )
                 .await
             {
-                tracing::error!("Failed to insert order in to database: {err:?}");
-                continue;
+                Ok(true) => order_count += 1,
+                Ok(false) => {
+                    tracing::debug!("Ignoring order {request_id:x}, already in progress");
+                }
+                Err(err) => {
+                    tracing::error!("Failed to insert order in to database: {err:?}");
+                }
             }
-            order_count += 1;
         }
 
         tracing::info!("Found {order_count} open orders");
@@ -314,7 +309,7 @@ where
         let tx_data =
             provider.get_transaction_by_hash(tx_hash).await?.context("Missing transaction data")?;
 
-        let calldata = IBoundlessMarket::submitRequestCall::abi_decode(tx_data.input(), true)
+        let calldata = find_submitted_request(tx_data.input(), U256::from(event.requestId))
             .context("Failed to decode calldata")?;
 
         // Check the request id flag to determine if the request is smart contract signed. If so we verify the
@@ -357,14 +352,16 @@ where
             return Ok(()); // Return early without propagating the error if signature verification fails.
         }
 
-        if let Err(err) = db
-            .add_order(
-                U256::from(calldata.request.id),
-                Order::new(calldata.request, calldata.clientSignature),
-            )
+        let order_id = U256::from(calldata.request.id);
+        match db
+            .add_or_replace_order(order_id, Order::new(calldata.request, calldata.clientSignature))
             .await
         {
-            match err {
+            Ok(true) => {}
+            Ok(false) => {
+                tracing::debug!("Ignoring order {order_id:x}, already known or in progress")
+            }
+            Err(err) => match err {
                 DbError::SqlErr(sqlx::Error::Database(db_err)) => {
                     if db_err.is_unique_violation() {
                         tracing::warn!("Duplicate order detected: {db_err:?}");
@@ -375,7 +372,7 @@ where
                 _ => {
                     tracing::error!("Failed to add new order into DB: {err:?}");
                 }
-            }
+            },
         }
         Ok(())
     }
@@ -437,7 +434,7 @@ mod tests {
         primitives::{Address, U256},
         providers::{ext::AnvilApi, ProviderBuilder, WalletProvider},
         signers::local::PrivateKeySigner,
-        sol_types::eip712_domain,
+        sol_types::{eip712_domain, SolCall},
     };
     use boundless_market::{
         contracts::{
@@ -523,6 +520,62 @@ mod tests {
         assert_eq!(orders, 1);
     }
 
+    #[tokio::test]
+    async fn find_orders_multicall() {
+        let anvil = Anvil::new().spawn();
+        let ctx = create_test_ctx(
+            &anvil,
+            SET_BUILDER_ID,
+            format!("file://{SET_BUILDER_PATH}"),
+            ASSESSOR_GUEST_ID,
+            format!("file://{ASSESSOR_GUEST_PATH}"),
+        )
+        .await
+        .unwrap();
+
+        // Requests submitted in a batch are sent in a single multicall transaction.
+        let requests = vec![new_request(1, &ctx).await, new_request(2, &ctx).await];
+        let request_ids =
+            ctx.customer_market.submit_requests(&requests, &ctx.customer_signer).await.unwrap();
+
+        let provider = Arc::new(ctx.customer_provider.clone());
+        let chain_monitor = Arc::new(ChainMonitorService::new(provider.clone()).await.unwrap());
+        tokio::spawn(chain_monitor.spawn());
+
+        let db: DbObj = Arc::new(SqliteDb::new("sqlite::memory:").await.unwrap());
+        let orders = MarketMonitor::find_open_orders(
+            10,
+            ctx.boundless_market_address,
+            provider.clone(),
+            db.clone(),
+            chain_monitor,
+        )
+        .await
+        .unwrap();
+        assert_eq!(orders, 2);
+        for (request, request_id) in requests.iter().zip(request_ids) {
+            let order = db.get_order(request_id).await.unwrap().unwrap();
+            assert_eq!(&order.request, request);
+        }
+
+        // New requests in a batch are picked up from their events.
+        let db: DbObj = Arc::new(SqliteDb::new("sqlite::memory:").await.unwrap());
+        let logs = ctx.customer_market.instance().RequestSubmitted_filter().query().await.unwrap();
+        let (event, log) = logs.last().unwrap().clone();
+        MarketMonitor::process_log(
+            event,
+            log,
+            provider,
+            ctx.boundless_market_address,
+            anvil.chain_id(),
+            &db,
+        )
+        .await
+        .unwrap();
+        let order = db.get_order(requests[1].id).await.unwrap().unwrap();
+        assert_eq!(order.request, requests[1]);
+    }
+
     #[tokio::test]
     async fn block_times() {
         let anvil = Anvil::new().spawn();
@@ -2,12 +2,19 @@
 //
 // All rights reserved.
 
-use alloy::{
-    primitives::U256,
-    signers::{local::PrivateKeySigner, Signer},
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use alloy::{primitives::U256, sol_types::SolStruct};
+use anyhow::{Context, Result};
+use boundless_market::{
+    clock::ClockObj,
+    contracts::eip712_domain,
+    order_stream_client::{
+        reconnecting_order_stream_events, CancelOrderMsg, Client as OrderStreamClient, OrderData,
+        OrderStreamEvent, ReconnectConfig,
+    },
+    signer::AnySigner,
 };
-use anyhow::Result;
-use boundless_market::order_stream_client::{order_stream, Client as OrderStreamClient};
 use futures_util::StreamExt;
 
 use crate::{
@@ -18,47 +25,67 @@ use crate::{
 pub struct OffchainMarketMonitor {
     db: DbObj,
     client: OrderStreamClient,
-    signer: PrivateKeySigner,
+    signer: AnySigner,
+    clock: ClockObj,
 }
 
 impl OffchainMarketMonitor {
-    pub fn new(db: DbObj, client: OrderStreamClient, signer: PrivateKeySigner) -> Self {
-        Self { db, client, signer }
+    pub fn new(db: DbObj, client: OrderStreamClient, signer: AnySigner, clock: ClockObj) -> Self {
+        Self { db, client, signer, clock }
     }
 
     async fn monitor_orders(
         client: OrderStreamClient,
-        signer: &impl Signer,
+        signer: AnySigner,
         db: DbObj,
+        clock: ClockObj,
     ) -> Result<(), SupervisorErr> {
-        // TODO: retry until it can reestablish a connection.
         tracing::debug!("Connecting to off-chain market: {}", client.base_url);
-        let socket = client.connect_async(signer).await.map_err(SupervisorErr::Recover)?;
-
-        let stream = order_stream(socket);
+        // Resume after the last order received, kept in the DB across restarts of the broker. On
+        // the first start, begin from the first order so that the orders still open are
+        // backfilled.
+        let start = db
+            .get_offchain_cursor()
+            .await
+            .context("Failed to get the order stream cursor")
+            .map_err(SupervisorErr::Recover)?
+            .unwrap_or(0);
+        let cursor = AtomicI64::new(start);
+        let stream = reconnecting_order_stream_events(
+            client.clone(),
+            signer,
+            Some(start),
+            ReconnectConfig::default(),
+        );
         tracing::info!("Subscribed to offchain Order stream");
         stream
-            .for_each(|order| async {
-                match order {
-                    Ok(elm) => {
-                        tracing::info!(
-                            "Detected new order {:x} - stream id: {}",
-                            elm.id,
-                            elm.order.request.id
-                        );
-                        if let Err(err) = db
-                            .add_order(
-                                U256::from(elm.order.request.id),
-                                Order::new(
-                                    elm.order.request,
-                                    elm.order.signature.as_bytes().into(),
-                                ),
-                            )
-                            .await
-                        {
+            .for_each(|event| async {
+                match event {
+                    Ok(OrderStreamEvent::Order(elm)) => {
+                        if cursor.fetch_max(elm.id, Ordering::Relaxed) < elm.id {
+                            if let Err(err) = db.set_offchain_cursor(elm.id).await {
+                                tracing::warn!("Failed to save the order stream cursor: {err:?}");
+                            }
+                        }
+                        if elm.order.request.expires_at() <= clock.now() {
+                            tracing::debug!(
+                                "Skipping expired order {:x} - stream id: {}",
+                                elm.order.request.id,
+                                elm.id
+                            );
+                            return;
+                        }
+                        if let Err(err) = Self::handle_order(elm, &db).await {
                             tracing::error!("Failed to add new order into DB: {err:?}");
                         }
                     }
+                    Ok(OrderStreamEvent::Cancellation { cancellation }) => {
+                        if let Err(err) =
+                            Self::handle_cancellation(cancellation, &client, &db).await
+                        {
+                            tracing::warn!("Failed to cancel order: {err:?}");
+                        }
+                    }
                     Err(err) => {
                         tracing::warn!("Failed to fetch order: {:?}", err);
                     }
@@ -72,15 +99,67 @@ impl OffchainMarketMonitor {
     }
 }
 
+impl OffchainMarketMonitor {
+    async fn handle_order(elm: OrderData, db: &DbObj) -> Result<()> {
+        tracing::info!("Detected new order {:x} - stream id: {}", elm.order.request.id, elm.id);
+        let order_id = U256::from(elm.order.request.id);
+        let order = Order {
+            offchain: true,
+            ..Order::new(elm.order.request, elm.order.signature.as_bytes().into())
+        };
+
+        // A known request ID with another request is a new version, superseding the stored one.
+        let exists = db.order_exists(order_id).await?;
+        if db.add_or_replace_order(order_id, order).await? {
+            if exists {
+                tracing::info!("Order {order_id:x} superseded by a new offer");
+            }
+        } else {
+            tracing::debug!("Ignoring order {order_id:x}, already known or in progress");
+        }
+        Ok(())
+    }
+
+    async fn handle_cancellation(
+        cancellation: CancelOrderMsg,
+        client: &OrderStreamClient,
+        db: &DbObj,
+    ) -> Result<()> {
+        let order_id = cancellation.request_id;
+        cancellation
+            .verify(client.boundless_market_address, client.chain_id)
+            .context("Invalid cancellation signature")?;
+
+        let Some(order) = db.get_order(order_id).await? else {
+            tracing::debug!("Ignoring cancellation of unknown order {order_id:x}");
+            return Ok(());
+        };
+        let domain = eip712_domain(client.boundless_market_address, client.chain_id);
+        if order.request.eip712_signing_hash(&domain.alloy_struct()) != cancellation.request_digest
+        {
+            tracing::debug!("Ignoring cancellation of another version of order {order_id:x}");
+            return Ok(());
+        }
+
+        if db.cancel_order(order_id).await? {
+            tracing::info!("Order {order_id:x} cancelled by its client");
+        } else {
+            tracing::info!("Ignoring cancellation of order {order_id:x}, already committed");
+        }
+        Ok(())
+    }
+}
+
 impl RetryTask for OffchainMarketMonitor {
     fn spawn(&self) -> RetryRes {
         let db = self.db.clone();
         let client = self.client.clone();
         let signer = self.signer.clone();
+        let clock = self.clock.clone();
 
         Box::pin(async move {
             tracing::info!("Starting up offchain market monitor");
-            Self::monitor_orders(client, &signer, db).await?;
+            Self::monitor_orders(client, signer, db, clock).await?;
             Ok(())
         })
     }
@@ -11,20 +11,23 @@ use crate::{
 };
 use alloy::{
     network::Ethereum,
-    primitives::{utils::parse_ether, Address, U256},
+    primitives::{Address, U256},
     providers::{Provider, WalletProvider},
 };
 use anyhow::{Context, Result};
-use boundless_market::contracts::{
-    boundless_market::{BoundlessMarketService, MarketError},
-    RequestStatus,
+use boundless_market::{
+    contracts::{
+        boundless_market::{BoundlessMarketService, MarketError},
+        RequestStatus,
+    },
+    simulator::MarketBackend,
 };
 use std::{sync::Arc, time::Duration};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum LockOrderErr {
-    #[error("Failed to fetch / push image: {0}")]
+    #[error("Failed to lock order: {0}")]
     OrderLockedInBlock(MarketError),
 
     #[error("Invalid order status for locking: {0:?}")]
@@ -43,13 +46,12 @@ pub struct OrderMonitor<P> {
     chain_monitor: Arc<ChainMonitorService<P>>,
     block_time: u64,
     config: ConfigLock,
-    market: BoundlessMarketService<Arc<P>>,
-    provider: Arc<P>,
+    market: Arc<dyn MarketBackend>,
 }
 
 impl<P> OrderMonitor<P>
 where
-    P: Provider + WalletProvider,
+    P: Provider + WalletProvider + 'static,
 {
     pub fn new(
         db: DbObj,
@@ -72,25 +74,13 @@ where
         if let Some(txn_timeout) = txn_timeout_opt {
             market = market.with_timeout(Duration::from_secs(txn_timeout));
         }
-        {
-            let config = config.lock_all().context("Failed to lock config")?;
-            market = market.with_stake_balance_alert(
-                &config
-                    .market
-                    .stake_balance_warn_threshold
-                    .as_ref()
-                    .map(|s| parse_ether(s))
-                    .transpose()?,
-                &config
-                    .market
-                    .stake_balance_error_threshold
-                    .as_ref()
-                    .map(|s| parse_ether(s))
-                    .transpose()?,
-            );
-        }
 
-        Ok(Self { db, chain_monitor, block_time, config, market, provider })
+        Ok(Self { db, chain_monitor, block_time, config, market: Arc::new(market) })
+    }
+
+    /// Lock orders on the given market, such as a simulated market, in place of the deployed one.
+    pub fn with_market(self, market: Arc<dyn MarketBackend>) -> Self {
+        Self { market, ..self }
     }
 
     async fn lock_order(&self, order_id: U256, order: &Order) -> Result<(), LockOrderErr> {
@@ -116,26 +106,15 @@ where
         };
 
         tracing::info!("Locking order: {order_id:x} for stake: {}", order.request.offer.lockStake);
-        let lock_block = self
+        let lock_price = self
             .market
             .lock_request(&order.request, &order.client_sig, conf_priority_gas)
             .await
-            .map_err(LockOrderErr::OrderLockedInBlock)?;
-
-        let lock_timestamp = self
-            .provider
-            .get_block_by_number(lock_block.into())
-            .await
-            .with_context(|| format!("failed to get block {lock_block}"))?
-            .with_context(|| format!("failed to get block {lock_block}: block not found"))?
-            .header
-            .timestamp;
-
-        let lock_price = order
-            .request
-            .offer
-            .price_at(lock_timestamp)
-            .context("Failed to calculate lock price")?;
+            .map_err(|err| match err {
+                // Another prover locked the order first.
+                MarketError::RequestIsLocked(_) => LockOrderErr::AlreadyLocked,
+                err => LockOrderErr::OrderLockedInBlock(err),
+            })?;
 
         self.db.set_proving_status(order_id, lock_price).await.with_context(|| {
             format!(
@@ -276,13 +255,19 @@ mod tests {
         providers::{ext::AnvilApi, ProviderBuilder},
         signers::local::PrivateKeySigner,
     };
-    use boundless_market::contracts::{
-        Input, InputType, Offer, Predicate, PredicateType, ProofRequest, RequestId, Requirements,
+    use boundless_market::{
+        clock::FakeClock,
+        contracts::{
+            Input, InputType, Offer, Predicate, PredicateType, ProofRequest, RequestId,
+            Requirements,
+        },
+        simulator::{SimMarket, SimMarketClient},
     };
     use boundless_market_test_utils::{deploy_boundless_market, deploy_hit_points};
     use chrono::Utc;
     use guest_assessor::{ASSESSOR_GUEST_ID, ASSESSOR_GUEST_PATH};
     use risc0_zkvm::sha::Digest;
+    use std::sync::Mutex;
     use tracing_test::traced_test;
 
     #[tokio::test]
@@ -363,6 +348,9 @@ mod tests {
             client_sig: client_sig.into(),
             lock_price: None,
             error_msg: None,
+            offchain: false,
+            callback_gas: None,
+            assumption_ids: vec![],
         };
         let request_id = boundless_market.submit_request(&order.request, &signer).await.unwrap();
         assert_eq!(request_id, order_id);
@@ -475,6 +463,9 @@ mod tests {
             client_sig,
             lock_price: None,
             error_msg: None,
+            offchain: false,
+            callback_gas: None,
+            assumption_ids: vec![],
         };
 
         let _request_id = boundless_market.submit_request(&order.request, &signer).await.unwrap();
@@ -500,4 +491,73 @@ mod tests {
         let order = db.get_order(order_id).await.unwrap().unwrap();
         assert_eq!(order.status, OrderStatus::PendingProving);
     }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn lock_order_sim_market() {
+        let anvil = Anvil::new().spawn();
+        let signer: PrivateKeySigner = anvil.keys()[0].clone().into();
+        let provider = Arc::new(
+            ProviderBuilder::new()
+                .wallet(EthereumWallet::from(signer.clone()))
+                .connect(&anvil.endpoint())
+                .await
+                .unwrap(),
+        );
+        let db: DbObj = Arc::new(SqliteDb::new("sqlite::memory:").await.unwrap());
+        let chain_monitor = Arc::new(ChainMonitorService::new(provider.clone()).await.unwrap());
+
+        let client = Address::repeat_byte(1);
+        let now = now_timestamp();
+        let mut sim_market = SimMarket::new(FakeClock::new(now));
+        sim_market.deposit(client, U256::from(10));
+        let sim_market = Arc::new(Mutex::new(sim_market));
+
+        let monitor = OrderMonitor::new(
+            db.clone(),
+            provider.clone(),
+            chain_monitor,
+            ConfigLock::default(),
+            2,
+            Address::ZERO,
+        )
+        .unwrap()
+        .with_market(Arc::new(SimMarketClient::new(sim_market.clone(), signer.address())));
+
+        let request = ProofRequest::new(
+            RequestId::new(client, 1),
+            Requirements::new(
+                Digest::ZERO,
+                Predicate { predicateType: PredicateType::PrefixMatch, data: Default::default() },
+            ),
+            "http://risczero.com/image",
+            Input { inputType: InputType::Inline, data: Default::default() },
+            Offer {
+                minPrice: U256::from(1),
+                maxPrice: U256::from(2),
+                biddingStart: now,
+                rampUpPeriod: 1,
+                timeout: 100,
+                lockTimeout: 100,
+                lockStake: U256::from(0),
+            },
+        );
+        let order_id = U256::from(request.id);
+        let mut order = Order::new(request.clone(), Default::default());
+        order.status = OrderStatus::Locking;
+        sim_market.lock().unwrap().submit_request(request).unwrap();
+        db.add_order(order_id, order.clone()).await.unwrap();
+
+        monitor.lock_order(order_id, &order).await.unwrap();
+        assert_eq!(sim_market.lock().unwrap().locked_by(order_id), Some(signer.address()));
+        let db_order = db.get_order(order_id).await.unwrap().unwrap();
+        assert_eq!(db_order.status, OrderStatus::PendingProving);
+        assert_eq!(db_order.lock_price, Some(U256::from(1)));
+
+        // A second attempt finds the order locked.
+        assert!(matches!(
+            monitor.lock_order(order_id, &order).await,
+            Err(LockOrderErr::AlreadyLocked)
+        ));
+    }
 }
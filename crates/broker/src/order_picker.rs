@@ -4,28 +4,38 @@
 
 use std::sync::Arc;
 
+use crate::provers::ProofResult;
 use crate::{
     chain_monitor::ChainMonitorService,
     config::ConfigLock,
     db::DbObj,
     provers::{ProverError, ProverObj},
+    storage::StorageErr,
     task::{RetryRes, RetryTask, SupervisorErr},
     Order,
 };
-use crate::{now_timestamp, provers::ProofResult};
 use alloy::{
-    network::Ethereum,
+    network::{Ethereum, TransactionBuilder},
     primitives::{
         aliases::U96,
         utils::{format_ether, format_units, parse_ether},
-        Address, U256,
+        Address, Bytes, U256,
     },
     providers::{Provider, WalletProvider},
+    rpc::types::{
+        state::{AccountOverride, StateOverride},
+        TransactionRequest,
+    },
+    sol,
+    sol_types::SolCall,
+    transports::RpcError,
 };
 use anyhow::{Context, Result};
 use boundless_market::{
+    clock::{Clock, ClockObj},
     contracts::{boundless_market::BoundlessMarketService, RequestError},
     selector::{ProofType, SupportedSelectors},
+    simulator::MarketBackend,
 };
 use thiserror::Error;
 use tokio::task::JoinSet;
@@ -45,6 +55,30 @@ const MAX_PRICING_BATCH_SIZE: u32 = 10;
 /// Gas allocated to verifying a smart contract signature. Copied from BoundlessMarket.sol.
 const ERC1271_MAX_GAS_FOR_CHECK: u64 = 100000;
 
+/// Intrinsic gas of a transaction, included in gas estimates for a simulated callback.
+const TX_BASE_GAS: u64 = 21000;
+
+/// JSON-RPC error code of a method the node does not implement.
+const RPC_METHOD_NOT_FOUND: i64 = -32601;
+
+/// Returns whether the error reports that the node cannot simulate the callback, as it does not
+/// support the method or state overrides, rather than that the callback fails.
+fn simulation_unsupported(code: i64, message: &str) -> bool {
+    let message = message.to_lowercase();
+    code == RPC_METHOD_NOT_FOUND
+        || (message.contains("override")
+            && (message.contains("not supported") || message.contains("unsupported")))
+}
+
+sol! {
+    #[sol(rpc)]
+    interface IBoundlessMarketCallback {
+        function handleProof(bytes32 imageId, bytes calldata journal, bytes calldata seal) external;
+        /// Verifier used by callbacks that extend BoundlessMarketCallback.sol.
+        function VERIFIER() external view returns (address);
+    }
+}
+
 #[derive(Error, Debug)]
 #[non_exhaustive]
 pub enum PriceOrderErr {
@@ -71,10 +105,12 @@ pub struct OrderPicker<P> {
     prover: ProverObj,
     provider: Arc<P>,
     chain_monitor: Arc<ChainMonitorService<P>>,
-    market: BoundlessMarketService<Arc<P>>,
+    market_addr: Address,
+    market: Arc<dyn MarketBackend>,
     supported_selectors: SupportedSelectors,
     // Tracks the timestamp when the prover estimates it will complete the locked orders.
     prover_available_at: Arc<tokio::sync::Mutex<u64>>,
+    clock: ClockObj,
 }
 
 #[derive(Debug)]
@@ -109,18 +145,32 @@ where
             provider.clone(),
             provider.default_signer_address(),
         );
+        let clock = chain_monitor.clock();
         Self {
             db,
             config,
             prover,
             provider,
             chain_monitor,
-            market,
+            market_addr,
+            market: Arc::new(market),
             supported_selectors: SupportedSelectors::default(),
-            prover_available_at: Arc::new(tokio::sync::Mutex::new(now_timestamp())),
+            prover_available_at: Arc::new(tokio::sync::Mutex::new(clock.now())),
+            clock,
         }
     }
 
+    /// Read balances from the given market, such as a simulated market, in place of the deployed
+    /// one.
+    pub fn with_market(self, market: Arc<dyn MarketBackend>) -> Self {
+        Self { market, ..self }
+    }
+
+    #[cfg(test)]
+    fn with_clock(self, clock: ClockObj) -> Self {
+        Self { clock, ..self }
+    }
+
     async fn price_order_and_update_db(&self, order_id: U256, order: &Order) -> bool {
         let f = || async {
             match self.price_order(order_id, order).await {
@@ -202,7 +252,7 @@ where
         let order_expiration =
             order.request.offer.biddingStart + order.request.offer.timeout as u64;
 
-        let now = now_timestamp();
+        let now = self.clock.now();
 
         // If order_expiration > lock_expiration the period in-between is when order can be filled
         // by anyone without staking to partially claim the slashed stake
@@ -316,13 +366,34 @@ where
             .await
             .map_err(PriceOrderErr::FetchImageErr)?;
 
-        let input_id = crate::upload_input_uri(&self.prover, order, &self.config)
-            .await
-            .map_err(PriceOrderErr::FetchInputErr)?;
+        let input_res = crate::upload_input_uri(&self.prover, order, &self.config).await;
+        let (input_id, assumption_ids) = match input_res {
+            Ok(ids) => ids,
+            Err(err)
+                if matches!(
+                    err.downcast_ref::<ProverError>(),
+                    Some(ProverError::UnsupportedEnv(_))
+                ) =>
+            {
+                tracing::info!(
+                    "Removing order {order_id:x} because its input env is not supported"
+                );
+                return Ok(Skip);
+            }
+            Err(err)
+                if err.downcast_ref::<StorageErr>().is_some_and(StorageErr::is_not_recipient) =>
+            {
+                tracing::info!(
+                    "Removing order {order_id:x} because its input is encrypted to another prover"
+                );
+                return Ok(Skip);
+            }
+            Err(err) => return Err(PriceOrderErr::FetchInputErr(err)),
+        };
 
         // Record the image/input IDs for proving stage
         self.db
-            .set_image_input_ids(order_id, &image_id, &input_id)
+            .set_image_input_ids(order_id, &image_id, &input_id, &assumption_ids)
             .await
             .context("Failed to record Input/Image IDs to DB")?;
 
@@ -369,12 +440,7 @@ where
         // TODO add a future timeout here to put a upper bound on how long to preflight for
         let proof_res = self
             .prover
-            .preflight(
-                &image_id,
-                &input_id,
-                vec![],
-                /* TODO assumptions */ Some(exec_limit * 1024 * 1024),
-            )
+            .preflight(&image_id, &input_id, assumption_ids, Some(exec_limit * 1024 * 1024))
             .await
             .map_err(|err| match err {
                 ProverError::ProvingFailed(ref err_msg) => {
@@ -423,6 +489,32 @@ where
             return Ok(Skip);
         }
 
+        // Simulate the callback with the preflight journal. Orders with callbacks that would fail
+        // are skipped, and the gas used replaces the callback gas limit in the gas cost estimate
+        // and in the gas reserved for committed orders.
+        let order_gas_cost = match order.request.requirements.callback.as_option() {
+            Some(callback) => {
+                let Some(callback_gas) = self.simulate_callback(order_id, order, &journal).await?
+                else {
+                    return Ok(Skip);
+                };
+                self.db
+                    .set_order_callback_gas(order_id, callback_gas)
+                    .await
+                    .context("Failed to set_order_callback_gas")?;
+                let gas_limit = u64::try_from(callback.gasLimit)?;
+                let order_gas =
+                    order_gas.saturating_sub(U256::from(gas_limit)) + U256::from(callback_gas);
+                let order_gas_cost = U256::from(gas_price) * order_gas;
+                tracing::debug!(
+                    "Order {order_id:x} callback simulated to use {callback_gas} of {gas_limit} gas; estimated {order_gas} gas to lock and fill, {} ether",
+                    format_ether(order_gas_cost)
+                );
+                order_gas_cost
+            }
+            None => order_gas_cost,
+        };
+
         // Check if the order can be completed before its deadline
         if let Some(peak_prove_khz) = peak_prove_khz {
             // TODO: this is a naive solution for the following reasons:
@@ -622,6 +714,100 @@ where
         Ok(stake)
     }
 
+    /// Simulate the request callback as it would be called by the market on fulfillment.
+    ///
+    /// Runs an `eth_call` of `handleProof` from the market address with the preflight journal,
+    /// then estimates the gas it uses. Returns the gas to reserve for the callback, or `None` if
+    /// the callback reverts or exceeds its gas limit. If the RPC cannot run the simulation, e.g.
+    /// because it does not support state overrides, the callback gas limit is returned.
+    async fn simulate_callback(
+        &self,
+        order_id: U256,
+        order: &Order,
+        journal: &[u8],
+    ) -> Result<Option<u64>> {
+        let callback = &order.request.requirements.callback;
+        let gas_limit = u64::try_from(callback.gasLimit)?;
+        let code = self
+            .provider
+            .get_code_at(callback.addr)
+            .await
+            .context("Failed to get callback code")?;
+        if code.is_empty() {
+            tracing::info!(
+                "Order {order_id:x} callback address {} has no code, skipping",
+                callback.addr
+            );
+            return Ok(None);
+        }
+
+        // The seal is not known until the order is proven. Callbacks that verify the seal against
+        // their VERIFIER are simulated with that contract replaced by code that accepts any proof.
+        let mut overrides = StateOverride::default();
+        let callback_contract = IBoundlessMarketCallback::new(callback.addr, self.provider.clone());
+        if let Ok(verifier) = callback_contract.VERIFIER().call().await {
+            overrides.insert(
+                verifier._0,
+                AccountOverride { code: Some(Bytes::from_static(&[0x00])), ..Default::default() },
+            );
+        }
+
+        let calldata = IBoundlessMarketCallback::handleProofCall {
+            imageId: order.request.requirements.imageId,
+            journal: journal.to_vec().into(),
+            seal: Bytes::new(),
+        }
+        .abi_encode();
+        let tx = TransactionRequest::default()
+            .with_from(self.market_addr)
+            .with_to(callback.addr)
+            .with_input(calldata);
+
+        // Only nodes that cannot simulate the callback fall back to its gas limit. Any other error
+        // response, such as a revert or running out of gas, means the callback would fail.
+        match self.provider.call(tx.clone()).overrides(overrides.clone()).await {
+            Ok(_) => {}
+            Err(RpcError::ErrorResp(err)) if simulation_unsupported(err.code, &err.message) => {
+                tracing::warn!(
+                    "Order {order_id:x} callback could not be simulated, using its gas limit {gas_limit}: {err}"
+                );
+                return Ok(Some(gas_limit));
+            }
+            Err(RpcError::ErrorResp(err)) => {
+                tracing::info!(
+                    "Order {order_id:x} callback reverts in simulation, skipping: {err}"
+                );
+                return Ok(None);
+            }
+            Err(err) => return Err(err).context("Failed to simulate callback"),
+        }
+
+        let gas = match self.provider.estimate_gas(tx).overrides(overrides).await {
+            Ok(gas) => gas,
+            Err(RpcError::ErrorResp(err)) if simulation_unsupported(err.code, &err.message) => {
+                tracing::warn!(
+                    "Order {order_id:x} callback gas could not be estimated, using its gas limit {gas_limit}: {err}"
+                );
+                return Ok(Some(gas_limit));
+            }
+            Err(RpcError::ErrorResp(err)) => {
+                tracing::info!("Order {order_id:x} callback gas estimation fails, skipping: {err}");
+                return Ok(None);
+            }
+            Err(err) => return Err(err).context("Failed to estimate callback gas"),
+        };
+
+        let callback_gas = gas.saturating_sub(TX_BASE_GAS);
+        if callback_gas > gas_limit {
+            tracing::info!(
+                "Order {order_id:x} callback uses {callback_gas} gas, exceeding its gas limit {gas_limit}, skipping"
+            );
+            return Ok(None);
+        }
+
+        Ok(Some(callback_gas))
+    }
+
     /// Estimate of gas for locking a single order
     /// Currently just uses the config estimate but this may change in the future
     async fn estimate_gas_to_lock(&self, order: &Order) -> Result<u64> {
@@ -646,16 +832,20 @@ where
 
         let mut estimate = base;
 
-        // Add gas for orders that make use of the callbacks feature.
-        estimate += u64::try_from(
-            order
-                .request
-                .requirements
-                .callback
-                .as_option()
-                .map(|callback| callback.gasLimit)
-                .unwrap_or(U96::ZERO),
-        )?;
+        // Add gas for orders that make use of the callbacks feature, using the simulated gas once
+        // the callback has been simulated during pricing.
+        estimate += match order.callback_gas {
+            Some(callback_gas) => callback_gas,
+            None => u64::try_from(
+                order
+                    .request
+                    .requirements
+                    .callback
+                    .as_option()
+                    .map(|callback| callback.gasLimit)
+                    .unwrap_or(U96::ZERO),
+            )?,
+        };
 
         estimate += match self
             .supported_selectors
@@ -734,7 +924,7 @@ where
     ///
     /// This is defined as the balance in staking tokens of the signer account minus any pending locked stake.
     async fn available_stake_balance(&self) -> Result<U256> {
-        let balance = self.market.balance_of_stake(self.provider.default_signer_address()).await?;
+        let balance = self.market.balance_of_stake(self.market.caller()).await?;
         let pending_balance = self.pending_locked_stake().await?;
         Ok(balance - pending_balance)
     }
@@ -763,7 +953,7 @@ where
             return Ok(());
         }
 
-        let order_res = self.db.update_orders_for_pricing(capacity, now_timestamp()).await?;
+        let order_res = self.db.update_orders_for_pricing(capacity, self.clock.now()).await?;
 
         for (order_id, order) in order_res {
             let picker_clone = self.clone();
@@ -847,7 +1037,7 @@ where
                     tracing::trace!(
                         "No in progress orders, setting prover available to current time"
                     );
-                    *picker_copy.prover_available_at.lock().await = now_timestamp();
+                    *picker_copy.prover_available_at.lock().await = picker_copy.clock.now();
                 }
                 tokio::select! {
                     _ = config_check_timer.tick() => {
@@ -898,7 +1088,8 @@ where
 mod tests {
     use super::*;
     use crate::{
-        chain_monitor::ChainMonitorService, db::SqliteDb, provers::DefaultProver, OrderStatus,
+        chain_monitor::ChainMonitorService, db::SqliteDb, now_timestamp,
+        order_monitor::OrderMonitor, provers::DefaultProver, OrderStatus,
     };
     use alloy::{
         network::EthereumWallet,
@@ -907,11 +1098,15 @@ mod tests {
         providers::{ext::AnvilApi, ProviderBuilder},
         signers::local::PrivateKeySigner,
     };
+    use boundless_market::clock::FakeClock;
     use boundless_market::contracts::{
         Callback, Input, Offer, Predicate, PredicateType, ProofRequest, RequestId, Requirements,
     };
+    use boundless_market::simulator::{SimMarket, SimMarketClient};
     use boundless_market::storage::{MockStorageProvider, StorageProvider};
-    use boundless_market_test_utils::{deploy_boundless_market, deploy_hit_points};
+    use boundless_market_test_utils::{
+        deploy_boundless_market, deploy_hit_points, deploy_mock_callback, deploy_mock_verifier,
+    };
     use chrono::Utc;
     use guest_assessor::{ASSESSOR_GUEST_ID, ASSESSOR_GUEST_PATH};
     use guest_util::{ECHO_ELF, ECHO_ID};
@@ -993,6 +1188,9 @@ mod tests {
                 client_sig: Bytes::new(),
                 lock_price: None,
                 error_msg: None,
+                offchain: false,
+                callback_gas: None,
+                assumption_ids: vec![],
             }
         }
     }
@@ -1106,6 +1304,72 @@ mod tests {
         assert_eq!(db_order.target_timestamp, Some(0));
     }
 
+    #[tokio::test]
+    #[traced_test]
+    async fn pick_and_lock_order_sim_market() {
+        let config = ConfigLock::default();
+        {
+            config.load_write().unwrap().market.mcycle_price = "0.0000001".into();
+        }
+        let ctx = TestCtxBuilder::default().with_config(config.clone()).build().await;
+        let prover = ctx.provider.default_signer_address();
+
+        let lock_stake = U256::from(10);
+        let order = ctx.generate_next_order(OrderParams { lock_stake, ..Default::default() }).await;
+        let order_id = order.request.id;
+
+        // The request and the stake only exist on the simulated market.
+        let mut sim_market = SimMarket::new(FakeClock::new(now_timestamp()));
+        sim_market.deposit(prover, order.request.offer.maxPrice);
+        sim_market.deposit_stake(prover, lock_stake);
+        sim_market.submit_request(order.request.clone()).unwrap();
+        let sim_market = Arc::new(std::sync::Mutex::new(sim_market));
+        let sim_client: Arc<dyn MarketBackend> =
+            Arc::new(SimMarketClient::new(sim_market.clone(), prover));
+
+        let picker = ctx.picker.clone().with_market(sim_client.clone());
+        ctx.db.add_order(order_id, order.clone()).await.unwrap();
+        assert!(picker.price_order_and_update_db(order_id, &order).await);
+        let db_order = ctx.db.get_order(order_id).await.unwrap().unwrap();
+        assert_eq!(db_order.status, OrderStatus::Locking);
+
+        let chain_monitor = Arc::new(ChainMonitorService::new(ctx.provider.clone()).await.unwrap());
+        tokio::spawn(chain_monitor.spawn());
+        let monitor = OrderMonitor::new(
+            ctx.db.clone(),
+            ctx.provider.clone(),
+            chain_monitor,
+            config,
+            2,
+            Address::ZERO,
+        )
+        .unwrap()
+        .with_market(sim_client);
+        monitor.start_monitor(Some(1)).await.unwrap();
+
+        assert_eq!(sim_market.lock().unwrap().locked_by(order_id), Some(prover));
+        assert_eq!(sim_market.lock().unwrap().balance_of_stake(prover), U256::ZERO);
+        let db_order = ctx.db.get_order(order_id).await.unwrap().unwrap();
+        assert_eq!(db_order.status, OrderStatus::PendingProving);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn skip_order_expired_on_chain_clock() {
+        let ctx = TestCtxBuilder::default().build().await;
+
+        let order = ctx.generate_next_order(Default::default()).await;
+        let order_id = order.request.id;
+        ctx.db.add_order(order_id, order.clone()).await.unwrap();
+
+        // The order is still open by the system clock, but past its timeout on the chain.
+        let expiration = order.request.offer.biddingStart + order.request.offer.timeout as u64;
+        let picker = ctx.picker.clone().with_clock(Arc::new(FakeClock::new(expiration)));
+        let locked = picker.price_order_and_update_db(order_id, &order).await;
+        assert!(!locked);
+        assert!(logs_contain("because it has expired"));
+    }
+
     #[tokio::test]
     #[traced_test]
     async fn skip_bad_predicate() {
@@ -1892,4 +2156,98 @@ mod tests {
         let db_order = ctx.db.get_order(order_id).await.unwrap().unwrap();
         assert_eq!(db_order.status, OrderStatus::PendingProving);
     }
+
+    #[test]
+    fn callback_simulation_errors() {
+        // Reverts and out of gas errors, as reported by geth, mean the callback would fail.
+        assert!(!simulation_unsupported(-32000, "execution reverted"));
+        assert!(!simulation_unsupported(-32000, "out of gas"));
+        assert!(!simulation_unsupported(3, "execution reverted"));
+
+        // Nodes without the method or state overrides cannot simulate the callback.
+        assert!(simulation_unsupported(
+            -32601,
+            "the method eth_call does not exist/is not available"
+        ));
+        assert!(simulation_unsupported(-32602, "state override is not supported"));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn simulate_callback() {
+        let config = ConfigLock::default();
+        {
+            config.load_write().unwrap().market.mcycle_price = "0.0000001".into();
+        }
+        let ctx = TestCtxBuilder::default().with_config(config).build().await;
+        let verifier = deploy_mock_verifier(ctx.provider.clone()).await.unwrap();
+        let market_addr = *ctx.boundless_market.instance().address();
+
+        // Callback that uses less gas than its limit.
+        let callback_addr = deploy_mock_callback(
+            ctx.provider.clone(),
+            verifier,
+            market_addr,
+            Digest::from(ECHO_ID),
+            U256::from(50_000),
+        )
+        .await
+        .unwrap();
+        let mut order =
+            ctx.generate_next_order(OrderParams { order_index: 1, ..Default::default() }).await;
+        order.request.requirements.callback =
+            Callback { addr: callback_addr, gasLimit: U96::from(500_000) };
+        let order_id = order.request.id;
+        let _request_id =
+            ctx.boundless_market.submit_request(&order.request, &ctx.signer(0)).await.unwrap();
+        ctx.db.add_order(order_id, order.clone()).await.unwrap();
+        assert!(ctx.picker.price_order_and_update_db(order_id, &order).await);
+        assert!(logs_contain(&format!("Order {order_id:x} callback simulated to use")));
+        let db_order = ctx.db.get_order(order_id).await.unwrap().unwrap();
+        let callback_gas = db_order.callback_gas.unwrap();
+        assert!(callback_gas < 500_000);
+        let fulfill_gas = ctx.picker.estimate_gas_to_fulfill(&db_order).await.unwrap();
+        assert_eq!(ctx.picker.estimate_gas_to_fulfill_pending().await.unwrap(), fulfill_gas);
+        assert_eq!(
+            fulfill_gas,
+            ctx.picker.estimate_gas_to_fulfill(&order).await.unwrap() - 500_000 + callback_gas
+        );
+
+        // Callback that uses more gas than its limit.
+        let mut order =
+            ctx.generate_next_order(OrderParams { order_index: 2, ..Default::default() }).await;
+        order.request.requirements.callback =
+            Callback { addr: callback_addr, gasLimit: U96::from(20_000) };
+        let order_id = order.request.id;
+        let _request_id =
+            ctx.boundless_market.submit_request(&order.request, &ctx.signer(0)).await.unwrap();
+        ctx.db.add_order(order_id, order.clone()).await.unwrap();
+        assert!(!ctx.picker.price_order_and_update_db(order_id, &order).await);
+        assert!(logs_contain("exceeding its gas limit 20000"));
+        let db_order = ctx.db.get_order(order_id).await.unwrap().unwrap();
+        assert_eq!(db_order.status, OrderStatus::Skipped);
+
+        // Callback that reverts, as it only accepts proofs of a different image.
+        let reverting_callback_addr = deploy_mock_callback(
+            ctx.provider.clone(),
+            verifier,
+            market_addr,
+            Digest::ZERO,
+            U256::from(50_000),
+        )
+        .await
+        .unwrap();
+        let mut order =
+            ctx.generate_next_order(OrderParams { order_index: 3, ..Default::default() }).await;
+        order.request.requirements.callback =
+            Callback { addr: reverting_callback_addr, gasLimit: U96::from(500_000) };
+        let order_id = order.request.id;
+        let _request_id =
+            ctx.boundless_market.submit_request(&order.request, &ctx.signer(0)).await.unwrap();
+        ctx.db.add_order(order_id, order.clone()).await.unwrap();
+        assert!(!ctx.picker.price_order_and_update_db(order_id, &order).await);
+        assert!(logs_contain(&format!("Order {order_id:x} callback reverts in simulation")));
+        let db_order = ctx.db.get_order(order_id).await.unwrap().unwrap();
+        assert_eq!(db_order.status, OrderStatus::Skipped);
+    }
 }
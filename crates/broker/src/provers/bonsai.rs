@@ -212,6 +212,16 @@ impl Prover for Bonsai {
         .await
     }
 
+    async fn upload_receipt(&self, receipt: Vec<u8>) -> Result<String, ProverError> {
+        retry::<String, ProverError, _, _>(
+            self.req_retry_count,
+            self.req_retry_sleep_ms,
+            || async { Ok(self.client.upload_receipt(receipt.clone()).await?) },
+            "upload receipt",
+        )
+        .await
+    }
+
     async fn upload_image(&self, image_id: &str, image: Vec<u8>) -> Result<(), ProverError> {
         retry::<(), ProverError, _, _>(
             self.req_retry_count,
@@ -330,7 +340,7 @@ impl Prover for Bonsai {
         Ok(Some(receipt.journal.bytes))
     }
 
-    async fn compress(&self, proof_id: &str) -> Result<String, ProverError> {
+    async fn start_compress(&self, proof_id: &str) -> Result<String, ProverError> {
         let proof_id = retry::<SnarkId, ProverError, _, _>(
             self.req_retry_count,
             self.req_retry_sleep_ms,
@@ -339,6 +349,12 @@ impl Prover for Bonsai {
         )
         .await?;
 
+        Ok(proof_id.uuid)
+    }
+
+    async fn wait_for_compress(&self, compress_proof_id: &str) -> Result<(), ProverError> {
+        let proof_id = SnarkId { uuid: compress_proof_id.into() };
+
         let poller = StatusPoller {
             poll_sleep_ms: self.status_poll_ms,
             retry_counts: self.status_poll_retry_count,
@@ -346,7 +362,7 @@ impl Prover for Bonsai {
 
         poller.poll_with_retries_snark_id(&proof_id, &self.client).await?;
 
-        Ok(proof_id.uuid)
+        Ok(())
     }
 
     async fn get_compressed_receipt(&self, proof_id: &str) -> Result<Option<Vec<u8>>, ProverError> {
@@ -371,3 +387,21 @@ impl Prover for Bonsai {
         Ok(Some(receipt_buf))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use boundless_market::input::InputBuilder;
+
+    #[tokio::test]
+    async fn upload_env_rejects_segment_limit_po2() {
+        let config = ConfigLock::default();
+        config.load_write().unwrap().prover.bonsai_r0_zkvm_ver = Some(risc0_zkvm::VERSION.into());
+        let prover = Bonsai::new(config, "http://localhost:8081", "").unwrap();
+
+        // Bonsai only accepts the stdin of the env, so a segment limit is not silently dropped.
+        let env = InputBuilder::new().with_segment_limit_po2(20).build_env().unwrap();
+        let err = prover.upload_env(env).await.unwrap_err();
+        assert!(matches!(err, ProverError::UnsupportedEnv(_)), "{err:?}");
+    }
+}
@@ -4,10 +4,11 @@
 
 use std::{borrow::Borrow, collections::HashMap, sync::Arc};
 
-use crate::config::ProverConf;
+use crate::config::{ConfigLock, ProverConf};
 use crate::provers::{ExecutorResp, ProofResult, Prover, ProverError};
 use anyhow::{Context, Result as AnyhowResult};
 use async_trait::async_trait;
+use boundless_market::input::GuestEnv;
 use risc0_zkvm::{
     default_executor, default_prover, ExecutorEnv, ProveInfo, ProverOpts, Receipt, SessionInfo,
     VERSION,
@@ -18,11 +19,12 @@ use uuid::Uuid;
 #[derive(Debug, Default)]
 pub struct DefaultProver {
     state: Arc<ProverState>,
+    config: ConfigLock,
 }
 
 #[derive(Debug, Default)]
 struct ProverState {
-    inputs: RwLock<HashMap<String, Vec<u8>>>,
+    inputs: RwLock<HashMap<String, GuestEnv>>,
     images: RwLock<HashMap<String, Vec<u8>>>,
     proofs: RwLock<HashMap<String, ProofData>>,
 }
@@ -52,20 +54,38 @@ impl DefaultProver {
         Self::default()
     }
 
+    /// Bounds the segment limit po2 of uploaded envs by the given config, in place of the default.
+    pub fn with_config(self, config: ConfigLock) -> Self {
+        Self { config, ..self }
+    }
+
+    fn executor_env<'a>(
+        input: GuestEnv,
+        assumptions: Vec<Receipt>,
+        executor_limit: Option<u64>,
+    ) -> AnyhowResult<ExecutorEnv<'a>> {
+        let mut env_builder = ExecutorEnv::builder();
+        env_builder.session_limit(executor_limit);
+        env_builder.write_slice(&input.stdin_bytes());
+        env_builder.env_vars(input.env_vars.into_iter().collect());
+        env_builder.args(&input.args);
+        if let Some(po2) = input.segment_limit_po2 {
+            env_builder.segment_limit_po2(po2);
+        }
+        assumptions.into_iter().for_each(|receipt| {
+            env_builder.add_assumption(receipt);
+        });
+        env_builder.build()
+    }
+
     async fn execute(
         elf: Vec<u8>,
-        input: Vec<u8>,
+        input: GuestEnv,
         assumptions: Vec<Receipt>,
         executor_limit: Option<u64>,
     ) -> AnyhowResult<SessionInfo> {
         tokio::task::spawn_blocking(move || {
-            let mut env_builder = ExecutorEnv::builder();
-            env_builder.session_limit(executor_limit);
-            env_builder.write_slice(&input);
-            assumptions.into_iter().for_each(|receipt| {
-                env_builder.add_assumption(receipt);
-            });
-            let env = env_builder.build()?;
+            let env = Self::executor_env(input, assumptions, executor_limit)?;
 
             default_executor().execute(env, &elf)
         })
@@ -75,17 +95,12 @@ impl DefaultProver {
 
     async fn prove(
         elf: Vec<u8>,
-        input: Vec<u8>,
+        input: GuestEnv,
         assumptions: Vec<Receipt>,
         opts: ProverOpts,
     ) -> AnyhowResult<ProveInfo> {
         tokio::task::spawn_blocking(move || {
-            let mut env_builder = ExecutorEnv::builder();
-            env_builder.write_slice(&input);
-            assumptions.into_iter().for_each(|receipt| {
-                env_builder.add_assumption(receipt);
-            });
-            let env = env_builder.build()?;
+            let env = Self::executor_env(input, assumptions, None)?;
 
             default_prover().prove_with_opts(env, &elf, &opts)
         })
@@ -93,7 +108,34 @@ impl DefaultProver {
         .unwrap()
     }
 
-    async fn get_input(&self, id: &str) -> Option<Vec<u8>> {
+    /// Waits for the proof to complete, polling its status.
+    async fn wait_for_proof(&self, proof_id: &str) -> Result<(), ProverError> {
+        const MAX_ATTEMPTS: u32 = 1800; // 30 minutes at 1 second intervals
+        const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+        for _ in 0..MAX_ATTEMPTS {
+            {
+                let proofs = self.state.proofs.read().await;
+                let proof_data = proofs
+                    .get(proof_id)
+                    .ok_or_else(|| ProverError::NotFound(format!("proof {proof_id}")))?;
+
+                match proof_data.status {
+                    Status::Running => {}
+                    Status::Succeeded => return Ok(()),
+                    Status::Failed => {
+                        return Err(ProverError::ProvingFailed(proof_data.error_msg.clone()));
+                    }
+                }
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+
+        Err(ProverError::ProvingFailed(format!("timeout after {:?}", POLL_INTERVAL * MAX_ATTEMPTS)))
+    }
+
+    async fn get_input(&self, id: &str) -> Option<GuestEnv> {
         self.state.inputs.read().await.get(id).cloned()
     }
 
@@ -123,14 +165,44 @@ impl DefaultProver {
 #[async_trait]
 impl Prover for DefaultProver {
     async fn upload_input(&self, input: Vec<u8>) -> Result<String, ProverError> {
+        let mut env = GuestEnv::default();
+        env.stdin = input;
+        self.upload_env(env).await
+    }
+
+    async fn upload_env(&self, env: GuestEnv) -> Result<String, ProverError> {
+        if let Some(po2) = env.segment_limit_po2 {
+            let (min_po2, max_po2) = {
+                let config = self.config.lock_all()?;
+                (config.prover.min_segment_limit_po2, config.prover.max_segment_limit_po2)
+            };
+            if !(min_po2..=max_po2).contains(&po2) {
+                return Err(ProverError::UnsupportedEnv(format!(
+                    "segment limit po2 {po2} outside of [{min_po2}, {max_po2}]"
+                )));
+            }
+        }
+
         let input_id = format!("input_{}", Uuid::new_v4());
 
         let mut inputs = self.state.inputs.write().await;
-        inputs.insert(input_id.clone(), input);
+        inputs.insert(input_id.clone(), env);
 
         Ok(input_id)
     }
 
+    async fn upload_receipt(&self, receipt: Vec<u8>) -> Result<String, ProverError> {
+        let receipt_id = format!("receipt_{}", Uuid::new_v4());
+        let proof = ProofData {
+            status: Status::Succeeded,
+            receipt: Some(bincode::deserialize(&receipt)?),
+            ..Default::default()
+        };
+        self.state.proofs.write().await.insert(receipt_id.clone(), proof);
+
+        Ok(receipt_id)
+    }
+
     async fn upload_image(&self, image_id: &str, image: Vec<u8>) -> Result<(), ProverError> {
         let mut images = self.state.images.write().await;
         images.insert(image_id.to_string(), image);
@@ -255,41 +327,23 @@ impl Prover for DefaultProver {
             return Err(ProverError::NotFound(format!("stark proof {proof_id}")))?;
         }
 
-        const MAX_ATTEMPTS: u32 = 1800; // 30 minutes at 1 second intervals
-        const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
-
-        for _ in 0..MAX_ATTEMPTS {
-            {
-                let proofs = self.state.proofs.read().await;
-                let proof_data = proofs
-                    .get(proof_id)
-                    .ok_or_else(|| ProverError::NotFound(format!("stark proof {proof_id}")))?;
-
-                match proof_data.status {
-                    Status::Running => {}
-                    Status::Succeeded => {
-                        let stats = proof_data.stats.as_ref().unwrap();
-                        return Ok(ProofResult {
-                            id: proof_id.to_string(),
-                            stats: ExecutorResp {
-                                segments: stats.segments,
-                                user_cycles: stats.user_cycles,
-                                total_cycles: stats.total_cycles,
-                                ..Default::default()
-                            },
-                            ..Default::default()
-                        });
-                    }
-                    Status::Failed => {
-                        return Err(ProverError::ProvingFailed(proof_data.error_msg.clone()));
-                    }
-                }
-            }
+        self.wait_for_proof(proof_id).await?;
 
-            tokio::time::sleep(POLL_INTERVAL).await;
-        }
-
-        Err(ProverError::ProvingFailed(format!("timeout after {:?}", POLL_INTERVAL * MAX_ATTEMPTS)))
+        let proofs = self.state.proofs.read().await;
+        let stats = proofs
+            .get(proof_id)
+            .and_then(|proof| proof.stats.as_ref())
+            .ok_or_else(|| ProverError::NotFound(format!("stark proof {proof_id}")))?;
+        Ok(ProofResult {
+            id: proof_id.to_string(),
+            stats: ExecutorResp {
+                segments: stats.segments,
+                user_cycles: stats.user_cycles,
+                total_cycles: stats.total_cycles,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
     }
 
     async fn get_receipt(&self, proof_id: &str) -> Result<Option<Receipt>, ProverError> {
@@ -316,7 +370,7 @@ impl Prover for DefaultProver {
         Ok(proof_data.receipt.as_ref().map(|receipt| receipt.journal.bytes.clone()))
     }
 
-    async fn compress(&self, proof_id: &str) -> Result<String, ProverError> {
+    async fn start_compress(&self, proof_id: &str) -> Result<String, ProverError> {
         let receipt = self
             .get_receipt(proof_id)
             .await?
@@ -325,39 +379,50 @@ impl Prover for DefaultProver {
         let proof_id = format!("snark_{}", Uuid::new_v4());
         self.state.proofs.write().await.insert(proof_id.clone(), ProofData::default());
 
-        // TODO: remove this workaround when default_prover().compress works for Bonsai
-        let compress_result = if default_prover().get_name() == "bonsai" {
-            let client = bonsai_sdk::non_blocking::Client::from_env(VERSION)?;
-            super::Bonsai::compress(&client, &receipt, &ProverConf::default()).await
-        } else {
-            tokio::task::spawn_blocking(move || {
-                default_prover().compress(&ProverOpts::groth16(), &receipt)
-            })
-            .await
-            .unwrap()
-            .map_err(ProverError::from)
-        };
-        let compressed_bytes = compress_result
-            .as_ref()
-            .map(|receipt| bincode::serialize(receipt).unwrap())
-            .unwrap_or_default();
-
-        let mut proofs = self.state.proofs.write().await;
-        let proof = proofs.get_mut(&proof_id).unwrap();
-        match compress_result {
-            Ok(_) => {
-                proof.status = Status::Succeeded;
-                proof.compressed_receipt = Some(compressed_bytes);
+        tokio::spawn({
+            let state = self.state.clone();
+            let proof_id = proof_id.clone();
+            async move {
+                // TODO: remove this workaround when default_prover().compress works for Bonsai
+                let compress_result = if default_prover().get_name() == "bonsai" {
+                    match bonsai_sdk::non_blocking::Client::from_env(VERSION) {
+                        Ok(client) => {
+                            super::Bonsai::compress(&client, &receipt, &ProverConf::default()).await
+                        }
+                        Err(err) => Err(err.into()),
+                    }
+                } else {
+                    tokio::task::spawn_blocking(move || {
+                        default_prover().compress(&ProverOpts::groth16(), &receipt)
+                    })
+                    .await
+                    .unwrap()
+                    .map_err(ProverError::from)
+                };
 
-                Ok(proof_id)
+                let mut proofs = state.proofs.write().await;
+                let proof = proofs.get_mut(&proof_id).unwrap();
+                match compress_result {
+                    Ok(receipt) => {
+                        proof.status = Status::Succeeded;
+                        proof.compressed_receipt = Some(bincode::serialize(&receipt).unwrap());
+                    }
+                    Err(err) => {
+                        proof.status = Status::Failed;
+                        proof.error_msg = err.to_string();
+                    }
+                }
             }
-            Err(err) => {
-                proof.status = Status::Failed;
-                proof.error_msg = err.to_string();
+        });
 
-                Err(err)
-            }
+        Ok(proof_id)
+    }
+
+    async fn wait_for_compress(&self, compress_proof_id: &str) -> Result<(), ProverError> {
+        if !compress_proof_id.starts_with("snark_") {
+            return Err(ProverError::NotFound(format!("snark proof {compress_proof_id}")));
         }
+        self.wait_for_proof(compress_proof_id).await
     }
 
     async fn get_compressed_receipt(&self, proof_id: &str) -> Result<Option<Vec<u8>>, ProverError> {
@@ -372,6 +437,7 @@ impl Prover for DefaultProver {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use boundless_market::input::InputBuilder;
     use guest_util::{ECHO_ELF, ECHO_ID};
     use risc0_zkvm::sha::Digest;
     use tokio::test;
@@ -390,13 +456,33 @@ mod tests {
 
         // Verify input was stored
         let stored_input = prover.get_input(&input_id).await.unwrap();
-        assert_eq!(stored_input, input_data);
+        assert_eq!(stored_input.stdin, input_data);
 
         // Verify image was stored
         let stored_image = prover.get_image(&image_id).await.unwrap();
         assert_eq!(stored_image.as_slice(), ECHO_ELF);
     }
 
+    #[test]
+    async fn test_upload_env_segment_limit_po2() {
+        let config = ConfigLock::default();
+        {
+            let mut config = config.load_write().unwrap();
+            config.prover.min_segment_limit_po2 = 16;
+            config.prover.max_segment_limit_po2 = 20;
+        }
+        let prover = DefaultProver::new().with_config(config);
+
+        let env = InputBuilder::new().with_segment_limit_po2(18).build_env().unwrap();
+        prover.upload_env(env).await.unwrap();
+
+        for po2 in [15, 21] {
+            let env = InputBuilder::new().with_segment_limit_po2(po2).build_env().unwrap();
+            let err = prover.upload_env(env).await.unwrap_err();
+            assert!(matches!(err, ProverError::UnsupportedEnv(_)), "{err:?}");
+        }
+    }
+
     #[test]
     async fn test_preflight() {
         let prover = DefaultProver::new();
@@ -417,6 +503,45 @@ mod tests {
         assert_eq!(journal, input_data);
     }
 
+    #[test]
+    async fn test_preflight_env() {
+        let prover = DefaultProver::new();
+
+        // Upload an env with stdin frames and env vars
+        let env = InputBuilder::new()
+            .write_slice(b"Hello")
+            .with_stdin_frame(b", World!")
+            .with_env_var("RUST_LOG", "info")
+            .build_env()
+            .unwrap();
+        let input_id = prover.upload_env(env.clone()).await.unwrap();
+        let image_id = Digest::from(ECHO_ID).to_string();
+        prover.upload_image(&image_id, ECHO_ELF.to_vec()).await.unwrap();
+
+        // The guest reads the stdin followed by the frames
+        let result = prover.preflight(&image_id, &input_id, vec![], None).await.unwrap();
+        let journal = prover.get_preflight_journal(&result.id).await.unwrap().unwrap();
+        assert_eq!(journal, env.stdin_bytes());
+    }
+
+    #[test]
+    async fn test_upload_receipt() {
+        let prover = DefaultProver::new();
+
+        let input_id = prover.upload_input(b"Hello, World!".to_vec()).await.unwrap();
+        let image_id = Digest::from(ECHO_ID);
+        prover.upload_image(&image_id.to_string(), ECHO_ELF.to_vec()).await.unwrap();
+        let result =
+            prover.prove_and_monitor_stark(&image_id.to_string(), &input_id, vec![]).await.unwrap();
+        let receipt = prover.get_receipt(&result.id).await.unwrap().unwrap();
+
+        // An uploaded receipt can be used as an assumption
+        let receipt_id =
+            prover.upload_receipt(bincode::serialize(&receipt).unwrap()).await.unwrap();
+        let receipts = prover.get_receipts([receipt_id]).await.unwrap();
+        assert_eq!(receipts[0].journal.bytes, receipt.journal.bytes);
+    }
+
     #[test]
     async fn test_prove_stark() {
         let prover = DefaultProver::new();
@@ -6,7 +6,7 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use bonsai_sdk::SdkErr;
-use boundless_market::input::InputBuilder;
+use boundless_market::input::{GuestEnv, InputBuilder};
 use risc0_zkvm::Receipt;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -55,6 +55,9 @@ pub enum ProverError {
     #[error("proof status expired retry count")]
     StatusFailure,
 
+    #[error("guest env not supported by the prover: {0}")]
+    UnsupportedEnv(String),
+
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
@@ -74,6 +77,23 @@ pub fn encode_input(input: &impl serde::Serialize) -> Result<Vec<u8>, anyhow::Er
 #[async_trait]
 pub trait Prover {
     async fn upload_input(&self, input: Vec<u8>) -> Result<String, ProverError>;
+    /// Upload the [GuestEnv] of an order, returning an input ID.
+    ///
+    /// Assumptions referenced by the env are not fetched, and must be uploaded with
+    /// [Prover::upload_receipt] and passed when proving. By default, only the stdin of the env is
+    /// uploaded, and envs with env vars, args or a segment limit po2 are rejected with
+    /// [ProverError::UnsupportedEnv].
+    async fn upload_env(&self, env: GuestEnv) -> Result<String, ProverError> {
+        if !env.env_vars.is_empty() || !env.args.is_empty() {
+            return Err(ProverError::UnsupportedEnv("env vars and args".into()));
+        }
+        if env.segment_limit_po2.is_some() {
+            return Err(ProverError::UnsupportedEnv("segment limit po2".into()));
+        }
+        self.upload_input(env.stdin_bytes()).await
+    }
+    /// Upload a bincode serialized [Receipt], returning an ID that can be used as an assumption.
+    async fn upload_receipt(&self, receipt: Vec<u8>) -> Result<String, ProverError>;
     async fn upload_image(&self, image_id: &str, image: Vec<u8>) -> Result<(), ProverError>;
     async fn preflight(
         &self,
@@ -101,7 +121,15 @@ pub trait Prover {
     async fn get_receipt(&self, proof_id: &str) -> Result<Option<Receipt>, ProverError>;
     async fn get_preflight_journal(&self, proof_id: &str) -> Result<Option<Vec<u8>>, ProverError>;
     async fn get_journal(&self, proof_id: &str) -> Result<Option<Vec<u8>>, ProverError>;
-    async fn compress(&self, proof_id: &str) -> Result<String, ProverError>;
+    /// Start compressing the proof into a Groth16 proof, returning the ID of the Groth16 proof.
+    async fn start_compress(&self, proof_id: &str) -> Result<String, ProverError>;
+    /// Wait for a compression started with [Prover::start_compress] to complete.
+    async fn wait_for_compress(&self, compress_proof_id: &str) -> Result<(), ProverError>;
+    async fn compress(&self, proof_id: &str) -> Result<String, ProverError> {
+        let compress_proof_id = self.start_compress(proof_id).await?;
+        self.wait_for_compress(&compress_proof_id).await?;
+        Ok(compress_proof_id)
+    }
     async fn get_compressed_receipt(&self, proof_id: &str) -> Result<Option<Vec<u8>>, ProverError>;
 }
 
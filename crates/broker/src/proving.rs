@@ -79,8 +79,8 @@ impl ProvingService {
                 .await
                 .context("Failed to upload image")?,
         };
-        let input_id = match order.input_id.as_ref() {
-            Some(val) => val.clone(),
+        let (input_id, assumption_ids) = match order.input_id.as_ref() {
+            Some(val) => (val.clone(), order.assumption_ids.clone()),
             None => crate::upload_input_uri(&self.prover, &order, &self.config)
                 .await
                 .context("Failed to upload input")?,
@@ -90,7 +90,7 @@ impl ProvingService {
 
         let proof_id = self
             .prover
-            .prove_stark(&image_id, &input_id, /* TODO assumptions */ vec![])
+            .prove_stark(&image_id, &input_id, assumption_ids)
             .await
             .context("Failed to prove customer proof STARK order")?;
 
@@ -301,6 +301,9 @@ mod tests {
             client_sig: Bytes::new(),
             lock_price: None,
             error_msg: None,
+            offchain: false,
+            callback_gas: None,
+            assumption_ids: vec![],
         };
 
         db.add_order(order_id, order.clone()).await.unwrap();
@@ -369,6 +372,9 @@ mod tests {
             client_sig: Bytes::new(),
             lock_price: None,
             error_msg: None,
+            offchain: false,
+            callback_gas: None,
+            assumption_ids: vec![],
         };
         let order_id = U256::from(order_id);
         db.add_order(order_id, order.clone()).await.unwrap();
@@ -2,8 +2,8 @@
 //
 // All rights reserved.
 
-use crate::config::ConfigLock;
-use alloy::primitives::bytes::Buf;
+use crate::config::{ConfigErr, ConfigLock};
+use alloy::primitives::{bytes::Buf, B256};
 use async_trait::async_trait;
 use aws_config::retry::RetryConfig;
 use aws_sdk_s3::{
@@ -11,6 +11,7 @@ use aws_sdk_s3::{
     error::ProvideErrorMetadata,
     Client as S3Client,
 };
+use boundless_market::input::{self, GuestEnv, SecretKey};
 use futures::StreamExt;
 use http_cache_reqwest::{CACacheManager, Cache, CacheMode, HttpCache, HttpCacheOptions};
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
@@ -24,6 +25,7 @@ use std::{
 };
 
 const ENV_VAR_ROLE_ARN: &str = "AWS_ROLE_ARN";
+const ENV_VAR_INPUT_DECRYPTION_KEY: &str = "INPUT_DECRYPTION_KEY";
 
 #[derive(thiserror::Error, Debug)]
 #[non_exhaustive]
@@ -48,6 +50,29 @@ pub enum StorageErr {
 
     #[error("AWS S3 error")]
     S3(#[source] Box<dyn StdError + Send + Sync + 'static>),
+
+    #[error("input is encrypted but no input decryption key is configured")]
+    NoDecryptionKey,
+
+    #[error("invalid input decryption key")]
+    InvalidDecryptionKey,
+
+    #[error("failed to decrypt input")]
+    Decryption(#[from] input::Error),
+
+    #[error("failed to read config")]
+    Config(#[from] ConfigErr),
+}
+
+impl StorageErr {
+    /// Returns true if the input is encrypted to a key other than the one configured, meaning the
+    /// order can never be fulfilled by this broker.
+    pub(crate) fn is_not_recipient(&self) -> bool {
+        matches!(
+            self,
+            StorageErr::NoDecryptionKey | StorageErr::Decryption(input::Error::NotRecipient(_))
+        )
+    }
 }
 
 pub(crate) async fn create_uri_handler(
@@ -62,7 +87,7 @@ pub(crate) async fn create_uri_handler(
                 return Err(StorageErr::UnsupportedScheme("file".to_string()));
             }
             let max_size = {
-                let config = &config.lock_all().expect("lock failed").market;
+                let config = &config.lock_all()?.market;
                 config.max_file_size
             };
             let handler = FileHandler { path: uri.path().into(), max_size };
@@ -71,7 +96,7 @@ pub(crate) async fn create_uri_handler(
         }
         "http" | "https" => {
             let (max_size, max_retries, cache_dir) = {
-                let config = &config.lock_all().expect("lock failed").market;
+                let config = &config.lock_all()?.market;
                 (config.max_file_size, config.max_fetch_retries, config.cache_dir.clone())
             };
             let handler = HttpHandler::new(uri, max_size, cache_dir, max_retries).await?;
@@ -80,7 +105,7 @@ pub(crate) async fn create_uri_handler(
         }
         "s3" => {
             let (max_size, max_retries) = {
-                let config = &config.lock_all().expect("lock failed").market;
+                let config = &config.lock_all()?.market;
                 (config.max_file_size, config.max_fetch_retries)
             };
             let handler = S3Handler::new(uri, max_size, max_retries).await?;
@@ -91,11 +116,71 @@ pub(crate) async fn create_uri_handler(
     }
 }
 
+/// Creates a handler for fetching request inputs, which decrypts encrypted inputs with the
+/// configured input decryption key.
+pub(crate) async fn create_input_handler(
+    uri_str: &str,
+    config: &ConfigLock,
+) -> Result<Arc<dyn Handler>, StorageErr> {
+    let inner = create_uri_handler(uri_str, config).await?;
+    let key = input_decryption_key(config)?;
+
+    Ok(Arc::new(DecryptingHandler { inner, key }))
+}
+
+/// Returns the input decryption key, if any.
+///
+/// The hex encoded key is read from the `INPUT_DECRYPTION_KEY` environment variable, or else from
+/// the key file set in the config, so that it is not stored in the config itself.
+pub(crate) fn input_decryption_key(config: &ConfigLock) -> Result<Option<SecretKey>, StorageErr> {
+    let key_hex = match env::var(ENV_VAR_INPUT_DECRYPTION_KEY) {
+        Ok(key_hex) => key_hex,
+        Err(_) => {
+            let key_file = config.lock_all()?.market.input_decryption_key_file.clone();
+            match key_file {
+                Some(path) => std::fs::read_to_string(path)?,
+                None => return Ok(None),
+            }
+        }
+    };
+    let key: B256 = key_hex.trim().parse().map_err(|_| StorageErr::InvalidDecryptionKey)?;
+    SecretKey::from_slice(key.as_slice()).map(Some).map_err(|_| StorageErr::InvalidDecryptionKey)
+}
+
+/// Decrypts the given encoded input if it is encrypted, returning the encoded plaintext
+/// [GuestEnv]. Inputs that are not encrypted are returned as is.
+pub(crate) fn decrypt_input(data: Vec<u8>, key: Option<&SecretKey>) -> Result<Vec<u8>, StorageErr> {
+    if !GuestEnv::is_encrypted(&data) {
+        return Ok(data);
+    }
+    let key = key.ok_or(StorageErr::NoDecryptionKey)?;
+    Ok(GuestEnv::decrypt(&data, key)?.encode()?)
+}
+
 #[async_trait]
 pub(crate) trait Handler: Display + Send + Sync {
     async fn fetch(&self) -> Result<Vec<u8>, StorageErr>;
 }
 
+/// Wraps another [Handler], decrypting the fetched input if it is encrypted.
+struct DecryptingHandler {
+    inner: Arc<dyn Handler>,
+    key: Option<SecretKey>,
+}
+
+impl Display for DecryptingHandler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+#[async_trait]
+impl Handler for DecryptingHandler {
+    async fn fetch(&self) -> Result<Vec<u8>, StorageErr> {
+        decrypt_input(self.inner.fetch().await?, self.key.as_ref())
+    }
+}
+
 struct FileHandler {
     path: PathBuf,
     max_size: usize,
@@ -323,7 +408,10 @@ mod tests {
     use aws_smithy_http_client::test_util::capture_request;
     use httpmock::prelude::*;
     use serial_test::serial;
-    use std::sync::atomic::{AtomicU8, Ordering};
+    use std::{
+        io::Write,
+        sync::atomic::{AtomicU8, Ordering},
+    };
     use tracing_test::traced_test;
 
     #[tokio::test]
@@ -373,6 +461,65 @@ mod tests {
         success_mock.assert();
     }
 
+    #[tokio::test]
+    #[traced_test]
+    async fn http_fetch_encrypted() {
+        let key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let other_key = SecretKey::from_slice(&[2u8; 32]).unwrap();
+        let encrypted = boundless_market::input::InputBuilder::new()
+            .write_slice(&[0x41u8, 0x41])
+            .with_recipient(key.public_key())
+            .build_vec()
+            .unwrap();
+
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/input");
+            then.status(200).body(&encrypted);
+        });
+        let url = url::Url::parse(&server.url("/input")).unwrap();
+        let inner: Arc<dyn Handler> =
+            Arc::new(HttpHandler::new(url, 1024, None, None).await.unwrap());
+
+        let handler = DecryptingHandler { inner: inner.clone(), key: Some(key) };
+        let data = handler.fetch().await.unwrap();
+        assert_eq!(GuestEnv::decode(&data).unwrap().stdin, vec![0x41, 0x41]);
+
+        let handler = DecryptingHandler { inner: inner.clone(), key: Some(other_key) };
+        assert!(handler.fetch().await.unwrap_err().is_not_recipient());
+
+        let handler = DecryptingHandler { inner, key: None };
+        assert!(handler.fetch().await.unwrap_err().is_not_recipient());
+    }
+
+    #[test]
+    #[serial] // Run serially because it modifies environment variables
+    fn input_decryption_key_sources() {
+        let key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let key_hex = format!("0x{}", hex::encode(key.to_bytes()));
+        let config = ConfigLock::default();
+
+        temp_env::with_var_unset(ENV_VAR_INPUT_DECRYPTION_KEY, || {
+            assert!(input_decryption_key(&config).unwrap().is_none());
+
+            let mut key_file = tempfile::NamedTempFile::new().unwrap();
+            writeln!(key_file, "{key_hex}").unwrap();
+            config.load_write().unwrap().market.input_decryption_key_file =
+                Some(key_file.path().into());
+            assert_eq!(input_decryption_key(&config).unwrap(), Some(key.clone()));
+        });
+
+        // The environment variable takes precedence over the key file.
+        let env_key = SecretKey::from_slice(&[2u8; 32]).unwrap();
+        let env_key_hex = hex::encode(env_key.to_bytes());
+        temp_env::with_var(ENV_VAR_INPUT_DECRYPTION_KEY, Some(&env_key_hex), || {
+            assert_eq!(input_decryption_key(&config).unwrap(), Some(env_key.clone()));
+        });
+        temp_env::with_var(ENV_VAR_INPUT_DECRYPTION_KEY, Some("0x1234"), || {
+            assert!(matches!(input_decryption_key(&config), Err(StorageErr::InvalidDecryptionKey)));
+        });
+    }
+
     #[tokio::test]
     #[traced_test]
     async fn http_max_size() {
@@ -2,38 +2,54 @@
 //
 // All rights reserved.
 
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 
 use alloy::{
     network::Ethereum,
-    primitives::{utils::format_ether, Address, B256, U256},
+    primitives::{utils::format_ether, Address, Bytes, FixedBytes, B256, U256},
     providers::{Provider, WalletProvider},
     sol_types::{SolStruct, SolValue},
 };
 use anyhow::{anyhow, bail, ensure, Context, Result};
+use boundless_assessor::process_tree;
 use boundless_market::{
     contracts::{
-        boundless_market::BoundlessMarketService, encode_seal, AssessorJournal, AssessorReceipt,
-        Fulfillment,
+        boundless_market::{BoundlessMarketService, MarketError},
+        encode_seal,
+        smart_contract_requestor::{ERC1271_MAGIC_VALUE, IERC1271},
+        AssessorCommitment, AssessorJournal, AssessorReceipt, Fulfillment, ProofRequest,
+        UNSPECIFIED_SELECTOR,
     },
-    selector::is_groth16_selector,
+    fulfillments::{write_archive_entry, FulfillmentData},
+    order_stream_client::Client as OrderStreamClient,
+    selector::{is_groth16_selector, SupportedSelectors},
 };
-use guest_assessor::ASSESSOR_GUEST_ID;
 use risc0_aggregation::{SetInclusionReceipt, SetInclusionReceiptVerifierParameters};
 use risc0_ethereum_contracts::set_verifier::SetVerifierService;
 use risc0_zkvm::{
     sha::{Digest, Digestible},
-    MaybePruned, Receipt, ReceiptClaim,
+    MaybePruned, Receipt, ReceiptClaim, VerifierContext,
 };
+use thiserror::Error;
 
 use crate::{
     config::ConfigLock,
     db::DbObj,
     provers::ProverObj,
     task::{RetryRes, RetryTask, SupervisorErr},
-    Batch,
+    Batch, OrderStatus,
 };
 
+/// Returned by [Submitter::submit_batch] when the local pre-submission checks reject orders in
+/// the batch. This is not counted as a submission attempt.
+#[derive(Error, Debug)]
+#[error("batch rejected by pre-submission checks, failed orders: {0:x?}")]
+pub struct PreflightRejected(Vec<U256>);
+
 #[derive(Clone)]
 pub struct Submitter<P> {
     db: DbObj,
@@ -42,7 +58,10 @@ pub struct Submitter<P> {
     set_verifier: SetVerifierService<Arc<P>>,
     set_verifier_addr: Address,
     set_builder_img_id: Digest,
+    assessor_img_id: Digest,
+    supported_selectors: SupportedSelectors,
     prover_address: Address,
+    order_stream: Option<OrderStreamClient>,
     config: ConfigLock,
 }
 
@@ -59,6 +78,8 @@ where
         set_verifier_addr: Address,
         market_addr: Address,
         set_builder_img_id: Digest,
+        assessor_img_id: Digest,
+        order_stream: Option<OrderStreamClient>,
     ) -> Result<Self> {
         let txn_timeout_opt = {
             let config = config.lock_all().context("Failed to read config")?;
@@ -84,6 +105,8 @@ where
         }
 
         let prover_address = provider.default_signer_address();
+        let supported_selectors =
+            SupportedSelectors::default().with_set_builder_image_id(set_builder_img_id);
 
         Ok(Self {
             db,
@@ -92,12 +115,15 @@ where
             set_verifier,
             set_verifier_addr,
             set_builder_img_id,
+            assessor_img_id,
+            supported_selectors,
             prover_address,
+            order_stream,
             config,
         })
     }
 
-    async fn fetch_encode_g16(&self, g16_proof_id: &str) -> Result<Vec<u8>> {
+    async fn fetch_g16_receipt(&self, g16_proof_id: &str) -> Result<Receipt> {
         let groth16_receipt = self
             .prover
             .get_compressed_receipt(g16_proof_id)
@@ -105,8 +131,11 @@ where
             .context("Failed to fetch g16 receipt")?
             .context("Groth16 receipt missing")?;
 
-        let groth16_receipt: Receipt =
-            bincode::deserialize(&groth16_receipt).context("Failed to deserialize g16 receipt")?;
+        bincode::deserialize(&groth16_receipt).context("Failed to deserialize g16 receipt")
+    }
+
+    async fn fetch_encode_g16(&self, g16_proof_id: &str) -> Result<Vec<u8>> {
+        let groth16_receipt = self.fetch_g16_receipt(g16_proof_id).await?;
 
         let encoded_seal =
             encode_seal(&groth16_receipt).context("Failed to encode g16 receipt seal")?;
@@ -114,6 +143,85 @@ where
         Ok(encoded_seal)
     }
 
+    /// Run the checks the market contract will make on a single fulfillment, without spending gas.
+    ///
+    /// Checks the seal against the requested selector, the journal against the predicate, and
+    /// the client signature (ECDSA, or EIP-1271 with an `eth_call` to the client contract).
+    async fn check_fulfillment(
+        &self,
+        request: &ProofRequest,
+        client_sig: &Bytes,
+        fulfillment: &Fulfillment,
+        groth16_receipt: Option<&Receipt>,
+    ) -> Result<()> {
+        ensure!(
+            request.requirements.predicate.eval(&fulfillment.journal),
+            "Journal does not satisfy the request predicate"
+        );
+
+        ensure!(fulfillment.seal.len() >= 4, "Seal is too short to contain a selector");
+        let seal_selector = FixedBytes::<4>::from_slice(&fulfillment.seal[..4]);
+        let required_selector = request.requirements.selector;
+        if required_selector != UNSPECIFIED_SELECTOR {
+            ensure!(
+                seal_selector == required_selector,
+                "Seal selector {seal_selector} does not match the requested selector {required_selector}"
+            );
+        }
+        ensure!(
+            self.supported_selectors.is_supported(seal_selector),
+            "Seal selector {seal_selector} is not supported by this prover"
+        );
+        if let Some(receipt) = groth16_receipt {
+            receipt
+                .verify_integrity_with_context(&VerifierContext::default())
+                .context("Groth16 receipt does not verify")?;
+            let claim = ReceiptClaim::ok(
+                Digest::from_bytes(fulfillment.imageId.0),
+                fulfillment.journal.to_vec(),
+            );
+            ensure!(
+                receipt.claim()?.digest() == claim.digest(),
+                "Groth16 receipt claim does not match the fulfillment"
+            );
+        }
+
+        let domain = self.market.eip712_domain().await?;
+        if request.is_smart_contract_signed() {
+            let request_hash = request.eip712_signing_hash(&domain.alloy_struct());
+            let client = IERC1271::new(request.client_address(), self.market.instance().provider());
+            let magic_value = client
+                .isValidSignature(request_hash, client_sig.clone())
+                .call()
+                .await
+                .context("Failed to call ERC1271 isValidSignature")?
+                .magicValue;
+            ensure!(
+                magic_value == ERC1271_MAGIC_VALUE,
+                "Invalid ERC1271 signature, contract returned magic value 0x{magic_value:x}"
+            );
+        } else {
+            request
+                .verify_signature(client_sig, domain.verifying_contract, domain.chain_id)
+                .context("Invalid client signature")?;
+        }
+
+        Ok(())
+    }
+
+    async fn set_orders_failure(&self, fulfillments: &[Fulfillment], err: &impl std::fmt::Debug) {
+        for fulfillment in fulfillments.iter() {
+            if let Err(db_err) =
+                self.db.set_order_failure(U256::from(fulfillment.id), format!("{err:?}")).await
+            {
+                tracing::error!(
+                    "Failed to set order failure during proof submission: {:x} {db_err:?}",
+                    fulfillment.id
+                );
+            }
+        }
+    }
+
     pub async fn submit_batch(&self, batch_id: usize, batch: &Batch) -> Result<()> {
         tracing::info!("Submitting batch {batch_id}");
 
@@ -134,7 +242,10 @@ where
         );
 
         // Collect the needed parts for the new merkle root:
-        let batch_seal = self.fetch_encode_g16(groth16_proof_id).await?;
+        let batch_seal = match &aggregation_state.groth16_seal {
+            Some(seal) => seal.to_vec(),
+            None => self.fetch_encode_g16(groth16_proof_id).await?,
+        };
         let batch_root = risc0_aggregation::merkle_root(&aggregation_state.claim_digests);
         let root = B256::from_slice(batch_root.as_bytes());
 
@@ -160,12 +271,33 @@ where
         let assessor_journal =
             AssessorJournal::abi_decode(&assessor_receipt.journal.bytes, true)
                 .context("Failed to decode assessor journal for {assessor_proof_id}")?;
+        ensure!(
+            assessor_journal.prover == self.prover_address,
+            "Assessor journal prover {} does not match submitter address {}",
+            assessor_journal.prover,
+            self.prover_address
+        );
+
+        // Reconstruct the full assessor claim from the receipt journal, and check it against the
+        // claim that was committed into the aggregation set.
+        let assessor_claim =
+            ReceiptClaim::ok(self.assessor_img_id, assessor_receipt.journal.bytes.clone());
+        ensure!(
+            assessor_claim.digest() == assessor_claim_digest,
+            "Assessor claim reconstructed from journal does not match receipt for {assessor_proof_id}"
+        );
 
         let inclusion_params =
             SetInclusionReceiptVerifierParameters { image_id: self.set_builder_img_id };
 
         let mut fulfillments = vec![];
         let mut order_prices = HashMap::new();
+        // Orders received from the order stream, whose fulfillments are published back to it.
+        let mut offchain_orders = HashSet::new();
+        // Orders that failed the local pre-submission checks, and the orders that passed along with
+        // the status that returns them to the aggregator.
+        let mut rejected = vec![];
+        let mut requeue = vec![];
 
         for order_id in batch.orders.iter() {
             tracing::info!("Submitting order {order_id:x}");
@@ -175,6 +307,16 @@ where
                     self.db.get_submission_order(*order_id).await.context(
                         "Failed to get order from DB for submission, order NOT finalized",
                     )?;
+                let order = self
+                    .db
+                    .get_order(*order_id)
+                    .await
+                    .context("Failed to get order from DB for submission")?
+                    .context("Order missing from DB")?;
+                let client_sig = order.client_sig;
+                if order.offchain {
+                    offchain_orders.insert(*order_id);
+                }
 
                 order_prices.insert(order_id, lock_price);
 
@@ -185,63 +327,139 @@ where
                     .context("Failed to get order journal from prover")?
                     .context("Order proof Journal missing")?;
 
-                let seal = if is_groth16_selector(order_request.requirements.selector) {
-                    let compressed_proof_id =
-                        self.db.get_order_compressed_proof_id(*order_id).await.context(
-                            "Failed to get order compressed proof ID from DB for submission",
-                        )?;
-                    self.fetch_encode_g16(&compressed_proof_id)
-                        .await
-                        .context("Failed to fetch and encode g16 proof")?
-                } else {
-                    // NOTE: We assume here that the order execution ended with exit code 0.
-                    let order_claim = ReceiptClaim::ok(
-                        order_img_id.0,
-                        MaybePruned::Pruned(order_journal.digest()),
-                    );
-                    let order_claim_index = aggregation_state
-                        .claim_digests
-                        .iter()
-                        .position(|claim| *claim == order_claim.digest())
-                        .ok_or(anyhow!(
-                            "Failed to find order claim {order_claim:x?} in aggregated claims"
-                        ))?;
-                    let order_path = risc0_aggregation::merkle_path(
-                        &aggregation_state.claim_digests,
-                        order_claim_index,
-                    );
-                    tracing::debug!(
-                        "Merkle path for order {order_id:x} : {:x?} : {order_path:x?}",
-                        order_claim.digest()
-                    );
-                    let set_inclusion_receipt = SetInclusionReceipt::from_path_with_verifier_params(
-                        order_claim,
-                        order_path,
-                        inclusion_params.digest(),
-                    );
-                    set_inclusion_receipt.abi_encode_seal().context("Failed to encode seal")?
-                };
+                let (seal, groth16_receipt) =
+                    if is_groth16_selector(order_request.requirements.selector) {
+                        let compressed_proof_id =
+                            self.db.get_order_compressed_proof_id(*order_id).await.context(
+                                "Failed to get order compressed proof ID from DB for submission",
+                            )?;
+                        let receipt = self
+                            .fetch_g16_receipt(&compressed_proof_id)
+                            .await
+                            .context("Failed to fetch g16 proof")?;
+                        let seal =
+                            encode_seal(&receipt).context("Failed to encode g16 receipt seal")?;
+                        (seal, Some(receipt))
+                    } else {
+                        // NOTE: We assume here that the order execution ended with exit code 0.
+                        let order_claim = ReceiptClaim::ok(
+                            order_img_id.0,
+                            MaybePruned::Pruned(order_journal.digest()),
+                        );
+                        let order_claim_index = aggregation_state
+                            .claim_digests
+                            .iter()
+                            .position(|claim| *claim == order_claim.digest())
+                            .ok_or(anyhow!(
+                                "Failed to find order claim {order_claim:x?} in aggregated claims"
+                            ))?;
+                        let order_path = risc0_aggregation::merkle_path(
+                            &aggregation_state.claim_digests,
+                            order_claim_index,
+                        );
+                        tracing::debug!(
+                            "Merkle path for order {order_id:x} : {:x?} : {order_path:x?}",
+                            order_claim.digest()
+                        );
+                        verify_inclusion(order_claim.digest(), &order_path, batch_root)
+                            .context("Order set inclusion receipt does not verify")?;
+                        let set_inclusion_receipt =
+                            SetInclusionReceipt::from_path_with_verifier_params(
+                                order_claim,
+                                order_path,
+                                inclusion_params.digest(),
+                            );
+                        let seal = set_inclusion_receipt
+                            .abi_encode_seal()
+                            .context("Failed to encode seal")?;
+                        (seal, None)
+                    };
 
                 tracing::debug!("Seal for order {order_id:x} : {}", hex::encode(seal.clone()));
 
                 let request_digest = order_request
                     .eip712_signing_hash(&self.market.eip712_domain().await?.alloy_struct());
-                fulfillments.push(Fulfillment {
+                let fulfillment = Fulfillment {
                     id: *order_id,
                     requestDigest: request_digest,
                     imageId: order_img_id,
                     journal: order_journal.into(),
                     seal: seal.into(),
-                });
-                anyhow::Ok(())
+                };
+                anyhow::Ok((order_request, client_sig, fulfillment, groth16_receipt))
             };
 
-            if let Err(err) = res.await {
-                tracing::error!("Failed to submit {order_id:x}: {err}");
-                if let Err(db_err) = self.db.set_order_failure(*order_id, err.to_string()).await {
+            let (order_request, client_sig, fulfillment, groth16_receipt) = match res.await {
+                Ok(res) => res,
+                Err(err) => {
+                    tracing::error!("Failed to submit {order_id:x}: {err}");
+                    if let Err(db_err) = self.db.set_order_failure(*order_id, err.to_string()).await
+                    {
+                        tracing::error!("Failed to set order failure during proof submission: {order_id:x} {db_err:?}");
+                    }
+                    continue;
+                }
+            };
+
+            if let Err(err) = self
+                .check_fulfillment(
+                    &order_request,
+                    &client_sig,
+                    &fulfillment,
+                    groth16_receipt.as_ref(),
+                )
+                .await
+            {
+                tracing::warn!("Order {order_id:x} failed pre-submission checks: {err:?}");
+                if let Err(db_err) = self.db.set_order_failure(*order_id, format!("{err:?}")).await
+                {
                     tracing::error!("Failed to set order failure during proof submission: {order_id:x} {db_err:?}");
                 }
+                rejected.push(*order_id);
+                continue;
             }
+
+            let status = match groth16_receipt {
+                Some(_) => OrderStatus::SkipAggregation,
+                None => OrderStatus::PendingAgg,
+            };
+            requeue.push((*order_id, status));
+            fulfillments.push(fulfillment);
+        }
+
+        // The assessor commits to every order in the batch, so the remaining orders can't be
+        // delivered without the rejected ones. Return them to the aggregator to be included in a
+        // new batch, reusing their existing proofs.
+        if !rejected.is_empty() {
+            for (order_id, status) in requeue {
+                if let Err(db_err) = self.db.set_order_status(order_id, status).await {
+                    tracing::error!(
+                        "Failed to return order {order_id:x} to aggregation: {db_err:?}"
+                    );
+                }
+            }
+            return Err(PreflightRejected(rejected).into());
+        }
+
+        ensure!(!fulfillments.is_empty(), "No orders in batch {batch_id} could be prepared");
+
+        // Recompute the root committed to by the assessor from the fulfillments we are about to
+        // send, as is done by the market contract. A mismatch (e.g. an order was dropped above)
+        // would revert on-chain.
+        let fulfillments_root = process_tree(
+            fulfillments
+                .iter()
+                .enumerate()
+                .map(|(index, fill)| assessor_commitment_digest(index, fill))
+                .collect(),
+        );
+        if <[u8; 32]>::from(fulfillments_root) != assessor_journal.root.0 {
+            let err = anyhow!(
+                "Assessor root {} does not match the fulfillments root {fulfillments_root}",
+                assessor_journal.root
+            );
+            self.set_orders_failure(&fulfillments, &err).await;
+            return Err(err);
         }
 
         let assessor_claim_index = aggregation_state
@@ -255,13 +473,11 @@ where
             "Merkle path for assessor : {:x?} : {assessor_path:x?}",
             assessor_claim_digest
         );
+        verify_inclusion(assessor_claim_digest, &assessor_path, batch_root)
+            .context("Assessor set inclusion receipt does not verify")?;
 
         let assessor_seal = SetInclusionReceipt::from_path_with_verifier_params(
-            // TODO: Set inclusion proofs, when ABI encoded, currently don't contain anything
-            // derived from the claim. So instead of constructing the journal, we simply use the
-            // zero digest. We should either plumb through the data for the assessor journal, or we
-            // should make an explicit way to encode an inclusion proof without the claim.
-            ReceiptClaim::ok(ASSESSOR_GUEST_ID, MaybePruned::Pruned(Digest::ZERO)),
+            assessor_claim,
             assessor_path,
             inclusion_params.digest(),
         );
@@ -278,35 +494,7 @@ where
             prover: self.prover_address,
             callbacks: assessor_journal.callbacks,
         };
-        if single_txn_fulfill {
-            if let Err(err) = self
-                .market
-                .submit_merkle_and_fulfill(
-                    self.set_verifier_addr,
-                    root,
-                    batch_seal.into(),
-                    fulfillments.clone(),
-                    assessor_receipt,
-                )
-                .await
-            {
-                tracing::error!("Failed to submit proofs for batch {batch_id}: {err:?}");
-
-                for fulfillment in fulfillments.iter() {
-                    if let Err(db_err) = self
-                        .db
-                        .set_order_failure(U256::from(fulfillment.id), format!("{err:?}"))
-                        .await
-                    {
-                        tracing::error!(
-                            "Failed to set order failure during proof submission: {:x} {db_err:?}",
-                            fulfillment.id
-                        );
-                    }
-                }
-                bail!("transaction to fulfill batch failed");
-            }
-        } else {
+        if !single_txn_fulfill {
             let contains_root = match self.set_verifier.contains_root(root).await {
                 Ok(res) => res,
                 Err(err) => {
@@ -317,34 +505,91 @@ where
             if !contains_root {
                 tracing::info!("Submitting app merkle root: {root}");
                 self.set_verifier
-                    .submit_merkle_root(root, batch_seal.into())
+                    .submit_merkle_root(root, batch_seal.clone().into())
                     .await
                     .context("Failed to submit app merkle_root")?;
             } else {
                 tracing::info!("Contract already contains root, skipping to fulfillment");
             }
+        }
 
-            if let Err(err) =
-                self.market.fulfill_batch(fulfillments.clone(), assessor_receipt).await
+        // Simulate the delivery with an eth_call before sending the fulfillment transaction. In
+        // single transaction mode the root is not yet known to the set verifier, so the combined
+        // call is simulated instead.
+        let simulation = if single_txn_fulfill {
+            self.market
+                .simulate_submit_merkle_and_fulfill(
+                    self.set_verifier_addr,
+                    root,
+                    batch_seal.clone().into(),
+                    &fulfillments,
+                    &assessor_receipt,
+                )
+                .await
+        } else {
+            self.market.verify_batch_delivery(&fulfillments, &assessor_receipt).await
+        };
+        match simulation {
+            Ok(()) => {}
+            Err(MarketError::SimulationReverted(err)) => {
+                tracing::error!("Delivery of batch {batch_id} would revert: {err:?}");
+                self.set_orders_failure(&fulfillments, &err).await;
+                return Err(
+                    PreflightRejected(fulfillments.iter().map(|fill| fill.id).collect()).into()
+                );
+            }
+            Err(err) => return Err(err).context("Failed to simulate batch delivery"),
+        }
+
+        if single_txn_fulfill {
+            if let Err(err) = self
+                .market
+                .submit_merkle_and_fulfill(
+                    self.set_verifier_addr,
+                    root,
+                    batch_seal.into(),
+                    fulfillments.clone(),
+                    assessor_receipt,
+                )
+                .await
             {
-                tracing::error!("Failed to submit proofs: {err:?} for batch {batch_id}");
-                for fulfillment in fulfillments.iter() {
-                    if let Err(db_err) = self
-                        .db
-                        .set_order_failure(U256::from(fulfillment.id), format!("{err:?}"))
-                        .await
-                    {
-                        tracing::error!(
-                            "Failed to set order failure during proof submission: {:x} {db_err:?}",
-                            fulfillment.id
-                        );
-                    }
-                }
+                tracing::error!("Failed to submit proofs for batch {batch_id}: {err:?}");
+                self.set_orders_failure(&fulfillments, &err).await;
                 bail!("transaction to fulfill batch failed");
             }
+        } else if let Err(err) =
+            self.market.fulfill_batch(fulfillments.clone(), assessor_receipt).await
+        {
+            tracing::error!("Failed to submit proofs: {err:?} for batch {batch_id}");
+            self.set_orders_failure(&fulfillments, &err).await;
+            bail!("transaction to fulfill batch failed");
         }
 
+        let archive_dir = {
+            let config = self.config.lock_all().context("Failed to read config")?;
+            config.market.fulfillment_archive_dir.clone()
+        };
         for fulfillment in fulfillments.iter() {
+            let data = FulfillmentData {
+                journal: fulfillment.journal.clone(),
+                seal: fulfillment.seal.clone(),
+            };
+            if let Some(dir) = &archive_dir {
+                if let Err(err) = write_archive_entry(dir, U256::from(fulfillment.id), &data).await
+                {
+                    tracing::warn!("Failed to archive fulfillment {:x}: {err:?}", fulfillment.id);
+                }
+            }
+            if let Some(order_stream) = &self.order_stream {
+                if offchain_orders.contains(&fulfillment.id) {
+                    if let Err(err) = order_stream.submit_fulfillment(fulfillment.id, &data).await {
+                        tracing::warn!(
+                            "Failed to publish fulfillment {:x} to the order stream: {err:?}",
+                            fulfillment.id
+                        );
+                    }
+                }
+            }
             if let Err(db_err) = self.db.set_order_complete(U256::from(fulfillment.id)).await {
                 tracing::error!(
                     "Failed to set order complete during proof submission: {:x} {db_err:?}",
@@ -396,6 +641,20 @@ where
                     );
                     return Ok(true);
                 }
+                Err(err) if err.is::<PreflightRejected>() => {
+                    // Rejected orders have already been marked as failed, and the remaining
+                    // orders returned to aggregation. Retrying this batch would not succeed.
+                    tracing::warn!("Batch {batch_id} failed pre-submission checks: {err}");
+                    if let Err(db_err) =
+                        self.db.set_batch_failure(batch_id, format!("{err:?}")).await
+                    {
+                        tracing::error!(
+                            "Failed to set batch failure in db: {batch_id} - {db_err:?}"
+                        );
+                        return Err(SupervisorErr::Recover(db_err.into()));
+                    }
+                    return Ok(false);
+                }
                 Err(err) => {
                     tracing::warn!(
                         "Batch submission attempt {}/{} failed",
@@ -415,6 +674,29 @@ where
     }
 }
 
+/// Check that the Merkle path resolves the given claim digest to the batch root.
+fn verify_inclusion(claim_digest: Digest, path: &[Digest], root: Digest) -> Result<()> {
+    let path_root = risc0_aggregation::merkle_path_root(claim_digest, path);
+    ensure!(
+        path_root == root,
+        "Merkle path for claim {claim_digest} resolves to {path_root}, expected batch root {root}"
+    );
+    Ok(())
+}
+
+/// Computes the leaf committed to by the assessor for the fulfillment at the given index.
+fn assessor_commitment_digest(index: usize, fill: &Fulfillment) -> Digest {
+    let claim_digest =
+        ReceiptClaim::ok(Digest::from_bytes(fill.imageId.0), fill.journal.to_vec()).digest();
+    let commitment = AssessorCommitment {
+        index: U256::from(index),
+        id: fill.id,
+        requestDigest: fill.requestDigest,
+        claimDigest: <[u8; 32]>::from(claim_digest).into(),
+    };
+    Digest::from_bytes(*commitment.eip712_hash_struct())
+}
+
 impl<P> RetryTask for Submitter<P>
 where
     P: Provider<Ethereum> + WalletProvider + 'static + Clone,
@@ -646,6 +928,9 @@ mod tests {
             client_sig: client_sig.into(),
             lock_price: Some(U256::ZERO),
             error_msg: None,
+            offchain: false,
+            callback_gas: None,
+            assumption_ids: vec![],
         };
         let order_id = U256::from(order.request.id);
         db.add_order(order_id, order.clone()).await.unwrap();
@@ -659,10 +944,12 @@ mod tests {
             start_time: Utc::now(),
             deadline: Some(order.request.offer.biddingStart + order.request.offer.timeout as u64),
             error_msg: None,
+            checkpoint: None,
             aggregation_state: Some(AggregationState {
                 guest_state: batch_guest_state,
                 proof_id: aggregation_proof.id,
                 groth16_proof_id: Some(batch_g16),
+                groth16_seal: None,
                 claim_digests: vec![
                     echo_receipt.claim().unwrap().digest(),
                     assessor_receipt.claim().unwrap().digest(),
@@ -681,6 +968,8 @@ mod tests {
             set_verifier,
             market_address,
             set_builder_id,
+            assessor_id,
+            None,
         )
         .unwrap();
 
@@ -713,6 +1002,83 @@ mod tests {
         process_next_batch(submitter, db, batch_id).await;
     }
 
+    #[tokio::test]
+    #[traced_test]
+    async fn submit_batch_preflight_rejected() {
+        let config = ConfigLock::default();
+        let (_anvil, mut submitter, db, batch_id) = build_submitter_and_batch(config).await;
+        // Without the set builder image ID, the set inclusion seal selector is not supported.
+        submitter.supported_selectors = SupportedSelectors::default();
+
+        let order_id = db.get_batch(batch_id).await.unwrap().orders[0];
+        assert!(!submitter.process_next_batch().await.unwrap());
+        assert!(logs_contain("failed pre-submission checks"));
+        assert!(!logs_contain("Batch submission attempt"));
+
+        let batch = db.get_batch(batch_id).await.unwrap();
+        assert_eq!(batch.status, BatchStatus::Failed);
+        let order = db.get_order(order_id).await.unwrap().unwrap();
+        assert_eq!(order.status, OrderStatus::Failed);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn submit_batch_assessor_claim_mismatch() {
+        let config = ConfigLock::default();
+        let (_anvil, mut submitter, db, batch_id) = build_submitter_and_batch(config).await;
+        // The assessor receipt does not prove a claim of this image ID.
+        submitter.assessor_img_id = Digest::ZERO;
+
+        let batch = db.get_batch(batch_id).await.unwrap();
+        let err = submitter.submit_batch(batch_id, &batch).await.unwrap_err();
+        assert!(err.to_string().contains("Assessor claim reconstructed from journal"));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn submit_batch_claim_digests_mismatch() {
+        let config = ConfigLock::default();
+        let (_anvil, submitter, db, batch_id) = build_submitter_and_batch(config).await;
+
+        // Claim digests that are not the ones aggregated into the guest state.
+        let mut batch = db.get_batch(batch_id).await.unwrap();
+        batch.aggregation_state.as_mut().unwrap().claim_digests.reverse();
+        let err = submitter.submit_batch(batch_id, &batch).await.unwrap_err();
+        assert!(err.to_string().contains("inconsistent with claim digests"));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn submit_batch_assessor_root_mismatch() {
+        let config = ConfigLock::default();
+        let (_anvil, submitter, db, batch_id) = build_submitter_and_batch(config).await;
+
+        // The aggregated claims are consistent, but the fulfillments sent differ from the ones
+        // committed to by the assessor.
+        let mut batch = db.get_batch(batch_id).await.unwrap();
+        let order_id = batch.orders[0];
+        batch.orders.push(order_id);
+        let err = submitter.submit_batch(batch_id, &batch).await.unwrap_err();
+        assert!(err.to_string().contains("does not match the fulfillments root"));
+
+        let order = db.get_order(order_id).await.unwrap().unwrap();
+        assert_eq!(order.status, OrderStatus::Failed);
+    }
+
+    #[test]
+    fn verify_inclusion_paths() {
+        let claims: Vec<Digest> = (0u8..5).map(|i| Digest::from_bytes([i; 32])).collect();
+        let root = risc0_aggregation::merkle_root(&claims);
+        for (i, claim) in claims.iter().enumerate() {
+            let path = risc0_aggregation::merkle_path(&claims, i);
+            verify_inclusion(*claim, &path, root).unwrap();
+        }
+
+        let path = risc0_aggregation::merkle_path(&claims, 0);
+        assert!(verify_inclusion(claims[1], &path, root).is_err());
+        assert!(verify_inclusion(claims[0], &path, Digest::ZERO).is_err());
+    }
+
     #[tokio::test]
     #[traced_test]
     async fn submit_batch_retry_max_attempts() {
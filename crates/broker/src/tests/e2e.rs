@@ -101,11 +101,13 @@ fn broker_args(
     Args {
         db_url: "sqlite::memory:".into(),
         config_file,
-        boundless_market_address,
-        set_verifier_address,
+        boundless_market_address: Some(boundless_market_address),
+        set_verifier_address: Some(set_verifier_address),
+        deployments_file: None,
         rpc_url,
         order_stream_url: None,
-        private_key,
+        deployment_order_stream: false,
+        signer: private_key.into(),
         bento_api_url: None,
         bonsai_api_key,
         bonsai_api_url,
@@ -165,9 +167,9 @@ async fn simple_e2e() {
         ctx.boundless_market_address,
         ctx.set_verifier_address,
         anvil.endpoint_url(),
-        ctx.prover_signer,
+        ctx.prover_signer.clone(),
     );
-    let broker = Broker::new(args, ctx.prover_provider).await.unwrap();
+    let broker = Broker::new(args, ctx.prover_provider, ctx.prover_signer.into()).await.unwrap();
 
     // Provide URL for ECHO ELF
     let storage = MockStorageProvider::start();
@@ -243,9 +245,10 @@ async fn simple_e2e_with_callback() {
         ctx.boundless_market_address,
         ctx.set_verifier_address,
         anvil.endpoint_url(),
-        ctx.prover_signer,
+        ctx.prover_signer.clone(),
     );
-    let broker = Broker::new(args, ctx.prover_provider.clone()).await.unwrap();
+    let broker =
+        Broker::new(args, ctx.prover_provider.clone(), ctx.prover_signer.into()).await.unwrap();
 
     // Provide URL for ECHO ELF
     let storage = MockStorageProvider::start();
@@ -324,9 +327,9 @@ async fn e2e_with_selector() {
         ctx.boundless_market_address,
         ctx.set_verifier_address,
         anvil.endpoint_url(),
-        ctx.prover_signer,
+        ctx.prover_signer.clone(),
     );
-    let broker = Broker::new(args, ctx.prover_provider).await.unwrap();
+    let broker = Broker::new(args, ctx.prover_provider, ctx.prover_signer.into()).await.unwrap();
 
     // Provide URL for ECHO ELF
     let storage = MockStorageProvider::start();
@@ -393,9 +396,9 @@ async fn e2e_with_multiple_requests() {
         ctx.boundless_market_address,
         ctx.set_verifier_address,
         anvil.endpoint_url(),
-        ctx.prover_signer,
+        ctx.prover_signer.clone(),
     );
-    let broker = Broker::new(args, ctx.prover_provider).await.unwrap();
+    let broker = Broker::new(args, ctx.prover_provider, ctx.prover_signer.into()).await.unwrap();
 
     // Provide URL for ECHO ELF
     let storage = MockStorageProvider::start();
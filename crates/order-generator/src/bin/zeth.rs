@@ -11,13 +11,13 @@ use alloy::{
         Address, U256,
     },
     providers::{Provider, ProviderBuilder},
-    signers::local::PrivateKeySigner,
 };
 use anyhow::{anyhow, bail, Result};
 use boundless_market::{
-    balance_alerts_layer::BalanceAlertConfig,
+    alerts::{BalanceSource, BalanceWatch, TracingSink},
     client::{Client, ClientBuilder},
     contracts::{Input, Offer, Predicate, ProofRequest, Requirements},
+    signer::SignerArgs,
     storage::{StorageProvider, StorageProviderConfig},
 };
 use clap::Parser;
@@ -51,9 +51,9 @@ struct Args {
     /// Storage provider to use
     #[clap(flatten)]
     storage_config: Option<StorageProviderConfig>,
-    /// Private key used to interact with the BoundlessMarket contract.
-    #[clap(long, env)]
-    private_key: PrivateKeySigner,
+    /// Signer used to interact with the BoundlessMarket contract.
+    #[clap(flatten)]
+    signer: SignerArgs,
     /// Address of the SetVerifier contract.
     #[clap(short, long, env)]
     set_verifier_address: Address,
@@ -127,13 +127,8 @@ async fn main() -> Result<()> {
 
     let args = Args::parse();
 
-    let wallet = EthereumWallet::from(args.private_key.clone());
-
-    let balance_alerts = BalanceAlertConfig {
-        watch_address: wallet.default_signer().address(),
-        warn_threshold: args.warn_balance_below,
-        error_threshold: args.error_balance_below,
-    };
+    let signer = args.signer.signer().await?;
+    let wallet = EthereumWallet::from(signer.clone());
 
     let provider = ProviderBuilder::new().wallet(wallet).on_http(args.zeth_rpc_url.clone());
     let rpc = Some(args.zeth_rpc_url.to_string());
@@ -147,11 +142,20 @@ async fn main() -> Result<()> {
         .with_order_stream_url(args.order_stream_url)
         .with_storage_provider_config(args.storage_config.clone())
         .await?
-        .with_private_key(args.private_key)
-        .with_balance_alerts(balance_alerts)
+        .with_signer(signer)
         .build()
         .await?;
 
+    let balance_monitor = boundless_client
+        .balance_monitor()
+        .with_watch(
+            BalanceWatch::new(BalanceSource::Native, boundless_client.caller())
+                .with_warn_below(args.warn_balance_below)
+                .with_error_below(args.error_balance_below),
+        )
+        .with_sink(TracingSink);
+    tokio::spawn(async move { balance_monitor.run().await });
+
     // Upload the ZETH_GUESTS_RETH_ETHEREUM ELF to the storage provider so that it can be fetched by the market.
     let image_url = boundless_client.upload_image(ZETH_GUESTS_RETH_ETHEREUM_ELF).await?;
     tracing::info!("Uploaded image to {}", image_url);
@@ -8,23 +8,25 @@ use std::{
 };
 
 use alloy::{
-    network::EthereumWallet,
+    network::Ethereum,
     primitives::{
         utils::{format_units, parse_ether},
         Address, U256,
     },
     providers::Provider,
-    signers::local::PrivateKeySigner,
 };
 use anyhow::{bail, Result};
 use boundless_market::{
-    balance_alerts_layer::BalanceAlertConfig,
-    client::ClientBuilder,
+    alerts::{BalanceSource, BalanceWatch, TracingSink},
+    client::{Client, ClientBuilder},
+    clock::Clock,
     contracts::{Input, Offer, Predicate, ProofRequest, Requirements},
     input::InputBuilder,
+    request_template::RequestTemplate,
+    signer::SignerArgs,
     storage::{
         storage_provider_from_config, storage_provider_from_env, BuiltinStorageProvider,
-        StorageProviderConfig,
+        StorageProvider, StorageProviderConfig,
     },
 };
 use clap::{Args, Parser};
@@ -43,9 +45,9 @@ struct MainArgs {
     /// If set, the order-generator will submit requests off-chain.
     #[clap(short, long, env)]
     order_stream_url: Option<Url>,
-    /// Private key used to sign and submit requests.
-    #[clap(long, env)]
-    private_key: PrivateKeySigner,
+    /// Signer used to sign and submit requests.
+    #[clap(flatten)]
+    signer: SignerArgs,
     /// Address of the SetVerifier contract.
     #[clap(short, long, env)]
     set_verifier_address: Address,
@@ -99,6 +101,11 @@ struct MainArgs {
     /// Use risc0_zkvm::serde to encode the input as a `Vec<u8>`
     #[clap(short, long)]
     encode_input: bool,
+    /// Request template to build the requests from, given as a path to a YAML, JSON or TOML file.
+    ///
+    /// If set, the guest, input and pricing are taken from the template instead of the arguments.
+    #[clap(long, conflicts_with_all = ["elf", "input", "input_file", "encode_input"])]
+    template: Option<PathBuf>,
     /// Balance threshold at which to log a warning.
     #[clap(long, value_parser = parse_ether, default_value = "1")]
     warn_balance_below: Option<U256>,
@@ -143,12 +150,7 @@ async fn main() -> Result<()> {
 }
 
 async fn run(args: &MainArgs) -> Result<()> {
-    let wallet = EthereumWallet::from(args.private_key.clone());
-    let balance_alerts = BalanceAlertConfig {
-        watch_address: wallet.default_signer().address(),
-        warn_threshold: args.warn_balance_below,
-        error_threshold: args.error_balance_below,
-    };
+    let signer = args.signer.signer().await?;
 
     let storage_provider = match &args.storage_config {
         Some(storage_config) => storage_provider_from_config(storage_config).await?,
@@ -161,12 +163,41 @@ async fn run(args: &MainArgs) -> Result<()> {
         .with_boundless_market_address(args.boundless_market_address)
         .with_set_verifier_address(args.set_verifier_address)
         .with_order_stream_url(args.order_stream_url.clone())
-        .with_private_key(args.private_key.clone())
+        .with_signer(signer)
         .with_bidding_start_delay(args.bidding_start_delay)
-        .with_balance_alerts(balance_alerts)
         .build()
         .await?;
 
+    let balance_monitor = boundless_client
+        .balance_monitor()
+        .with_watch(
+            BalanceWatch::new(BalanceSource::Native, boundless_client.caller())
+                .with_warn_below(args.warn_balance_below)
+                .with_error_below(args.error_balance_below),
+        )
+        .with_sink(TracingSink);
+    tokio::spawn(async move { balance_monitor.run().await });
+
+    if let Some(path) = &args.template {
+        let template = RequestTemplate::load(path)?;
+        // Build once, as building uploads the image and input and may execute the guest. The
+        // request ID is left to the client, so only the bidding start is refreshed on each submit.
+        let built = template.build_request(&boundless_client).await?;
+        let mut i = 0u64;
+        while args.count.is_none_or(|count| i < count) {
+            let mut request = built.clone();
+            if let Some(delay) = template.offer.bidding_start_delay {
+                request.offer.biddingStart = boundless_client.clock.now() + delay.0.as_secs();
+            }
+            tracing::info!("Request: {:?}", request);
+            submit(args, &boundless_client, &request).await?;
+
+            i += 1;
+            tokio::time::sleep(Duration::from_secs(args.interval)).await;
+        }
+        return Ok(());
+    }
+
     let elf = match &args.elf {
         Some(path) => std::fs::read(path)?,
         None => {
@@ -256,25 +287,7 @@ async fn run(args: &MainArgs) -> Result<()> {
             .build()?;
 
         tracing::info!("Request: {:?}", request);
-
-        let submit_offchain = args.order_stream_url.is_some();
-        let (request_id, _) = if submit_offchain {
-            boundless_client.submit_request_offchain(&request).await?
-        } else {
-            boundless_client.submit_request(&request).await?
-        };
-
-        if submit_offchain {
-            tracing::info!(
-                "Request 0x{request_id:x} submitted offchain to {}",
-                args.order_stream_url.clone().unwrap()
-            );
-        } else {
-            tracing::info!(
-                "Request 0x{request_id:x} submitted onchain to {}",
-                args.boundless_market_address
-            );
-        }
+        submit(args, &boundless_client, &request).await?;
 
         i += 1;
         tokio::time::sleep(Duration::from_secs(args.interval)).await;
@@ -283,6 +296,32 @@ async fn run(args: &MainArgs) -> Result<()> {
     Ok(())
 }
 
+async fn submit<P, S>(args: &MainArgs, client: &Client<P, S>, request: &ProofRequest) -> Result<()>
+where
+    P: Provider<Ethereum> + 'static + Clone,
+    S: StorageProvider,
+{
+    let submit_offchain = args.order_stream_url.is_some();
+    let (request_id, _) = if submit_offchain {
+        client.submit_request_offchain(request).await?
+    } else {
+        client.submit_request(request).await?
+    };
+
+    if submit_offchain {
+        tracing::info!(
+            "Request 0x{request_id:x} submitted offchain to {}",
+            args.order_stream_url.clone().unwrap()
+        );
+    } else {
+        tracing::info!(
+            "Request 0x{request_id:x} submitted onchain to {}",
+            args.boundless_market_address
+        );
+    }
+    Ok(())
+}
+
 async fn fetch_http(url: &Url) -> Result<Vec<u8>> {
     let response = reqwest::get(url.as_str()).await?;
     let status = response.status();
@@ -324,7 +363,7 @@ mod tests {
             rpc_url: anvil.endpoint_url(),
             order_stream_url: None,
             storage_config: Some(StorageProviderConfig::dev_mode()),
-            private_key: ctx.customer_signer,
+            signer: ctx.customer_signer.into(),
             set_verifier_address: ctx.set_verifier_address,
             boundless_market_address: ctx.boundless_market_address,
             interval: 1,
@@ -339,6 +378,7 @@ mod tests {
             elf: None,
             input: OrderInput { input: None, input_file: None },
             encode_input: false,
+            template: None,
             warn_balance_below: None,
             error_balance_below: None,
         };
@@ -0,0 +1 @@
+ALTER TABLE orders ADD COLUMN cancelled_at TIMESTAMPTZ;
@@ -0,0 +1,5 @@
+CREATE TABLE fulfillments (
+    request_id TEXT PRIMARY KEY,
+    fulfillment_data JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
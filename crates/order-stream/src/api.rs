@@ -5,9 +5,15 @@
 use alloy::primitives::Address;
 use anyhow::Context;
 use axum::extract::{Json, Path, Query, State};
-use boundless_market::order_stream_client::{
-    ErrMsg, Nonce, OrderData, SubmitOrderRes, AUTH_GET_NONCE, HEALTH_CHECK, ORDER_LIST_PATH,
-    ORDER_SUBMISSION_PATH,
+use boundless_market::{
+    contracts::IBoundlessMarket,
+    fulfillments::{verify_fulfillment, FulfillmentData},
+    order_stream_client::{
+        CancelOrderMsg, ErrMsg, FulfillmentSubmission, Nonce, OrderData, SubmitOrderRes,
+        SubmitOrdersRes, AUTH_GET_NONCE, FULFILLMENT_PATH, FULFILLMENT_SUBMISSION_PATH,
+        HEALTH_CHECK, ORDER_BATCH_SUBMISSION_PATH, ORDER_CANCEL_PATH, ORDER_LIST_PATH,
+        ORDER_SUBMISSION_PATH,
+    },
 };
 use serde::Deserialize;
 use std::sync::Arc;
@@ -41,6 +47,180 @@ pub(crate) async fn submit_order(
     Ok(Json(SubmitOrderRes { status: "success".into(), request_id: order_req_id }))
 }
 
+/// Maximum number of orders in a batch submission
+pub(crate) const MAX_BATCH_ORDERS: usize = 100;
+
+#[utoipa::path(
+    post,
+    path = ORDER_BATCH_SUBMISSION_PATH,
+    request_body = Vec<Order>,
+    responses(
+        (status = 200, description = "Result of each order submission", body = Vec<SubmitOrdersRes>),
+        (status = 400, description = "Too many orders", body = ErrMsg),
+        (status = 500, description = "Internal error", body = ErrMsg)
+    )
+)]
+/// Submit a batch of orders to the market order-stream
+///
+/// Each order is validated and added independently, and the result of each is returned in order.
+pub(crate) async fn submit_orders(
+    State(state): State<Arc<AppState>>,
+    Json(orders): Json<Vec<Order>>,
+) -> Result<Json<Vec<SubmitOrdersRes>>, AppError> {
+    if orders.len() > MAX_BATCH_ORDERS {
+        return Err(AppError::TooManyOrders(orders.len()));
+    }
+
+    let mut results = Vec::with_capacity(orders.len());
+    for order in orders {
+        let request_id = order.request.id;
+        let error = match order.validate(state.config.market_address, state.chain_id) {
+            Ok(()) => match state.db.add_order(order).await {
+                Ok(order_id) => {
+                    tracing::debug!("Order 0x{request_id:x} - [{order_id}] submitted");
+                    None
+                }
+                Err(err) => {
+                    tracing::error!("Failed to add order 0x{request_id:x} to db: {err:?}");
+                    Some("failed to add order to db".to_string())
+                }
+            },
+            Err(err) => Some(AppError::from(err).to_string()),
+        };
+        results.push(SubmitOrdersRes { request_id, error });
+    }
+    Ok(Json(results))
+}
+
+#[utoipa::path(
+    post,
+    path = ORDER_CANCEL_PATH,
+    request_body = CancelOrderMsg,
+    responses(
+        (status = 200, description = "Order cancellation response", body = SubmitOrderRes),
+        (status = 400, description = "Invalid cancellation", body = ErrMsg),
+        (status = 404, description = "No open order found", body = ErrMsg),
+        (status = 409, description = "Order already locked", body = ErrMsg),
+        (status = 500, description = "Internal error", body = ErrMsg)
+    )
+)]
+/// Cancel an open order on the market order-stream
+///
+/// The cancellation must be signed by the client of the request. Connected brokers are notified
+/// of the cancellation.
+pub(crate) async fn cancel_order(
+    State(state): State<Arc<AppState>>,
+    Json(cancellation): Json<CancelOrderMsg>,
+) -> Result<Json<SubmitOrderRes>, AppError> {
+    cancellation.verify(state.config.market_address, state.chain_id)?;
+    let request_id = cancellation.request_id;
+
+    let boundless_market =
+        IBoundlessMarket::new(state.config.market_address, state.rpc_provider.clone());
+    let locked = boundless_market
+        .requestIsLocked(request_id)
+        .call()
+        .await
+        .context("Failed to check request lock status")?
+        ._0;
+    if locked {
+        return Err(AppError::OrderLocked(request_id));
+    }
+
+    let order_id = match state.db.cancel_order(&cancellation).await {
+        Ok(order_id) => order_id,
+        Err(OrderDbErr::NoRows(_)) => {
+            return Err(AppError::OrderNotFound(cancellation.request_digest));
+        }
+        Err(err) => return Err(AppError::InternalErr(err.into())),
+    };
+
+    tracing::debug!("Order 0x{request_id:x} - [{order_id}] cancelled");
+    Ok(Json(SubmitOrderRes { status: "cancelled".into(), request_id }))
+}
+
+#[utoipa::path(
+    post,
+    path = FULFILLMENT_SUBMISSION_PATH,
+    request_body = FulfillmentSubmission,
+    responses(
+        (status = 200, description = "Fulfillment submission response", body = SubmitOrderRes),
+        (status = 400, description = "Invalid fulfillment", body = ErrMsg),
+        (status = 404, description = "No order found", body = ErrMsg),
+        (status = 500, description = "Internal error", body = ErrMsg)
+    )
+)]
+/// Submit the fulfillment of an order on the market order-stream
+///
+/// The journal must satisfy the requirements of an order with the same request ID, and the seal
+/// must verify against the verifier of the market. Only the first fulfillment of a request is
+/// kept.
+pub(crate) async fn submit_fulfillment(
+    State(state): State<Arc<AppState>>,
+    Json(submission): Json<FulfillmentSubmission>,
+) -> Result<Json<SubmitOrderRes>, AppError> {
+    let request_id = submission.request_id;
+    let orders = state
+        .db
+        .find_orders_by_request_id(request_id.to_string())
+        .await
+        .context("Failed to query DB")?;
+    if orders.is_empty() {
+        return Err(AppError::RequestNotFound(request_id));
+    }
+
+    let verifier = state.verifier_address().await?;
+    let mut verified = false;
+    for order in &orders {
+        if verify_fulfillment(
+            state.rpc_provider.clone(),
+            verifier,
+            &order.order.request.requirements,
+            &submission.fulfillment,
+        )
+        .await
+        .context("Failed to verify fulfillment")?
+        {
+            verified = true;
+            break;
+        }
+    }
+    if !verified {
+        return Err(AppError::InvalidFulfillment(request_id));
+    }
+
+    if state
+        .db
+        .add_fulfillment(request_id, &submission.fulfillment)
+        .await
+        .context("Failed to add fulfillment to db")?
+    {
+        tracing::debug!("Fulfillment of 0x{request_id:x} submitted");
+    }
+    Ok(Json(SubmitOrderRes { status: "success".into(), request_id }))
+}
+
+#[utoipa::path(
+    get,
+    path = format!("{}/<request_id>", FULFILLMENT_PATH),
+    params(
+        ("id" = String, Path, description = "Request ID")
+    ),
+    responses(
+        (status = 200, description = "fulfillment", body = FulfillmentData),
+        (status = 404, description = "No fulfillment found", body = ErrMsg),
+        (status = 500, description = "Internal error", body = ErrMsg)
+    )
+)]
+/// Returns the fulfillment of the order with the given request_id.
+pub(crate) async fn find_fulfillment(
+    State(state): State<Arc<AppState>>,
+    Path(request_id): Path<String>,
+) -> Result<Json<FulfillmentData>, AppError> {
+    let fulfillment = state.db.get_fulfillment(request_id).await.context("Failed to query DB")?;
+    Ok(Json(fulfillment.ok_or(AppError::FulfillmentNotFound)?))
+}
+
 const MAX_ORDERS: u64 = 1000;
 
 /// Paging query parameters
@@ -11,7 +11,7 @@ use alloy::providers::fillers::{
 };
 use alloy::providers::Identity;
 use alloy::{
-    primitives::{utils::parse_ether, Address, U256},
+    primitives::{utils::parse_ether, Address, B256, U256},
     providers::{Provider, ProviderBuilder, RootProvider},
     rpc::client::RpcClient,
     transports::layers::RetryBackoffLayer,
@@ -24,9 +24,13 @@ use axum::{
     routing::{get, post},
     Router,
 };
-use boundless_market::order_stream_client::{
-    AuthMsg, ErrMsg, Order, OrderError, AUTH_GET_NONCE, HEALTH_CHECK, ORDER_LIST_PATH,
-    ORDER_SUBMISSION_PATH, ORDER_WS_PATH,
+use boundless_market::{
+    contracts::boundless_market::BoundlessMarketService,
+    order_stream_client::{
+        AuthMsg, ErrMsg, Order, OrderError, AUTH_GET_NONCE, FULFILLMENT_PATH,
+        FULFILLMENT_SUBMISSION_PATH, HEALTH_CHECK, ORDER_BATCH_SUBMISSION_PATH, ORDER_CANCEL_PATH,
+        ORDER_LIST_PATH, ORDER_SUBMISSION_PATH, ORDER_WS_PATH,
+    },
 };
 use clap::Parser;
 use reqwest::Url;
@@ -34,7 +38,7 @@ use serde::Deserialize;
 use sqlx::PgPool;
 use std::sync::Arc;
 use thiserror::Error;
-use tokio::sync::{Mutex, RwLock};
+use tokio::sync::{Mutex, OnceCell, RwLock};
 use tokio_util::sync::CancellationToken;
 use tower_http::{limit::RequestBodyLimitLayer, timeout::TimeoutLayer, trace::TraceLayer};
 use utoipa::OpenApi;
@@ -45,8 +49,11 @@ mod order_db;
 mod ws;
 
 use api::{
-    __path_find_orders_by_request_id, __path_get_nonce, __path_health, __path_list_orders,
-    __path_submit_order, find_orders_by_request_id, get_nonce, health, list_orders, submit_order,
+    __path_cancel_order, __path_find_fulfillment, __path_find_orders_by_request_id,
+    __path_get_nonce, __path_health, __path_list_orders, __path_submit_fulfillment,
+    __path_submit_order, __path_submit_orders, cancel_order, find_fulfillment,
+    find_orders_by_request_id, get_nonce, health, list_orders, submit_fulfillment, submit_order,
+    submit_orders,
 };
 use order_db::OrderDb;
 use ws::{__path_websocket_handler, start_broadcast_task, websocket_handler, ConnectionsMap};
@@ -63,6 +70,24 @@ pub enum AppError {
     #[error("address not found")]
     AddrNotFound(Address),
 
+    #[error("too many orders in batch: {0}")]
+    TooManyOrders(usize),
+
+    #[error("no open order with digest {0}")]
+    OrderNotFound(B256),
+
+    #[error("request 0x{0:x} is already locked")]
+    OrderLocked(U256),
+
+    #[error("no order with request ID 0x{0:x}")]
+    RequestNotFound(U256),
+
+    #[error("fulfillment of request 0x{0:x} does not verify")]
+    InvalidFulfillment(U256),
+
+    #[error("no fulfillment found")]
+    FulfillmentNotFound,
+
     #[error("internal error")]
     InternalErr(AnyhowErr),
 }
@@ -73,6 +98,12 @@ impl AppError {
             Self::InvalidOrder(_) => "InvalidOrder",
             Self::QueryParamErr(_) => "QueryParamErr",
             Self::AddrNotFound(_) => "AddrNotFound",
+            Self::TooManyOrders(_) => "TooManyOrders",
+            Self::OrderNotFound(_) => "OrderNotFound",
+            Self::OrderLocked(_) => "OrderLocked",
+            Self::RequestNotFound(_) => "RequestNotFound",
+            Self::InvalidFulfillment(_) => "InvalidFulfillment",
+            Self::FulfillmentNotFound => "FulfillmentNotFound",
             Self::InternalErr(_) => "InternalErr",
         }
         .into()
@@ -94,8 +125,15 @@ impl From<OrderError> for AppError {
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let code = match self {
-            Self::InvalidOrder(_) | Self::QueryParamErr(_) => StatusCode::BAD_REQUEST,
-            Self::AddrNotFound(_) => StatusCode::NOT_FOUND,
+            Self::InvalidOrder(_)
+            | Self::QueryParamErr(_)
+            | Self::TooManyOrders(_)
+            | Self::InvalidFulfillment(_) => StatusCode::BAD_REQUEST,
+            Self::AddrNotFound(_)
+            | Self::OrderNotFound(_)
+            | Self::RequestNotFound(_)
+            | Self::FulfillmentNotFound => StatusCode::NOT_FOUND,
+            Self::OrderLocked(_) => StatusCode::CONFLICT,
             Self::InternalErr(_) => StatusCode::INTERNAL_SERVER_ERROR,
         };
         tracing::error!("api error, code {code}: {self:?}");
@@ -335,6 +373,8 @@ pub struct AppState {
     config: Config,
     /// chain_id
     chain_id: u64,
+    /// Address of the verifier used by the market, fetched on first use
+    verifier_address: OnceCell<Address>,
     /// Cancellation tokens set when a graceful shutdown is triggered
     shutdown: CancellationToken,
 }
@@ -366,10 +406,27 @@ impl AppState {
             rpc_provider,
             config: config.clone(),
             chain_id,
+            verifier_address: OnceCell::new(),
             shutdown: CancellationToken::new(),
         }))
     }
 
+    /// Returns the address of the verifier used by the market, for verifying fulfillments.
+    pub(crate) async fn verifier_address(&self) -> Result<Address> {
+        self.verifier_address
+            .get_or_try_init(|| async {
+                BoundlessMarketService::new(
+                    self.config.market_address,
+                    self.rpc_provider.clone(),
+                    Address::ZERO,
+                )
+                .verifier_address()
+                .await
+            })
+            .await
+            .copied()
+    }
+
     /// Pending connection timeout on failed upgrade.
     const PENDING_CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
 
@@ -416,8 +473,12 @@ const MAX_ORDER_SIZE: usize = 25 * 1024 * 1024; // 25 mb
 #[openapi(
     paths(
         submit_order,
+        submit_orders,
+        cancel_order,
         list_orders,
         find_orders_by_request_id,
+        submit_fulfillment,
+        find_fulfillment,
         get_nonce,
         health,
         websocket_handler
@@ -438,9 +499,13 @@ pub fn app(state: Arc<AppState>) -> Router {
     let body_size_limit = RequestBodyLimitLayer::new(MAX_ORDER_SIZE);
 
     Router::new()
-        .route(ORDER_SUBMISSION_PATH, post(submit_order).layer(body_size_limit))
+        .route(ORDER_SUBMISSION_PATH, post(submit_order).layer(body_size_limit.clone()))
+        .route(ORDER_BATCH_SUBMISSION_PATH, post(submit_orders).layer(body_size_limit))
+        .route(ORDER_CANCEL_PATH, post(cancel_order))
         .route(ORDER_LIST_PATH, get(list_orders))
         .route(&format!("{ORDER_LIST_PATH}/{{request_id}}"), get(find_orders_by_request_id))
+        .route(FULFILLMENT_SUBMISSION_PATH, post(submit_fulfillment))
+        .route(&format!("{FULFILLMENT_PATH}/{{request_id}}"), get(find_fulfillment))
         .route(&format!("{AUTH_GET_NONCE}{{addr}}"), get(get_nonce))
         .route(ORDER_WS_PATH, get(websocket_handler))
         .route(HEALTH_CHECK, get(health))
@@ -472,7 +537,9 @@ pub async fn run_from_parts(
     tokio::spawn(async move {
         loop {
             let order_stream = app_state_clone.db.order_stream().await.unwrap();
-            let broadcast_task = start_broadcast_task(app_state_clone.clone(), order_stream);
+            let cancel_stream = app_state_clone.db.cancel_stream().await.unwrap();
+            let broadcast_task =
+                start_broadcast_task(app_state_clone.clone(), order_stream, cancel_stream);
 
             match broadcast_task.await {
                 Ok(_) => {
@@ -522,21 +589,26 @@ mod tests {
     use crate::order_db::{DbOrder, OrderDbErr};
     use alloy::{
         node_bindings::{Anvil, AnvilInstance},
-        primitives::U256,
+        primitives::{B256, U256},
         providers::{Provider, WalletProvider},
     };
     use boundless_market::{
         contracts::{
-            hit_points::default_allowance, Offer, Predicate, ProofRequest, RequestId, Requirements,
+            eip712_domain, hit_points::default_allowance, Offer, Predicate, ProofRequest,
+            RequestId, Requirements,
         },
+        fulfillments::FulfillmentData,
         input::InputBuilder,
-        order_stream_client::{order_stream, Client},
+        order_stream_client::{
+            order_stream, order_stream_events, Client, OrderStreamEvent, SubmitOrdersRes,
+        },
     };
-    use boundless_market_test_utils::{create_test_ctx, TestCtx};
+    use boundless_market_test_utils::{create_test_ctx, mock_singleton, TestCtx};
 
     use futures_util::StreamExt;
     use guest_assessor::{ASSESSOR_GUEST_ID, ASSESSOR_GUEST_PATH};
     use guest_set_builder::{SET_BUILDER_ID, SET_BUILDER_PATH};
+    use guest_util::ECHO_ID;
     use reqwest::Url;
     use risc0_zkvm::sha::Digest;
     use sqlx::PgPool;
@@ -775,6 +847,161 @@ mod tests {
         server_handle.abort();
     }
 
+    #[sqlx::test]
+    async fn test_submit_orders(pool: PgPool) {
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)))
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (app_state, ctx, _anvil) = setup_test_env(pool, 20, Some(&listener)).await;
+        let client = Client::new(
+            Url::parse(&format!("http://{addr}")).unwrap(),
+            app_state.config.market_address,
+            app_state.chain_id,
+        );
+
+        let app_state_clone = app_state.clone();
+        let server_handle = tokio::spawn(async move {
+            self::run_from_parts(app_state_clone, listener).await.unwrap();
+        });
+        wait_for_server_health(&client, &addr, 5).await;
+
+        // Requests failing validation are rejected locally, without failing the batch.
+        let signer_addr = ctx.customer_signer.address();
+        let mut invalid_request = new_request(2, &signer_addr);
+        invalid_request.offer.timeout = 0;
+        let requests =
+            [new_request(1, &signer_addr), invalid_request, new_request(3, &signer_addr)];
+        let results = client.submit_requests(&requests, &ctx.customer_signer).await.unwrap();
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+        assert!(results[2].is_ok());
+        assert_eq!(app_state.db.list_orders(0, 10).await.unwrap().len(), 2);
+
+        // Orders failing validation on the server are reported individually.
+        let valid = results[0].as_ref().unwrap().clone();
+        let mut invalid = results[2].as_ref().unwrap().clone();
+        invalid.request_digest = B256::ZERO;
+        let response: Vec<SubmitOrdersRes> = client
+            .client
+            .post(format!("http://{addr}{ORDER_BATCH_SUBMISSION_PATH}"))
+            .json(&[valid, invalid])
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(response[0].error.is_none());
+        assert!(response[1].error.is_some());
+
+        // Batches over the size limit are rejected as a whole.
+        let requests: Vec<_> = (0..=api::MAX_BATCH_ORDERS as u32)
+            .map(|idx| new_request(idx + 10, &signer_addr))
+            .collect();
+        client.submit_requests(&requests, &ctx.customer_signer).await.unwrap_err();
+
+        server_handle.abort();
+    }
+
+    #[sqlx::test]
+    async fn test_cancel_order(pool: PgPool) {
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)))
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (app_state, ctx, _anvil) = setup_test_env(pool, 20, Some(&listener)).await;
+        let client = Client::new(
+            Url::parse(&format!("http://{addr}")).unwrap(),
+            app_state.config.market_address,
+            app_state.chain_id,
+        );
+
+        let app_state_clone = app_state.clone();
+        let server_handle = tokio::spawn(async move {
+            self::run_from_parts(app_state_clone, listener).await.unwrap();
+        });
+        wait_for_server_health(&client, &addr, 5).await;
+
+        let socket = client.connect_async(&ctx.prover_signer).await.unwrap();
+        let mut events = order_stream_events(socket);
+
+        let order = client
+            .submit_request(&new_request(1, &ctx.customer_signer.address()), &ctx.customer_signer)
+            .await
+            .unwrap();
+        let event = tokio::time::timeout(Duration::from_secs(4), events.next()).await.unwrap();
+        let Some(Ok(OrderStreamEvent::Order(received))) = event else {
+            panic!("expected an order, got {event:?}");
+        };
+        assert_eq!(received.order, order);
+
+        // Only the client of the request can cancel it.
+        client.cancel_request(&order.request, &ctx.prover_signer).await.unwrap_err();
+
+        let cancellation =
+            client.cancel_request(&order.request, &ctx.customer_signer).await.unwrap();
+        let event = tokio::time::timeout(Duration::from_secs(4), events.next()).await.unwrap();
+        let Some(Ok(OrderStreamEvent::Cancellation { cancellation: received })) = event else {
+            panic!("expected a cancellation, got {event:?}");
+        };
+        assert_eq!(received, cancellation);
+        received.verify(app_state.config.market_address, app_state.chain_id).unwrap();
+
+        // Cancelled orders are no longer listed, and cannot be cancelled again.
+        let orders =
+            app_state.db.find_orders_by_request_id(order.request.id.to_string()).await.unwrap();
+        assert!(orders.is_empty());
+        client.cancel_request(&order.request, &ctx.customer_signer).await.unwrap_err();
+
+        server_handle.abort();
+    }
+
+    #[sqlx::test]
+    async fn test_submit_fulfillment(pool: PgPool) {
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)))
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (app_state, ctx, _anvil) = setup_test_env(pool, 20, Some(&listener)).await;
+        let client = Client::new(
+            Url::parse(&format!("http://{addr}")).unwrap(),
+            app_state.config.market_address,
+            app_state.chain_id,
+        );
+
+        let app_state_clone = app_state.clone();
+        let server_handle = tokio::spawn(async move {
+            self::run_from_parts(app_state_clone, listener).await.unwrap();
+        });
+        wait_for_server_health(&client, &addr, 5).await;
+
+        let mut request = new_request(1, &ctx.customer_signer.address());
+        request.requirements =
+            Requirements::new(Digest::from(ECHO_ID), Predicate::prefix_match([0x41]));
+        let (root, set_verifier_seal, fill, _) = mock_singleton(
+            &request,
+            eip712_domain(app_state.config.market_address, app_state.chain_id).alloy_struct(),
+            ctx.prover_signer.address(),
+        );
+        ctx.set_verifier.submit_merkle_root(root, set_verifier_seal).await.unwrap();
+        let fulfillment = FulfillmentData { journal: fill.journal, seal: fill.seal };
+
+        // Fulfillments are only accepted for known orders.
+        client.submit_fulfillment(request.id, &fulfillment).await.unwrap_err();
+        client.submit_request(&request, &ctx.customer_signer).await.unwrap();
+
+        // The journal must match the predicate of the order.
+        let tampered = FulfillmentData { journal: vec![0x42].into(), ..fulfillment.clone() };
+        client.submit_fulfillment(request.id, &tampered).await.unwrap_err();
+        assert_eq!(client.fetch_fulfillment(request.id).await.unwrap(), None);
+
+        client.submit_fulfillment(request.id, &fulfillment).await.unwrap();
+        assert_eq!(client.fetch_fulfillment(request.id).await.unwrap(), Some(fulfillment));
+
+        server_handle.abort();
+    }
+
     #[sqlx::test]
     async fn test_pending_connection_timeout(pool: PgPool) {
         // No need for a listener in this test
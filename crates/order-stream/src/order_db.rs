@@ -2,9 +2,12 @@
 //
 // All rights reserved.
 
-use alloy::primitives::Address;
+use alloy::primitives::{Address, U256};
 use async_stream::stream;
-use boundless_market::order_stream_client::Order;
+use boundless_market::{
+    fulfillments::FulfillmentData,
+    order_stream_client::{CancelOrderMsg, Order},
+};
 use futures_util::Stream;
 use serde::{Deserialize, Serialize};
 use sqlx::{
@@ -53,8 +56,10 @@ pub struct OrderDb {
 }
 
 const ORDER_CHANNEL: &str = "new_orders";
+const CANCEL_CHANNEL: &str = "cancelled_orders";
 
 pub type OrderStream = Pin<Box<dyn Stream<Item = Result<DbOrder, OrderDbErr>> + Send>>;
+pub type CancelStream = Pin<Box<dyn Stream<Item = Result<CancelOrderMsg, OrderDbErr>> + Send>>;
 
 impl OrderDb {
     /// Constructs a [OrderDb] from an existing [PgPool]
@@ -190,6 +195,36 @@ impl OrderDb {
         Ok(id)
     }
 
+    /// Cancel an open order and notify listeners
+    ///
+    /// Marks the order matching the cancellation request ID and digest as cancelled, returning
+    /// its db identifier. Cancelled orders are no longer listed. The cancellation must be verified
+    /// by the caller.
+    pub async fn cancel_order(&self, cancellation: &CancelOrderMsg) -> Result<i64, OrderDbErr> {
+        let mut txn = self.pool.begin().await?;
+        let id: Option<i64> = sqlx::query_scalar(
+            "UPDATE orders SET cancelled_at = NOW() WHERE request_digest = $1 AND request_id = $2 AND cancelled_at IS NULL RETURNING id",
+        )
+        .bind(cancellation.request_digest.to_string())
+        .bind(cancellation.request_id.to_string())
+        .fetch_optional(&mut *txn)
+        .await?;
+
+        let Some(id) = id else {
+            return Err(OrderDbErr::NoRows("open order"));
+        };
+
+        sqlx::query("SELECT pg_notify($1, $2::text)")
+            .bind(CANCEL_CHANNEL)
+            .bind(sqlx::types::Json(cancellation))
+            .execute(&mut *txn)
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(id)
+    }
+
     /// Deletes a order from the database
     #[cfg(test)]
     pub async fn delete_order(&self, id: i64) -> Result<(), OrderDbErr> {
@@ -213,24 +248,61 @@ impl OrderDb {
         &self,
         request_id: String,
     ) -> Result<Vec<DbOrder>, OrderDbErr> {
-        let rows: Vec<DbOrder> = sqlx::query_as("SELECT * FROM orders WHERE request_id = $1")
-            .bind(request_id)
-            .fetch_all(&self.pool)
-            .await?;
+        let rows: Vec<DbOrder> =
+            sqlx::query_as("SELECT * FROM orders WHERE request_id = $1 AND cancelled_at IS NULL")
+                .bind(request_id)
+                .fetch_all(&self.pool)
+                .await?;
 
         Ok(rows)
     }
 
+    /// Add the fulfillment of a request to the DB
+    ///
+    /// Returns false if a fulfillment of the request is already stored, in which case it is kept.
+    /// The fulfillment must be verified by the caller.
+    pub async fn add_fulfillment(
+        &self,
+        request_id: U256,
+        fulfillment: &FulfillmentData,
+    ) -> Result<bool, OrderDbErr> {
+        let res = sqlx::query(
+            "INSERT INTO fulfillments (request_id, fulfillment_data) VALUES ($1, $2) ON CONFLICT (request_id) DO NOTHING",
+        )
+        .bind(request_id.to_string())
+        .bind(sqlx::types::Json(fulfillment))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected() == 1)
+    }
+
+    /// Get the fulfillment of a request, if stored
+    pub async fn get_fulfillment(
+        &self,
+        request_id: String,
+    ) -> Result<Option<FulfillmentData>, OrderDbErr> {
+        let row: Option<sqlx::types::Json<FulfillmentData>> =
+            sqlx::query_scalar("SELECT fulfillment_data FROM fulfillments WHERE request_id = $1")
+                .bind(request_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(row.map(|json| json.0))
+    }
+
     /// List orders with pagination
     ///
     /// Lists all orders the the database with a size bound and start id. The index_id will be
     /// equal to the DB ID since they are sequential for listing all new orders after a specific ID
     pub async fn list_orders(&self, index_id: i64, size: i64) -> Result<Vec<DbOrder>, OrderDbErr> {
-        let rows: Vec<DbOrder> = sqlx::query_as("SELECT * FROM orders WHERE id >= $1 LIMIT $2")
-            .bind(index_id)
-            .bind(size)
-            .fetch_all(&self.pool)
-            .await?;
+        let rows: Vec<DbOrder> = sqlx::query_as(
+            "SELECT * FROM orders WHERE id >= $1 AND cancelled_at IS NULL ORDER BY id LIMIT $2",
+        )
+        .bind(index_id)
+        .bind(size)
+        .fetch_all(&self.pool)
+        .await?;
 
         Ok(rows)
     }
@@ -250,6 +322,21 @@ impl OrderDb {
         }))
     }
 
+    /// Returns a stream of order cancellations from the DB
+    ///
+    /// listens to the cancelled orders and emits them as a async Stream
+    pub async fn cancel_stream(&self) -> Result<CancelStream, OrderDbErr> {
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener.listen(CANCEL_CHANNEL).await?;
+
+        Ok(Box::pin(stream! {
+            while let Some(elm) = listener.try_recv().await? {
+                let cancellation: CancelOrderMsg = serde_json::from_str(elm.payload())?;
+                yield Ok(cancellation);
+            }
+        }))
+    }
+
     /// Simple health check to test postgesql connectivity
     pub async fn health_check(&self) -> Result<(), OrderDbErr> {
         sqlx::query("SELECT COUNT(*) FROM orders LIMIT 1").execute(&self.pool).await?;
@@ -395,6 +482,58 @@ mod tests {
         assert_eq!(orders[0].id, order_id_2);
     }
 
+    #[sqlx::test]
+    async fn cancel_order(pool: PgPool) {
+        let db = OrderDb::from_pool(pool).await.unwrap();
+        let order = create_order(U256::from(1)).await;
+        let order2 = create_order(U256::from(2)).await;
+        let order_id = db.add_order(order.clone()).await.unwrap();
+        db.add_order(order2).await.unwrap();
+
+        let cancellation = CancelOrderMsg {
+            request_id: order.request.id,
+            request_digest: order.request_digest,
+            signature: order.signature,
+        };
+        assert_eq!(db.cancel_order(&cancellation).await.unwrap(), order_id);
+        assert!(db
+            .find_orders_by_request_id(order.request.id.to_string())
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(db.list_orders(0, 10).await.unwrap().len(), 1);
+
+        // Cancelling twice, or cancelling an unknown order, fails.
+        db.cancel_order(&cancellation).await.unwrap_err();
+        let unknown = CancelOrderMsg { request_id: U256::from(3), ..cancellation };
+        db.cancel_order(&unknown).await.unwrap_err();
+    }
+
+    #[sqlx::test]
+    async fn cancel_stream(pool: PgPool) {
+        let db = Arc::new(OrderDb::from_pool(pool).await.unwrap());
+        let order = create_order(U256::from(1)).await;
+        db.add_order(order.clone()).await.unwrap();
+
+        let db_copy = db.clone();
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let task: JoinHandle<Result<CancelOrderMsg, OrderDbErr>> = tokio::spawn(async move {
+            let mut cancellations = db_copy.cancel_stream().await.unwrap();
+            tx.send(()).unwrap();
+            cancellations.next().await.unwrap()
+        });
+
+        rx.await.unwrap();
+
+        let cancellation = CancelOrderMsg {
+            request_id: order.request.id,
+            request_digest: order.request_digest,
+            signature: order.signature,
+        };
+        db.cancel_order(&cancellation).await.unwrap();
+        assert_eq!(task.await.unwrap().unwrap(), cancellation);
+    }
+
     #[sqlx::test]
     async fn order_stream(pool: PgPool) {
         let db = Arc::new(OrderDb::from_pool(pool).await.unwrap());
@@ -434,4 +573,18 @@ mod tests {
 
         assert!(db_nonce.is_some());
     }
+
+    #[sqlx::test]
+    async fn add_fulfillment(pool: PgPool) {
+        let db = OrderDb::from_pool(pool).await.unwrap();
+        let request_id = U256::from(1);
+        let fulfillment = FulfillmentData { journal: vec![1, 2].into(), seal: vec![3, 4].into() };
+        assert_eq!(db.get_fulfillment(request_id.to_string()).await.unwrap(), None);
+
+        assert!(db.add_fulfillment(request_id, &fulfillment).await.unwrap());
+        // The first fulfillment of a request is kept.
+        let other = FulfillmentData { journal: vec![5].into(), seal: vec![6].into() };
+        assert!(!db.add_fulfillment(request_id, &other).await.unwrap());
+        assert_eq!(db.get_fulfillment(request_id.to_string()).await.unwrap(), Some(fulfillment));
+    }
 }
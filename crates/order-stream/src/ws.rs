@@ -14,7 +14,7 @@ use axum::{
 };
 use boundless_market::{
     contracts::IBoundlessMarket,
-    order_stream_client::{AuthMsg, ErrMsg, ORDER_WS_PATH},
+    order_stream_client::{AuthMsg, CancelOrderMsg, ErrMsg, OrderStreamEvent, ORDER_WS_PATH},
 };
 use futures_util::{SinkExt, StreamExt};
 use rand::{seq::SliceRandom, Rng};
@@ -22,7 +22,7 @@ use std::collections::{hash_map::Entry, HashMap};
 use std::sync::Arc;
 use tokio::{sync::mpsc, task::JoinHandle};
 
-use crate::order_db::{DbOrder, OrderDbErr, OrderStream};
+use crate::order_db::{CancelStream, DbOrder, OrderDbErr, OrderStream};
 use crate::{AppError, AppState};
 
 pub(crate) struct ClientConnection {
@@ -172,6 +172,30 @@ async fn broadcast_order(db_order: &DbOrder, state: Arc<AppState>) {
         }
     };
 
+    broadcast(order_json, state).await;
+    tracing::debug!("Order 0x{:x} broadcasted", db_order.order.request.id);
+}
+
+// Function to broadcast an order cancellation to all WebSocket clients in random order
+async fn broadcast_cancellation(cancellation: &CancelOrderMsg, state: Arc<AppState>) {
+    let event = OrderStreamEvent::Cancellation { cancellation: cancellation.clone() };
+    let cancel_json = match serde_json::to_string(&event) {
+        Ok(cancel_json) => cancel_json,
+        Err(err) => {
+            tracing::error!(
+                "Failed to serialize cancellation 0x{:x}: {}",
+                cancellation.request_id,
+                err
+            );
+            return;
+        }
+    };
+
+    broadcast(cancel_json, state).await;
+    tracing::debug!("Cancellation 0x{:x} broadcasted", cancellation.request_id);
+}
+
+async fn broadcast(msg_json: String, state: Arc<AppState>) {
     // Shuffle the connections
     let connections_list = {
         let connections = state.connections.read().await;
@@ -183,7 +207,7 @@ async fn broadcast_order(db_order: &DbOrder, state: Arc<AppState>) {
 
     let mut clients_to_remove = Vec::new();
     for (address, sender) in connections_list {
-        match sender.try_send(order_json.clone()) {
+        match sender.try_send(msg_json.clone()) {
             Ok(_) => {}
             Err(mpsc::error::TrySendError::Full(_)) => {
                 tracing::warn!("Client {}'s message queue is full, message dropped", address);
@@ -204,8 +228,6 @@ async fn broadcast_order(db_order: &DbOrder, state: Arc<AppState>) {
             }
         }
     }
-
-    tracing::debug!("Order 0x{:x} broadcasted", db_order.order.request.id);
 }
 
 async fn websocket_connection(socket: WebSocket, address: Address, state: Arc<AppState>) {
@@ -349,11 +371,20 @@ async fn websocket_connection(socket: WebSocket, address: Address, state: Arc<Ap
 pub(crate) fn start_broadcast_task(
     app_state: Arc<AppState>,
     mut order_stream: OrderStream,
+    mut cancel_stream: CancelStream,
 ) -> JoinHandle<Result<(), OrderDbErr>> {
     tokio::spawn(async move {
-        while let Some(order) = order_stream.next().await {
-            let order = order?;
-            broadcast_order(&order, app_state.clone()).await;
+        loop {
+            tokio::select! {
+                order = order_stream.next() => {
+                    let Some(order) = order else { break };
+                    broadcast_order(&order?, app_state.clone()).await;
+                }
+                cancellation = cancel_stream.next() => {
+                    let Some(cancellation) = cancellation else { break };
+                    broadcast_cancellation(&cancellation?, app_state.clone()).await;
+                }
+            }
         }
         Ok(())
     })
@@ -18,7 +18,7 @@ use alloy::{
     transports::{RpcError, TransportErrorKind},
 };
 use boundless_market::{
-    balance_alerts_layer::{BalanceAlertConfig, BalanceAlertLayer, BalanceAlertProvider},
+    alerts::{BalanceMonitor, BalanceSource, BalanceWatch, TracingSink},
     contracts::boundless_market::{BoundlessMarketService, MarketError},
 };
 use db::{DbError, DbObj, SqliteDb};
@@ -36,7 +36,7 @@ type ProviderWallet = FillProvider<
         >,
         WalletFiller<EthereumWallet>,
     >,
-    BalanceAlertProvider<RootProvider>,
+    RootProvider,
 >;
 
 #[derive(Error, Debug)]
@@ -68,6 +68,7 @@ pub struct SlashService<P> {
     pub boundless_market: BoundlessMarketService<P>,
     pub db: DbObj,
     pub config: SlashServiceConfig,
+    pub balance_monitor: Arc<BalanceMonitor<P>>,
 }
 
 #[derive(Clone)]
@@ -90,23 +91,22 @@ impl SlashService<ProviderWallet> {
         let caller = private_key.address();
         let wallet = EthereumWallet::from(private_key.clone());
 
-        let balance_alerts_layer = BalanceAlertLayer::new(BalanceAlertConfig {
-            watch_address: wallet.default_signer().address(),
-            warn_threshold: config.balance_warn_threshold,
-            error_threshold: config.balance_error_threshold,
-        });
-
-        let provider = ProviderBuilder::new()
-            .layer(balance_alerts_layer)
-            .wallet(wallet.clone())
-            .on_http(rpc_url);
+        let provider = ProviderBuilder::new().wallet(wallet).on_http(rpc_url);
 
         let boundless_market =
             BoundlessMarketService::new(boundless_market_address, provider.clone(), caller);
 
+        let balance_monitor = BalanceMonitor::new(provider)
+            .with_watch(
+                BalanceWatch::new(BalanceSource::Native, caller)
+                    .with_warn_below(config.balance_warn_threshold)
+                    .with_error_below(config.balance_error_threshold),
+            )
+            .with_sink(TracingSink);
+
         let db: DbObj = Arc::new(SqliteDb::new(db_conn).await.unwrap());
 
-        Ok(Self { boundless_market, db, config })
+        Ok(Self { boundless_market, db, config, balance_monitor: Arc::new(balance_monitor) })
     }
 }
 
@@ -136,6 +136,8 @@ where
                         Ok(_) => {
                             attempt = 0;
                             from_block = to_block + 1;
+                            // Failures are logged by the monitor, and retried on the next tick.
+                            let _ = self.balance_monitor.check().await;
                         }
                         Err(e) => match e {
                             // Irrecoverable errors
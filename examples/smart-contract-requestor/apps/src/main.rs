@@ -15,7 +15,10 @@ use anyhow::{bail, Context, Result};
 use boundless_market::storage::BuiltinStorageProvider;
 use boundless_market::{
     client::{Client, ClientBuilder},
-    contracts::{Input, Offer, Predicate, ProofRequest, RequestId, Requirements},
+    contracts::{
+        smart_contract_requestor::SmartContractRequestor, Input, Offer, Predicate, ProofRequest,
+        Requirements,
+    },
     storage::{StorageProvider, StorageProviderConfig},
 };
 use clap::Parser;
@@ -120,8 +123,11 @@ async fn run<P: StorageProvider>(
     // Create the request id, using days_since_epoch as the index, and with the smart contract signed flag set.
     // The smart contract signed flag is used to indicate that the request is "signed" by the smart contract
     // and must be validated using ERC-1271's isValidSignature function, and not a regular ECDSA recovery.
-    let request_id = RequestId::new(smart_contract_requestor_address, days_since_epoch)
-        .set_smart_contract_signed_flag();
+    let requestor = SmartContractRequestor::new(
+        smart_contract_requestor_address,
+        boundless_client.boundless_market.clone(),
+    );
+    let request_id = requestor.request_id(days_since_epoch);
 
     // Create the requirements for the request. We use the predicate type `DigestMatch` to ensure that the journal
     // of the guest program matches a specific value. The pattern we use here is for our guest program to output the input
@@ -155,10 +161,11 @@ async fn run<P: StorageProvider>(
         )
         .build()?;
 
-    // Send the request and wait for it to be completed.
+    // Send the request and wait for it to be completed. The requestor checks the signature with
+    // the smart contract before submitting, so a rejected request fails here with a clear error.
     let signature: Bytes = request.abi_encode().into();
-    let (request_id, expires_at) =
-        boundless_client.submit_request_with_signature_bytes(&request, &signature).await?;
+    let request_id = requestor.submit_request(&request, &signature).await?;
+    let expires_at = request.expires_at();
     tracing::info!("Request {} submitted", request_id);
 
     // Wait for the request to be fulfilled by the market. The market will return the journal and
@@ -219,18 +226,16 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tests::SmartContractRequestor::SmartContractRequestorInstance;
     use alloy::{
         network::EthereumWallet,
         node_bindings::{Anvil, AnvilInstance},
         primitives::Address,
         providers::{Provider, ProviderBuilder, WalletProvider},
         signers::local::PrivateKeySigner,
-        sol_types::SolCall,
     };
     use boundless_market::contracts::{
-        hit_points::default_allowance,
-        IBoundlessMarket::{self},
+        boundless_market::BoundlessMarketService, hit_points::default_allowance,
+        smart_contract_requestor::SmartContractRequestor as Requestor,
     };
     use boundless_market::storage::MockStorageProvider;
     use boundless_market_test_utils::{create_test_ctx, TestCtx};
@@ -292,22 +297,19 @@ mod tests {
 
         let value_to_fund = parse_ether("0.5").unwrap();
 
-        let smart_contract_requestor = SmartContractRequestorInstance::new(
+        // Fund the smart contract client with ETH and deposit to the market, through the owner of
+        // the contract.
+        let owner_address = smart_contract_requestor_owner.default_signer_address();
+        let requestor = Requestor::new(
             smart_contract_requestor_address,
-            smart_contract_requestor_owner.clone(),
-        )
-        .clone();
-
-        // Fund the smart contract client with ETH and deposit to the market.
-        let deposit_call = IBoundlessMarket::depositCall {}.abi_encode();
-
-        let pending_deposit_tx = smart_contract_requestor
-            .execute(ctx.boundless_market_address, deposit_call.into(), value_to_fund)
-            .value(value_to_fund)
-            .send()
-            .await
-            .unwrap();
-        pending_deposit_tx.watch().await.unwrap();
+            BoundlessMarketService::new(
+                ctx.boundless_market_address,
+                smart_contract_requestor_owner.clone(),
+                owner_address,
+            ),
+        );
+        requestor.deposit(value_to_fund).await.unwrap();
+        assert_eq!(requestor.balance().await.unwrap(), value_to_fund);
 
         // A JoinSet automatically aborts all its tasks when dropped
         let mut tasks = JoinSet::new();